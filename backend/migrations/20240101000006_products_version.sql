@@ -0,0 +1,3 @@
+-- Optimistic concurrency: every update bumps the version, and callers can
+-- make an update conditional on the version they last read
+ALTER TABLE products ADD COLUMN IF NOT EXISTS version BIGINT NOT NULL DEFAULT 1;
@@ -0,0 +1,7 @@
+-- Returns start out pending; approving one puts the stock back on the shelf
+CREATE TYPE devolution_status AS ENUM ('pending', 'approved', 'rejected');
+
+ALTER TABLE product_devolutions
+    ADD COLUMN IF NOT EXISTS status devolution_status NOT NULL DEFAULT 'pending',
+    ADD COLUMN IF NOT EXISTS rejection_reason TEXT NULL,
+    ADD COLUMN IF NOT EXISTS decided_at TIMESTAMPTZ NULL;
@@ -0,0 +1,11 @@
+-- One row per price change made through update_product
+CREATE TABLE IF NOT EXISTS product_price_history (
+    id              UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    product_id      UUID NOT NULL REFERENCES products(id) ON DELETE CASCADE,
+    old_price_cents BIGINT NOT NULL,
+    new_price_cents BIGINT NOT NULL,
+    changed_at      TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_price_history_product_changed
+    ON product_price_history(product_id, changed_at DESC);
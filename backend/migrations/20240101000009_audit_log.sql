@@ -0,0 +1,14 @@
+-- One row per POST/PUT/DELETE, written by AuditLayer (built with the `audit` feature)
+CREATE TABLE IF NOT EXISTS audit_log (
+    id                UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    request_id        UUID,
+    method            TEXT NOT NULL,
+    path              TEXT NOT NULL,
+    user_agent        TEXT,
+    request_body_json JSONB,
+    response_status   INT NOT NULL,
+    duration_ms       DOUBLE PRECISION NOT NULL,
+    created_at        TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_log_created ON audit_log(created_at DESC);
@@ -0,0 +1,14 @@
+-- Timing entries drained from the in-memory MetricsStore by the persistence task
+CREATE TABLE IF NOT EXISTS metrics (
+    id          BIGSERIAL PRIMARY KEY,
+    timestamp   TIMESTAMPTZ NOT NULL,
+    operation   TEXT NOT NULL,
+    set_type    TEXT NOT NULL,
+    duration_ns BIGINT NOT NULL,
+    item_count  INT NOT NULL,
+    success     BOOL NOT NULL,
+    notes       TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_metrics_operation_timestamp ON metrics(operation, timestamp DESC);
+CREATE INDEX IF NOT EXISTS idx_metrics_timestamp ON metrics(timestamp DESC);
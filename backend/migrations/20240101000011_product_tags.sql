@@ -0,0 +1,14 @@
+-- Free-form labels; a product can carry any number of them
+CREATE TABLE IF NOT EXISTS tags (
+    id   UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name TEXT NOT NULL UNIQUE
+);
+
+CREATE TABLE IF NOT EXISTS product_tags (
+    product_id UUID NOT NULL REFERENCES products(id) ON DELETE CASCADE,
+    tag_id     UUID NOT NULL REFERENCES tags(id) ON DELETE CASCADE,
+    PRIMARY KEY (product_id, tag_id)
+);
+
+-- The primary key covers lookups by product; this one serves tag filters
+CREATE INDEX IF NOT EXISTS idx_product_tags_tag ON product_tags(tag_id);
@@ -0,0 +1,3 @@
+-- Serves left-anchored LIKE 'prefix%' on name regardless of the database
+-- collation. Case-insensitive `name_contains` filters (ILIKE '%term%') still scan.
+CREATE INDEX IF NOT EXISTS idx_products_name_pattern ON products (name varchar_pattern_ops);
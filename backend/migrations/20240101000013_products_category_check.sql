@@ -0,0 +1,24 @@
+-- Categories are a fixed list (see models::Category). Rows that only differ in
+-- case are brought to the canonical spelling first.
+UPDATE products p
+SET category = c.name
+FROM (VALUES
+    ('Electronics'), ('Clothing'), ('Food & Beverage'), ('Home & Garden'), ('Toys & Games'),
+    ('Sports & Outdoors'), ('Books'), ('Automotive'), ('Health & Beauty'), ('Office Supplies'),
+    ('Musical Instruments'), ('Pet Supplies'), ('Jewelry'), ('Tools & Hardware'), ('Baby Products')
+) AS c(name)
+WHERE lower(p.category) = lower(c.name) AND p.category <> c.name;
+
+-- NOT VALID: rows with some other category don't block the migration, but
+-- every insert and update from now on is checked
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_constraint WHERE conname = 'products_category_known') THEN
+        ALTER TABLE products ADD CONSTRAINT products_category_known CHECK (category IN (
+            'Electronics', 'Clothing', 'Food & Beverage', 'Home & Garden', 'Toys & Games',
+            'Sports & Outdoors', 'Books', 'Automotive', 'Health & Beauty', 'Office Supplies',
+            'Musical Instruments', 'Pet Supplies', 'Jewelry', 'Tools & Hardware', 'Baby Products'
+        )) NOT VALID;
+    END IF;
+END
+$$;
@@ -0,0 +1,13 @@
+-- One row per POST /api/products/:id/restock; the product's quantity is
+-- raised in the same transaction
+CREATE TABLE IF NOT EXISTS restock_events (
+    id             UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    product_id     UUID NOT NULL REFERENCES products(id) ON DELETE CASCADE,
+    quantity_added INT NOT NULL CHECK (quantity_added > 0),
+    supplier       TEXT,
+    notes          TEXT,
+    created_at     TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_restock_events_product_created
+    ON restock_events(product_id, created_at DESC);
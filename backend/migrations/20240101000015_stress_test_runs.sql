@@ -0,0 +1,9 @@
+-- One row per completed (non-dry-run) POST /api/stress-test; `report` is the
+-- full StressReport
+CREATE TABLE IF NOT EXISTS stress_test_runs (
+    id     UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    run_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    report JSONB NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_stress_test_runs_at ON stress_test_runs(run_at DESC);
@@ -0,0 +1,6 @@
+-- Incremental set sync (POST /api/benchmark/sets/sync) asks for rows changed
+-- or soft-deleted since its last run
+CREATE INDEX IF NOT EXISTS idx_products_updated_at ON products(updated_at);
+
+CREATE INDEX IF NOT EXISTS idx_products_deleted_at ON products(deleted_at)
+    WHERE deleted_at IS NOT NULL;
@@ -0,0 +1,13 @@
+-- Responses to POST/PUT requests sent with an `Idempotency-Key` header, so a
+-- retried request gets the original response instead of running twice.
+-- `request_path` is "<METHOD> <path>"; a key is only replayed for the same one.
+-- Rows older than 24 hours are ignored and purged periodically.
+CREATE TABLE IF NOT EXISTS idempotency_cache (
+    key           TEXT PRIMARY KEY,
+    request_path  TEXT NOT NULL,
+    response_body JSONB NOT NULL,
+    status_code   INT NOT NULL,
+    created_at    TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_idempotency_cache_created_at ON idempotency_cache(created_at);
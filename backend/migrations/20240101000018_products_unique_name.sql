@@ -0,0 +1,22 @@
+-- Live product names are unique. Soft-deleted rows are left out, so a name
+-- can be reused after a delete (restoring the old row then needs it free).
+--
+-- Duplicates already present would make the index fail. Rather than change
+-- data here, the migration stops and names them; resolve them with
+-- scripts/soft_delete_duplicate_names.sql (or by hand) and restart.
+DO $$
+DECLARE
+    duplicated BIGINT;
+BEGIN
+    SELECT COUNT(*) INTO duplicated
+    FROM (SELECT name FROM products WHERE deleted_at IS NULL GROUP BY name HAVING COUNT(*) > 1) d;
+
+    IF duplicated > 0 THEN
+        RAISE EXCEPTION '% product name(s) are shared by more than one live product; the unique name index can''t be created', duplicated
+            USING HINT = 'List them with SELECT name, COUNT(*) FROM products WHERE deleted_at IS NULL GROUP BY name HAVING COUNT(*) > 1, '
+                      || 'then run scripts/soft_delete_duplicate_names.sql or rename them before migrating again.';
+    END IF;
+END
+$$;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_products_name_live ON products(name) WHERE deleted_at IS NULL;
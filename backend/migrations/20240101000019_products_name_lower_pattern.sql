@@ -0,0 +1,6 @@
+-- Serves the case-insensitive prefix match behind GET /api/products/search/suggest
+-- (lower(name) LIKE 'prefix%'), which idx_products_name_pattern can't since
+-- ILIKE isn't indexable with a btree.
+CREATE INDEX IF NOT EXISTS idx_products_name_lower_pattern
+    ON products (lower(name) text_pattern_ops)
+    WHERE deleted_at IS NULL;
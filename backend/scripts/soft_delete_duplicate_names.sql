@@ -0,0 +1,17 @@
+-- One-off cleanup for migration 18 (unique live product names).
+--
+-- Soft-deletes every live product but the oldest of each duplicated name, so
+-- the index can be built. The removed copies stay in /api/products/deleted.
+--
+--   psql "$DATABASE_URL" -f scripts/soft_delete_duplicate_names.sql
+UPDATE products
+SET deleted_at = NOW()
+WHERE id IN (
+    SELECT id
+    FROM (
+        SELECT id, ROW_NUMBER() OVER (PARTITION BY name ORDER BY created_at, id) AS position
+        FROM products
+        WHERE deleted_at IS NULL
+    ) ranked
+    WHERE position > 1
+);
@@ -0,0 +1,186 @@
+use std::{
+    task::{Context, Poll},
+    time::Instant,
+};
+
+use axum::{
+    body::Body,
+    http::{
+        header::{CONTENT_LENGTH, CONTENT_TYPE, USER_AGENT},
+        Method, Request,
+    },
+    response::Response,
+};
+use futures_util::future::BoxFuture;
+use sqlx::PgPool;
+use tower::{Layer, Service};
+use tracing::warn;
+
+use crate::{db, extract::RequestId, models::NewAuditEntry};
+
+/// JSON bodies up to this size are stored with the entry; larger ones (and
+/// bodies without a `Content-Length`) are passed through unread.
+const MAX_AUDITED_BODY_BYTES: usize = 64 * 1024;
+
+/// Records every POST/PUT/DELETE in `audit_log`.
+///
+/// The row is written from a spawned task once the handler has produced its
+/// response, so the client never waits on it, and a failed write is logged
+/// rather than turned into an error. Install it inside
+/// [`crate::middleware::RequestIdLayer`] so entries carry the request ID.
+#[derive(Debug, Clone)]
+pub struct AuditLayer {
+    pool: PgPool,
+}
+
+impl AuditLayer {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+impl<S> Layer<S> for AuditLayer {
+    type Service = AuditService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        AuditService { inner, pool: self.pool.clone() }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AuditService<S> {
+    inner: S,
+    pool: PgPool,
+}
+
+impl<S> Service<Request<Body>> for AuditService<S>
+where
+    S: Service<Request<Body>, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Response, S::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        // `poll_ready` was called on `self.inner`, so that is the one to use
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        if !matches!(*req.method(), Method::POST | Method::PUT | Method::DELETE) {
+            return Box::pin(inner.call(req));
+        }
+
+        let pool = self.pool.clone();
+        Box::pin(async move {
+            let start = Instant::now();
+            let request_id = req.extensions().get::<RequestId>().map(|id| id.0);
+            let method = req.method().to_string();
+            let path = req.uri().path_and_query().map_or_else(|| req.uri().path().to_string(), ToString::to_string);
+            let user_agent = req.headers().get(USER_AGENT).and_then(|v| v.to_str().ok()).map(str::to_string);
+            let (req, request_body_json) = capture_json_body(req).await;
+
+            let response = inner.call(req).await?;
+
+            let entry = NewAuditEntry {
+                request_id,
+                method,
+                path,
+                user_agent,
+                request_body_json,
+                response_status: i32::from(response.status().as_u16()),
+                duration_ms: start.elapsed().as_secs_f64() * 1000.0,
+            };
+            tokio::spawn(async move {
+                if let Err(e) = db::insert_audit_entry(&pool, &entry).await {
+                    warn!(error = %e, method = %entry.method, path = %entry.path, "Failed to write audit log entry");
+                }
+            });
+
+            Ok(response)
+        })
+    }
+}
+
+/// Buffers a small JSON body so it can be both recorded and handed on to the
+/// handler. Anything else is left untouched.
+async fn capture_json_body(req: Request<Body>) -> (Request<Body>, Option<serde_json::Value>) {
+    let is_json = req
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.starts_with("application/json"));
+    let length = req
+        .headers()
+        .get(CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<usize>().ok());
+    if !is_json || length.is_none_or(|len| len > MAX_AUDITED_BODY_BYTES) {
+        return (req, None);
+    }
+
+    let (parts, body) = req.into_parts();
+    match axum::body::to_bytes(body, MAX_AUDITED_BODY_BYTES).await {
+        Ok(bytes) => {
+            let json = serde_json::from_slice(&bytes).ok();
+            (Request::from_parts(parts, Body::from(bytes)), json)
+        }
+        // The client broke off mid-body; the handler would have failed to read it too
+        Err(_) => (Request::from_parts(parts, Body::empty()), None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::offline_state;
+    use axum::{http::StatusCode, routing::post, Router};
+    use tower::ServiceExt;
+
+    async fn echo(body: String) -> String {
+        body
+    }
+
+    fn json_request(body: &str) -> Request<Body> {
+        Request::builder()
+            .method(Method::POST)
+            .uri("/echo")
+            .header(CONTENT_TYPE, "application/json")
+            .header(CONTENT_LENGTH, body.len())
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn captured_body_still_reaches_the_handler() {
+        let (req, json) = capture_json_body(json_request(r#"{"name":"Lamp"}"#)).await;
+        assert_eq!(json, Some(serde_json::json!({ "name": "Lamp" })));
+        let bytes = axum::body::to_bytes(req.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(&bytes[..], br#"{"name":"Lamp"}"#);
+    }
+
+    #[tokio::test]
+    async fn non_json_and_unsized_bodies_are_not_captured() {
+        let mut form = json_request("a=1");
+        form.headers_mut().insert(CONTENT_TYPE, "application/x-www-form-urlencoded".parse().unwrap());
+        assert_eq!(capture_json_body(form).await.1, None);
+
+        let mut chunked = json_request("{}");
+        chunked.headers_mut().remove(CONTENT_LENGTH);
+        assert_eq!(capture_json_body(chunked).await.1, None);
+    }
+
+    #[tokio::test]
+    async fn failed_audit_write_does_not_affect_the_response() {
+        // The offline pool can't reach a database, so the spawned insert fails
+        let app = Router::new().route("/echo", post(echo)).layer(AuditLayer::new(offline_state().db));
+        let response = app.oneshot(json_request(r#"{"ok":true}"#)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(&body[..], br#"{"ok":true}"#);
+    }
+}
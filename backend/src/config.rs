@@ -1,22 +1,260 @@
+use std::{str::FromStr, time::Duration};
+
 use anyhow::Context;
+use serde::Serialize;
+use sqlx::postgres::PgPoolOptions;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Config {
+    /// Holds the DB password, so it is never serialized
+    #[serde(skip_serializing)]
     pub database_url: String,
     pub host: String,
     pub port: u16,
+    /// Maximum number of timing entries kept by the metrics store
+    pub metrics_capacity: usize,
+    /// Metrics entries older than this are purged by the background retention task
+    pub metrics_retention_secs: u64,
+    /// How often metrics entries are moved into the `metrics` table (0 = never)
+    pub metrics_persist_interval_secs: u64,
+    /// Track an extra `FxHashSet` alongside the other in-memory sets
+    pub enable_fx_set: bool,
+    /// Keep a patricia trie of product names for `GET /api/products/autocomplete`
+    pub enable_trie_index: bool,
+    /// Serve `POST /api/admin/explain`; off by default since it runs client SQL
+    pub enable_explain_endpoint: bool,
+    /// Serve `DELETE /api/admin/reset`; off by default since it deletes every product
+    pub enable_full_reset: bool,
+    /// Skip gzip/brotli/deflate response compression
+    pub disable_compression: bool,
+    /// Where the metrics store is written as CSV on shutdown (skipped when unset)
+    pub metrics_dump_path: Option<String>,
+    /// How long in-flight requests get to finish after SIGTERM/SIGINT
+    pub shutdown_timeout_secs: u64,
+    /// Requests still running after this long get a 408
+    pub request_timeout_secs: u64,
+    /// The same limit for `POST /api/benchmark/run` and `POST /api/stress-test`
+    pub benchmark_timeout_secs: u64,
+    /// OTLP/gRPC collector for trace export (`opentelemetry` feature; off when unset)
+    pub otlp_endpoint: Option<String>,
+    /// Pool size cap; stress tests run many queries at once
+    pub db_max_connections: u32,
+    /// Connections kept open even when idle
+    pub db_min_connections: u32,
+    /// How long a query waits for a free connection before failing
+    pub db_connect_timeout_secs: u64,
+    /// Idle connections above `db_min_connections` are closed after this long
+    pub db_idle_timeout_secs: u64,
+    /// Connections are recycled after this long, busy or not
+    pub db_max_lifetime_secs: u64,
+    /// Retries after a query times out waiting for a free connection (0 = none)
+    pub retry_attempts: u32,
+    /// Wait before the first retry; doubled for each one after
+    pub retry_delay_ms: u64,
+    /// Products kept by the `GET /api/products/:id` LRU cache (0 = no caching)
+    pub product_cache_size: usize,
 }
 
 impl Config {
     pub fn from_env() -> anyhow::Result<Self> {
-        Ok(Self {
-            database_url: std::env::var("DATABASE_URL")
-                .context("DATABASE_URL must be set")?,
-            host: std::env::var("HOST").unwrap_or_else(|_| "127.0.0.1".to_string()),
-            port: std::env::var("PORT")
-                .unwrap_or_else(|_| "3000".to_string())
-                .parse()
-                .context("PORT must be a valid number")?,
-        })
+        Self::from_lookup(|key| std::env::var(key).ok())
+    }
+
+    /// Builds the config from `lookup` (the process environment in production,
+    /// a map in tests).
+    pub fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> anyhow::Result<Self> {
+        let config = Self {
+            database_url: lookup("DATABASE_URL").context("DATABASE_URL must be set")?,
+            host: lookup("HOST").unwrap_or_else(|| "127.0.0.1".to_string()),
+            port: parse_or(&lookup, "PORT", 3000)?,
+            metrics_capacity: parse_or(&lookup, "METRICS_CAPACITY", crate::metrics::MetricsStore::DEFAULT_CAPACITY)?,
+            metrics_retention_secs: parse_or(&lookup, "METRICS_RETENTION_SECS", 24 * 60 * 60)?,
+            metrics_persist_interval_secs: parse_or(&lookup, "METRICS_PERSIST_INTERVAL_SECS", 30)?,
+            enable_fx_set: lookup("ENABLE_FX_SET")
+                .map(|v| v.parse())
+                .transpose()
+                .context("ENABLE_FX_SET must be true or false")?
+                .unwrap_or(false),
+            enable_trie_index: lookup("ENABLE_TRIE_INDEX")
+                .map(|v| v.parse())
+                .transpose()
+                .context("ENABLE_TRIE_INDEX must be true or false")?
+                .unwrap_or(false),
+            enable_explain_endpoint: lookup("ENABLE_EXPLAIN_ENDPOINT")
+                .map(|v| v.parse())
+                .transpose()
+                .context("ENABLE_EXPLAIN_ENDPOINT must be true or false")?
+                .unwrap_or(false),
+            enable_full_reset: lookup("ENABLE_FULL_RESET")
+                .map(|v| v.parse())
+                .transpose()
+                .context("ENABLE_FULL_RESET must be true or false")?
+                .unwrap_or(false),
+            disable_compression: lookup("DISABLE_COMPRESSION")
+                .map(|v| v.parse())
+                .transpose()
+                .context("DISABLE_COMPRESSION must be true or false")?
+                .unwrap_or(false),
+            metrics_dump_path: lookup("METRICS_DUMP_PATH").filter(|p| !p.is_empty()),
+            shutdown_timeout_secs: parse_or(&lookup, "SHUTDOWN_TIMEOUT_SECS", 30)?,
+            request_timeout_secs: parse_or(&lookup, "REQUEST_TIMEOUT_SECS", 120)?,
+            benchmark_timeout_secs: parse_or(&lookup, "BENCHMARK_TIMEOUT_SECS", 1800)?,
+            otlp_endpoint: lookup("OTLP_ENDPOINT").filter(|e| !e.is_empty()),
+            db_max_connections: parse_or(&lookup, "DB_MAX_CONNECTIONS", 20)?,
+            db_min_connections: parse_or(&lookup, "DB_MIN_CONNECTIONS", 0)?,
+            db_connect_timeout_secs: parse_or(&lookup, "DB_CONNECT_TIMEOUT_SECS", 30)?,
+            db_idle_timeout_secs: parse_or(&lookup, "DB_IDLE_TIMEOUT_SECS", 600)?,
+            db_max_lifetime_secs: parse_or(&lookup, "DB_MAX_LIFETIME_SECS", 1800)?,
+            retry_attempts: parse_or(&lookup, "DB_RETRY_ATTEMPTS", 3)?,
+            retry_delay_ms: parse_or(&lookup, "DB_RETRY_DELAY_MS", 50)?,
+            product_cache_size: parse_or(&lookup, "PRODUCT_CACHE_SIZE", 1000)?,
+        };
+        anyhow::ensure!(
+            (1..=crate::metrics::MetricsStore::MAX_CAPACITY).contains(&config.metrics_capacity),
+            "METRICS_CAPACITY must be between 1 and {}",
+            crate::metrics::MetricsStore::MAX_CAPACITY
+        );
+        anyhow::ensure!(config.db_max_connections >= 1, "DB_MAX_CONNECTIONS must be at least 1");
+        anyhow::ensure!(
+            config.db_min_connections <= config.db_max_connections,
+            "DB_MIN_CONNECTIONS must not exceed DB_MAX_CONNECTIONS"
+        );
+        Ok(config)
+    }
+
+    /// Pool options from the `db_*` fields.
+    pub fn pool_options(&self) -> PgPoolOptions {
+        PgPoolOptions::new()
+            .max_connections(self.db_max_connections)
+            .min_connections(self.db_min_connections)
+            .acquire_timeout(Duration::from_secs(self.db_connect_timeout_secs))
+            .idle_timeout(Duration::from_secs(self.db_idle_timeout_secs))
+            .max_lifetime(Duration::from_secs(self.db_max_lifetime_secs))
+    }
+}
+
+/// `key` parsed as a number, or `default` when unset.
+fn parse_or<T: FromStr>(lookup: &impl Fn(&str) -> Option<String>, key: &str, default: T) -> anyhow::Result<T> {
+    match lookup(key) {
+        Some(v) => v.parse().ok().with_context(|| format!("{} must be a valid number", key)),
+        None => Ok(default),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn config_with(vars: &[(&str, &str)]) -> anyhow::Result<Config> {
+        let mut env: HashMap<String, String> = vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        env.entry("DATABASE_URL".to_string()).or_insert_with(|| "postgres://localhost/test".to_string());
+        Config::from_lookup(|key| env.get(key).cloned())
+    }
+
+    #[test]
+    fn compression_is_on_unless_disabled() {
+        assert!(!config_with(&[]).unwrap().disable_compression);
+        assert!(config_with(&[("DISABLE_COMPRESSION", "true")]).unwrap().disable_compression);
+        assert!(config_with(&[("DISABLE_COMPRESSION", "yes")]).is_err());
+    }
+
+    #[test]
+    fn trie_index_is_off_unless_enabled() {
+        assert!(!config_with(&[]).unwrap().enable_trie_index);
+        assert!(config_with(&[("ENABLE_TRIE_INDEX", "true")]).unwrap().enable_trie_index);
+        assert!(config_with(&[("ENABLE_TRIE_INDEX", "1")]).is_err());
+    }
+
+    #[test]
+    fn explain_endpoint_is_off_unless_enabled() {
+        assert!(!config_with(&[]).unwrap().enable_explain_endpoint);
+        assert!(config_with(&[("ENABLE_EXPLAIN_ENDPOINT", "true")]).unwrap().enable_explain_endpoint);
+        assert!(config_with(&[("ENABLE_EXPLAIN_ENDPOINT", "on")]).is_err());
+    }
+
+    #[test]
+    fn product_cache_size_defaults_to_a_thousand() {
+        assert_eq!(config_with(&[]).unwrap().product_cache_size, 1000);
+        assert_eq!(config_with(&[("PRODUCT_CACHE_SIZE", "0")]).unwrap().product_cache_size, 0);
+        assert!(config_with(&[("PRODUCT_CACHE_SIZE", "-1")]).is_err());
+    }
+
+    #[test]
+    fn full_reset_is_off_unless_enabled() {
+        assert!(!config_with(&[]).unwrap().enable_full_reset);
+        assert!(config_with(&[("ENABLE_FULL_RESET", "true")]).unwrap().enable_full_reset);
+        assert!(config_with(&[("ENABLE_FULL_RESET", "1")]).is_err());
+    }
+
+    #[test]
+    fn pool_fields_default_when_unset() {
+        let config = config_with(&[]).unwrap();
+        assert_eq!(config.db_max_connections, 20);
+        assert_eq!(config.db_min_connections, 0);
+        assert_eq!(config.db_connect_timeout_secs, 30);
+        assert_eq!(config.db_idle_timeout_secs, 600);
+        assert_eq!(config.db_max_lifetime_secs, 1800);
+        assert_eq!((config.retry_attempts, config.retry_delay_ms), (3, 50));
+    }
+
+    #[test]
+    fn pool_fields_read_their_env_vars() {
+        let config = config_with(&[
+            ("DB_MAX_CONNECTIONS", "50"),
+            ("DB_MIN_CONNECTIONS", "5"),
+            ("DB_CONNECT_TIMEOUT_SECS", "3"),
+            ("DB_IDLE_TIMEOUT_SECS", "60"),
+            ("DB_MAX_LIFETIME_SECS", "900"),
+            ("DB_RETRY_ATTEMPTS", "0"),
+            ("DB_RETRY_DELAY_MS", "10"),
+        ])
+        .unwrap();
+        assert_eq!(config.db_max_connections, 50);
+        assert_eq!(config.db_min_connections, 5);
+        assert_eq!(config.db_connect_timeout_secs, 3);
+        assert_eq!(config.db_idle_timeout_secs, 60);
+        assert_eq!(config.db_max_lifetime_secs, 900);
+        assert_eq!((config.retry_attempts, config.retry_delay_ms), (0, 10));
+
+        let options = config.pool_options();
+        assert_eq!(options.get_max_connections(), 50);
+        assert_eq!(options.get_min_connections(), 5);
+        assert_eq!(options.get_acquire_timeout(), Duration::from_secs(3));
+        assert_eq!(options.get_idle_timeout(), Some(Duration::from_secs(60)));
+        assert_eq!(options.get_max_lifetime(), Some(Duration::from_secs(900)));
+    }
+
+    #[test]
+    fn invalid_pool_settings_are_rejected() {
+        let err = config_with(&[("DB_MAX_CONNECTIONS", "many")]).unwrap_err();
+        assert_eq!(err.to_string(), "DB_MAX_CONNECTIONS must be a valid number");
+        assert!(config_with(&[("DB_IDLE_TIMEOUT_SECS", "-1")]).is_err());
+        assert!(config_with(&[("DB_MAX_CONNECTIONS", "0")]).is_err());
+        let err = config_with(&[("DB_MAX_CONNECTIONS", "4"), ("DB_MIN_CONNECTIONS", "5")]).unwrap_err();
+        assert_eq!(err.to_string(), "DB_MIN_CONNECTIONS must not exceed DB_MAX_CONNECTIONS");
+    }
+
+    #[test]
+    fn metrics_capacity_takes_the_same_range_as_the_api() {
+        assert_eq!(config_with(&[("METRICS_CAPACITY", "10000000")]).unwrap().metrics_capacity, 10_000_000);
+        for capacity in ["0", "10000001"] {
+            let err = config_with(&[("METRICS_CAPACITY", capacity)]).unwrap_err();
+            assert_eq!(err.to_string(), "METRICS_CAPACITY must be between 1 and 10000000", "{}", capacity);
+        }
+    }
+
+    #[test]
+    fn database_url_is_required_and_never_serialized() {
+        let err = Config::from_lookup(|_| None).unwrap_err();
+        assert_eq!(err.to_string(), "DATABASE_URL must be set");
+
+        let json = serde_json::to_value(config_with(&[]).unwrap()).unwrap();
+        assert!(json.get("database_url").is_none());
+        assert_eq!(json["db_max_connections"], 20);
+        assert_eq!(json["metrics_retention_secs"], 86_400);
+        assert_eq!(json["metrics_persist_interval_secs"], 30);
+        assert_eq!((json["request_timeout_secs"].as_u64(), json["benchmark_timeout_secs"].as_u64()), (Some(120), Some(1800)));
     }
 }
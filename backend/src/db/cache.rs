@@ -0,0 +1,221 @@
+use std::{
+    num::NonZeroUsize,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use lru::LruCache;
+use serde::Serialize;
+use sqlx::PgPool;
+use tokio::sync::RwLock;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::{error::AppResult, models::Product};
+
+/// Counters behind `GET /api/admin/cache/stats`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+pub struct ProductCacheStats {
+    pub size: usize,
+    pub capacity: usize,
+    pub hit_count: u64,
+    pub miss_count: u64,
+    /// Entries dropped to make room, or because their product was written
+    pub evictions: u64,
+}
+
+struct Entries {
+    lru: LruCache<Uuid, (Product, Instant)>,
+    /// Bumped by every invalidation, so a read that started before one
+    /// doesn't put the old row back
+    generation: u64,
+}
+
+/// LRU cache of live products by id, in front of `GET /api/products/:id`.
+/// Handlers that write a product evict it; a capacity of 0 turns caching off.
+#[derive(Clone)]
+pub struct ProductCache {
+    entries: Option<Arc<RwLock<Entries>>>,
+    hits: Arc<AtomicU64>,
+    misses: Arc<AtomicU64>,
+    evictions: Arc<AtomicU64>,
+}
+
+impl ProductCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: NonZeroUsize::new(capacity)
+                .map(|cap| Arc::new(RwLock::new(Entries { lru: LruCache::new(cap), generation: 0 }))),
+            hits: Arc::new(AtomicU64::new(0)),
+            misses: Arc::new(AtomicU64::new(0)),
+            evictions: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// The cached product and how long ago it was read from the DB, counting
+    /// a hit or a miss.
+    async fn get(&self, id: Uuid) -> Option<(Product, Duration)> {
+        let cached = match &self.entries {
+            Some(entries) => entries.write().await.lru.get(&id).map(|(p, at)| (p.clone(), at.elapsed())),
+            None => None,
+        };
+        let counter = if cached.is_some() { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+        cached
+    }
+
+    async fn generation(&self) -> u64 {
+        match &self.entries {
+            Some(entries) => entries.read().await.generation,
+            None => 0,
+        }
+    }
+
+    /// Caches `product` unless something was invalidated since `generation`.
+    async fn insert(&self, product: Product, generation: u64) {
+        let Some(entries) = &self.entries else { return };
+        let mut entries = entries.write().await;
+        if entries.generation != generation {
+            return;
+        }
+        let id = product.id;
+        if let Some((evicted, _)) = entries.lru.push(id, (product, Instant::now())) {
+            if evicted != id {
+                self.evictions.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Drops `ids` after their products were updated or deleted.
+    pub async fn invalidate(&self, ids: impl IntoIterator<Item = Uuid>) {
+        let Some(entries) = &self.entries else { return };
+        let mut entries = entries.write().await;
+        entries.generation += 1;
+        for id in ids {
+            if entries.lru.pop(&id).is_some() {
+                self.evictions.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Drops every entry, for writes that may touch any product.
+    pub async fn clear(&self) {
+        let Some(entries) = &self.entries else { return };
+        let mut entries = entries.write().await;
+        entries.generation += 1;
+        self.evictions.fetch_add(entries.lru.len() as u64, Ordering::Relaxed);
+        entries.lru.clear();
+    }
+
+    pub async fn stats(&self) -> ProductCacheStats {
+        let (size, capacity) = match &self.entries {
+            Some(entries) => {
+                let entries = entries.read().await;
+                (entries.lru.len(), entries.lru.cap().get())
+            }
+            None => (0, 0),
+        };
+        ProductCacheStats {
+            size,
+            capacity,
+            hit_count: self.hits.load(Ordering::Relaxed),
+            miss_count: self.misses.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Name suggestions by lowercased prefix and limit, behind
+/// `GET /api/products/search/suggest`. Entries aren't evicted on writes, so a
+/// new or renamed product can take [`NAME_SUGGESTION_TTL`] to show up.
+pub type NameSuggestionCache = moka::sync::Cache<String, Vec<String>>;
+
+pub const NAME_SUGGESTION_TTL: Duration = Duration::from_secs(5);
+
+pub fn name_suggestion_cache() -> NameSuggestionCache {
+    moka::sync::Cache::builder().max_capacity(10_000).time_to_live(NAME_SUGGESTION_TTL).build()
+}
+
+/// [`fetch_product_by_id`](super::fetch_product_by_id) through `cache`. The
+/// age of the cached copy comes back on a hit; `None` means the row was just
+/// read. Writes keep calling the uncached fetch, since their version checks
+/// need the current row.
+pub async fn fetch_product_cached(pool: &PgPool, cache: &ProductCache, id: Uuid) -> AppResult<(Product, Option<Duration>)> {
+    if let Some((product, age)) = cache.get(id).await {
+        return Ok((product, Some(age)));
+    }
+    let generation = cache.generation().await;
+    let product = super::fetch_product_by_id(pool, id).await?;
+    cache.insert(product.clone(), generation).await;
+    Ok((product, None))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn product(name: &str) -> Product {
+        Product {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            description: None,
+            price_cents: 100,
+            quantity: 1,
+            category: "Books".to_string(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            version: 1,
+        }
+    }
+
+    #[tokio::test]
+    async fn least_recently_used_entry_is_evicted_at_capacity() {
+        let cache = ProductCache::new(2);
+        let (a, b, c) = (product("A"), product("B"), product("C"));
+        for p in [&a, &b] {
+            cache.insert(p.clone(), 0).await;
+        }
+        assert!(cache.get(a.id).await.is_some(), "touching A makes B the oldest");
+        cache.insert(c.clone(), 0).await;
+
+        assert!(cache.get(b.id).await.is_none());
+        assert_eq!(cache.get(c.id).await.unwrap().0.name, "C");
+        let stats = cache.stats().await;
+        assert_eq!(
+            stats,
+            ProductCacheStats { size: 2, capacity: 2, hit_count: 2, miss_count: 1, evictions: 1 }
+        );
+    }
+
+    #[tokio::test]
+    async fn invalidation_drops_entries_and_blocks_older_reads() {
+        let cache = ProductCache::new(10);
+        let p = product("Lamp");
+        cache.insert(p.clone(), 0).await;
+
+        // A read that began before the write must not re-cache the old row
+        let before_write = cache.generation().await;
+        cache.invalidate([p.id]).await;
+        cache.insert(p.clone(), before_write).await;
+        assert!(cache.get(p.id).await.is_none());
+
+        cache.insert(p.clone(), cache.generation().await).await;
+        cache.clear().await;
+        let stats = cache.stats().await;
+        assert_eq!((stats.size, stats.evictions), (0, 2));
+    }
+
+    #[tokio::test]
+    async fn zero_capacity_disables_caching() {
+        let cache = ProductCache::new(0);
+        let p = product("Rug");
+        cache.insert(p.clone(), 0).await;
+        assert!(cache.get(p.id).await.is_none());
+        let stats = cache.stats().await;
+        assert_eq!((stats.size, stats.capacity, stats.miss_count), (0, 0, 1));
+    }
+}
@@ -0,0 +1,141 @@
+use std::{future::Future, sync::Arc, time::Duration, time::Instant};
+
+use tokio::sync::RwLock;
+use tracing::warn;
+
+use crate::{
+    config::Config,
+    error::{AppError, AppResult},
+    metrics::MetricsStore,
+};
+
+/// How [`retry_with_backoff`] retries calls that timed out waiting for a
+/// pooled connection. Retry `n` waits `base_delay * 2^(n - 1)`, so the
+/// defaults wait 50, 100 and 200 ms.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt; 0 disables retrying
+    pub attempts: u32,
+    pub base_delay: Duration,
+    /// Where each retry is recorded as a `db_retry` entry
+    metrics: Option<Arc<RwLock<MetricsStore>>>,
+}
+
+impl RetryPolicy {
+    pub fn new(attempts: u32, base_delay: Duration) -> Self {
+        Self { attempts, base_delay, metrics: None }
+    }
+
+    pub fn from_config(config: &Config) -> Self {
+        Self::new(config.retry_attempts, Duration::from_millis(config.retry_delay_ms))
+    }
+
+    pub fn with_metrics(self, metrics: Arc<RwLock<MetricsStore>>) -> Self {
+        Self { metrics: Some(metrics), ..self }
+    }
+
+    /// The wait before retry `retry` (1-based).
+    pub fn delay(&self, retry: u32) -> Duration {
+        self.base_delay.saturating_mul(2u32.saturating_pow(retry.saturating_sub(1)))
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new(3, Duration::from_millis(50))
+    }
+}
+
+/// Runs `f` until it returns anything other than
+/// [`AppError::DatabaseTimeout`], or `policy.attempts` retries are used up.
+/// Other errors are returned straight away: only pool exhaustion is expected
+/// to clear up on its own.
+pub async fn retry_with_backoff<T, F, Fut>(policy: &RetryPolicy, operation: &str, mut f: F) -> AppResult<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = AppResult<T>>,
+{
+    let mut retry = 0;
+    loop {
+        let start = Instant::now();
+        match f().await {
+            Err(AppError::DatabaseTimeout(msg)) if retry < policy.attempts => {
+                retry += 1;
+                let delay = policy.delay(retry);
+                warn!(operation, retry, delay_ms = delay.as_millis() as u64, error = %msg, "Database pool timed out, retrying");
+                if let Some(metrics) = &policy.metrics {
+                    metrics.write().await.record_raw_with_notes(
+                        "db_retry",
+                        "DB",
+                        start.elapsed().as_nanos() as u64,
+                        retry as usize,
+                        operation,
+                    );
+                }
+                tokio::time::sleep(delay).await;
+            }
+            result => return result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /// Fails with a pool timeout `failures` times, then succeeds.
+    fn flaky(failures: u32, calls: &AtomicU32) -> impl Future<Output = AppResult<u32>> + '_ {
+        let call = calls.fetch_add(1, Ordering::SeqCst) + 1;
+        async move {
+            if call <= failures {
+                Err(AppError::DatabaseTimeout("pool timed out while waiting for an open connection".to_string()))
+            } else {
+                Ok(call)
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn pool_timeouts_are_retried_until_the_call_succeeds() {
+        let metrics = Arc::new(RwLock::new(MetricsStore::new()));
+        let policy = RetryPolicy::new(3, Duration::from_millis(1)).with_metrics(metrics.clone());
+        let calls = AtomicU32::new(0);
+
+        let result = retry_with_backoff(&policy, "fetch_all_products", || flaky(2, &calls)).await;
+        assert_eq!(result.unwrap(), 3, "the third attempt succeeds");
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        let metrics = metrics.read().await;
+        let retries: Vec<(&str, usize, &str)> =
+            metrics.entries.iter().map(|e| (e.operation.as_str(), e.item_count, e.notes.as_deref().unwrap_or(""))).collect();
+        assert_eq!(retries, [("db_retry", 1, "fetch_all_products"), ("db_retry", 2, "fetch_all_products")]);
+    }
+
+    #[tokio::test]
+    async fn retrying_stops_after_the_configured_attempts() {
+        let calls = AtomicU32::new(0);
+        let policy = RetryPolicy::new(2, Duration::from_millis(1));
+        let err = retry_with_backoff(&policy, "insert_product", || flaky(10, &calls)).await.unwrap_err();
+        assert!(matches!(err, AppError::DatabaseTimeout(_)), "{:?}", err);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        // Anything but a pool timeout is returned at once
+        let calls = AtomicU32::new(0);
+        let err = retry_with_backoff(&policy, "insert_product", || {
+            calls.fetch_add(1, Ordering::SeqCst);
+            async { Err::<(), _>(AppError::NotFound("gone".to_string())) }
+        })
+        .await
+        .unwrap_err();
+        assert!(matches!(err, AppError::NotFound(_)));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn delays_double_from_the_base_delay() {
+        let policy = RetryPolicy::default();
+        let delays: Vec<u128> = (1..=3).map(|n| policy.delay(n).as_millis()).collect();
+        assert_eq!(delays, [50, 100, 200]);
+    }
+}
@@ -3,13 +3,20 @@ use axum::{
     response::{IntoResponse, Response},
     Json,
 };
-use serde_json::json;
+use serde::Serialize;
 use thiserror::Error;
+use utoipa::ToSchema;
+
+use crate::models::{join_validation_errors, ValidationError};
 
 #[derive(Debug, Error)]
 pub enum AppError {
     #[error("Database error: {0}")]
-    Database(#[from] sqlx::Error),
+    Database(sqlx::Error),
+
+    /// No pooled connection freed up in time; see `db::retry_with_backoff`
+    #[error("Database timeout: {0}")]
+    DatabaseTimeout(String),
 
     #[error("Not found: {0}")]
     NotFound(String),
@@ -17,27 +24,75 @@ pub enum AppError {
     #[error("Bad request: {0}")]
     BadRequest(String),
 
+    #[error("Conflict: {0}")]
+    Conflict(String),
+
+    /// A capacity limit was hit; retrying later may succeed
+    #[error("Service unavailable: {0}")]
+    ServiceUnavailable(String),
+
+    /// Every rule the request broke, not just the first
+    #[error("Validation failed: {}", join_validation_errors(.0))]
+    Validation(Vec<ValidationError>),
+
     #[error("Internal error: {0}")]
     Internal(#[from] anyhow::Error),
 }
 
+impl From<sqlx::Error> for AppError {
+    fn from(e: sqlx::Error) -> Self {
+        match e {
+            sqlx::Error::PoolTimedOut => AppError::DatabaseTimeout(e.to_string()),
+            e => AppError::Database(e),
+        }
+    }
+}
+
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
         let (status, message) = match &self {
+            // e.g. a second live product with the same name
+            AppError::Database(sqlx::Error::Database(db)) if db.is_unique_violation() => {
+                (StatusCode::CONFLICT, db.message().to_string())
+            }
+            // e.g. a write that breaks `price_cents >= 0` or `products_category_known`
+            AppError::Database(sqlx::Error::Database(db)) if db.is_check_violation() => {
+                (StatusCode::UNPROCESSABLE_ENTITY, db.message().to_string())
+            }
             AppError::Database(e) => {
                 tracing::error!("Database error: {}", e);
                 (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
             }
+            AppError::DatabaseTimeout(msg) => {
+                tracing::warn!("Database timeout: {}", msg);
+                (StatusCode::SERVICE_UNAVAILABLE, msg.clone())
+            }
             AppError::NotFound(msg) => (StatusCode::NOT_FOUND, msg.clone()),
             AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg.clone()),
+            AppError::Conflict(msg) => (StatusCode::CONFLICT, msg.clone()),
+            AppError::ServiceUnavailable(msg) => (StatusCode::SERVICE_UNAVAILABLE, msg.clone()),
+            AppError::Validation(errors) => (StatusCode::UNPROCESSABLE_ENTITY, join_validation_errors(errors)),
             AppError::Internal(e) => {
                 tracing::error!("Internal error: {}", e);
                 (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
             }
         };
 
-        (status, Json(json!({ "error": message }))).into_response()
+        let errors = match self {
+            AppError::Validation(errors) => Some(errors),
+            _ => None,
+        };
+        (status, Json(ErrorResponse { error: message, errors })).into_response()
     }
 }
 
+/// Body of every error response.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ErrorResponse {
+    pub error: String,
+    /// Every broken rule — only on 422 Unprocessable Entity
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub errors: Option<Vec<ValidationError>>,
+}
+
 pub type AppResult<T> = Result<T, AppError>;
@@ -0,0 +1,122 @@
+use std::fmt;
+
+use axum::{
+    async_trait,
+    body::{Body, Bytes},
+    extract::{FromRequest, FromRequestParts, Request},
+    http::request::Parts,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::de::DeserializeOwned;
+use uuid::Uuid;
+
+use crate::error::AppError;
+
+/// Per-request correlation ID, assigned by [`crate::middleware::RequestIdLayer`]
+/// and echoed back in the `X-Request-Id` response header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RequestId(pub Uuid);
+
+impl RequestId {
+    pub fn new() -> Self {
+        Self(Uuid::new_v4())
+    }
+}
+
+impl Default for RequestId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Display for RequestId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for RequestId {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<RequestId>()
+            .copied()
+            .ok_or_else(|| anyhow::anyhow!("RequestIdLayer is not installed on this router").into())
+    }
+}
+
+/// JSON body that may be omitted entirely. An empty body yields
+/// `T::default()`; anything else goes through [`Json`], so a malformed or
+/// mistyped body is still rejected instead of silently replaced.
+#[derive(Debug, Clone, Default)]
+pub struct JsonOrDefault<T>(pub T);
+
+#[async_trait]
+impl<T, S> FromRequest<S> for JsonOrDefault<T>
+where
+    T: DeserializeOwned + Default,
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let (parts, body) = req.into_parts();
+        let bytes = Bytes::from_request(Request::from_parts(parts.clone(), body), state)
+            .await
+            .map_err(IntoResponse::into_response)?;
+        if bytes.is_empty() {
+            return Ok(Self(T::default()));
+        }
+        let Json(value) = Json::<T>::from_request(Request::from_parts(parts, Body::from(bytes)), state)
+            .await
+            .map_err(IntoResponse::into_response)?;
+        Ok(Self(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{header, StatusCode};
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, Default, Deserialize, PartialEq)]
+    struct Params {
+        size: Option<u32>,
+    }
+
+    async fn extract(content_type: Option<&str>, body: &'static str) -> Result<Params, StatusCode> {
+        let mut builder = Request::builder().method("POST").uri("/");
+        if let Some(ct) = content_type {
+            builder = builder.header(header::CONTENT_TYPE, ct);
+        }
+        let req = builder.body(Body::from(body)).unwrap();
+        JsonOrDefault::<Params>::from_request(req, &())
+            .await
+            .map(|JsonOrDefault(p)| p)
+            .map_err(|r| r.status())
+    }
+
+    #[tokio::test]
+    async fn empty_body_yields_default() {
+        assert_eq!(extract(None, "").await, Ok(Params::default()));
+        assert_eq!(extract(Some("application/json"), "").await, Ok(Params::default()));
+    }
+
+    #[tokio::test]
+    async fn well_formed_body_is_parsed() {
+        assert_eq!(extract(Some("application/json"), r#"{"size":3}"#).await, Ok(Params { size: Some(3) }));
+    }
+
+    #[tokio::test]
+    async fn bad_bodies_are_rejected_not_defaulted() {
+        assert_eq!(extract(Some("application/json"), "{not json").await, Err(StatusCode::BAD_REQUEST));
+        assert_eq!(extract(Some("application/json"), r#"{"size":"big"}"#).await, Err(StatusCode::UNPROCESSABLE_ENTITY));
+        assert_eq!(extract(None, r#"{"size":3}"#).await, Err(StatusCode::UNSUPPORTED_MEDIA_TYPE));
+    }
+}
@@ -0,0 +1,400 @@
+use std::time::Instant;
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
+use serde::Deserialize;
+use tracing::info;
+use utoipa::ToSchema;
+
+use crate::{
+    db::{self, ProductCacheStats},
+    error::{AppError, AppResult},
+    extract::RequestId,
+    models::{AuditLogParams, PoolConnections},
+    AppState,
+};
+
+/// Body of `POST /api/admin/explain`.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ExplainRequest {
+    /// One statement starting with an entry of `ALLOWED_EXPLAIN_TEMPLATES`,
+    /// followed only by `WHERE`, `ORDER BY`, `LIMIT`, `OFFSET` or `FOR`
+    /// clauses without subqueries or function calls
+    pub sql: String,
+    /// Values for `$1`, `$2`, ... in order
+    #[serde(default)]
+    #[schema(value_type = Vec<Object>)]
+    pub params: Vec<serde_json::Value>,
+}
+
+// ── GET /api/admin/audit-log ──────────────────────────────────────────────────
+
+/// Recorded POST/PUT/DELETE calls, newest first. Entries are only written by
+/// builds with the `audit` feature; otherwise the list stays empty.
+#[utoipa::path(
+    get,
+    path = "/api/admin/audit-log",
+    tag = "admin",
+    params(AuditLogParams),
+    responses(
+        (status = 200, description = "`data`: [AuditLogEntry], newest first, plus `audit_enabled`", body = Object),
+    )
+)]
+pub async fn audit_log(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    Query(params): Query<AuditLogParams>,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    let limit = params.limit.unwrap_or(50).clamp(1, 1_000);
+    let method = params.method.as_deref().map(str::to_ascii_uppercase);
+
+    let start = Instant::now();
+    let entries = db::fetch_audit_log(&state.db, limit, method.as_deref()).await?;
+    let elapsed = start.elapsed();
+
+    info!(request_id = %request_id, count = entries.len(), method = ?method, "Fetched audit log");
+
+    state.metrics.write().await.record_raw(
+        "db_query:audit_log",
+        "DB",
+        elapsed.as_nanos() as u64,
+        entries.len(),
+    );
+
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "data": entries,
+            "count": entries.len(),
+            "audit_enabled": cfg!(feature = "audit"),
+            "query_time_ms": elapsed.as_secs_f64() * 1000.0,
+        })),
+    ))
+}
+
+// ── GET /api/admin/config ─────────────────────────────────────────────────────
+
+/// The configuration the server started with. `database_url` is left out
+/// because it carries the DB password.
+#[utoipa::path(
+    get,
+    path = "/api/admin/config",
+    tag = "admin",
+    responses(
+        (status = 200, description = "`config`: the startup configuration, without `database_url`", body = Object),
+    )
+)]
+pub async fn config(
+    State(state): State<AppState>,
+    request_id: RequestId,
+) -> (StatusCode, Json<serde_json::Value>) {
+    info!(request_id = %request_id, "Fetched config");
+    (StatusCode::OK, Json(serde_json::json!({ "config": *state.config })))
+}
+
+// ── GET /api/admin/pool ───────────────────────────────────────────────────────
+
+/// Current connection pool occupancy.
+#[utoipa::path(
+    get,
+    path = "/api/admin/pool",
+    tag = "admin",
+    responses(
+        (status = 200, description = "Pool `size`, `idle`, `active`, `max`, `min` and `closed`", body = Object),
+    )
+)]
+pub async fn pool(
+    State(state): State<AppState>,
+    request_id: RequestId,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let pool = PoolConnections::of(&state.db);
+    info!(request_id = %request_id, open = pool.open, idle = pool.idle, "Fetched pool state");
+    (
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "size": pool.open,
+            "idle": pool.idle,
+            "active": pool.active,
+            "max": pool.max,
+            "min": state.db.options().get_min_connections(),
+            "closed": state.db.is_closed(),
+        })),
+    )
+}
+
+// ── GET /api/admin/cache/stats ────────────────────────────────────────────────
+
+/// Occupancy and hit rate of the `GET /api/products/:id` cache.
+#[utoipa::path(
+    get,
+    path = "/api/admin/cache/stats",
+    tag = "admin",
+    responses(
+        (status = 200, description = "ProductCacheStats", body = ProductCacheStats),
+    )
+)]
+pub async fn cache_stats(
+    State(state): State<AppState>,
+    request_id: RequestId,
+) -> (StatusCode, Json<ProductCacheStats>) {
+    let stats = state.product_cache.stats().await;
+    info!(request_id = %request_id, size = stats.size, hits = stats.hit_count, misses = stats.miss_count, "Fetched cache stats");
+    (StatusCode::OK, Json(stats))
+}
+
+// ── DELETE /api/admin/reset ───────────────────────────────────────────────────
+
+/// Deletes every product (devolutions cascade) and clears the sets, the
+/// product cache and the metrics. Off unless `ENABLE_FULL_RESET=true`;
+/// `DELETE /api/benchmark/sets` and `DELETE /api/metrics` clear the
+/// in-memory parts on their own.
+#[utoipa::path(
+    delete,
+    path = "/api/admin/reset",
+    tag = "admin",
+    responses(
+        (status = 200, description = "`deleted_products` count, plus `sets_cleared` and `metrics_cleared`", body = Object),
+        (status = 404, description = "The endpoint is disabled", body = ErrorResponse),
+    )
+)]
+pub async fn reset_all(
+    State(state): State<AppState>,
+    request_id: RequestId,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    if !state.config.enable_full_reset {
+        return Err(AppError::NotFound("full reset is disabled; set ENABLE_FULL_RESET=true".to_string()));
+    }
+
+    // 1. Wipe DB (devolutions cascade automatically)
+    let rows_deleted = db::delete_all_products(&state.db).await?;
+
+    // 2. Clear in-memory sets + last benchmark report, and cached products
+    state.sets.write().await.reset();
+    state.product_cache.clear().await;
+
+    // 3. Clear accumulated metrics
+    state.metrics.write().await.clear();
+
+    info!(request_id = %request_id, rows_deleted, "Full reset: DB, sets, and metrics cleared");
+
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "deleted_products": rows_deleted,
+            "sets_cleared": true,
+            "metrics_cleared": true,
+        })),
+    ))
+}
+
+// ── POST /api/admin/explain ───────────────────────────────────────────────────
+
+/// `EXPLAIN (ANALYZE, FORMAT JSON)` for one of the service's own query shapes,
+/// for index tuning without direct DB access. The statement really runs, in a
+/// read-only transaction that is rolled back. Off unless
+/// `ENABLE_EXPLAIN_ENDPOINT=true`.
+#[utoipa::path(
+    post,
+    path = "/api/admin/explain",
+    tag = "admin",
+    request_body = ExplainRequest,
+    responses(
+        (status = 200, description = "`plan`: Postgres' JSON plan, plus the matched `template`, `execution_time_ms` and `planning_time_ms`", body = Object),
+        (status = 400, description = "SQL outside the allowed templates, more than one statement, an unsupported param, or a failing query (SQLSTATE only)", body = ErrorResponse),
+        (status = 404, description = "The endpoint is disabled", body = ErrorResponse),
+    )
+)]
+pub async fn explain(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    Json(body): Json<ExplainRequest>,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    if !state.config.enable_explain_endpoint {
+        return Err(AppError::NotFound("EXPLAIN endpoint is disabled; set ENABLE_EXPLAIN_ENDPOINT=true".to_string()));
+    }
+    if body.sql.contains(';') {
+        return Err(AppError::BadRequest("sql must be a single statement without ';'".to_string()));
+    }
+    let template = db::explain_template(&body.sql).ok_or_else(|| {
+        AppError::BadRequest(format!(
+            "sql must start with one of the allowed templates and only add WHERE, ORDER BY, LIMIT, OFFSET or FOR \
+             clauses without subqueries, other tables or function calls: {}",
+            db::ALLOWED_EXPLAIN_TEMPLATES.join(" | ")
+        ))
+    })?;
+
+    let start = Instant::now();
+    let plan = db::explain_analyze(&state.db, &body.sql, &body.params).await?;
+    let elapsed = start.elapsed();
+
+    state.metrics.write().await.record_raw("db_query:explain", "DB", elapsed.as_nanos() as u64, 1);
+
+    let execution_time_ms = plan[0]["Execution Time"].as_f64();
+    info!(request_id = %request_id, template, execution_time_ms, "Explained query");
+
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "template": template,
+            "plan": plan,
+            "execution_time_ms": execution_time_ms,
+            "planning_time_ms": plan[0]["Planning Time"].as_f64(),
+        })),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::offline_state;
+    use std::sync::Arc;
+
+    fn explain_enabled(state: AppState) -> AppState {
+        let config = crate::config::Config { enable_explain_endpoint: true, ..(*state.config).clone() };
+        AppState { config: Arc::new(config), ..state }
+    }
+
+    fn request(sql: &str, params: Vec<serde_json::Value>) -> Json<ExplainRequest> {
+        Json(ExplainRequest { sql: sql.to_string(), params })
+    }
+
+    #[tokio::test]
+    async fn explain_is_off_by_default_and_only_takes_allowed_templates() {
+        let sql = "SELECT id FROM products WHERE deleted_at IS NULL";
+        let err = explain(State(offline_state()), RequestId::new(), request(sql, vec![])).await.unwrap_err();
+        assert!(matches!(err, AppError::NotFound(_)));
+
+        let state = explain_enabled(offline_state());
+        for sql in [
+            "DELETE FROM products",
+            "SELECT * FROM pg_shadow",
+            "SELECT id FROM products; DROP TABLE products",
+            "SELECT id FROM products WHERE deleted_at IS NULL AND pg_sleep(1) IS NOT NULL",
+        ] {
+            let err = explain(State(state.clone()), RequestId::new(), request(sql, vec![])).await.unwrap_err();
+            assert!(matches!(err, AppError::BadRequest(_)), "{}", sql);
+        }
+        assert_eq!(db::explain_template("select  id\n  FROM products"), Some("SELECT id FROM products"));
+    }
+
+    #[test]
+    fn explain_templates_only_take_filters_ordering_and_paging_after_the_prefix() {
+        for rest in [
+            "",
+            " WHERE category = ANY($1) AND (price_cents > $2 OR quantity = 0)",
+            " WHERE name ILIKE 'pg_sleep(1)%' ORDER BY created_at DESC, id LIMIT $1 OFFSET $2",
+            " WHERE id = $1::uuid FOR UPDATE",
+        ] {
+            let sql = format!("SELECT id FROM products{}", rest);
+            assert_eq!(db::explain_template(&sql), Some("SELECT id FROM products"), "{}", sql);
+        }
+        for rest in [
+            " WHERE deleted_at IS NULL AND pg_sleep(1) IS NOT NULL",
+            " WHERE pg_catalog.pg_terminate_backend (1)",
+            " WHERE id IN (SELECT product_id FROM product_tags)",
+            " WHERE id IN (TABLE product_tags)",
+            ", pg_authid",
+            "_archive",
+            " JOIN tags t ON true",
+            " WHERE id = $1 UNION SELECT 1",
+            " WHERE \"pg_sleep\"(1) IS NULL",
+            " WHERE name = $$x$$",
+            " WHERE id = $1 -- comment",
+            " WHERE name = 'unterminated",
+        ] {
+            let sql = format!("SELECT id FROM products{}", rest);
+            assert_eq!(db::explain_template(&sql), None, "{}", sql);
+        }
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn explain_returns_the_plan_and_execution_time(pool: sqlx::PgPool) {
+        crate::seed::seed_products(&pool, 20).await.unwrap();
+        let state = explain_enabled(AppState { db: pool, ..offline_state() });
+
+        let sql = "SELECT id, name, description, price_cents, quantity, category, created_at, updated_at, version
+                   FROM products WHERE id = $1 AND deleted_at IS NULL";
+        let params = vec![serde_json::json!(uuid::Uuid::new_v4())];
+        let (_, Json(body)) = explain(State(state.clone()), RequestId::new(), request(sql, params)).await.unwrap();
+        assert!(body["execution_time_ms"].as_f64().unwrap() >= 0.0);
+        assert!(body["plan"][0]["Plan"]["Node Type"].is_string());
+        assert_eq!(body["template"], db::ALLOWED_EXPLAIN_TEMPLATES[0]);
+
+        let sql = "SELECT id FROM products WHERE category = ANY($1) AND price_cents > $2";
+        let params = vec![serde_json::json!(["Books", "Toys & Games"]), serde_json::json!(100)];
+        let (_, Json(body)) = explain(State(state.clone()), RequestId::new(), request(sql, params)).await.unwrap();
+        assert_eq!(body["template"], "SELECT id FROM products");
+
+        let sql = format!("{} WHERE d.id = $1", db::ALLOWED_EXPLAIN_TEMPLATES[5]);
+        let params = vec![serde_json::json!(uuid::Uuid::new_v4())];
+        let (_, Json(body)) = explain(State(state.clone()), RequestId::new(), request(&sql, params)).await.unwrap();
+        assert_eq!(body["template"], db::ALLOWED_EXPLAIN_TEMPLATES[5]);
+
+        // Runs read-only, so row locks (like any write) are refused
+        let err = explain(State(state.clone()), RequestId::new(), request("SELECT id FROM products FOR UPDATE", vec![]))
+            .await
+            .unwrap_err();
+        assert!(matches!(&err, AppError::BadRequest(m) if m == "query failed (SQLSTATE 25006)"), "{:?}", err);
+        let err = explain(State(state), RequestId::new(), request("SELECT id FROM products WHERE nope = 1", vec![]))
+            .await
+            .unwrap_err();
+        // The undefined-column message would name the column; only the code comes back
+        assert!(matches!(&err, AppError::BadRequest(m) if m == "query failed (SQLSTATE 42703)"), "{:?}", err);
+    }
+
+    #[tokio::test]
+    async fn config_hides_the_database_url() {
+        let (status, Json(body)) = config(State(offline_state()), RequestId::new()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body["config"].get("database_url").is_none());
+        assert_eq!(body["config"]["db_max_connections"], 20);
+        assert!(!body.to_string().contains("postgres://"));
+    }
+
+    #[tokio::test]
+    async fn pool_reports_an_unused_pool_as_empty() {
+        let state = offline_state();
+        let (_, Json(body)) = pool(State(state.clone()), RequestId::new()).await;
+        assert_eq!((body["size"].as_u64(), body["idle"].as_u64(), body["active"].as_u64()), (Some(0), Some(0), Some(0)));
+        assert_eq!(body["max"], state.db.options().get_max_connections());
+        assert_eq!(body["closed"], false);
+    }
+
+    #[tokio::test]
+    async fn cache_stats_start_empty_at_the_configured_capacity() {
+        let state = AppState { product_cache: db::ProductCache::new(25), ..offline_state() };
+        let (status, Json(stats)) = cache_stats(State(state), RequestId::new()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            serde_json::to_value(stats).unwrap(),
+            serde_json::json!({ "size": 0, "capacity": 25, "hit_count": 0, "miss_count": 0, "evictions": 0 })
+        );
+    }
+
+    #[tokio::test]
+    async fn full_reset_is_not_found_unless_enabled() {
+        let err = reset_all(State(offline_state()), RequestId::new()).await.unwrap_err();
+        assert!(matches!(&err, AppError::NotFound(m) if m.contains("ENABLE_FULL_RESET")), "{:?}", err);
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn enabled_full_reset_clears_the_database_sets_and_metrics(pool: sqlx::PgPool) {
+        let products = crate::seed::seed_products(&pool, 10).await.unwrap();
+        let config = crate::config::Config { enable_full_reset: true, ..(*offline_state().config).clone() };
+        let state = AppState { db: pool, config: Arc::new(config), ..offline_state() };
+        state.sets.write().await.sync_from_db(&products);
+        state.metrics.write().await.record_raw("lookup", "HashSet", 1, 1);
+
+        let (status, Json(body)) = reset_all(State(state.clone()), RequestId::new()).await.unwrap();
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["deleted_products"], 10);
+        assert_eq!(db::count_products(&state.db).await.unwrap(), 0);
+        assert_eq!(state.sets.read().await.hash_set.len(), 0);
+        assert!(state.metrics.read().await.entries.is_empty());
+    }
+}
@@ -1,34 +1,247 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::time::Instant;
 
 use axum::{
-    extract::{Query, State},
+    extract::{Path, Query, State},
     http::{header, StatusCode},
     response::Response,
     Json,
 };
+use rand::seq::SliceRandom;
 use serde::Deserialize;
-use tracing::info;
+use tracing::{info, warn};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
 
-use crate::{db, error::AppResult, seed, AppState};
+use crate::{
+    db,
+    error::AppResult,
+    extract::{JsonOrDefault, RequestId},
+    models::{join_validation_errors, Product, SampleStrategy, SetSnapshot},
+    seed,
+    sets::{self, BenchmarkReport, SetBenchmarkConfig},
+    util::parse_comma_list,
+    AppState,
+};
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct SeedParams {
     /// Number of products to seed (default: 1000, max: 50 000)
+    #[serde(alias = "seed_count")]
+    pub count: Option<usize>,
+    /// Fixed RNG seed for reproducible data; random when absent
+    pub rng_seed: Option<u64>,
+    /// Comma-separated `category:weight` pairs, e.g. `Electronics:5,Books:1`
+    /// (URL-encode `&` in names as `%26`). Weights are relative; unlisted
+    /// categories get no products. Uniform when absent.
+    #[serde(default, deserialize_with = "weight_list")]
+    #[param(value_type = Option<String>, example = "Electronics:5,Books:1")]
+    pub category_weights: Option<HashMap<String, f64>>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SeedDevolutionsParams {
+    /// Number of devolutions to seed (default: 500, max: 50 000)
+    pub count: Option<usize>,
+    /// Fixed RNG seed for reproducible data; random when absent
+    pub rng_seed: Option<u64>,
+    /// Comma-separated `reason:weight` pairs, e.g. `Changed mind:5,Missing parts:1`.
+    /// Weights are relative; unlisted reasons are never picked. Uniform when absent.
+    #[serde(default, deserialize_with = "weight_list")]
+    #[param(value_type = Option<String>, example = "Changed mind:5,Missing parts:1")]
+    pub reason_weights: Option<HashMap<String, f64>>,
+}
+
+/// `"Electronics:5, Books:1"` → `{"Electronics": 5.0, "Books": 1.0}`; a
+/// missing or blank parameter is `None`.
+fn weight_list<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Option<HashMap<String, f64>>, D::Error> {
+    let Some(pairs) = parse_comma_list(Option::<String>::deserialize(deserializer)?) else {
+        return Ok(None);
+    };
+    pairs
+        .iter()
+        .map(|pair| {
+            let (name, weight) = pair
+                .rsplit_once(':')
+                .ok_or_else(|| serde::de::Error::custom(format!("expected category:weight, got '{}'", pair)))?;
+            let weight = weight.trim().parse().map_err(|_| serde::de::Error::custom(format!("invalid weight in '{}'", pair)))?;
+            Ok((name.trim().to_string(), weight))
+        })
+        .collect::<Result<_, _>>()
+        .map(Some)
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct HistoryParams {
+    /// Number of runs to return, newest first (default: 20, max: 200)
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SyncParams {
+    /// Reload every product instead of only the changes since the last sync
+    /// (default: false; always full when there is no previous sync)
+    pub full: Option<bool>,
+}
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DiffParams {
+    /// Reload every product from the DB when any drift is found (default: false)
+    pub reconcile: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct CompareParams {
+    pub run_a: Uuid,
+    pub run_b: Uuid,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PrefixSearchParams {
+    /// Name prefix, ASCII case-insensitive; empty matches every product
+    #[serde(default)]
+    pub prefix: String,
+    /// Products listed per implementation (default: 50, max: 1000); counts are always complete
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct TopNParams {
+    /// Products to return (default: 10, max: 1000)
+    pub n: Option<usize>,
+    #[serde(default)]
+    pub by: TopNField,
+}
+
+/// What `GET /api/benchmark/sets/top-n` ranks products by.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TopNField {
+    #[default]
+    Price,
+    Quantity,
+}
+
+impl TopNField {
+    fn as_str(self) -> &'static str {
+        match self {
+            TopNField::Price => "price",
+            TopNField::Quantity => "quantity",
+        }
+    }
+
+    fn key(self) -> fn(&crate::models::Product) -> i64 {
+        match self {
+            TopNField::Price => |p| p.price_cents,
+            TopNField::Quantity => |p| i64::from(p.quantity),
+        }
+    }
+}
+
+/// Optional JSON body for `POST /api/benchmark/concurrent`.
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct ConcurrentBenchmarkParams {
+    /// Lookup tasks (default: 4, max: 64)
+    pub readers: Option<usize>,
+    /// Update tasks (default: 1, max: 64)
+    pub writers: Option<usize>,
+    /// Lock acquisitions per task (default: 10 000, max: 100 000)
+    pub ops_per_task: Option<usize>,
+    /// In-memory products to load into the shared sets (default: 10 000, max: 50 000)
+    pub seed_count: Option<usize>,
+}
+
+/// Optional JSON body for `POST /api/benchmark/lock-contention`.
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct LockContentionParams {
+    /// Lookup tasks (default: 8, max: 64)
+    pub readers: Option<usize>,
+    /// Update tasks (default: 1, max: 64)
+    pub writers: Option<usize>,
+    /// How long the tasks compete (default: 2, 1–60)
+    pub duration_secs: Option<u64>,
+    /// In-memory products to load into the shared sets (default: 10 000, max: 50 000)
+    pub seed_count: Option<usize>,
+}
+
+/// Optional JSON body for `POST /api/benchmark/serialization`.
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct SerializationParams {
+    /// Generated products to encode as one list (default: 10 000, max: 50 000)
+    pub seed_count: Option<usize>,
+}
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct CloneOverheadParams {
+    /// Generated products to clone (default: 10 000, max: 50 000)
     pub count: Option<usize>,
 }
 
+/// Body of `POST /api/benchmark/range`: the price percentiles (0–100) bounding the range.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RangeBenchmarkParams {
+    pub low_pct: f64,
+    pub high_pct: f64,
+}
+
+/// Body of `POST /api/benchmark/range-delete`: the share (0–1) of products to remove.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RangeDeleteParams {
+    pub remove_fraction: f64,
+}
+
+/// Optional JSON body for `POST /api/benchmark/run`.
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct RunBenchmarkParams {
+    pub config: Option<SetBenchmarkConfig>,
+    /// Stored run to grade this one against; adds `regression_check` to the response
+    pub baseline_run_id: Option<Uuid>,
+    /// Benchmark at most this many products instead of every product in the DB
+    pub sample_size: Option<usize>,
+    /// Which products to sample (default: `first`). `random` also shuffles
+    /// the loaded products, with or without `sample_size`, and is not
+    /// reproducible between runs.
+    pub sample_strategy: Option<SampleStrategy>,
+}
+
 // ── POST /api/seed ────────────────────────────────────────────────────────────
 
+#[utoipa::path(
+    post,
+    path = "/api/seed",
+    tag = "benchmark",
+    params(SeedParams),
+    responses(
+        (status = 200, description = "`seeded` and `total_in_db` counts, `category_counts` for the seeded products, the normalized `category_weights` and timings", body = Object),
+        (status = 400, description = "Malformed `category_weights`, an unknown category or a negative weight", body = ErrorResponse),
+    )
+)]
 pub async fn seed_data(
     State(state): State<AppState>,
+    request_id: RequestId,
     Query(params): Query<SeedParams>,
 ) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
     let count = params.count.unwrap_or(1_000).min(50_000);
+    let weights = params.category_weights.as_ref().map(seed::CategoryWeights::new).transpose()?;
 
     let start = Instant::now();
-    let products = seed::seed_products(&state.db, count).await?;
+    let products = seed::seed_products_with(&state.db, count, params.rng_seed, weights.as_ref()).await?;
     let seed_elapsed = start.elapsed();
 
+    let mut category_counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for p in &products {
+        *category_counts.entry(p.category.as_str()).or_default() += 1;
+    }
+
     // Sync sets
     let sync_start = Instant::now();
     state.sets.write().await.sync_from_db(&products);
@@ -37,8 +250,10 @@ pub async fn seed_data(
     let total_in_db = db::count_products(&state.db).await?;
 
     info!(
+        request_id = %request_id,
         seeded = products.len(),
         total_in_db,
+        rng_seed = params.rng_seed,
         seed_ms = seed_elapsed.as_millis(),
         "Seeding complete"
     );
@@ -48,23 +263,128 @@ pub async fn seed_data(
         Json(serde_json::json!({
             "seeded": products.len(),
             "total_in_db": total_in_db,
+            "rng_seed": params.rng_seed,
+            "category_counts": category_counts,
+            "category_weights": weights.as_ref().map(seed::CategoryWeights::normalized),
             "seed_time_ms": seed_elapsed.as_secs_f64() * 1000.0,
             "set_sync_time_ms": sync_elapsed.as_secs_f64() * 1000.0,
         })),
     ))
 }
 
+// ── POST /api/seed/devolutions ────────────────────────────────────────────────
+
+/// Returns against random live products, so devolution queries have data to
+/// work on. Seed products first.
+#[utoipa::path(
+    post,
+    path = "/api/seed/devolutions",
+    tag = "benchmark",
+    params(SeedDevolutionsParams),
+    responses(
+        (status = 200, description = "`seeded`, `reason_counts`, the normalized `reason_weights`, `stats` (DevolutionStats over every devolution) and timings", body = Object),
+        (status = 400, description = "No live products, malformed `reason_weights`, an unknown reason or a negative weight", body = ErrorResponse),
+    )
+)]
+pub async fn seed_devolutions(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    Query(params): Query<SeedDevolutionsParams>,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    let count = params.count.unwrap_or(500).min(50_000);
+    let weights = params.reason_weights.as_ref().map(seed::ReasonWeights::new).transpose()?;
+
+    let start = Instant::now();
+    let devolutions = seed::seed_devolutions(&state.db, count, params.rng_seed, weights.as_ref()).await?;
+    let seed_elapsed = start.elapsed();
+
+    let mut reason_counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for d in &devolutions {
+        *reason_counts.entry(d.reason.as_str()).or_default() += 1;
+    }
+
+    let stats_start = Instant::now();
+    let stats = db::fetch_devolution_stats(&state.db, None).await?;
+    let stats_elapsed = stats_start.elapsed();
+
+    info!(
+        request_id = %request_id,
+        seeded = devolutions.len(),
+        total_returns = stats.total_returns,
+        rng_seed = params.rng_seed,
+        seed_ms = seed_elapsed.as_millis(),
+        "Devolution seeding complete"
+    );
+
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "seeded": devolutions.len(),
+            "rng_seed": params.rng_seed,
+            "reason_counts": reason_counts,
+            "reason_weights": weights.as_ref().map(seed::ReasonWeights::normalized),
+            "stats": stats,
+            "seed_time_ms": seed_elapsed.as_secs_f64() * 1000.0,
+            "stats_time_ms": stats_elapsed.as_secs_f64() * 1000.0,
+        })),
+    ))
+}
+
 // ── POST /api/benchmark/run ───────────────────────────────────────────────────
 
+#[utoipa::path(
+    post,
+    path = "/api/benchmark/run",
+    tag = "benchmark",
+    request_body(content = Option<RunBenchmarkParams>, description = "Optional config, baseline run and product sample"),
+    responses(
+        (status = 200, description = "`report`: BenchmarkReport, plus `config`, `sample`, `ascii_table` and, with a baseline, `regression_check`: [RegressionAlert]", body = Object),
+        (status = 400, description = "`sample_size` is 0", body = ErrorResponse),
+        (status = 404, description = "Unknown `baseline_run_id`", body = ErrorResponse),
+        (status = 408, description = "Ran past `BENCHMARK_TIMEOUT_SECS`", body = ErrorResponse),
+    )
+)]
 pub async fn run_benchmark(
     State(state): State<AppState>,
+    request_id: RequestId,
+    JsonOrDefault(params): JsonOrDefault<RunBenchmarkParams>,
 ) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
-    info!("Starting benchmark run...");
+    let config = params.config.unwrap_or_default().normalized();
+    let strategy = params.sample_strategy.unwrap_or_default();
+    if params.sample_size == Some(0) {
+        return Err(crate::error::AppError::BadRequest("sample_size must be at least 1".to_string()));
+    }
+    info!(
+        request_id = %request_id,
+        threads = config.threads,
+        top_k = config.top_k,
+        warmup_count = config.warmup_count,
+        lookup_samples = config.lookup_samples,
+        repeat_runs = config.repeat_runs,
+        sample_size = ?params.sample_size,
+        sample_strategy = ?strategy,
+        "Starting benchmark run..."
+    );
 
-    // Load all products from DB
+    // Fail on an unknown baseline before spending time on the benchmark
+    let baseline = match params.baseline_run_id {
+        Some(id) => Some(stored_report(&state, id).await?),
+        None => None,
+    };
+
+    // Load all products from DB, or just the sample
     let db_start = Instant::now();
-    let products = db::fetch_all_products_unbounded(&state.db).await?;
+    let mut products = match params.sample_size {
+        Some(size) => {
+            let limit = i64::try_from(size).unwrap_or(i64::MAX);
+            db::fetch_product_sample(&state.db, limit, strategy).await?
+        }
+        None => db::fetch_all_products_unbounded(&state.db).await?,
+    };
     let db_elapsed = db_start.elapsed();
+    if strategy == SampleStrategy::Random {
+        products.shuffle(&mut rand::thread_rng());
+    }
 
     if products.is_empty() {
         return Ok((
@@ -76,45 +396,53 @@ pub async fn run_benchmark(
         ));
     }
 
-    info!(count = products.len(), "Loaded products for benchmark");
+    info!(request_id = %request_id, count = products.len(), "Loaded products for benchmark");
 
     let bench_start = Instant::now();
-    let report = state.sets.write().await.run_benchmark(products);
+    let report = state.sets.write().await.run_benchmark(products, &config)?;
     let bench_elapsed = bench_start.elapsed();
 
+    db::insert_benchmark_run(&state.db, &report).await?;
+
     // Persist to metrics store (appended — history is preserved across runs)
     {
         let mut metrics = state.metrics.write().await;
+        let notes = request_id.to_string();
         for result in &report.results {
-            metrics.record_raw(
+            metrics.record_raw_with_notes(
                 "insert_all",
                 &result.set_type,
                 result.insert_all.duration_ns,
                 result.product_count,
+                &notes,
             );
-            metrics.record_raw(
+            metrics.record_raw_with_notes(
                 "lookup_hit",
                 &result.set_type,
                 result.lookup_hit.duration_ns,
                 1,
+                &notes,
             );
-            metrics.record_raw(
+            metrics.record_raw_with_notes(
                 "lookup_miss",
                 &result.set_type,
                 result.lookup_miss.duration_ns,
                 1,
+                &notes,
             );
-            metrics.record_raw(
+            metrics.record_raw_with_notes(
                 "iterate_all",
                 &result.set_type,
                 result.iterate_all.duration_ns,
                 result.product_count,
+                &notes,
             );
-            metrics.record_raw(
+            metrics.record_raw_with_notes(
                 "remove_half",
                 &result.set_type,
                 result.remove_half.duration_ns,
                 result.product_count / 2,
+                &notes,
             );
         }
     }
@@ -122,7 +450,12 @@ pub async fn run_benchmark(
     // Build ASCII summary table
     let ascii = render_benchmark_ascii_table(&report);
 
+    let regression_check = baseline.as_ref().map(|baseline| {
+        sets::regression_check(baseline, &report, sets::REGRESSION_WARN_PCT, sets::REGRESSION_FAIL_PCT)
+    });
+
     info!(
+        request_id = %request_id,
         product_count = report.product_count,
         bench_ms = bench_elapsed.as_millis(),
         winner_insert = %report.winner_insert,
@@ -131,24 +464,377 @@ pub async fn run_benchmark(
         "Benchmark complete"
     );
 
+    let mut body = serde_json::json!({
+        "report": report,
+        "db_load_time_ms": db_elapsed.as_secs_f64() * 1000.0,
+        "benchmark_time_ms": bench_elapsed.as_secs_f64() * 1000.0,
+        "config": config,
+        "sample": { "sample_size": params.sample_size, "sample_strategy": strategy },
+        "ascii_table": ascii,
+    });
+    if let Some(alerts) = regression_check {
+        let regressions = alerts.iter().filter(|a| a.severity == sets::Severity::Regression).count();
+        if regressions > 0 {
+            warn!(request_id = %request_id, regressions, baseline_run_id = ?params.baseline_run_id, "Benchmark regressed against baseline");
+        }
+        body["baseline_run_id"] = serde_json::json!(params.baseline_run_id);
+        body["regression_check"] = serde_json::json!(alerts);
+    }
+
+    Ok((StatusCode::OK, Json(body)))
+}
+
+// ── POST /api/benchmark/range ─────────────────────────────────────────────────
+
+/// Times a price-range selection over every product in the DB: `range()` on a
+/// `(price_cents, id)` index vs. filtering the whole name-ordered `BTreeSet`.
+#[utoipa::path(
+    post,
+    path = "/api/benchmark/range",
+    tag = "benchmark",
+    request_body = RangeBenchmarkParams,
+    responses(
+        (status = 200, description = "`result`: RangeBenchmarkResult, plus `product_count` and `db_load_time_ms`", body = Object),
+        (status = 400, description = "Percentiles outside 0–100 or `low_pct` above `high_pct`", body = ErrorResponse),
+    )
+)]
+pub async fn run_range_benchmark(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    Json(params): Json<RangeBenchmarkParams>,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    let RangeBenchmarkParams { low_pct, high_pct } = params;
+    if !(0.0..=100.0).contains(&low_pct) || !(0.0..=100.0).contains(&high_pct) || low_pct > high_pct {
+        return Err(crate::error::AppError::BadRequest(
+            "low_pct and high_pct must be between 0 and 100, with low_pct <= high_pct".to_string(),
+        ));
+    }
+
+    let db_start = Instant::now();
+    let products = db::fetch_all_products_unbounded(&state.db).await?;
+    let db_elapsed = db_start.elapsed();
+
+    if products.is_empty() {
+        return Ok((
+            StatusCode::OK,
+            Json(serde_json::json!({
+                "message": "No products in database. POST /api/seed?count=5000 first.",
+                "product_count": 0,
+            })),
+        ));
+    }
+
+    let result = sets::benchmark_btree_range(&products, low_pct, high_pct);
+
+    let notes = request_id.to_string();
+    let mut metrics = state.metrics.write().await;
+    metrics.record_raw_with_notes("range_query", "BTreeMap (price index)", result.range_query_ns, result.matches, &notes);
+    metrics.record_raw_with_notes("range_filter_scan", "BTreeSet", result.filter_scan_ns, result.matches, &notes);
+    drop(metrics);
+
+    info!(
+        request_id = %request_id,
+        low_pct,
+        high_pct,
+        matches = result.matches,
+        range_query_ns = result.range_query_ns,
+        filter_scan_ns = result.filter_scan_ns,
+        "Range benchmark complete"
+    );
+
     Ok((
         StatusCode::OK,
         Json(serde_json::json!({
-            "report": report,
+            "result": result,
+            "product_count": products.len(),
+            "db_load_time_ms": db_elapsed.as_secs_f64() * 1000.0,
+        })),
+    ))
+}
+
+// ── POST /api/benchmark/range-delete ──────────────────────────────────────────
+
+/// Times removing a share of the products in the DB from a `BTreeSet` by
+/// `retain` vs. a filtered rebuild, and sweeps batch sizes for the crossover.
+#[utoipa::path(
+    post,
+    path = "/api/benchmark/range-delete",
+    tag = "benchmark",
+    request_body = RangeDeleteParams,
+    responses(
+        (status = 200, description = "`result`: RangeDeleteResult, plus `product_count` and `db_load_time_ms`", body = Object),
+        (status = 400, description = "`remove_fraction` outside 0–1", body = ErrorResponse),
+    )
+)]
+pub async fn run_range_delete_benchmark(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    Json(params): Json<RangeDeleteParams>,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    let RangeDeleteParams { remove_fraction } = params;
+    if !(0.0..=1.0).contains(&remove_fraction) {
+        return Err(crate::error::AppError::BadRequest("remove_fraction must be between 0 and 1".to_string()));
+    }
+
+    let db_start = Instant::now();
+    let products = db::fetch_all_products_unbounded(&state.db).await?;
+    let db_elapsed = db_start.elapsed();
+
+    if products.is_empty() {
+        return Ok((
+            StatusCode::OK,
+            Json(serde_json::json!({
+                "message": "No products in database. POST /api/seed?count=5000 first.",
+                "product_count": 0,
+            })),
+        ));
+    }
+
+    let result = sets::benchmark_btree_range_delete(&products, remove_fraction);
+
+    let notes = request_id.to_string();
+    let mut metrics = state.metrics.write().await;
+    metrics.record_raw_with_notes("batch_remove:retain", "BTreeSet", result.retain_ns, result.removed, &notes);
+    metrics.record_raw_with_notes("batch_remove:rebuild", "BTreeSet", result.rebuild_ns, result.removed, &notes);
+    drop(metrics);
+
+    info!(
+        request_id = %request_id,
+        remove_fraction,
+        removed = result.removed,
+        retain_ns = result.retain_ns,
+        rebuild_ns = result.rebuild_ns,
+        crossover_fraction = ?result.crossover_fraction,
+        "Range delete benchmark complete"
+    );
+
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "result": result,
+            "product_count": products.len(),
             "db_load_time_ms": db_elapsed.as_secs_f64() * 1000.0,
-            "benchmark_time_ms": bench_elapsed.as_secs_f64() * 1000.0,
-            "ascii_table": ascii,
         })),
     ))
 }
 
+// ── POST /api/benchmark/concurrent ────────────────────────────────────────────
+
+/// Readers and writers hammering one `RwLock<SetManager>` at the same time.
+/// Runs on freshly generated products, so the live sets and the DB are untouched.
+#[utoipa::path(
+    post,
+    path = "/api/benchmark/concurrent",
+    tag = "benchmark",
+    request_body(content = Option<ConcurrentBenchmarkParams>, description = "Optional task counts"),
+    responses(
+        (status = 200, description = "`result`: ConcurrentBenchmarkResult", body = Object),
+        (status = 400, description = "No readers and no writers", body = ErrorResponse),
+    )
+)]
+pub async fn run_concurrent_benchmark(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    JsonOrDefault(params): JsonOrDefault<ConcurrentBenchmarkParams>,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    let readers = params.readers.unwrap_or(4).min(64);
+    let writers = params.writers.unwrap_or(1).min(64);
+    let ops_per_task = params.ops_per_task.unwrap_or(10_000).clamp(1, 100_000);
+    let seed_count = params.seed_count.unwrap_or(10_000).clamp(1, 50_000);
+    if readers + writers == 0 {
+        return Err(crate::error::AppError::BadRequest(
+            "readers and writers must not both be 0".to_string(),
+        ));
+    }
+
+    let products = seed::generate_products(seed_count);
+    let result = sets::benchmark_concurrent(&products, readers, writers, ops_per_task).await;
+
+    info!(
+        request_id = %request_id,
+        readers,
+        writers,
+        ops_per_task,
+        wall_ms = result.total_wall_ns / 1_000_000,
+        read_waits = result.read_contention_waits,
+        write_waits = result.write_contention_waits,
+        "Concurrent benchmark complete"
+    );
+
+    state.metrics.write().await.record_raw_with_notes(
+        "concurrent",
+        "RwLock<SetManager>",
+        result.total_wall_ns,
+        result.total_reads + result.total_writes,
+        request_id.to_string(),
+    );
+
+    Ok((StatusCode::OK, Json(serde_json::json!({ "result": result }))))
+}
+
+// ── POST /api/benchmark/lock-contention ──────────────────────────────────────
+
+/// Readers and writers competing for one `RwLock<SetManager>` for a fixed
+/// time, measuring how long each acquisition waited. Like
+/// `POST /api/benchmark/concurrent`, it runs on freshly generated products.
+#[utoipa::path(
+    post,
+    path = "/api/benchmark/lock-contention",
+    tag = "benchmark",
+    request_body(content = Option<LockContentionParams>, description = "Optional task counts and duration"),
+    responses(
+        (status = 200, description = "`result`: LockContentionResult", body = Object),
+        (status = 400, description = "No readers and no writers", body = ErrorResponse),
+    )
+)]
+pub async fn run_lock_contention_benchmark(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    JsonOrDefault(params): JsonOrDefault<LockContentionParams>,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    let readers = params.readers.unwrap_or(8).min(64);
+    let writers = params.writers.unwrap_or(1).min(64);
+    let duration_secs = params.duration_secs.unwrap_or(2).clamp(1, 60);
+    let seed_count = params.seed_count.unwrap_or(10_000).clamp(1, 50_000);
+    if readers + writers == 0 {
+        return Err(crate::error::AppError::BadRequest(
+            "readers and writers must not both be 0".to_string(),
+        ));
+    }
+
+    let products = seed::generate_products(seed_count);
+    let duration = std::time::Duration::from_secs(duration_secs);
+    let result = sets::benchmark_rwlock_contention(&products, readers, writers, duration).await;
+
+    info!(
+        request_id = %request_id,
+        readers,
+        writers,
+        duration_secs,
+        reads = result.total_reads,
+        writes = result.total_writes,
+        max_wait_ns = result.max_wait_ns,
+        "Lock contention benchmark complete"
+    );
+
+    let notes = request_id.to_string();
+    let mut metrics = state.metrics.write().await;
+    metrics.record_raw_with_notes("lock_wait_read", "RwLock<SetManager>", result.avg_read_wait_ns as u64, result.total_reads, &notes);
+    metrics.record_raw_with_notes("lock_wait_write", "RwLock<SetManager>", result.avg_write_wait_ns as u64, result.total_writes, &notes);
+    drop(metrics);
+
+    Ok((StatusCode::OK, Json(serde_json::json!({ "result": result }))))
+}
+
+// ── POST /api/benchmark/serialization ────────────────────────────────────────
+
+/// Encodes one list of freshly generated products as JSON, bincode and
+/// MessagePack and decodes it back, timing both directions and comparing
+/// the encoded sizes.
+#[utoipa::path(
+    post,
+    path = "/api/benchmark/serialization",
+    tag = "benchmark",
+    request_body(content = Option<SerializationParams>, description = "Optional product count"),
+    responses(
+        (status = 200, description = "`result`: SerializationBenchmarkResult", body = Object),
+    )
+)]
+pub async fn run_serialization_benchmark(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    JsonOrDefault(params): JsonOrDefault<SerializationParams>,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    let seed_count = params.seed_count.unwrap_or(10_000).clamp(1, 50_000);
+
+    let products = seed::generate_products(seed_count);
+    let result = sets::benchmark_serialization(&products)?;
+
+    info!(
+        request_id = %request_id,
+        products = seed_count,
+        json_bytes = result.json_size_bytes,
+        bincode_bytes = result.bincode_size_bytes,
+        msgpack_bytes = result.msgpack_size_bytes,
+        smallest = %result.smallest_format,
+        "Serialization benchmark complete"
+    );
+
+    let notes = request_id.to_string();
+    let mut metrics = state.metrics.write().await;
+    for (format, serialize_ns, deserialize_ns) in [
+        ("JSON", result.json_serialize_ns, result.json_deserialize_ns),
+        ("bincode", result.bincode_serialize_ns, result.bincode_deserialize_ns),
+        ("MessagePack", result.msgpack_serialize_ns, result.msgpack_deserialize_ns),
+    ] {
+        metrics.record_raw_with_notes("serialize", format, serialize_ns, seed_count, &notes);
+        metrics.record_raw_with_notes("deserialize", format, deserialize_ns, seed_count, &notes);
+    }
+    drop(metrics);
+
+    Ok((StatusCode::OK, Json(serde_json::json!({ "result": result }))))
+}
+
+// ── POST /api/benchmark/clone-overhead ───────────────────────────────────────
+
+/// Times copying freshly generated products the way `insert_product` does
+/// against sharing them through `Arc<Product>`.
+#[utoipa::path(
+    post,
+    path = "/api/benchmark/clone-overhead",
+    tag = "benchmark",
+    params(CloneOverheadParams),
+    responses(
+        (status = 200, description = "`result`: CloneOverheadResult", body = Object),
+    )
+)]
+pub async fn run_clone_overhead_benchmark(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    Query(params): Query<CloneOverheadParams>,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    let count = params.count.unwrap_or(10_000).clamp(1, 50_000);
+
+    let products = seed::generate_products(count);
+    let result = sets::benchmark_clone_overhead(&products);
+
+    info!(
+        request_id = %request_id,
+        products = count,
+        clone_vec_ns = result.clone_vec_ns,
+        arc_wrap_ns = result.arc_wrap_ns,
+        arc_insert_ns = result.arc_insert_ns,
+        "Clone overhead benchmark complete"
+    );
+
+    let notes = request_id.to_string();
+    let copies = count * result.clones_per_product;
+    let mut metrics = state.metrics.write().await;
+    metrics.record_raw_with_notes("clone", "Vec<Product>", result.clone_vec_ns, copies, &notes);
+    metrics.record_raw_with_notes("clone", "Vec<Arc<Product>>", result.arc_wrap_ns, copies, &notes);
+    metrics.record_raw_with_notes("insert", "AHashSet<Arc<Product>>", result.arc_insert_ns, count, &notes);
+    drop(metrics);
+
+    Ok((StatusCode::OK, Json(serde_json::json!({ "result": result }))))
+}
+
 // ── GET /api/benchmark/report ─────────────────────────────────────────────────
 
+#[utoipa::path(
+    get,
+    path = "/api/benchmark/report",
+    tag = "benchmark",
+    responses(
+        (status = 200, description = "`report`: BenchmarkReport, plus `current_set_sizes` and `ascii_table`; only `message` and the sizes before the first run", body = Object),
+    )
+)]
 pub async fn get_report(
     State(state): State<AppState>,
+    request_id: RequestId,
 ) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
     let sets = state.sets.read().await;
-    let (hs, lh, bt) = sets.sizes();
+
+    info!(request_id = %request_id, has_report = sets.last_report.is_some(), "Fetched benchmark report");
 
     match &sets.last_report {
         Some(report) => {
@@ -157,11 +843,7 @@ pub async fn get_report(
                 StatusCode::OK,
                 Json(serde_json::json!({
                     "report": report,
-                    "current_set_sizes": {
-                        "hash_set": hs,
-                        "index_set": lh,
-                        "btree_set": bt,
-                    },
+                    "current_set_sizes": sets.sizes(),
                     "ascii_table": ascii,
                 })),
             ))
@@ -170,23 +852,126 @@ pub async fn get_report(
             StatusCode::OK,
             Json(serde_json::json!({
                 "message": "No benchmark has been run yet. POST /api/benchmark/run first.",
-                "current_set_sizes": {
-                    "hash_set": hs,
-                    "index_set": lh,
-                    "btree_set": bt,
-                },
+                "current_set_sizes": sets.sizes(),
             })),
         )),
     }
 }
 
+// ── GET /api/benchmark/history ────────────────────────────────────────────────
+
+#[utoipa::path(
+    get,
+    path = "/api/benchmark/history",
+    tag = "benchmark",
+    params(HistoryParams),
+    responses(
+        (status = 200, description = "`data`: [BenchmarkRunSummary], newest first", body = Object),
+    )
+)]
+pub async fn benchmark_history(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    Query(params): Query<HistoryParams>,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    let limit = params.limit.unwrap_or(20).clamp(1, 200);
+    let runs = db::fetch_benchmark_history(&state.db, limit).await?;
+
+    info!(request_id = %request_id, count = runs.len(), "Listed benchmark history");
+
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "data": runs,
+            "count": runs.len(),
+        })),
+    ))
+}
+
+// ── GET /api/benchmark/history/:id ────────────────────────────────────────────
+
+#[utoipa::path(
+    get,
+    path = "/api/benchmark/history/{id}",
+    tag = "benchmark",
+    params(("id" = Uuid, Path, description = "Benchmark run id")),
+    responses(
+        (status = 200, description = "`report`: the stored BenchmarkReport", body = Object),
+        (status = 404, description = "No stored run with this id", body = ErrorResponse),
+    )
+)]
+pub async fn benchmark_history_run(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    Path(id): Path<Uuid>,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    let report = db::fetch_benchmark_run(&state.db, id).await?;
+
+    info!(request_id = %request_id, id = %id, "Fetched benchmark run");
+
+    Ok((StatusCode::OK, Json(serde_json::json!({ "report": report }))))
+}
+
+// ── GET /api/benchmark/compare ────────────────────────────────────────────────
+
+#[utoipa::path(
+    get,
+    path = "/api/benchmark/compare",
+    tag = "benchmark",
+    params(CompareParams),
+    responses(
+        (status = 200, description = "`delta`: BenchmarkDelta, plus both runs' timestamps", body = Object),
+        (status = 404, description = "Either run is unknown", body = ErrorResponse),
+    )
+)]
+pub async fn compare_benchmark_runs(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    Query(params): Query<CompareParams>,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    let a = stored_report(&state, params.run_a).await?;
+    let b = stored_report(&state, params.run_b).await?;
+    let delta = sets::compare_reports(&a, &b);
+
+    info!(
+        request_id = %request_id,
+        run_a = %params.run_a,
+        run_b = %params.run_b,
+        "Compared benchmark runs"
+    );
+
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "run_a_at": a.run_at,
+            "run_b_at": b.run_at,
+            "delta": delta,
+        })),
+    ))
+}
+
+/// A run from `benchmark_runs`, parsed back into a `BenchmarkReport`.
+async fn stored_report(state: &AppState, id: Uuid) -> AppResult<BenchmarkReport> {
+    let value = db::fetch_benchmark_run(&state.db, id).await?;
+    serde_json::from_value(value)
+        .map_err(|e| anyhow::anyhow!("stored benchmark run {} is unreadable: {}", id, e).into())
+}
+
 // ── GET /api/benchmark/sets/status ───────────────────────────────────────────
 
+#[utoipa::path(
+    get,
+    path = "/api/benchmark/sets/status",
+    tag = "sets",
+    responses(
+        (status = 200, description = "Set sizes, `memory_estimate`: SetMemoryEstimate, and a sample of each set's iteration order", body = Object),
+    )
+)]
 pub async fn sets_status(
     State(state): State<AppState>,
+    request_id: RequestId,
 ) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
     let sets = state.sets.read().await;
-    let (hs, lh, bt) = sets.sizes();
 
     // Sample first 5 elements from each set
     let hash_sample: Vec<_> = sets
@@ -210,41 +995,793 @@ pub async fn sets_status(
         .map(|p| serde_json::json!({"id": p.id, "name": p.name}))
         .collect();
 
-    Ok((
-        StatusCode::OK,
-        Json(serde_json::json!({
-            "sizes": {
-                "hash_set": hs,
-                "index_set": lh,
-                "btree_set": bt,
+    let ahash_sample: Vec<_> = sets
+        .ahash_set
+        .iter()
+        .take(5)
+        .map(|p| serde_json::json!({"id": p.id, "name": p.name}))
+        .collect();
+
+    let dash_sample: Vec<_> = sets
+        .dash_set
+        .iter()
+        .take(5)
+        .map(|p| serde_json::json!({"id": p.id, "name": p.name}))
+        .collect();
+
+    let fx_sample: Option<Vec<_>> = sets.fx_set.as_ref().map(|fx| {
+        fx.iter()
+            .take(5)
+            .map(|p| serde_json::json!({"id": p.id, "name": p.name}))
+            .collect()
+    });
+
+    let mut body = serde_json::json!({
+        "sizes": sets.sizes(),
+        "memory_estimate": sets.memory_estimate_bytes(),
+        "sample_first_5": {
+            "hash_set": {
+                "note": "Arbitrary order (hash-based, not predictable)",
+                "items": hash_sample,
             },
-            "sample_first_5": {
-                "hash_set": {
-                    "note": "Arbitrary order (hash-based, not predictable)",
-                    "items": hash_sample,
-                },
-                "index_set": {
-                    "note": "Insertion order preserved (FIFO) — IndexSet / LinkedHashSet equivalent",
-                    "items": linked_sample,
-                },
-                "btree_set": {
-                    "note": "Alphabetically sorted by product name",
-                    "items": btree_sample,
-                },
+            "index_set": {
+                "note": "Insertion order preserved (FIFO) — IndexSet / LinkedHashSet equivalent",
+                "items": linked_sample,
             },
-        })),
-    ))
+            "btree_set": {
+                "note": "Alphabetically sorted by product name",
+                "items": btree_sample,
+            },
+            "ahash_set": {
+                "note": "Arbitrary order (aHash-based, not predictable)",
+                "items": ahash_sample,
+            },
+            "dash_set": {
+                "note": "Arbitrary order (sharded hash, not predictable)",
+                "items": dash_sample,
+            },
+        },
+    });
+    if let Some(items) = fx_sample {
+        body["sample_first_5"]["fx_set"] = serde_json::json!({
+            "note": "Arbitrary order (FxHash-based, not predictable)",
+            "items": items,
+        });
+    }
+
+    info!(request_id = %request_id, "Fetched set status");
+
+    Ok((StatusCode::OK, Json(body)))
 }
 
-// ── GET /api/benchmark/export/csv ────────────────────────────────────────────
+// ── GET /api/benchmark/sets/consistency ──────────────────────────────────────
 
-pub async fn export_csv(State(state): State<AppState>) -> Result<Response, crate::error::AppError> {
-    let metrics = state.metrics.read().await;
-    let csv = metrics.to_csv().map_err(anyhow::Error::from)?;
+#[utoipa::path(
+    get,
+    path = "/api/benchmark/sets/consistency",
+    tag = "sets",
+    responses(
+        (status = 200, description = "Whether every set holds the same products, with a sample of those that don't", body = Object),
+    )
+)]
+pub async fn sets_consistency(
+    State(state): State<AppState>,
+    request_id: RequestId,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    let sets = state.sets.read().await;
 
-    Ok(Response::builder()
-        .status(StatusCode::OK)
-        .header(header::CONTENT_TYPE, "text/csv")
+    let start = Instant::now();
+    let in_all_sets = sets.intersection().len();
+    let diverging = sets.symmetric_difference();
+    let elapsed = start.elapsed();
+
+    info!(
+        request_id = %request_id,
+        in_all_sets,
+        diverging = diverging.len(),
+        "Checked set consistency"
+    );
+
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "consistent": diverging.is_empty(),
+            "sizes": sets.sizes(),
+            "in_all_sets": in_all_sets,
+            "diverging_count": diverging.len(),
+            "diverging_sample": diverging.iter().take(20).collect::<Vec<_>>(),
+            "check_time_ms": elapsed.as_secs_f64() * 1000.0,
+        })),
+    ))
+}
+
+// ── GET /api/benchmark/sets/search ───────────────────────────────────────────
+
+/// Runs the same prefix search three ways — `IndexSet` scan, `BTreeSet`
+/// range and `HashSet` scan — and reports each one's matches and time.
+#[utoipa::path(
+    get,
+    path = "/api/benchmark/sets/search",
+    tag = "sets",
+    params(PrefixSearchParams),
+    responses(
+        (status = 200, description = "Matches and timing for each of `index_set`, `btree_set` and `hash_set`", body = Object),
+    )
+)]
+pub async fn sets_prefix_search(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    Query(params): Query<PrefixSearchParams>,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    let limit = params.limit.unwrap_or(50).clamp(1, 1000);
+    let prefix = params.prefix.as_str();
+    let sets = state.sets.read().await;
+
+    let (index_found, index_elapsed) = sets::timed(|| sets.find_by_name_prefix(prefix));
+    let (btree_found, btree_elapsed) = sets::timed(|| sets.find_by_name_prefix_btree(prefix));
+    let (hash_found, hash_elapsed) = sets::timed(|| sets::filter_by_name_prefix(sets.hash_set.iter(), prefix));
+
+    let result = |found: &[&crate::models::Product], elapsed: std::time::Duration, order: &str| {
+        serde_json::json!({
+            "count": found.len(),
+            "order": order,
+            "time_ns": elapsed.as_nanos() as u64,
+            "time_us": elapsed.as_secs_f64() * 1_000_000.0,
+            "items": found.iter().take(limit).map(|p| serde_json::json!({"id": p.id, "name": p.name})).collect::<Vec<_>>(),
+        })
+    };
+    let body = serde_json::json!({
+        "prefix": prefix,
+        "consistent": index_found.len() == btree_found.len() && btree_found.len() == hash_found.len(),
+        "index_set": result(&index_found, index_elapsed, "Insertion order (full scan)"),
+        "btree_set": result(&btree_found, btree_elapsed, "Sorted by (name, id) (range seek)"),
+        "hash_set": result(&hash_found, hash_elapsed, "Arbitrary (full scan)"),
+    });
+    let timings = [
+        ("IndexSet", index_elapsed, index_found.len()),
+        ("BTreeSet", btree_elapsed, btree_found.len()),
+        ("HashSet", hash_elapsed, hash_found.len()),
+    ];
+    drop(sets);
+
+    let notes = request_id.to_string();
+    let mut metrics = state.metrics.write().await;
+    for (set_type, elapsed, count) in timings {
+        metrics.record_raw_with_notes("prefix_search", set_type, elapsed.as_nanos() as u64, count, &notes);
+    }
+
+    info!(request_id = %request_id, prefix, matches = timings[0].2, "Searched sets by name prefix");
+
+    Ok((StatusCode::OK, Json(body)))
+}
+
+// ── GET /api/benchmark/sets/top-n ────────────────────────────────────────────
+
+/// Top N products by price or quantity, found with a bounded `BinaryHeap` over
+/// `hash_set` and, for comparison, by sorting a full `BTreeSet` iteration.
+#[utoipa::path(
+    get,
+    path = "/api/benchmark/sets/top-n",
+    tag = "sets",
+    params(TopNParams),
+    responses(
+        (status = 200, description = "The top products and timing from `heap` and from `btree_sort`", body = Object),
+    )
+)]
+pub async fn sets_top_n(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    Query(params): Query<TopNParams>,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    let n = params.n.unwrap_or(10).clamp(1, 1000);
+    let by = params.by;
+    let sets = state.sets.read().await;
+
+    let (heap_top, heap_elapsed) = sets::timed(|| match by {
+        TopNField::Price => sets.top_n_by_price(n),
+        TopNField::Quantity => sets.top_n_by_quantity(n),
+    });
+    let (sort_top, sort_elapsed) = sets::timed(|| sets::top_n_by_sort(sets.btree_set.iter(), n, by.key()));
+
+    let result = |found: &[&crate::models::Product], elapsed: std::time::Duration, method: &str| {
+        serde_json::json!({
+            "method": method,
+            "time_ns": elapsed.as_nanos() as u64,
+            "time_us": elapsed.as_secs_f64() * 1_000_000.0,
+            "items": found
+                .iter()
+                .map(|p| serde_json::json!({"id": p.id, "name": p.name, "price_cents": p.price_cents, "quantity": p.quantity}))
+                .collect::<Vec<_>>(),
+        })
+    };
+    let body = serde_json::json!({
+        "by": by.as_str(),
+        "n": n,
+        "product_count": sets.hash_set.len(),
+        "consistent": heap_top.iter().map(|p| p.id).eq(sort_top.iter().map(|p| p.id)),
+        "faster": if heap_elapsed <= sort_elapsed { "heap" } else { "btree_sort" },
+        "heap": result(&heap_top, heap_elapsed, "HashSet scan into a bounded BinaryHeap, O(len · log n)"),
+        "btree_sort": result(&sort_top, sort_elapsed, "BTreeSet iteration, then a full sort, O(len · log len)"),
+    });
+    let found = heap_top.len();
+    drop(sets);
+
+    let notes = request_id.to_string();
+    let operation = format!("top_n:{}", by.as_str());
+    let mut metrics = state.metrics.write().await;
+    metrics.record_raw_with_notes(operation.as_str(), "HashSet+BinaryHeap", heap_elapsed.as_nanos() as u64, found, &notes);
+    metrics.record_raw_with_notes(operation, "BTreeSet", sort_elapsed.as_nanos() as u64, found, &notes);
+
+    info!(request_id = %request_id, by = by.as_str(), n, found, "Found top products in sets");
+
+    Ok((StatusCode::OK, Json(body)))
+}
+
+// ── GET /api/benchmark/sets/partitions ───────────────────────────────────────
+
+/// Products grouped by category from a single `BTreeSet` pass, summarised per
+/// category, with the time the grouping took.
+#[utoipa::path(
+    get,
+    path = "/api/benchmark/sets/partitions",
+    tag = "sets",
+    responses(
+        (status = 200, description = "`partitions` (`category`, `count`, `total_quantity`, `total_value_cents`, by category name), `largest` and `smallest` categories, and `partition_time_ns`", body = Object),
+    )
+)]
+pub async fn sets_partitions(
+    State(state): State<AppState>,
+    request_id: RequestId,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    let sets = state.sets.read().await;
+    let (partitions, elapsed) = sets::timed(|| sets.partition_by_category());
+
+    let mut summaries: Vec<serde_json::Value> = partitions
+        .iter()
+        .map(|(category, partition)| {
+            serde_json::json!({
+                "category": category,
+                "count": partition.products.len(),
+                "total_quantity": partition.total_quantity,
+                "total_value_cents": partition.total_value_cents,
+            })
+        })
+        .collect();
+    summaries.sort_by(|a, b| a["category"].as_str().cmp(&b["category"].as_str()));
+    let extreme = |found: Option<(&str, usize)>| found.map(|(category, count)| serde_json::json!({"category": category, "count": count}));
+    let body = serde_json::json!({
+        "product_count": sets.btree_set.len(),
+        "category_count": summaries.len(),
+        "largest": extreme(sets.largest_category()),
+        "smallest": extreme(sets.smallest_category()),
+        "partitions": summaries,
+        "partition_time_ns": elapsed.as_nanos() as u64,
+        "partition_time_us": elapsed.as_secs_f64() * 1_000_000.0,
+    });
+    let (product_count, category_count) = (sets.btree_set.len(), partitions.len());
+    drop(partitions);
+    drop(sets);
+
+    state.metrics.write().await.record_raw_with_notes(
+        "partition_by_category",
+        "BTreeSet",
+        elapsed.as_nanos() as u64,
+        product_count,
+        request_id.to_string(),
+    );
+
+    info!(request_id = %request_id, categories = category_count, "Partitioned sets by category");
+
+    Ok((StatusCode::OK, Json(body)))
+}
+
+// ── GET /api/benchmark/sets/stats ────────────────────────────────────────────
+
+/// Price and quantity aggregates from one pass over the in-memory sets,
+/// checked against the same figures from the DB.
+#[utoipa::path(
+    get,
+    path = "/api/benchmark/sets/stats",
+    tag = "sets",
+    responses(
+        (status = 200, description = "`in_memory` and `db`: SetStats, plus `results_match`, the `discrepancies` between them, `inventory_value_dollars` and both timings", body = Object),
+    )
+)]
+pub async fn sets_stats(
+    State(state): State<AppState>,
+    request_id: RequestId,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    let db_start = Instant::now();
+    let db_stats = sets::SetStats::from_product_stats(&db::fetch_product_stats(&state.db, None).await?);
+    let db_elapsed = db_start.elapsed();
+
+    let set_start = Instant::now();
+    let set_stats = state.sets.read().await.stats();
+    let set_elapsed = set_start.elapsed();
+
+    let discrepancies = set_stats.discrepancies(&db_stats);
+    info!(
+        request_id = %request_id,
+        products = set_stats.product_count,
+        discrepancies = discrepancies.len(),
+        "Compared set stats with the DB"
+    );
+
+    let mut metrics = state.metrics.write().await;
+    metrics.record_raw("db_query:stats", "DB", db_elapsed.as_nanos() as u64, db_stats.product_count);
+    metrics.record_raw("stats", "HashSet", set_elapsed.as_nanos() as u64, set_stats.product_count);
+    drop(metrics);
+
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "in_memory": set_stats,
+            "db": db_stats,
+            "results_match": discrepancies.is_empty(),
+            "discrepancies": discrepancies,
+            "inventory_value_dollars": set_stats.inventory_value_dollars(),
+            "db_time_ms": db_elapsed.as_secs_f64() * 1000.0,
+            "set_time_ms": set_elapsed.as_secs_f64() * 1000.0,
+        })),
+    ))
+}
+
+// ── POST /api/benchmark/sets/snapshot ────────────────────────────────────────
+
+/// Captures every product in the sets. The response body is the snapshot
+/// itself, ready to be sent back to `POST /api/benchmark/sets/restore`.
+#[utoipa::path(
+    post,
+    path = "/api/benchmark/sets/snapshot",
+    tag = "sets",
+    responses(
+        (status = 200, description = "The captured SetSnapshot", body = SetSnapshot),
+    )
+)]
+pub async fn sets_snapshot(
+    State(state): State<AppState>,
+    request_id: RequestId,
+) -> AppResult<(StatusCode, Json<SetSnapshot>)> {
+    let (snapshot, elapsed, set_label) = {
+        let sets = state.sets.read().await;
+        let (snapshot, elapsed) = sets::timed(|| sets.snapshot());
+        (snapshot, elapsed, sets.label())
+    };
+
+    state.metrics.write().await.record_raw_with_notes(
+        "snapshot",
+        set_label,
+        elapsed.as_nanos() as u64,
+        snapshot.products.len(),
+        request_id.to_string(),
+    );
+
+    info!(request_id = %request_id, products = snapshot.products.len(), "Captured set snapshot");
+
+    Ok((StatusCode::OK, Json(snapshot)))
+}
+
+// ── POST /api/benchmark/sets/restore ─────────────────────────────────────────
+
+/// Largest snapshot body `POST /api/benchmark/sets/restore` accepts; a
+/// 50 000-product snapshot is roughly 15 MB of JSON.
+pub const MAX_SNAPSHOT_BYTES: usize = 64 * 1024 * 1024;
+
+/// Replaces the contents of every set with a snapshot from
+/// `POST /api/benchmark/sets/snapshot`. The database is not touched.
+#[utoipa::path(
+    post,
+    path = "/api/benchmark/sets/restore",
+    tag = "sets",
+    request_body = SetSnapshot,
+    responses(
+        (status = 200, description = "`restored` product count, the snapshot's `captured_at` and the new set `sizes`", body = Object),
+        (status = 400, description = "Duplicate product ids, or a product that breaks the validation rules", body = ErrorResponse),
+    )
+)]
+pub async fn sets_restore(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    Json(snapshot): Json<SetSnapshot>,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    let mut ids = std::collections::HashSet::with_capacity(snapshot.products.len());
+    for (index, product) in snapshot.products.iter().enumerate() {
+        if !ids.insert(product.id) {
+            return Err(crate::error::AppError::BadRequest(format!("products[{}]: duplicate id {}", index, product.id)));
+        }
+        if let Err(errors) = product.validate() {
+            return Err(crate::error::AppError::BadRequest(format!("products[{}]: {}", index, join_validation_errors(&errors))));
+        }
+    }
+
+    let mut sets = state.sets.write().await;
+    let ((), elapsed) = sets::timed(|| sets.restore_from_snapshot(&snapshot));
+    let sizes = sets.sizes();
+    let set_label = sets.label();
+    drop(sets);
+
+    state.metrics.write().await.record_raw_with_notes(
+        "restore",
+        set_label,
+        elapsed.as_nanos() as u64,
+        snapshot.products.len(),
+        request_id.to_string(),
+    );
+
+    info!(
+        request_id = %request_id,
+        products = snapshot.products.len(),
+        captured_at = %snapshot.captured_at,
+        "Restored sets from snapshot"
+    );
+
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "restored": snapshot.products.len(),
+            "captured_at": snapshot.captured_at,
+            "sizes": sizes,
+            "restore_time_ms": elapsed.as_secs_f64() * 1000.0,
+        })),
+    ))
+}
+
+// ── POST /api/benchmark/sets/sync ────────────────────────────────────────────
+
+/// Brings the sets in line with the database. A full sync reloads every live
+/// product; an incremental one applies only rows updated or soft-deleted since
+/// the previous sync.
+#[utoipa::path(
+    post,
+    path = "/api/benchmark/sets/sync",
+    tag = "sets",
+    params(SyncParams),
+    responses(
+        (status = 200, description = "`mode` (`full` or `incremental`), `since`, `synced_at`, `upserted` and `removed` counts, the new `sizes` and timings", body = Object),
+    )
+)]
+pub async fn sets_sync(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    Query(params): Query<SyncParams>,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    let since = match params.full {
+        Some(true) => None,
+        _ => state.sets.read().await.last_sync_at,
+    };
+
+    let db_start = Instant::now();
+    let db::SyncChanges { products, deleted, synced_at } = db::fetch_sync_changes(&state.db, since).await?;
+    let db_elapsed = db_start.elapsed();
+
+    let mut sets = state.sets.write().await;
+    let ((), sync_elapsed) = sets::timed(|| {
+        if since.is_some() {
+            sets.remove_product_batch(&deleted);
+            sets.incremental_sync(&products);
+        } else {
+            sets.sync_from_db(&products);
+        }
+    });
+    sets.last_sync_at = Some(synced_at);
+    let sizes = sets.sizes();
+    let set_label = sets.label();
+    drop(sets);
+
+    let mode = if since.is_some() { "incremental" } else { "full" };
+    let notes = request_id.to_string();
+    let mut metrics = state.metrics.write().await;
+    metrics.record_raw_with_notes(format!("db_query:sync_{}", mode), "DB", db_elapsed.as_nanos() as u64, products.len(), &notes);
+    metrics.record_raw_with_notes(format!("sync_{}", mode), set_label, sync_elapsed.as_nanos() as u64, products.len(), &notes);
+    drop(metrics);
+
+    info!(
+        request_id = %request_id,
+        mode,
+        upserted = products.len(),
+        removed = deleted.len(),
+        "Synced sets from the database"
+    );
+
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "mode": mode,
+            "since": since,
+            "synced_at": synced_at,
+            "upserted": products.len(),
+            "removed": deleted.len(),
+            "sizes": sizes,
+            "db_time_ms": db_elapsed.as_secs_f64() * 1000.0,
+            "sync_time_ms": sync_elapsed.as_secs_f64() * 1000.0,
+        })),
+    ))
+}
+
+// ── GET /api/benchmark/sets/diff ─────────────────────────────────────────────
+
+/// Reports products that are live in the DB but missing from the sets, or the
+/// other way round, e.g. after a crash between a DB write and the set update.
+/// With `reconcile=true`, any drift triggers a full reload.
+#[utoipa::path(
+    get,
+    path = "/api/benchmark/sets/diff",
+    tag = "sets",
+    params(DiffParams),
+    responses(
+        (status = 200, description = "`diff`: SetDiff, `drift`, `reconciled` and, after a reload, `sizes`; plus timings", body = Object),
+    )
+)]
+pub async fn sets_diff(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    Query(params): Query<DiffParams>,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    let start = Instant::now();
+    let diff = sets::diff_with_db(&*state.sets.read().await, &state.db).await?;
+    let diff_elapsed = start.elapsed();
+    let drift = diff.has_drift();
+
+    state.metrics.write().await.record_raw_with_notes(
+        "diff_with_db",
+        "HashSet",
+        diff_elapsed.as_nanos() as u64,
+        diff.in_db_not_in_sets.len() + diff.in_sets_not_in_db.len(),
+        request_id.to_string(),
+    );
+    if drift {
+        warn!(
+            request_id = %request_id,
+            missing_from_sets = diff.in_db_not_in_sets.len(),
+            missing_from_db = diff.in_sets_not_in_db.len(),
+            "Sets have drifted from the database"
+        );
+    }
+
+    let mut body = serde_json::json!({
+        "diff": diff,
+        "drift": drift,
+        "reconciled": false,
+        "diff_time_ms": diff_elapsed.as_secs_f64() * 1000.0,
+    });
+
+    if drift && params.reconcile == Some(true) {
+        let db::SyncChanges { products, synced_at, .. } = db::fetch_sync_changes(&state.db, None).await?;
+        let mut sets = state.sets.write().await;
+        let ((), sync_elapsed) = sets::timed(|| sets.sync_from_db(&products));
+        sets.last_sync_at = Some(synced_at);
+        body["sizes"] = serde_json::json!(sets.sizes());
+        let set_label = sets.label();
+        drop(sets);
+
+        state.metrics.write().await.record_raw_with_notes(
+            "sync_full",
+            set_label,
+            sync_elapsed.as_nanos() as u64,
+            products.len(),
+            request_id.to_string(),
+        );
+        info!(request_id = %request_id, products = products.len(), "Reconciled sets with the database");
+        body["reconciled"] = serde_json::json!(true);
+        body["sync_time_ms"] = serde_json::json!(sync_elapsed.as_secs_f64() * 1000.0);
+    }
+
+    Ok((StatusCode::OK, Json(body)))
+}
+
+// ── POST /api/benchmark/sets/rebalance ───────────────────────────────────────
+
+/// Restores oldest-first order in `index_set` when swap-removes have shuffled
+/// it. Already-ordered sets are left alone.
+#[utoipa::path(
+    post,
+    path = "/api/benchmark/sets/rebalance",
+    tag = "sets",
+    responses(
+        (status = 200, description = "`was_fifo`, `rebalanced`, `is_fifo`, `product_count` and `check_time_ns`/`rebalance_time_ns`", body = Object),
+    )
+)]
+pub async fn sets_rebalance(
+    State(state): State<AppState>,
+    request_id: RequestId,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    let mut sets = state.sets.write().await;
+    let (was_fifo, check_elapsed) = sets::timed(|| sets.index_set_is_fifo());
+    let rebalance_elapsed = if was_fifo {
+        None
+    } else {
+        let ((), elapsed) = sets::timed(|| sets.rebalance_index_set());
+        Some(elapsed)
+    };
+    let is_fifo = sets.index_set_is_fifo();
+    let product_count = sets.index_set.len();
+    drop(sets);
+
+    let mut metrics = state.metrics.write().await;
+    metrics.record_raw_with_notes(
+        "index_set_is_fifo",
+        "IndexSet",
+        check_elapsed.as_nanos() as u64,
+        product_count,
+        request_id.to_string(),
+    );
+    if let Some(elapsed) = rebalance_elapsed {
+        metrics.record_raw_with_notes(
+            "rebalance_index_set",
+            "IndexSet",
+            elapsed.as_nanos() as u64,
+            product_count,
+            request_id.to_string(),
+        );
+    }
+    drop(metrics);
+
+    info!(request_id = %request_id, was_fifo, products = product_count, "Checked IndexSet order");
+
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "was_fifo": was_fifo,
+            "rebalanced": rebalance_elapsed.is_some(),
+            "is_fifo": is_fifo,
+            "product_count": product_count,
+            "check_time_ns": check_elapsed.as_nanos() as u64,
+            "rebalance_time_ns": rebalance_elapsed.map(|d| d.as_nanos() as u64),
+        })),
+    ))
+}
+
+// ── POST /api/benchmark/sets/batch-insert ────────────────────────────────────
+
+/// Loads every product in the DB into fresh sets twice: one write lock and a
+/// [`batch_insert`](sets::SetManager::batch_insert), vs. a lock and an insert
+/// per product. The live sets are not touched.
+#[utoipa::path(
+    post,
+    path = "/api/benchmark/sets/batch-insert",
+    tag = "sets",
+    responses(
+        (status = 200, description = "`result`: BatchInsertResult, plus `db_load_time_ms`", body = Object),
+    )
+)]
+pub async fn sets_batch_insert(
+    State(state): State<AppState>,
+    request_id: RequestId,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    let db_start = Instant::now();
+    let products = db::fetch_all_products_unbounded(&state.db).await?;
+    let db_elapsed = db_start.elapsed();
+
+    if products.is_empty() {
+        return Ok((
+            StatusCode::OK,
+            Json(serde_json::json!({
+                "message": "No products in database. POST /api/seed?count=5000 first.",
+                "product_count": 0,
+            })),
+        ));
+    }
+
+    let result = sets::benchmark_batch_insert(&products).await;
+
+    let notes = request_id.to_string();
+    let mut metrics = state.metrics.write().await;
+    metrics.record_raw_with_notes("batch_insert", &result.sets, result.batch_ns, result.product_count, &notes);
+    metrics.record_raw_with_notes("sequential_insert", &result.sets, result.sequential_ns, result.product_count, &notes);
+    drop(metrics);
+
+    info!(
+        request_id = %request_id,
+        products = result.product_count,
+        batch_ns = result.batch_ns,
+        sequential_ns = result.sequential_ns,
+        speedup = result.speedup,
+        "Batch insert benchmark complete"
+    );
+
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "result": result,
+            "db_load_time_ms": db_elapsed.as_secs_f64() * 1000.0,
+        })),
+    ))
+}
+
+// ── GET /api/benchmark/sets/iteration-order-diff ─────────────────────────────
+
+/// Products compared by `GET /api/benchmark/sets/iteration-order-diff`.
+const ITERATION_ORDER_SAMPLE: usize = 100;
+
+/// How differently `index_set` (insertion order), `btree_set` (name order) and
+/// `hash_set` (hash order) iterate, as Kendall tau correlations.
+///
+/// The sample is the first 100 products of `index_set`; each set's order is
+/// where those same products appear in a full iteration of that set, so every
+/// pair of orders ranks the same items.
+#[utoipa::path(
+    get,
+    path = "/api/benchmark/sets/iteration-order-diff",
+    tag = "sets",
+    responses(
+        (status = 200, description = "`index_vs_btree_tau`, `hash_vs_btree_tau`, `hash_vs_index_tau`, the sampled names in each set's order, `sample_size` and `compare_time_ns`", body = Object),
+    )
+)]
+pub async fn sets_iteration_order_diff(
+    State(state): State<AppState>,
+    request_id: RequestId,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    let sets = state.sets.read().await;
+    let sample: HashSet<Uuid> = sets.index_set.iter().take(ITERATION_ORDER_SAMPLE).map(|p| p.id).collect();
+    let in_sample = |p: &&Product| sample.contains(&p.id);
+    let index_order: Vec<&Product> = sets.index_set.iter().filter(in_sample).collect();
+    let btree_order: Vec<&Product> = sets.btree_set.iter().filter(in_sample).collect();
+    let hash_order: Vec<&Product> = sets.hash_set.iter().filter(in_sample).collect();
+
+    let ids = |order: &[&Product]| order.iter().map(|p| p.id).collect::<Vec<Uuid>>();
+    let names = |order: &[&Product]| order.iter().map(|p| p.name.clone()).collect::<Vec<String>>();
+    let (index_ids, btree_ids, hash_ids) = (ids(&index_order), ids(&btree_order), ids(&hash_order));
+    let ((index_vs_btree, hash_vs_btree, hash_vs_index), elapsed) = sets::timed(|| {
+        (
+            sets::kendall_tau(&index_ids, &btree_ids),
+            sets::kendall_tau(&hash_ids, &btree_ids),
+            sets::kendall_tau(&hash_ids, &index_ids),
+        )
+    });
+    let body = serde_json::json!({
+        "sample_size": sample.len(),
+        "index_vs_btree_tau": index_vs_btree,
+        "hash_vs_btree_tau": hash_vs_btree,
+        "hash_vs_index_tau": hash_vs_index,
+        "index_sample": names(&index_order),
+        "btree_sample": names(&btree_order),
+        "hash_sample": names(&hash_order),
+        "compare_time_ns": elapsed.as_nanos() as u64,
+    });
+    drop(sets);
+
+    state.metrics.write().await.record_raw_with_notes(
+        "iteration_order_diff",
+        "HashSet+LinkedHashSet+BTreeSet",
+        elapsed.as_nanos() as u64,
+        sample.len(),
+        request_id.to_string(),
+    );
+
+    info!(
+        request_id = %request_id,
+        sample = sample.len(),
+        index_vs_btree,
+        hash_vs_btree,
+        hash_vs_index,
+        "Compared set iteration orders"
+    );
+
+    Ok((StatusCode::OK, Json(body)))
+}
+
+// ── GET /api/benchmark/export/csv ────────────────────────────────────────────
+
+#[utoipa::path(
+    get,
+    path = "/api/benchmark/export/csv",
+    tag = "benchmark",
+    responses(
+        (status = 200, description = "Every metrics entry as `benchmark_metrics.csv`", content_type = "text/csv", body = String),
+    )
+)]
+pub async fn export_csv(
+    State(state): State<AppState>,
+    request_id: RequestId,
+) -> Result<Response, crate::error::AppError> {
+    let metrics = state.metrics.read().await;
+    let csv = metrics.to_csv()?;
+
+    info!(request_id = %request_id, entries = metrics.entries.len(), "Exported metrics as CSV");
+
+    Ok(Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "text/csv")
         .header(
             header::CONTENT_DISPOSITION,
             "attachment; filename=\"benchmark_metrics.csv\"",
@@ -253,16 +1790,119 @@ pub async fn export_csv(State(state): State<AppState>) -> Result<Response, crate
         .unwrap())
 }
 
+// ── GET /api/benchmark/export/parquet ────────────────────────────────────────
+
+/// Every metrics entry as a Parquet file, readable directly by DuckDB, Spark
+/// or pandas. Only built with the `parquet` feature.
+#[cfg(feature = "parquet")]
+#[utoipa::path(
+    get,
+    path = "/api/benchmark/export/parquet",
+    tag = "benchmark",
+    responses(
+        (status = 200, description = "Every metrics entry as `benchmark_metrics.parquet`", content_type = "application/octet-stream", body = Vec<u8>),
+    )
+)]
+pub async fn export_parquet(
+    State(state): State<AppState>,
+    request_id: RequestId,
+) -> Result<Response, crate::error::AppError> {
+    let metrics = state.metrics.read().await;
+    let bytes = metrics.to_parquet_bytes()?;
+
+    info!(request_id = %request_id, entries = metrics.entries.len(), bytes = bytes.len(), "Exported metrics as Parquet");
+
+    Ok(Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "application/octet-stream")
+        .header(
+            header::CONTENT_DISPOSITION,
+            "attachment; filename=\"benchmark_metrics.parquet\"",
+        )
+        .body(axum::body::Body::from(bytes))
+        .unwrap())
+}
+
+// ── GET /api/benchmark/export/influx ─────────────────────────────────────────
+
+/// Every metrics entry in InfluxDB line protocol, ready for `influx write`.
+#[utoipa::path(
+    get,
+    path = "/api/benchmark/export/influx",
+    tag = "benchmark",
+    responses(
+        (status = 200, description = "Every metrics entry in InfluxDB line protocol", content_type = "text/plain", body = String),
+    )
+)]
+pub async fn export_influx(
+    State(state): State<AppState>,
+    request_id: RequestId,
+) -> Result<Response, crate::error::AppError> {
+    let metrics = state.metrics.read().await;
+    let body = metrics.to_influx_line_protocol();
+
+    info!(request_id = %request_id, entries = metrics.entries.len(), "Exported metrics as line protocol");
+
+    Ok(Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+        .body(axum::body::Body::from(body))
+        .unwrap())
+}
+
+// ── GET /api/benchmark/export/msgpack ────────────────────────────────────────
+
+/// The last benchmark report as MessagePack — a compact binary alternative to
+/// `GET /api/benchmark/report`.
+#[utoipa::path(
+    get,
+    path = "/api/benchmark/export/msgpack",
+    tag = "benchmark",
+    responses(
+        (status = 200, description = "The last BenchmarkReport, MessagePack-encoded", content_type = "application/x-msgpack", body = Vec<u8>),
+        (status = 404, description = "No benchmark has been run yet", body = ErrorResponse),
+    )
+)]
+pub async fn export_msgpack(
+    State(state): State<AppState>,
+    request_id: RequestId,
+) -> Result<Response, crate::error::AppError> {
+    let sets = state.sets.read().await;
+    let report = sets.last_report.as_ref().ok_or_else(|| {
+        crate::error::AppError::NotFound("No benchmark has been run yet. POST /api/benchmark/run first.".to_string())
+    })?;
+    let body = report.to_msgpack()?;
+
+    info!(request_id = %request_id, run_id = %report.run_id, bytes = body.len(), "Exported benchmark report as MessagePack");
+
+    Ok(Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "application/x-msgpack")
+        .body(axum::body::Body::from(body))
+        .unwrap())
+}
+
 // ── GET /api/benchmark/export/json ───────────────────────────────────────────
 
+#[utoipa::path(
+    get,
+    path = "/api/benchmark/export/json",
+    tag = "benchmark",
+    responses(
+        (status = 200, description = "Raw metrics `entries`, their `aggregated` figures and an `ascii_table`", body = Object),
+    )
+)]
 pub async fn export_json(
     State(state): State<AppState>,
+    request_id: RequestId,
 ) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
     let metrics = state.metrics.read().await;
     let entries = &metrics.entries;
     let aggregated = metrics.aggregated();
     let ascii = metrics.ascii_table();
 
+    info!(request_id = %request_id, entries = entries.len(), "Exported metrics as JSON");
+
     Ok((
         StatusCode::OK,
         Json(serde_json::json!({
@@ -274,28 +1914,214 @@ pub async fn export_json(
     ))
 }
 
-// ── DELETE /api/reset ─────────────────────────────────────────────────────────
+// ── GET /api/benchmark/schema ────────────────────────────────────────────────
 
-pub async fn reset_all(
-    State(state): State<AppState>,
+#[utoipa::path(
+    get,
+    path = "/api/benchmark/schema",
+    tag = "benchmark",
+    responses(
+        (status = 200, description = "JSON Schema (draft-07) for BenchmarkReport", body = Object),
+    )
+)]
+pub async fn export_benchmark_json_schema(
+    request_id: RequestId,
 ) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
-    // 1. Wipe DB (devolutions cascade automatically)
-    let rows_deleted = db::delete_all_products(&state.db).await?;
+    info!(request_id = %request_id, "Served benchmark report schema");
 
-    // 2. Clear in-memory sets + last benchmark report
-    state.sets.write().await.reset();
+    Ok((StatusCode::OK, Json(benchmark_report_schema())))
+}
 
-    // 3. Clear accumulated metrics
-    state.metrics.write().await.clear();
+/// Hand-authored JSON Schema (draft-07) for `BenchmarkReport`.
+///
+/// Keep in sync with `BenchmarkReport`, `SetBenchmarkResult`, `OpTiming`,
+/// `PriceRangeBenchmark` and `SummaryRow` in `sets/mod.rs` — client generators rely on it.
+fn benchmark_report_schema() -> serde_json::Value {
+    serde_json::json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "$id": "https://inventory-service/schemas/benchmark-report.json",
+        "title": "BenchmarkReport",
+        "description": "Result of one POST /api/benchmark/run comparing all in-memory set types.",
+        "type": "object",
+        "required": [
+            "run_id", "run_at", "product_count", "results", "winner_insert",
+            "winner_lookup", "winner_iterate", "summary_table"
+        ],
+        "properties": {
+            "run_id": {
+                "type": "string",
+                "format": "uuid",
+                "description": "ID of the run in benchmark history (GET /api/benchmark/history/:id)"
+            },
+            "run_at": {
+                "type": "string",
+                "format": "date-time",
+                "description": "RFC 3339 timestamp of the run"
+            },
+            "product_count": {
+                "type": "integer",
+                "minimum": 0,
+                "description": "Number of products loaded into each set"
+            },
+            "results": {
+                "type": "array",
+                "description": "One entry per benchmarked set type",
+                "items": { "$ref": "#/definitions/SetBenchmarkResult" }
+            },
+            "winner_insert": { "type": "string", "description": "Set type with the fastest insert_all" },
+            "winner_lookup": { "type": "string", "description": "Set type with the fastest lookup_hit" },
+            "winner_iterate": { "type": "string", "description": "Set type with the fastest iterate_all" },
+            "summary_table": {
+                "type": "array",
+                "description": "Flattened per-set timings used by the ASCII table",
+                "items": { "$ref": "#/definitions/SummaryRow" }
+            },
+            "price_range": { "$ref": "#/definitions/PriceRangeBenchmark" },
+            "prefix_search": { "$ref": "#/definitions/PrefixSearchBenchmark" }
+        },
+        "definitions": {
+            "OpTiming": {
+                "type": "object",
+                "description": "Duration of a single measured operation (median across runs when repeat_runs > 1)",
+                "required": ["duration_ns", "duration_us", "duration_ms"],
+                "properties": {
+                    "duration_ns": { "type": "integer", "minimum": 0, "description": "Nanoseconds elapsed" },
+                    "duration_us": { "type": "number", "description": "Microseconds elapsed" },
+                    "duration_ms": { "type": "number", "description": "Milliseconds elapsed" },
+                    "min_ns": { "type": "integer", "minimum": 0, "description": "Fastest run in nanoseconds (repeat_runs > 1 only)" },
+                    "max_ns": { "type": "integer", "minimum": 0, "description": "Slowest run in nanoseconds (repeat_runs > 1 only)" }
+                }
+            },
+            "PriceRangeBenchmark": {
+                "type": "object",
+                "description": "Selecting every product priced within [min_cents, max_cents]: full BTreeSet scan vs. price index (absent when there were no products)",
+                "required": ["min_cents", "max_cents", "matches", "full_scan", "price_index"],
+                "properties": {
+                    "min_cents": { "type": "integer", "description": "Inclusive lower price bound" },
+                    "max_cents": { "type": "integer", "description": "Inclusive upper price bound" },
+                    "matches": { "type": "integer", "minimum": 0, "description": "Products in the range" },
+                    "full_scan": { "$ref": "#/definitions/OpTiming" },
+                    "price_index": { "$ref": "#/definitions/OpTiming" }
+                }
+            },
+            "PrefixSearchBenchmark": {
+                "type": "object",
+                "description": "Case-insensitive name-prefix search: IndexSet scan vs. BTreeSet range, for a short and a long prefix (absent when there were no products)",
+                "required": ["short", "long"],
+                "properties": {
+                    "short": { "$ref": "#/definitions/PrefixSearchTiming" },
+                    "long": { "$ref": "#/definitions/PrefixSearchTiming" }
+                }
+            },
+            "PrefixSearchTiming": {
+                "type": "object",
+                "required": ["prefix", "matches", "index_scan", "btree_range", "faster"],
+                "properties": {
+                    "prefix": { "type": "string", "description": "Lowercased prefix searched for" },
+                    "matches": { "type": "integer", "minimum": 0, "description": "Products whose name starts with the prefix" },
+                    "index_scan": { "$ref": "#/definitions/OpTiming" },
+                    "btree_range": { "$ref": "#/definitions/OpTiming" },
+                    "faster": { "type": "string", "enum": ["IndexSet scan", "BTreeSet range"] }
+                }
+            },
+            "SetBenchmarkResult": {
+                "type": "object",
+                "description": "Timings for one set type",
+                "required": [
+                    "set_type", "description", "product_count", "insert_all", "lookup_hit",
+                    "lookup_miss", "iterate_all", "remove_half", "iteration_order_sample",
+                    "order_guaranteed", "order_type"
+                ],
+                "properties": {
+                    "set_type": { "type": "string", "description": "Set implementation name" },
+                    "description": { "type": "string", "description": "What makes this set unique" },
+                    "product_count": { "type": "integer", "minimum": 0, "description": "Products inserted" },
+                    "insert_all": { "$ref": "#/definitions/OpTiming" },
+                    "lookup_hit": { "$ref": "#/definitions/OpTiming" },
+                    "lookup_miss": { "$ref": "#/definitions/OpTiming" },
+                    "iterate_all": { "$ref": "#/definitions/OpTiming" },
+                    "remove_half": { "$ref": "#/definitions/OpTiming" },
+                    "iteration_order_sample": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "maxItems": 10,
+                        "description": "First 10 product names in iteration order"
+                    },
+                    "order_guaranteed": { "type": "boolean", "description": "Whether iteration order is deterministic" },
+                    "order_type": { "type": "string", "description": "Human-readable iteration order" },
+                    "threads": {
+                        "type": "integer",
+                        "minimum": 1,
+                        "description": "Worker threads (concurrent benchmarks only)"
+                    },
+                    "per_thread_ops_per_sec": {
+                        "type": "number",
+                        "description": "Operations per second per worker thread (concurrent benchmarks only)"
+                    },
+                    "extra_info": {
+                        "type": "string",
+                        "description": "Structure-specific notes (e.g. BinaryHeap top-k extraction time)"
+                    },
+                    "remove_strategy": {
+                        "type": "string",
+                        "enum": ["swap", "shift"],
+                        "description": "IndexSet removal: swap_remove (O(1), reorders) or shift_remove (O(n), keeps order)"
+                    }
+                }
+            },
+            "SummaryRow": {
+                "type": "object",
+                "description": "One row of the benchmark summary table",
+                "required": [
+                    "set_type", "insert_ms", "lookup_hit_us", "lookup_miss_us",
+                    "iterate_ms", "remove_ms", "order"
+                ],
+                "properties": {
+                    "set_type": { "type": "string", "description": "Set implementation name" },
+                    "insert_ms": { "type": "number", "description": "insert_all in milliseconds" },
+                    "lookup_hit_us": { "type": "number", "description": "lookup_hit in microseconds" },
+                    "lookup_miss_us": { "type": "number", "description": "lookup_miss in microseconds" },
+                    "iterate_ms": { "type": "number", "description": "iterate_all in milliseconds" },
+                    "remove_ms": { "type": "number", "description": "remove_half in milliseconds" },
+                    "order": { "type": "string", "description": "Human-readable iteration order" }
+                }
+            }
+        }
+    })
+}
 
-    info!(rows_deleted, "Full reset: DB, sets, and metrics cleared");
+// ── DELETE /api/benchmark/sets ────────────────────────────────────────────────
+
+/// Empties the in-memory sets and drops the last benchmark report, leaving
+/// the database and the metrics alone. `POST /api/benchmark/sets/sync`
+/// reloads them.
+#[utoipa::path(
+    delete,
+    path = "/api/benchmark/sets",
+    tag = "sets",
+    responses(
+        (status = 200, description = "`cleared`: per-set sizes before the reset, `report_cleared`, and the now-empty `sizes`", body = Object),
+    )
+)]
+pub async fn sets_reset(
+    State(state): State<AppState>,
+    request_id: RequestId,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    let mut sets = state.sets.write().await;
+    let cleared = sets.sizes();
+    let report_cleared = sets.last_report.is_some();
+    sets.reset();
+    let sizes = sets.sizes();
+    drop(sets);
+
+    info!(request_id = %request_id, cleared = ?cleared, report_cleared, "In-memory sets cleared");
 
     Ok((
         StatusCode::OK,
         Json(serde_json::json!({
-            "deleted_products": rows_deleted,
-            "sets_cleared": true,
-            "metrics_cleared": true,
+            "cleared": cleared,
+            "report_cleared": report_cleared,
+            "sizes": sizes,
         })),
     ))
 }
@@ -351,3 +2177,584 @@ fn render_benchmark_ascii_table(report: &crate::sets::BenchmarkReport) -> String
 
     out
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::offline_state;
+    use crate::sets::{SetManager, SetSizes};
+
+    #[sqlx::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn compare_diffs_two_stored_runs(pool: sqlx::PgPool) {
+        let products = seed::seed_products(&pool, 50).await.unwrap();
+        let state = AppState { db: pool, ..offline_state() };
+        let config = SetBenchmarkConfig { threads: 1, ..Default::default() };
+        let mut sets = SetManager::new();
+        let a = sets.run_benchmark(products.clone(), &config).unwrap();
+        let b = sets.run_benchmark(products, &config).unwrap();
+        db::insert_benchmark_run(&state.db, &a).await.unwrap();
+        db::insert_benchmark_run(&state.db, &b).await.unwrap();
+
+        let params = CompareParams { run_a: a.run_id, run_b: b.run_id };
+        let (status, Json(body)) = compare_benchmark_runs(State(state.clone()), RequestId::new(), Query(params)).await.unwrap();
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["delta"]["run_a"], a.run_id.to_string());
+        assert_eq!(body["delta"]["sets"].as_array().unwrap().len(), a.results.len());
+
+        let missing = CompareParams { run_a: a.run_id, run_b: Uuid::new_v4() };
+        let err = compare_benchmark_runs(State(state), RequestId::new(), Query(missing)).await.unwrap_err();
+        assert!(matches!(err, crate::error::AppError::NotFound(_)));
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn clearing_the_sets_keeps_every_product_in_the_database(pool: sqlx::PgPool) {
+        let products = seed::seed_products(&pool, 30).await.unwrap();
+        let state = AppState { db: pool, ..offline_state() };
+        // Leaves the sets holding every product, plus the report
+        state.sets.write().await.run_benchmark(products, &SetBenchmarkConfig { threads: 1, ..Default::default() }).unwrap();
+        state.metrics.write().await.record_raw("lookup", "HashSet", 1, 1);
+
+        let (status, Json(body)) = sets_reset(State(state.clone()), RequestId::new()).await.unwrap();
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["cleared"]["hash_set"], 30);
+        assert_eq!(body["report_cleared"], true);
+
+        let sets = state.sets.read().await;
+        assert_eq!(sets.sizes(), SetSizes::uniform(0, None));
+        assert!(sets.last_report.is_none());
+        assert_eq!(db::count_products(&state.db).await.unwrap(), 30);
+        assert_eq!(state.metrics.read().await.entries.len(), 1, "metrics are left alone");
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn run_with_baseline_adds_a_regression_check(pool: sqlx::PgPool) {
+        let products = seed::seed_products(&pool, 50).await.unwrap();
+        let state = AppState { db: pool, ..offline_state() };
+        let config = SetBenchmarkConfig { threads: 1, ..Default::default() };
+        let baseline = SetManager::new().run_benchmark(products, &config).unwrap();
+        db::insert_benchmark_run(&state.db, &baseline).await.unwrap();
+
+        let params = RunBenchmarkParams {
+            config: Some(config.clone()),
+            baseline_run_id: Some(baseline.run_id),
+            ..Default::default()
+        };
+        let (_, Json(body)) = run_benchmark(State(state.clone()), RequestId::new(), JsonOrDefault(params)).await.unwrap();
+        assert_eq!(body["baseline_run_id"], baseline.run_id.to_string());
+        let alerts = body["regression_check"].as_array().unwrap();
+        assert_eq!(alerts.len(), baseline.results.len() * 5);
+        assert!(alerts.iter().all(|a| ["ok", "warning", "regression"].contains(&a["severity"].as_str().unwrap())));
+
+        let (_, Json(body)) = run_benchmark(State(state.clone()), RequestId::new(), JsonOrDefault::default()).await.unwrap();
+        assert!(body.get("regression_check").is_none());
+
+        let missing = RunBenchmarkParams { config: Some(config), baseline_run_id: Some(Uuid::new_v4()), ..Default::default() };
+        let err = run_benchmark(State(state), RequestId::new(), JsonOrDefault(missing)).await.unwrap_err();
+        assert!(matches!(err, crate::error::AppError::NotFound(_)));
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn sampled_runs_benchmark_only_the_sample(pool: sqlx::PgPool) {
+        let products = seed::seed_products(&pool, 40).await.unwrap();
+        let state = AppState { db: pool, ..offline_state() };
+        let config = SetBenchmarkConfig { threads: 1, ..Default::default() };
+
+        for strategy in [SampleStrategy::First, SampleStrategy::Last, SampleStrategy::Random] {
+            let params = RunBenchmarkParams {
+                config: Some(config.clone()),
+                sample_size: Some(15),
+                sample_strategy: Some(strategy),
+                ..Default::default()
+            };
+            let (_, Json(body)) = run_benchmark(State(state.clone()), RequestId::new(), JsonOrDefault(params)).await.unwrap();
+            assert_eq!(body["report"]["product_count"], 15, "{:?}", strategy);
+            assert_eq!(body["sample"]["sample_strategy"], serde_json::json!(strategy));
+        }
+
+        // Larger than the table: everything is loaded
+        let params = RunBenchmarkParams { config: Some(config.clone()), sample_size: Some(1_000), ..Default::default() };
+        let (_, Json(body)) = run_benchmark(State(state.clone()), RequestId::new(), JsonOrDefault(params)).await.unwrap();
+        assert_eq!(body["report"]["product_count"], products.len());
+
+        let params = RunBenchmarkParams { sample_size: Some(0), ..Default::default() };
+        let err = run_benchmark(State(state), RequestId::new(), JsonOrDefault(params)).await.unwrap_err();
+        assert!(matches!(err, crate::error::AppError::BadRequest(_)));
+    }
+
+    #[tokio::test]
+    async fn msgpack_export_serves_the_last_report() {
+        let state = offline_state();
+        let err = export_msgpack(State(state.clone()), RequestId::new()).await.unwrap_err();
+        assert!(matches!(err, crate::error::AppError::NotFound(_)));
+
+        let products = seed::generate_products(30);
+        let config = SetBenchmarkConfig { threads: 1, ..Default::default() };
+        let report = state.sets.write().await.run_benchmark(products, &config).unwrap();
+
+        let response = export_msgpack(State(state), RequestId::new()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/x-msgpack");
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(BenchmarkReport::from_msgpack(&bytes).unwrap().run_id, report.run_id);
+    }
+
+    #[tokio::test]
+    async fn prefix_search_reports_all_three_sets() {
+        let state = offline_state();
+        {
+            let mut sets = state.sets.write().await;
+            for name in ["Pro Drill", "probe", "Widget"] {
+                sets.insert_product(&crate::models::Product {
+                    id: Uuid::new_v4(),
+                    name: name.to_string(),
+                    description: None,
+                    price_cents: 100,
+                    quantity: 1,
+                    category: "Tools".to_string(),
+                    created_at: chrono::Utc::now(),
+                    updated_at: chrono::Utc::now(),
+                    version: 1,
+                });
+            }
+        }
+
+        let params = PrefixSearchParams { prefix: "PRO".to_string(), limit: Some(1) };
+        let (status, Json(body)) = sets_prefix_search(State(state.clone()), RequestId::new(), Query(params)).await.unwrap();
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["consistent"], true);
+        for set in ["index_set", "btree_set", "hash_set"] {
+            assert_eq!(body[set]["count"], 2, "{}", set);
+            assert_eq!(body[set]["items"].as_array().unwrap().len(), 1, "{}", set);
+        }
+        assert_eq!(body["btree_set"]["items"][0]["name"], "Pro Drill");
+        assert_eq!(state.metrics.read().await.entries.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn top_n_ranks_by_the_requested_field_both_ways() {
+        let state = offline_state();
+        {
+            let mut sets = state.sets.write().await;
+            for p in seed::generate_products(200) {
+                sets.insert_product(&p);
+            }
+        }
+
+        let params = TopNParams { n: Some(5), by: TopNField::Quantity };
+        let (status, Json(body)) = sets_top_n(State(state.clone()), RequestId::new(), Query(params)).await.unwrap();
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!((body["by"].as_str(), body["n"].as_u64(), body["consistent"].as_bool()), (Some("quantity"), Some(5), Some(true)));
+        let quantities: Vec<i64> = body["heap"]["items"].as_array().unwrap().iter().map(|p| p["quantity"].as_i64().unwrap()).collect();
+        assert_eq!(quantities.len(), 5);
+        assert!(quantities.windows(2).all(|w| w[0] >= w[1]), "{:?}", quantities);
+        let max = state.sets.read().await.hash_set.iter().map(|p| p.quantity).max().unwrap();
+        assert_eq!(quantities[0], i64::from(max));
+        assert_eq!(state.metrics.read().await.entries.len(), 2);
+
+        let parsed: TopNParams = serde_json::from_str("{}").unwrap();
+        assert_eq!(parsed.by, TopNField::Price);
+    }
+
+    #[tokio::test]
+    async fn partitions_summarise_every_category_in_name_order() {
+        let state = offline_state();
+        {
+            let mut sets = state.sets.write().await;
+            for p in seed::generate_products(200) {
+                sets.insert_product(&p);
+            }
+        }
+
+        let (status, Json(body)) = sets_partitions(State(state.clone()), RequestId::new()).await.unwrap();
+        assert_eq!(status, StatusCode::OK);
+        let partitions = body["partitions"].as_array().unwrap();
+        let categories: Vec<&str> = partitions.iter().map(|p| p["category"].as_str().unwrap()).collect();
+        assert!(categories.windows(2).all(|w| w[0] < w[1]), "{:?}", categories);
+        assert_eq!(partitions.iter().map(|p| p["count"].as_u64().unwrap()).sum::<u64>(), 200);
+        let largest = body["largest"]["count"].as_u64().unwrap();
+        assert!(partitions.iter().all(|p| p["count"].as_u64().unwrap() <= largest));
+        assert_eq!(state.metrics.read().await.entries.len(), 1);
+
+        let (_, Json(body)) = sets_partitions(State(offline_state()), RequestId::new()).await.unwrap();
+        assert_eq!((body["category_count"].as_u64(), body["largest"].is_null()), (Some(0), true));
+    }
+
+    #[tokio::test]
+    async fn rebalance_only_reorders_an_index_set_that_is_out_of_order() {
+        let state = offline_state();
+        {
+            let mut sets = state.sets.write().await;
+            let start = chrono::Utc::now();
+            for (i, mut p) in seed::generate_products(5).into_iter().enumerate() {
+                p.created_at = start + chrono::Duration::seconds(i as i64);
+                sets.insert_product(&p);
+            }
+        }
+
+        let (_, Json(body)) = sets_rebalance(State(state.clone()), RequestId::new()).await.unwrap();
+        assert_eq!((body["was_fifo"].as_bool(), body["rebalanced"].as_bool()), (Some(true), Some(false)));
+        assert!(body["rebalance_time_ns"].is_null());
+
+        {
+            let mut sets = state.sets.write().await;
+            let first = sets.index_set[0].clone();
+            sets.index_set.swap_remove(&first);
+            sets.index_set.insert(first);
+        }
+        let (status, Json(body)) = sets_rebalance(State(state.clone()), RequestId::new()).await.unwrap();
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["was_fifo"].as_bool(), Some(false));
+        assert_eq!((body["rebalanced"].as_bool(), body["is_fifo"].as_bool()), (Some(true), Some(true)));
+        assert_eq!(body["product_count"].as_u64(), Some(5));
+        assert!(state.sets.read().await.index_set_is_fifo());
+        assert_eq!(state.metrics.read().await.entries.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn iteration_order_diff_ranks_the_same_sample_in_every_set() {
+        let state = offline_state();
+        {
+            let mut sets = state.sets.write().await;
+            // Inserted in name order, so insertion order and name order agree
+            let mut products = seed::generate_products(300);
+            products.sort();
+            for p in &products {
+                sets.insert_product(p);
+            }
+        }
+
+        let (status, Json(body)) = sets_iteration_order_diff(State(state.clone()), RequestId::new()).await.unwrap();
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["sample_size"].as_u64(), Some(100));
+        assert_eq!(body["index_vs_btree_tau"].as_f64(), Some(1.0));
+        assert_eq!(body["index_sample"], body["btree_sample"]);
+        let mut hash_sample: Vec<String> = serde_json::from_value(body["hash_sample"].clone()).unwrap();
+        hash_sample.sort();
+        assert_eq!(serde_json::json!(hash_sample), body["btree_sample"], "same products, in hash order");
+        assert!(body["hash_vs_btree_tau"].as_f64().unwrap() < 0.9);
+        assert_eq!(state.metrics.read().await.entries.len(), 1);
+
+        let (_, Json(body)) = sets_iteration_order_diff(State(offline_state()), RequestId::new()).await.unwrap();
+        assert_eq!((body["sample_size"].as_u64(), body["hash_vs_index_tau"].as_f64()), (Some(0), Some(1.0)));
+    }
+
+    #[tokio::test]
+    async fn lock_contention_rejects_an_empty_run_and_records_both_waits() {
+        let none = LockContentionParams { readers: Some(0), writers: Some(0), ..Default::default() };
+        let err = run_lock_contention_benchmark(State(offline_state()), RequestId::new(), JsonOrDefault(none)).await.unwrap_err();
+        assert!(matches!(err, crate::error::AppError::BadRequest(_)));
+
+        let state = offline_state();
+        let params = LockContentionParams { readers: Some(4), writers: Some(1), duration_secs: Some(0), seed_count: Some(100) };
+        let (status, Json(body)) = run_lock_contention_benchmark(State(state.clone()), RequestId::new(), JsonOrDefault(params)).await.unwrap();
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["result"]["duration_secs"], 1, "clamped to at least a second");
+        assert!(body["result"]["total_reads"].as_u64().unwrap() > 0);
+        let metrics = state.metrics.read().await;
+        let ops: Vec<&str> = metrics.entries.iter().map(|e| e.operation.as_str()).collect();
+        assert_eq!(ops, ["lock_wait_read", "lock_wait_write"]);
+    }
+
+    #[tokio::test]
+    async fn serialization_benchmark_records_both_directions_per_format() {
+        let state = offline_state();
+        let params = SerializationParams { seed_count: Some(100) };
+        let (status, Json(body)) = run_serialization_benchmark(State(state.clone()), RequestId::new(), JsonOrDefault(params)).await.unwrap();
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["result"]["product_count"], 100);
+        assert!(body["result"]["msgpack_size_bytes"].as_u64().unwrap() > 0);
+        let metrics = state.metrics.read().await;
+        let recorded: Vec<(&str, &str)> = metrics.entries.iter().map(|e| (e.operation.as_str(), e.set_type.as_str())).collect();
+        assert_eq!(recorded.len(), 6);
+        assert_eq!(recorded[..2], [("serialize", "JSON"), ("deserialize", "JSON")]);
+    }
+
+    #[tokio::test]
+    async fn snapshot_round_trips_through_restore() {
+        let state = offline_state();
+        {
+            let mut sets = state.sets.write().await;
+            for p in seed::generate_products(30) {
+                sets.insert_product(&p);
+            }
+        }
+        let (_, Json(snapshot)) = sets_snapshot(State(state.clone()), RequestId::new()).await.unwrap();
+        assert_eq!(snapshot.products.len(), 30);
+        let snapshot: SetSnapshot = serde_json::from_value(serde_json::to_value(&snapshot).unwrap()).unwrap();
+
+        state.sets.write().await.reset();
+        let (status, Json(body)) = sets_restore(State(state.clone()), RequestId::new(), Json(snapshot.clone())).await.unwrap();
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["restored"], 30);
+        let ids: Vec<Uuid> = state.sets.read().await.index_set.iter().map(|p| p.id).collect();
+        assert_eq!(ids, snapshot.products.iter().map(|p| p.id).collect::<Vec<_>>());
+
+        let mut duplicated = snapshot;
+        duplicated.products.push(duplicated.products[0].clone());
+        let err = sets_restore(State(state), RequestId::new(), Json(duplicated)).await.unwrap_err();
+        assert!(matches!(err, crate::error::AppError::BadRequest(m) if m.starts_with("products[30]: duplicate id")));
+    }
+
+    #[tokio::test]
+    async fn range_benchmark_rejects_bad_percentiles() {
+        for (low_pct, high_pct) in [(-1.0, 50.0), (10.0, 101.0), (60.0, 40.0), (f64::NAN, 50.0)] {
+            let params = RangeBenchmarkParams { low_pct, high_pct };
+            let err = run_range_benchmark(State(offline_state()), RequestId::new(), Json(params)).await.unwrap_err();
+            assert!(matches!(err, crate::error::AppError::BadRequest(_)), "{} / {}", low_pct, high_pct);
+        }
+    }
+
+    #[tokio::test]
+    async fn range_delete_benchmark_rejects_fractions_outside_zero_to_one() {
+        for remove_fraction in [-0.1, 1.5, f64::NAN] {
+            let params = RangeDeleteParams { remove_fraction };
+            let err = run_range_delete_benchmark(State(offline_state()), RequestId::new(), Json(params)).await.unwrap_err();
+            assert!(matches!(err, crate::error::AppError::BadRequest(_)), "{}", remove_fraction);
+        }
+    }
+
+    #[tokio::test]
+    async fn clone_overhead_benchmark_times_every_step() {
+        let state = offline_state();
+        let params = CloneOverheadParams { count: Some(500) };
+        let (status, Json(body)) = run_clone_overhead_benchmark(State(state.clone()), RequestId::new(), Query(params)).await.unwrap();
+        assert_eq!(status, StatusCode::OK);
+        let result = &body["result"];
+        assert_eq!(result["product_count"], 500);
+        for field in ["clone_vec_ns", "arc_wrap_ns", "arc_insert_ns"] {
+            assert!(result[field].as_u64().unwrap() > 0, "{}", field);
+        }
+        assert!(!result["recommendation"].as_str().unwrap().is_empty());
+        assert_eq!(state.metrics.read().await.entries.len(), 3);
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn range_benchmark_runs_over_the_stored_products(pool: sqlx::PgPool) {
+        seed::seed_products(&pool, 300).await.unwrap();
+        let state = AppState { db: pool, ..offline_state() };
+
+        let params = RangeBenchmarkParams { low_pct: 25.0, high_pct: 75.0 };
+        let (status, Json(body)) = run_range_benchmark(State(state.clone()), RequestId::new(), Json(params)).await.unwrap();
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["product_count"], 300);
+        assert_eq!(body["result"]["results_match"], true);
+        let matches = body["result"]["matches"].as_u64().unwrap();
+        assert!((100..=300).contains(&matches), "{}", matches);
+        assert_eq!(state.metrics.read().await.entries.len(), 2);
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn sets_stats_agree_with_the_db_when_empty_and_after_a_sync(pool: sqlx::PgPool) {
+        let state = AppState { db: pool, ..offline_state() };
+        let (_, Json(body)) = sets_stats(State(state.clone()), RequestId::new()).await.unwrap();
+        assert_eq!(body["results_match"], true);
+        assert_eq!(body["db"], body["in_memory"]);
+        assert_eq!((body["db"]["product_count"].as_u64(), body["db"]["max_price_cents"].as_i64()), (Some(0), Some(0)));
+
+        let products = seed::seed_products(&state.db, 40).await.unwrap();
+        state.sets.write().await.sync_from_db(&products);
+        let (_, Json(body)) = sets_stats(State(state.clone()), RequestId::new()).await.unwrap();
+        assert_eq!(body["discrepancies"], serde_json::json!([]));
+        assert_eq!(body["in_memory"]["product_count"], products.len());
+
+        // Dropping a product from the sets alone shows up as a discrepancy
+        state.sets.write().await.remove_product(products[0].id);
+        let (_, Json(body)) = sets_stats(State(state), RequestId::new()).await.unwrap();
+        assert_eq!(body["results_match"], false);
+        assert!(body["discrepancies"].as_array().unwrap().contains(&"product_count".into()));
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn sets_sync_applies_only_changes_after_the_first_full_sync(pool: sqlx::PgPool) {
+        let products = seed::seed_products(&pool, 20).await.unwrap();
+        // Outside the overlap window, so the incremental sync sees only the changes below
+        sqlx::query("UPDATE products SET updated_at = NOW() - INTERVAL '1 hour'").execute(&pool).await.unwrap();
+        let state = AppState { db: pool, ..offline_state() };
+
+        let (_, Json(body)) = sets_sync(State(state.clone()), RequestId::new(), Query(SyncParams::default())).await.unwrap();
+        assert_eq!(body["mode"], "full");
+        assert_eq!(body["upserted"], 20);
+        assert!(body["since"].is_null());
+
+        let update = crate::models::UpdateProduct {
+            name: Some("Synced Rename".to_string()),
+            description: None,
+            price_cents: None,
+            quantity: None,
+            category: None,
+            expected_version: None,
+        };
+        db::update_product(&state.db, products[0].id, &update).await.unwrap();
+        db::delete_product(&state.db, products[1].id).await.unwrap();
+
+        let (_, Json(body)) = sets_sync(State(state.clone()), RequestId::new(), Query(SyncParams::default())).await.unwrap();
+        assert_eq!(body["mode"], "incremental");
+        assert_eq!(body["upserted"], 1);
+        assert_eq!(body["removed"], 1);
+        assert_eq!(body["sizes"]["hash_set"], 19);
+        let sets = state.sets.read().await;
+        assert_eq!(sets.hash_set.get(&products[0]).unwrap().name, "Synced Rename");
+        assert!(!sets.hash_set.contains(&products[1]));
+        drop(sets);
+
+        let params = SyncParams { full: Some(true) };
+        let (_, Json(body)) = sets_sync(State(state), RequestId::new(), Query(params)).await.unwrap();
+        assert_eq!(body["mode"], "full");
+        assert_eq!(body["upserted"], 19);
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn sets_sync_rereads_changes_stamped_inside_the_overlap_window(pool: sqlx::PgPool) {
+        let products = seed::seed_products(&pool, 5).await.unwrap();
+        sqlx::query("UPDATE products SET updated_at = NOW() - INTERVAL '1 hour'").execute(&pool).await.unwrap();
+        let state = AppState { db: pool.clone(), ..offline_state() };
+        let (_, Json(body)) = sets_sync(State(state.clone()), RequestId::new(), Query(SyncParams::default())).await.unwrap();
+        assert_eq!(body["mode"], "full");
+        let watermark = state.sets.read().await.last_sync_at.unwrap();
+
+        // As if both writes committed after the sync but were stamped before its watermark
+        let stamp = |id: Uuid, name: &'static str, updated_at: chrono::DateTime<chrono::Utc>| {
+            sqlx::query("UPDATE products SET name = $2, updated_at = $3 WHERE id = $1")
+                .bind(id)
+                .bind(name)
+                .bind(updated_at)
+                .execute(&pool)
+        };
+        let second = chrono::TimeDelta::seconds(1);
+        stamp(products[0].id, "Inside Overlap", watermark - db::SYNC_OVERLAP + second).await.unwrap();
+        stamp(products[1].id, "Before Overlap", watermark - db::SYNC_OVERLAP - second).await.unwrap();
+
+        let (_, Json(body)) = sets_sync(State(state.clone()), RequestId::new(), Query(SyncParams::default())).await.unwrap();
+        assert_eq!(body["mode"], "incremental");
+        assert_eq!(body["upserted"], 1);
+        let sets = state.sets.read().await;
+        assert_eq!(sets.hash_set.get(&products[0]).unwrap().name, "Inside Overlap");
+        assert_ne!(sets.hash_set.get(&products[1]).unwrap().name, "Before Overlap");
+    }
+
+    #[test]
+    fn seed_weights_parse_from_the_query_string() {
+        let parse = |uri: &str| Query::<SeedParams>::try_from_uri(&uri.parse().unwrap()).map(|q| q.0.category_weights);
+        let weights = parse("/api/seed?category_weights=Electronics:5,%20Home%20%26%20Garden:%201.5").unwrap().unwrap();
+        assert_eq!(weights.len(), 2);
+        assert_eq!((weights["Electronics"], weights["Home & Garden"]), (5.0, 1.5));
+        assert_eq!(parse("/api/seed?count=10").unwrap(), None);
+        assert_eq!(parse("/api/seed?category_weights=").unwrap(), None);
+        assert!(parse("/api/seed?category_weights=Books").is_err());
+        assert!(parse("/api/seed?category_weights=Books:lots").is_err());
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn weighted_seed_reports_category_counts(pool: sqlx::PgPool) {
+        let state = AppState { db: pool, ..offline_state() };
+        let uri = "/api/seed?count=1000&rng_seed=3&category_weights=Books:9,Jewelry:1".parse().unwrap();
+        let (_, Json(body)) = seed_data(State(state.clone()), RequestId::new(), Query::try_from_uri(&uri).unwrap()).await.unwrap();
+        assert_eq!(body["seeded"], 1000);
+        let counts = body["category_counts"].as_object().unwrap();
+        assert_eq!(counts.keys().collect::<Vec<_>>(), ["Books", "Jewelry"]);
+        let books = counts["Books"].as_u64().unwrap();
+        assert!((850..=950).contains(&books), "{}", books);
+        assert_eq!(body["category_weights"]["Books"], 0.9);
+
+        let uri = "/api/seed?count=10&category_weights=Gizmos:1".parse().unwrap();
+        let err = seed_data(State(state), RequestId::new(), Query::try_from_uri(&uri).unwrap()).await.unwrap_err();
+        assert!(matches!(err, crate::error::AppError::BadRequest(_)));
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn seeding_devolutions_reports_weighted_reasons_and_stats(pool: sqlx::PgPool) {
+        let state = crate::AppState { db: pool, ..offline_state() };
+        let uri = "/api/seed/devolutions?count=10".parse().unwrap();
+        let err = seed_devolutions(State(state.clone()), RequestId::new(), Query::try_from_uri(&uri).unwrap()).await.unwrap_err();
+        assert!(matches!(err, crate::error::AppError::BadRequest(_)), "no products to return yet");
+
+        seed::seed_products_with(&state.db, 50, Some(2), None).await.unwrap();
+        let uri = "/api/seed/devolutions?count=200&rng_seed=2&reason_weights=Changed%20mind:1,Missing%20parts:0".parse().unwrap();
+        let (status, Json(body)) = seed_devolutions(State(state.clone()), RequestId::new(), Query::try_from_uri(&uri).unwrap()).await.unwrap();
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["seeded"], 200);
+        assert_eq!(body["reason_counts"], serde_json::json!({"Changed mind": 200}));
+        assert_eq!(body["reason_weights"]["Missing parts"], 0.0);
+        assert_eq!(body["stats"]["total_returns"], 200);
+        assert_eq!(body["stats"]["top_reasons"][0]["reason"], "Changed mind");
+
+        let uri = "/api/seed/devolutions?reason_weights=Too%20shiny:1".parse().unwrap();
+        let err = seed_devolutions(State(state), RequestId::new(), Query::try_from_uri(&uri).unwrap()).await.unwrap_err();
+        assert!(matches!(err, crate::error::AppError::BadRequest(_)));
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn sets_diff_detects_drift_and_reconciles_on_request(pool: sqlx::PgPool) {
+        let products = seed::seed_products(&pool, 10).await.unwrap();
+        let state = AppState { db: pool, ..offline_state() };
+        state.sets.write().await.sync_from_db(&products);
+
+        let (_, Json(body)) = sets_diff(State(state.clone()), RequestId::new(), Query(DiffParams::default())).await.unwrap();
+        assert_eq!(body["drift"], false);
+
+        // Written to the DB behind the sets' back
+        let payload = crate::models::CreateProduct {
+            name: "Unsynced Lamp".to_string(),
+            description: None,
+            price_cents: 900,
+            quantity: 4,
+            category: crate::models::Category::HomeAndGarden,
+        };
+        let added = db::insert_product(&state.db, &payload).await.unwrap();
+        db::hard_delete_product(&state.db, products[0].id).await.unwrap();
+
+        let (_, Json(body)) = sets_diff(State(state.clone()), RequestId::new(), Query(DiffParams::default())).await.unwrap();
+        assert_eq!(body["drift"], true);
+        assert_eq!(body["reconciled"], false);
+        assert_eq!(body["diff"]["in_db_not_in_sets"], serde_json::json!([added.id]));
+        assert_eq!(body["diff"]["in_sets_not_in_db"], serde_json::json!([products[0].id]));
+        assert_eq!(body["diff"]["count_mismatch"], false);
+        assert!(!state.sets.read().await.hash_set.contains(&added), "reporting alone changes nothing");
+
+        let params = DiffParams { reconcile: Some(true) };
+        let (_, Json(body)) = sets_diff(State(state.clone()), RequestId::new(), Query(params)).await.unwrap();
+        assert_eq!(body["reconciled"], true);
+        assert_eq!(body["sizes"]["hash_set"], 10);
+        let sets = state.sets.read().await;
+        assert!(sets.hash_set.contains(&added) && !sets.hash_set.contains(&products[0]));
+        assert!(!sets::diff_with_db(&sets, &state.db).await.unwrap().has_drift());
+    }
+
+    #[tokio::test]
+    async fn schema_describes_product_count_as_integer() {
+        let (status, Json(body)) = export_benchmark_json_schema(RequestId::new()).await.unwrap();
+        assert_eq!(status, StatusCode::OK);
+
+        let raw = serde_json::to_string(&body).unwrap();
+        let schema: serde_json::Value = serde_json::from_str(&raw).unwrap();
+        assert_eq!(schema["type"], "object");
+        assert_eq!(schema["title"], "BenchmarkReport");
+        assert!(schema["$id"].is_string());
+        assert_eq!(schema["properties"]["product_count"]["type"], "integer");
+    }
+
+    #[test]
+    fn schema_covers_every_report_field() {
+        let schema = benchmark_report_schema();
+        let report = SetManager::new().run_benchmark(vec![], &SetBenchmarkConfig::default()).unwrap();
+        let value = serde_json::to_value(&report).unwrap();
+        for key in value.as_object().unwrap().keys() {
+            assert!(
+                schema["properties"].get(key).is_some(),
+                "schema is missing BenchmarkReport field `{}`",
+                key
+            );
+        }
+    }
+}
@@ -1,36 +1,120 @@
 use std::time::Instant;
 
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     Json,
 };
 use tracing::info;
 use uuid::Uuid;
 
-use crate::{db, error::AppResult, models::CreateDevolution, AppState};
+use crate::{
+    db,
+    error::AppResult,
+    extract::{JsonOrDefault, RequestId},
+    models::{CreateDevolution, DevolutionFilters, DevolutionStatsParams, DevolutionStatus, RejectDevolution},
+    AppState,
+};
 
+#[utoipa::path(
+    get,
+    path = "/api/devolutions",
+    tag = "devolutions",
+    params(DevolutionFilters),
+    responses(
+        (status = 200, description = "`data`: [DevolutionWithProduct] newest first, plus `count`, `total_count`, `has_more` and `query_time_ms`", body = Object),
+        (status = 400, description = "`start_date` after `end_date`", body = ErrorResponse),
+    )
+)]
 pub async fn list_devolutions(
     State(state): State<AppState>,
+    request_id: RequestId,
+    Query(filters): Query<DevolutionFilters>,
 ) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    if let (Some(start), Some(end)) = (filters.start_date, filters.end_date) {
+        if start > end {
+            return Err(crate::error::AppError::BadRequest("start_date must not be after end_date".to_string()));
+        }
+    }
+
     let start = Instant::now();
-    let devolutions = db::fetch_all_devolutions(&state.db).await?;
+    let (devolutions, total_count) = db::fetch_all_devolutions(&state.db, &filters).await?;
     let elapsed = start.elapsed();
 
-    info!(count = devolutions.len(), "Listed devolutions");
+    let has_more = filters.page_offset() + (devolutions.len() as i64) < total_count;
+    info!(request_id = %request_id, count = devolutions.len(), total_count, "Listed devolutions");
 
     Ok((
         StatusCode::OK,
         Json(serde_json::json!({
             "data": devolutions,
             "count": devolutions.len(),
+            "total_count": total_count,
+            "has_more": has_more,
+            "query_time_ms": elapsed.as_secs_f64() * 1000.0,
+        })),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/devolutions/stats",
+    tag = "devolutions",
+    params(DevolutionStatsParams),
+    responses(
+        (status = 200, description = "`data`: DevolutionStats", body = Object),
+        (status = 400, description = "`period_days` out of range", body = ErrorResponse),
+    )
+)]
+pub async fn devolution_stats(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    Query(params): Query<DevolutionStatsParams>,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    if params
+        .period_days
+        .is_some_and(|d| !(1..=DevolutionStatsParams::MAX_PERIOD_DAYS).contains(&d))
+    {
+        return Err(crate::error::AppError::BadRequest(format!(
+            "period_days must be between 1 and {}",
+            DevolutionStatsParams::MAX_PERIOD_DAYS
+        )));
+    }
+
+    let start = Instant::now();
+    let stats = db::fetch_devolution_stats(&state.db, params.period_days).await?;
+    let elapsed = start.elapsed();
+
+    info!(
+        request_id = %request_id,
+        period_days = ?params.period_days,
+        total_returns = stats.total_returns,
+        "Computed devolution stats"
+    );
+
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "data": stats,
             "query_time_ms": elapsed.as_secs_f64() * 1000.0,
         })),
     ))
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/devolutions",
+    tag = "devolutions",
+    request_body = CreateDevolution,
+    responses(
+        (status = 201, description = "`data`: the pending DevolutionWithProduct", body = Object),
+        (status = 400, description = "Non-positive quantity", body = ErrorResponse),
+        (status = 404, description = "No live product with `product_id`", body = ErrorResponse),
+    )
+)]
 pub async fn create_devolution(
     State(state): State<AppState>,
+    request_id: RequestId,
     Json(payload): Json<CreateDevolution>,
 ) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
     let start = Instant::now();
@@ -38,6 +122,7 @@ pub async fn create_devolution(
     let elapsed = start.elapsed();
 
     info!(
+        request_id = %request_id,
         id = %devolution.id,
         product_id = %devolution.product_id,
         quantity = devolution.quantity,
@@ -53,14 +138,111 @@ pub async fn create_devolution(
     ))
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/devolutions/{id}/approve",
+    tag = "devolutions",
+    params(("id" = Uuid, Path, description = "Devolution id")),
+    responses(
+        (status = 200, description = "`data`: the approved DevolutionWithProduct, plus the restocked `product_quantity`", body = Object),
+        (status = 400, description = "Restocking would push the quantity past 1 000 000", body = ErrorResponse),
+        (status = 404, description = "No devolution with this id", body = ErrorResponse),
+        (status = 409, description = "Already decided, or the product is deleted", body = ErrorResponse),
+    )
+)]
+pub async fn approve_devolution(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    Path(id): Path<Uuid>,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    let start = Instant::now();
+    let devolution = db::update_devolution_status(&state.db, id, DevolutionStatus::Approved, None).await?;
+    let elapsed = start.elapsed();
+
+    // Approval restocked the product, so refresh it in every set
+    state.product_cache.invalidate([devolution.product_id]).await;
+    let product = db::fetch_product_by_id(&state.db, devolution.product_id).await?;
+    state.sets.write().await.insert_product(&product);
+
+    info!(
+        request_id = %request_id,
+        id = %id,
+        product_id = %devolution.product_id,
+        restocked = devolution.quantity,
+        quantity = product.quantity,
+        "Approved devolution"
+    );
+
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "data": devolution,
+            "product_quantity": product.quantity,
+            "db_time_ms": elapsed.as_secs_f64() * 1000.0,
+        })),
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/devolutions/{id}/reject",
+    tag = "devolutions",
+    params(("id" = Uuid, Path, description = "Devolution id")),
+    request_body(content = Option<RejectDevolution>, description = "Optional rejection reason"),
+    responses(
+        (status = 200, description = "`data`: the rejected DevolutionWithProduct", body = Object),
+        (status = 404, description = "No devolution with this id", body = ErrorResponse),
+        (status = 409, description = "Already decided", body = ErrorResponse),
+    )
+)]
+pub async fn reject_devolution(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    Path(id): Path<Uuid>,
+    JsonOrDefault(payload): JsonOrDefault<RejectDevolution>,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    let start = Instant::now();
+    let devolution = db::update_devolution_status(
+        &state.db,
+        id,
+        DevolutionStatus::Rejected,
+        payload.rejection_reason.as_deref(),
+    )
+    .await?;
+    let elapsed = start.elapsed();
+
+    info!(request_id = %request_id, id = %id, "Rejected devolution");
+
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "data": devolution,
+            "db_time_ms": elapsed.as_secs_f64() * 1000.0,
+        })),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/devolutions/{id}",
+    tag = "devolutions",
+    params(("id" = Uuid, Path, description = "Devolution id")),
+    responses(
+        (status = 200, description = "`data`: DevolutionWithProduct", body = Object),
+        (status = 404, description = "No devolution with this id", body = ErrorResponse),
+    )
+)]
 pub async fn get_devolution(
     State(state): State<AppState>,
+    request_id: RequestId,
     Path(id): Path<Uuid>,
 ) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
     let start = Instant::now();
     let devolution = db::fetch_devolution_by_id(&state.db, id).await?;
     let elapsed = start.elapsed();
 
+    info!(request_id = %request_id, id = %id, "Fetched devolution");
+
     Ok((
         StatusCode::OK,
         Json(serde_json::json!({
@@ -69,3 +251,26 @@ pub async fn get_devolution(
         })),
     ))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::offline_state;
+
+    #[tokio::test]
+    async fn stats_reject_out_of_range_periods() {
+        for period_days in [0, DevolutionStatsParams::MAX_PERIOD_DAYS + 1] {
+            let params = DevolutionStatsParams { period_days: Some(period_days) };
+            let err = devolution_stats(State(offline_state()), RequestId::new(), Query(params)).await.unwrap_err();
+            assert!(matches!(err, crate::error::AppError::BadRequest(_)), "{}", period_days);
+        }
+    }
+
+    #[tokio::test]
+    async fn list_rejects_a_start_after_the_end() {
+        let now = chrono::Utc::now();
+        let filters = DevolutionFilters { start_date: Some(now), end_date: Some(now - chrono::Duration::days(1)), ..Default::default() };
+        let err = list_devolutions(State(offline_state()), RequestId::new(), Query(filters)).await.unwrap_err();
+        assert!(matches!(err, crate::error::AppError::BadRequest(_)));
+    }
+}
@@ -0,0 +1,315 @@
+use std::{sync::PoisonError, time::Instant};
+
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Query, State,
+    },
+    http::StatusCode,
+    response::Response,
+    Json,
+};
+use serde::Deserialize;
+use tokio::sync::mpsc;
+use tracing::{debug, info};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{
+    db,
+    error::{AppError, AppResult},
+    extract::RequestId,
+    metrics::{self, MetricEntry, MetricsStore},
+    models::MetricHistoryParams,
+    AppState,
+};
+
+/// Most `GET /api/ws/metrics` clients connected at once.
+pub const MAX_WS_CLIENTS: usize = 100;
+
+/// Entries buffered per WebSocket client; a client further behind misses entries.
+const WS_BUFFER: usize = 1_024;
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct MetricsConfigUpdate {
+    pub capacity: usize,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PurgeParams {
+    /// Only drop entries older than this (default: drop every entry)
+    pub older_than_secs: Option<u64>,
+}
+
+// ── GET /api/metrics/config ───────────────────────────────────────────────────
+
+#[utoipa::path(
+    get,
+    path = "/api/metrics/config",
+    tag = "metrics",
+    responses(
+        (status = 200, description = "`capacity`, `entry_count` and `max_capacity`", body = Object),
+    )
+)]
+pub async fn get_config(
+    State(state): State<AppState>,
+    request_id: RequestId,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    let metrics = state.metrics.read().await;
+
+    info!(request_id = %request_id, capacity = metrics.capacity(), "Fetched metrics config");
+
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "capacity": metrics.capacity(),
+            "entry_count": metrics.entries.len(),
+            "max_capacity": MetricsStore::MAX_CAPACITY,
+        })),
+    ))
+}
+
+// ── PUT /api/metrics/config ───────────────────────────────────────────────────
+
+/// Resizes the metrics window. Shrinking evicts the oldest entries right away.
+#[utoipa::path(
+    put,
+    path = "/api/metrics/config",
+    tag = "metrics",
+    request_body = MetricsConfigUpdate,
+    responses(
+        (status = 200, description = "The new `capacity`, `entry_count` and how many entries were `evicted`", body = Object),
+        (status = 400, description = "Capacity outside 1..=`max_capacity`", body = ErrorResponse),
+    )
+)]
+pub async fn update_config(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    Json(payload): Json<MetricsConfigUpdate>,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    if !(1..=MetricsStore::MAX_CAPACITY).contains(&payload.capacity) {
+        return Err(AppError::BadRequest(format!(
+            "capacity must be between 1 and {}",
+            MetricsStore::MAX_CAPACITY
+        )));
+    }
+
+    let mut metrics = state.metrics.write().await;
+    let evicted = metrics.set_capacity(payload.capacity);
+
+    info!(request_id = %request_id, capacity = payload.capacity, evicted, "Metrics capacity updated");
+
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "capacity": metrics.capacity(),
+            "entry_count": metrics.entries.len(),
+            "evicted": evicted,
+        })),
+    ))
+}
+
+// ── DELETE /api/metrics?older_than_secs=3600 ──────────────────────────────────
+
+/// Drops entries recorded more than `older_than_secs` ago, or every entry
+/// when it is left out. Persisted rows in the `metrics` table are kept.
+#[utoipa::path(
+    delete,
+    path = "/api/metrics",
+    tag = "metrics",
+    params(PurgeParams),
+    responses(
+        (status = 200, description = "`purged` count, remaining `entry_count` and the `cutoff` used (`null` when everything was cleared)", body = Object),
+    )
+)]
+pub async fn purge(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    Query(params): Query<PurgeParams>,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    let cutoff = params.older_than_secs.map(metrics::cutoff_for_age);
+    let mut metrics = state.metrics.write().await;
+    let purged = match cutoff {
+        Some(cutoff) => metrics.purge_before(cutoff),
+        None => {
+            let purged = metrics.entries.len();
+            metrics.clear();
+            purged
+        }
+    };
+
+    info!(request_id = %request_id, older_than_secs = params.older_than_secs, purged, "Purged metrics entries");
+
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "purged": purged,
+            "entry_count": metrics.entries.len(),
+            "cutoff": cutoff,
+        })),
+    ))
+}
+
+// ── GET /api/metrics/history ──────────────────────────────────────────────────
+
+/// Entries the persistence task has moved to the `metrics` table, newest first.
+#[utoipa::path(
+    get,
+    path = "/api/metrics/history",
+    tag = "metrics",
+    params(MetricHistoryParams),
+    responses(
+        (status = 200, description = "`data`: [StoredMetric], newest first, plus `count` and `query_time_ms`", body = Object),
+    )
+)]
+pub async fn history(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    Query(params): Query<MetricHistoryParams>,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    let limit = params.limit.unwrap_or(100).clamp(1, 10_000);
+
+    let start = Instant::now();
+    let entries = db::fetch_metric_history(&state.db, params.operation.as_deref(), limit).await?;
+    let elapsed = start.elapsed();
+
+    info!(request_id = %request_id, count = entries.len(), operation = ?params.operation, "Fetched metric history");
+
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "data": entries,
+            "count": entries.len(),
+            "query_time_ms": elapsed.as_secs_f64() * 1000.0,
+        })),
+    ))
+}
+
+// ── GET /api/ws/metrics ───────────────────────────────────────────────────────
+
+/// Streams metrics over a WebSocket: first `{"type": "snapshot", "aggregated":
+/// [AggregatedMetric]}`, then `{"type": "entry", "entry": MetricEntry}` for
+/// each entry recorded while connected. Messages from the client are ignored.
+#[utoipa::path(
+    get,
+    path = "/api/ws/metrics",
+    tag = "metrics",
+    responses(
+        (status = 101, description = "Switched to a WebSocket carrying one `snapshot` message, then `entry` messages"),
+        (status = 503, description = "Already 100 clients connected", body = ErrorResponse),
+    )
+)]
+pub async fn ws_metrics(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    ws: WebSocketUpgrade,
+) -> AppResult<Response> {
+    let (tx, rx) = mpsc::channel(WS_BUFFER);
+    {
+        let mut subscribers = state.metric_subscribers.write().unwrap_or_else(PoisonError::into_inner);
+        subscribers.retain(|tx| !tx.is_closed());
+        if subscribers.len() >= MAX_WS_CLIENTS {
+            return Err(AppError::ServiceUnavailable(format!(
+                "{} metric stream clients already connected",
+                MAX_WS_CLIENTS
+            )));
+        }
+        subscribers.push(tx);
+        info!(request_id = %request_id, clients = subscribers.len(), "Metric stream client connected");
+    }
+
+    Ok(ws.on_upgrade(move |socket| stream_metrics(socket, state, rx, request_id)))
+}
+
+/// Runs until either side closes. Dropping `rx` on the way out lets the next
+/// recorded entry prune this client's sender.
+async fn stream_metrics(mut socket: WebSocket, state: AppState, mut rx: mpsc::Receiver<MetricEntry>, request_id: RequestId) {
+    let snapshot = serde_json::json!({ "type": "snapshot", "aggregated": state.metrics.read().await.aggregated() });
+    if socket.send(Message::Text(snapshot.to_string())).await.is_err() {
+        return;
+    }
+
+    loop {
+        tokio::select! {
+            entry = rx.recv() => {
+                let Some(entry) = entry else { break };
+                let message = serde_json::json!({ "type": "entry", "entry": entry });
+                if socket.send(Message::Text(message.to_string())).await.is_err() {
+                    break;
+                }
+            }
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Close(_)) | Err(_)) | None => break,
+                // axum answers pings itself
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+
+    debug!(request_id = %request_id, "Metric stream client disconnected");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::offline_state;
+
+    #[tokio::test]
+    async fn shrinking_capacity_reports_evictions() {
+        let state = offline_state();
+        {
+            let mut metrics = state.metrics.write().await;
+            for ns in 0..10 {
+                metrics.record_raw("lookup", "HashSet", ns, 1);
+            }
+        }
+
+        let (_, Json(body)) = update_config(State(state.clone()), RequestId::new(), Json(MetricsConfigUpdate { capacity: 4 }))
+            .await
+            .unwrap();
+        assert_eq!(body["evicted"], 6);
+        assert_eq!(body["entry_count"], 4);
+
+        let (_, Json(body)) = get_config(State(state), RequestId::new()).await.unwrap();
+        assert_eq!(body["capacity"], 4);
+    }
+
+    #[tokio::test]
+    async fn purge_drops_only_old_entries() {
+        let state = offline_state();
+        {
+            let mut metrics = state.metrics.write().await;
+            metrics.record_raw("lookup", "HashSet", 1, 1);
+            let mut old = crate::metrics::MetricEntry::new("lookup", "HashSet", 2, 1, true, None);
+            old.timestamp = chrono::Utc::now() - chrono::TimeDelta::hours(2);
+            metrics.record(old);
+        }
+
+        let (status, Json(body)) = purge(State(state.clone()), RequestId::new(), Query(PurgeParams { older_than_secs: Some(3600) }))
+            .await
+            .unwrap();
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["purged"], 1);
+        assert_eq!(body["entry_count"], 1);
+        assert_eq!(state.metrics.read().await.entries[0].duration_ns, 1);
+    }
+
+    #[tokio::test]
+    async fn purge_without_an_age_clears_every_entry() {
+        let state = offline_state();
+        for ns in 1..=3 {
+            state.metrics.write().await.record_raw("lookup", "HashSet", ns, 1);
+        }
+        let (_, Json(body)) = purge(State(state.clone()), RequestId::new(), Query(PurgeParams { older_than_secs: None })).await.unwrap();
+        assert_eq!((body["purged"].as_u64(), body["entry_count"].as_u64()), (Some(3), Some(0)));
+        assert!(body["cutoff"].is_null());
+    }
+
+    #[tokio::test]
+    async fn zero_capacity_is_rejected() {
+        let err = update_config(State(offline_state()), RequestId::new(), Json(MetricsConfigUpdate { capacity: 0 }))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::BadRequest(_)));
+    }
+}
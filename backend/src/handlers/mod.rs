@@ -1,11 +1,182 @@
+pub mod admin;
 pub mod benchmark;
 pub mod devolutions;
+pub mod metrics;
 pub mod products;
+pub mod reports;
 pub mod stress;
 
-use axum::{http::StatusCode, Json};
+use std::time::{Duration, Instant};
+
+use axum::{extract::State, http::StatusCode, Json};
+use chrono::Utc;
 use serde_json::json;
+use tracing::{debug, warn};
+
+use crate::{
+    db,
+    extract::RequestId,
+    models::{HealthStatus, PoolConnections},
+    AppState,
+};
+
+/// A readiness probe should fail quickly rather than hang on a stuck database.
+const DB_PING_TIMEOUT: Duration = Duration::from_secs(2);
 
-pub async fn health() -> (StatusCode, Json<serde_json::Value>) {
+/// Polled by load balancers, so it logs at debug rather than info.
+#[utoipa::path(
+    get,
+    path = "/health",
+    tag = "health",
+    responses(
+        (status = 200, description = "Liveness: `status` is always `ok`", body = Object),
+    )
+)]
+pub async fn health(request_id: RequestId) -> (StatusCode, Json<serde_json::Value>) {
+    debug!(request_id = %request_id, "Health check");
     (StatusCode::OK, Json(json!({ "status": "ok", "service": "inventory-service" })))
 }
+
+/// `GET /api/health/detailed` — readiness probe. 200 when the database answers
+/// and the in-memory sets agree on their size, 503 otherwise; the body lists
+/// every check either way.
+#[utoipa::path(
+    get,
+    path = "/api/health/detailed",
+    tag = "health",
+    responses(
+        (status = 200, description = "Ready; `checks`: HealthStatus", body = Object),
+        (status = 503, description = "Not ready; `checks`: HealthStatus", body = Object),
+    )
+)]
+pub async fn health_detailed(
+    State(state): State<AppState>,
+    request_id: RequestId,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let start = Instant::now();
+    let db_ok = matches!(tokio::time::timeout(DB_PING_TIMEOUT, db::ping(&state.db)).await, Ok(Ok(())));
+    let db_latency = start.elapsed();
+
+    let last_benchmark_age_secs = if db_ok {
+        db::fetch_last_benchmark_run_at(&state.db)
+            .await
+            .ok()
+            .flatten()
+            .map(|run_at| (Utc::now() - run_at).num_seconds())
+    } else {
+        None
+    };
+
+    let sets_consistent = state.sets.read().await.sizes().consistent();
+
+    let health = HealthStatus {
+        db_ok,
+        db_latency_ms: db_ok.then_some(db_latency.as_secs_f64() * 1000.0),
+        sets_consistent,
+        last_benchmark_age_secs,
+        pool_connections: PoolConnections::of(&state.db),
+    };
+
+    let (status, label) = if health.is_ready() {
+        debug!(request_id = %request_id, "Detailed health check passed");
+        (StatusCode::OK, "ok")
+    } else {
+        warn!(request_id = %request_id, db_ok, sets_consistent, "Detailed health check failed");
+        (StatusCode::SERVICE_UNAVAILABLE, "unavailable")
+    };
+
+    (status, Json(json!({ "status": label, "checks": health })))
+}
+
+/// `GET /metrics` — Prometheus scrape target. Only takes the metrics read lock.
+#[cfg(feature = "prometheus")]
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    tag = "metrics",
+    responses(
+        (status = 200, description = "Prometheus text exposition format", content_type = "text/plain", body = String),
+    )
+)]
+pub async fn prometheus_metrics(
+    axum::extract::State(state): axum::extract::State<crate::AppState>,
+    request_id: RequestId,
+) -> impl axum::response::IntoResponse {
+    let body = state.metrics.read().await.to_prometheus();
+    debug!(request_id = %request_id, bytes = body.len(), "Served Prometheus scrape");
+    (
+        StatusCode::OK,
+        [(axum::http::header::CONTENT_TYPE, "text/plain; version=0.0.4; charset=utf-8")],
+        body,
+    )
+}
+
+/// State whose pool never connects — any DB call fails fast. For handler tests
+/// that must not touch the database.
+#[cfg(test)]
+pub(crate) fn offline_state() -> crate::AppState {
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::sync::RwLock;
+
+    let metrics = crate::metrics::MetricsStore::new();
+    crate::AppState {
+        db: sqlx::postgres::PgPoolOptions::new()
+            .acquire_timeout(Duration::from_millis(50))
+            .connect_lazy("postgres://nobody@127.0.0.1:1/none")
+            .unwrap(),
+        sets: Arc::new(RwLock::new(crate::sets::SetManager::new())),
+        metric_subscribers: metrics.subscribers(),
+        metrics: Arc::new(RwLock::new(metrics)),
+        config: Arc::new(
+            crate::config::Config::from_lookup(|key| {
+                (key == "DATABASE_URL").then(|| "postgres://nobody@127.0.0.1:1/none".to_string())
+            })
+            .unwrap(),
+        ),
+        set_cache_hits: Arc::new(std::sync::atomic::AtomicU64::new(0)),
+        // The pool can never connect, so retrying would only slow tests down
+        db_retry: crate::db::RetryPolicy::new(0, Duration::ZERO),
+        product_cache: crate::db::ProductCache::new(100),
+        name_suggestions: crate::db::name_suggestion_cache(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn unreachable_database_is_unavailable() {
+        let (status, Json(body)) = health_detailed(State(offline_state()), RequestId::new()).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["status"], "unavailable");
+        assert_eq!(body["checks"]["db_ok"], false);
+        assert!(body["checks"]["db_latency_ms"].is_null());
+        assert_eq!(body["checks"]["sets_consistent"], true);
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn healthy_until_the_sets_drift_apart(pool: sqlx::PgPool) {
+        let state = AppState { db: pool, ..offline_state() };
+        let (status, Json(body)) = health_detailed(State(state.clone()), RequestId::new()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body["checks"]["db_latency_ms"].as_f64().unwrap() >= 0.0);
+        assert!(body["checks"]["last_benchmark_age_secs"].is_null());
+        assert!(body["checks"]["pool_connections"]["open"].as_u64().unwrap() >= 1);
+
+        let products = crate::seed::generate_products(10);
+        let report = state.sets.write().await.run_benchmark(products, &Default::default()).unwrap();
+        db::insert_benchmark_run(&state.db, &report).await.unwrap();
+        let (_, Json(body)) = health_detailed(State(state.clone()), RequestId::new()).await;
+        assert!(body["checks"]["last_benchmark_age_secs"].as_i64().unwrap() >= 0);
+
+        // A product only one set knows about
+        let stray = crate::seed::generate_products(1).remove(0);
+        state.sets.write().await.hash_set.insert(stray);
+        let (status, Json(body)) = health_detailed(State(state), RequestId::new()).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["checks"]["sets_consistent"], false);
+    }
+}
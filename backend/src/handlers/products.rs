@@ -1,33 +1,81 @@
-use std::time::Instant;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::Ordering;
+use std::time::{Duration, Instant};
 
 use axum::{
-    extract::{Path, Query, State},
-    http::StatusCode,
+    body::Body,
+    extract::{Multipart, Path, Query, State},
+    http::{header, HeaderName, StatusCode},
+    response::Response,
     Json,
 };
+use futures_util::{stream, StreamExt};
 use tracing::info;
 use uuid::Uuid;
 
 use crate::{
     db,
     error::AppResult,
+    extract::{JsonOrDefault, RequestId},
     metrics::MetricEntry,
-    models::{CreateProduct, ProductFilters, UpdateProduct},
+    models::{
+        join_validation_errors, AddTag, AutocompleteParams, BulkCreateRequest, Category, BulkDeleteRequest, CreateProduct, CsvFieldMap, DuplicateProduct, ImportParams, ImportReport, ImportRowError,
+        LowStockParams, parse_price_buckets, PriceDistributionParams, PriceHistoryParams, PriceRangeParams, Product, ProductCursor, ProductFilters,
+        QuantityAdjustment, RelatedParams, RestockHistoryParams, RestockRequest, SearchParams, StatsParams, SuggestParams, TaggedProduct, UpdateProduct,
+    },
     AppState,
 };
 
 // ── List ──────────────────────────────────────────────────────────────────────
 
+/// Total rows matching a listing's filters, across every page.
+static X_TOTAL_COUNT: HeaderName = HeaderName::from_static("x-total-count");
+
+#[utoipa::path(
+    get,
+    path = "/api/products",
+    tag = "products",
+    params(ProductFilters),
+    responses(
+        (status = 200, description = "`data`: [TaggedProduct], plus `count`, `total_count`, `has_next_page`, `has_prev_page`, `next_cursor` and `query_time_ms`",
+            body = Object,
+            headers(("x-total-count" = i64, description = "Same as `total_count`"))),
+        (status = 400, description = "Invalid cursor, or a cursor combined with `sort_by`", body = ErrorResponse),
+    )
+)]
 pub async fn list_products(
     State(state): State<AppState>,
+    request_id: RequestId,
     Query(filters): Query<ProductFilters>,
-) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+) -> AppResult<(StatusCode, [(HeaderName, String); 1], Json<serde_json::Value>)> {
     let start = Instant::now();
-    let products = db::fetch_all_products(&state.db, &filters).await?;
+    let (products, total_count) =
+        db::retry_with_backoff(&state.db_retry, "fetch_all_products", || db::fetch_all_products(&state.db, &filters)).await?;
     let elapsed = start.elapsed();
 
+    let ids: Vec<Uuid> = products.iter().map(|p| p.id).collect();
+    let mut tags = db::fetch_tags_for_products(&state.db, &ids).await?;
+
+    // A full page means there may be more rows after the last one served;
+    // custom sort orders page with `offset` instead
+    let next_cursor = if filters.supports_cursor() && products.len() as i64 == filters.page_limit() {
+        products.last().map(|p| ProductCursor::after(p).encode())
+    } else {
+        None
+    };
+    // A cursor page has no offset to count from, so it leans on `next_cursor`
+    let (has_next_page, has_prev_page) = match filters.cursor {
+        Some(_) => (next_cursor.is_some(), true),
+        None => {
+            let offset = filters.offset.unwrap_or(0);
+            (offset + (products.len() as i64) < total_count, offset > 0)
+        }
+    };
+
     info!(
+        request_id = %request_id,
         count = products.len(),
+        total_count,
         elapsed_ms = elapsed.as_millis(),
         "Listed products"
     );
@@ -39,151 +87,580 @@ pub async fn list_products(
         products.len(),
     );
 
+    let count = products.len();
     Ok((
         StatusCode::OK,
+        [(X_TOTAL_COUNT.clone(), total_count.to_string())],
         Json(serde_json::json!({
-            "data": products,
-            "count": products.len(),
+            "data": TaggedProduct::attach(products, &mut tags),
+            "count": count,
+            "total_count": total_count,
+            "has_next_page": has_next_page,
+            "has_prev_page": has_prev_page,
+            "next_cursor": next_cursor,
             "query_time_ms": elapsed.as_secs_f64() * 1000.0,
         })),
     ))
 }
 
-// ── Create ────────────────────────────────────────────────────────────────────
+// ── Search ────────────────────────────────────────────────────────────────────
 
-pub async fn create_product(
+#[utoipa::path(
+    get,
+    path = "/api/products/search",
+    tag = "products",
+    params(SearchParams),
+    responses(
+        (status = 200, description = "`data`: [Product] ranked by relevance, plus `query`, `count` and `query_time_ms`", body = Object),
+        (status = 400, description = "Empty query", body = ErrorResponse),
+    )
+)]
+pub async fn search_products(
     State(state): State<AppState>,
-    Json(payload): Json<CreateProduct>,
+    request_id: RequestId,
+    Query(params): Query<SearchParams>,
 ) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
-    if payload.name.trim().is_empty() {
-        return Err(crate::error::AppError::BadRequest(
-            "name must not be empty".to_string(),
-        ));
-    }
-    if payload.price_cents < 0 {
+    let q = params.q.trim();
+    if q.is_empty() {
         return Err(crate::error::AppError::BadRequest(
-            "price_cents must be >= 0".to_string(),
+            "q must not be empty".to_string(),
         ));
     }
+    let limit = params.limit.unwrap_or(50).clamp(1, 500);
+
+    let start = Instant::now();
+    let products = db::search_products(&state.db, q, limit).await?;
+    let elapsed = start.elapsed();
+
+    info!(request_id = %request_id, q, count = products.len(), elapsed_ms = elapsed.as_millis(), "Searched products");
+
+    state.metrics.write().await.record_raw(
+        "db_query:search",
+        "DB",
+        elapsed.as_nanos() as u64,
+        products.len(),
+    );
+
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "query": q,
+            "data": products,
+            "count": products.len(),
+            "query_time_ms": elapsed.as_secs_f64() * 1000.0,
+        })),
+    ))
+}
 
+// ── Stats ─────────────────────────────────────────────────────────────────────
+
+#[utoipa::path(
+    get,
+    path = "/api/products/stats",
+    tag = "products",
+    params(StatsParams),
+    responses(
+        (status = 200, description = "`data`: ProductStats, plus `query_time_ms`", body = Object),
+    )
+)]
+pub async fn product_stats(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    Query(params): Query<StatsParams>,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    let start = Instant::now();
+    let stats = db::fetch_product_stats(&state.db, params.category.as_deref()).await?;
+    let elapsed = start.elapsed();
+
+    info!(request_id = %request_id, count = stats.count, "Computed product stats");
+
+    state.metrics.write().await.record_raw(
+        "db_query:stats",
+        "DB",
+        elapsed.as_nanos() as u64,
+        stats.count as usize,
+    );
+
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "data": stats,
+            "query_time_ms": elapsed.as_secs_f64() * 1000.0,
+        })),
+    ))
+}
+
+// ── ABC analysis ──────────────────────────────────────────────────────────────
+
+/// Computed from the database, and from `btree_set` for comparison like
+/// `GET /api/reports/inventory-value`.
+#[utoipa::path(
+    get,
+    path = "/api/products/abc-analysis",
+    tag = "products",
+    responses(
+        (status = 200, description = "`data`: AbcAnalysisResult from the DB, plus `results_match` with the in-memory analysis and both timings", body = Object),
+    )
+)]
+pub async fn abc_analysis(
+    State(state): State<AppState>,
+    request_id: RequestId,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
     let db_start = Instant::now();
-    let product = db::insert_product(&state.db, &payload).await?;
+    let result = db::abc_analysis(&state.db).await?;
     let db_elapsed = db_start.elapsed();
 
-    // Sync into all three in-memory sets and time each individually
     let set_start = Instant::now();
-    state.sets.write().await.insert_product(&product);
+    let set_result = state.sets.read().await.abc_analysis_from_sets();
     let set_elapsed = set_start.elapsed();
+    let results_match = result == set_result;
+
+    info!(
+        request_id = %request_id,
+        a = result.a_count,
+        b = result.b_count,
+        c = result.c_count,
+        results_match,
+        "Computed ABC analysis"
+    );
 
     let mut metrics = state.metrics.write().await;
-    metrics.record_raw("db_query:insert", "DB", db_elapsed.as_nanos() as u64, 1);
-    metrics.record(MetricEntry::new(
-        "insert",
-        "HashSet+LinkedHashSet+BTreeSet",
-        set_elapsed.as_nanos() as u64,
-        1,
-        true,
-        Some("all three sets updated atomically".to_string()),
-    ));
+    metrics.record_raw("db_query:abc_analysis", "DB", db_elapsed.as_nanos() as u64, result.items.len());
+    metrics.record_raw("abc_analysis", "BTreeSet", set_elapsed.as_nanos() as u64, set_result.items.len());
+
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "data": result,
+            "results_match": results_match,
+            "db_time_ms": db_elapsed.as_secs_f64() * 1000.0,
+            "set_time_ms": set_elapsed.as_secs_f64() * 1000.0,
+        })),
+    ))
+}
+
+// ── Price distribution ────────────────────────────────────────────────────────
+
+/// Histogram of live products by price, from the database and from
+/// `SetManager`'s price index, so the two can be compared.
+#[utoipa::path(
+    get,
+    path = "/api/products/price-distribution",
+    tag = "products",
+    params(PriceDistributionParams),
+    responses(
+        (status = 200, description = "`data`: [PriceBucket] from the DB, `in_memory`: the same from the price index, plus `results_match`, `total_count` and timings", body = Object),
+        (status = 400, description = "Malformed, unsorted or too many bucket boundaries", body = ErrorResponse),
+    )
+)]
+pub async fn price_distribution(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    Query(params): Query<PriceDistributionParams>,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    let buckets = parse_price_buckets(params.buckets.as_deref()).map_err(crate::error::AppError::BadRequest)?;
+
+    let db_start = Instant::now();
+    let histogram = db::fetch_price_histogram(&state.db, &buckets).await?;
+    let db_elapsed = db_start.elapsed();
 
-    info!(id = %product.id, name = %product.name, "Created product");
+    let set_start = Instant::now();
+    let in_memory = state.sets.read().await.price_histogram(&buckets);
+    let set_elapsed = set_start.elapsed();
+    let results_match = histogram == in_memory;
+    let total_count: i64 = histogram.iter().map(|b| b.count).sum();
+
+    info!(
+        request_id = %request_id,
+        buckets = buckets.len(),
+        total_count,
+        results_match,
+        "Computed price distribution"
+    );
+
+    let mut metrics = state.metrics.write().await;
+    metrics.record_raw("db_query:price_histogram", "DB", db_elapsed.as_nanos() as u64, total_count as usize);
+    metrics.record_raw(
+        "price_histogram",
+        "BTreeMap (price index)",
+        set_elapsed.as_nanos() as u64,
+        in_memory.iter().map(|b| b.count as usize).sum(),
+    );
 
     Ok((
-        StatusCode::CREATED,
+        StatusCode::OK,
         Json(serde_json::json!({
-            "data": product,
+            "data": histogram,
+            "in_memory": in_memory,
+            "results_match": results_match,
+            "total_count": total_count,
             "db_time_ms": db_elapsed.as_secs_f64() * 1000.0,
-            "set_sync_time_ms": set_elapsed.as_secs_f64() * 1000.0,
+            "set_time_ms": set_elapsed.as_secs_f64() * 1000.0,
         })),
     ))
 }
 
-// ── Get by ID ─────────────────────────────────────────────────────────────────
+// ── Price range (in-memory) ───────────────────────────────────────────────────
 
-pub async fn get_product(
+/// Served from `SetManager`'s price index, not the database.
+#[utoipa::path(
+    get,
+    path = "/api/products/range",
+    tag = "products",
+    params(PriceRangeParams),
+    responses(
+        (status = 200, description = "`data`: [Product] by price, plus `count`, the bounds and query timings", body = Object),
+        (status = 400, description = "`min_price` above `max_price`", body = ErrorResponse),
+    )
+)]
+pub async fn products_in_price_range(
     State(state): State<AppState>,
-    Path(id): Path<Uuid>,
+    request_id: RequestId,
+    Query(params): Query<PriceRangeParams>,
 ) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
-    let start = Instant::now();
-    let product = db::fetch_product_by_id(&state.db, id).await?;
-    let db_elapsed = start.elapsed();
+    let min_price = params.min_price.unwrap_or(0);
+    let max_price = params.max_price.unwrap_or(i64::MAX);
+    if min_price > max_price {
+        return Err(crate::error::AppError::BadRequest(
+            "min_price must be <= max_price".to_string(),
+        ));
+    }
 
-    // Show lookup time across all three in-memory sets
     let sets = state.sets.read().await;
+    let start = Instant::now();
+    let products: Vec<Product> = sets.range_by_price(min_price, max_price).into_iter().cloned().collect();
+    let elapsed = start.elapsed();
+    drop(sets);
 
-    let hs_start = Instant::now();
-    let in_hash = sets.hash_set.contains(&product);
-    let hs_elapsed = hs_start.elapsed();
+    info!(
+        request_id = %request_id,
+        min_price,
+        max_price,
+        count = products.len(),
+        "Queried products by price range"
+    );
 
-    let lh_start = Instant::now();
-    let in_linked = sets.index_set.contains(&product);
-    let lh_elapsed = lh_start.elapsed();
+    state.metrics.write().await.record_raw(
+        "range_by_price",
+        "BTreeMap (price index)",
+        elapsed.as_nanos() as u64,
+        products.len(),
+    );
 
-    let bt_start = Instant::now();
-    let in_btree = sets.btree_set.contains(&product);
-    let bt_elapsed = bt_start.elapsed();
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "data": products,
+            "count": products.len(),
+            "min_price": min_price,
+            "max_price": max_price,
+            "query_time_ns": elapsed.as_nanos(),
+            "query_time_us": elapsed.as_secs_f64() * 1_000_000.0,
+        })),
+    ))
+}
+
+// ── By category ───────────────────────────────────────────────────────────────
+
+/// DB totals per category next to the in-memory counts, so drift between the
+/// two is visible at a glance.
+#[utoipa::path(
+    get,
+    path = "/api/products/by-category",
+    tag = "products",
+    responses(
+        (status = 200, description = "`data`: [CategorySummary], plus `in_memory_counts` and `mismatched_categories`", body = Object),
+    )
+)]
+pub async fn products_by_category(
+    State(state): State<AppState>,
+    request_id: RequestId,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    let start = Instant::now();
+    let categories = db::fetch_category_summary(&state.db).await?;
+    let elapsed = start.elapsed();
+
+    let in_memory = state.sets.read().await.category_counts();
+    let mismatched: Vec<&str> = categories
+        .iter()
+        .filter(|c| in_memory.get(&c.category).copied().unwrap_or(0) as i64 != c.product_count)
+        .map(|c| c.category.as_str())
+        .chain(
+            in_memory
+                .keys()
+                .filter(|k| !categories.iter().any(|c| &c.category == *k))
+                .map(String::as_str),
+        )
+        .collect();
+
+    info!(
+        request_id = %request_id,
+        categories = categories.len(),
+        mismatched = mismatched.len(),
+        "Summarised products by category"
+    );
+
+    state.metrics.write().await.record_raw(
+        "db_query:by_category",
+        "DB",
+        elapsed.as_nanos() as u64,
+        categories.len(),
+    );
+
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "data": categories,
+            "in_memory_counts": in_memory,
+            "mismatched_categories": mismatched,
+            "query_time_ms": elapsed.as_secs_f64() * 1000.0,
+        })),
+    ))
+}
+
+// ── Low stock ─────────────────────────────────────────────────────────────────
+
+/// Served from the database; the in-memory count is returned alongside so a
+/// stale `SetManager` shows up as a mismatch.
+#[utoipa::path(
+    get,
+    path = "/api/products/low-stock",
+    tag = "products",
+    params(LowStockParams),
+    responses(
+        (status = 200, description = "`data`: [Product], plus `threshold`, `count` and the in-memory comparison", body = Object),
+        (status = 400, description = "Negative threshold", body = ErrorResponse),
+    )
+)]
+pub async fn low_stock_products(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    Query(params): Query<LowStockParams>,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    let threshold = params.threshold.unwrap_or(10);
+    if threshold < 0 {
+        return Err(crate::error::AppError::BadRequest(
+            "threshold must be >= 0".to_string(),
+        ));
+    }
 
+    let start = Instant::now();
+    let products = db::fetch_low_stock_products(&state.db, threshold).await?;
+    let elapsed = start.elapsed();
+
+    let sets = state.sets.read().await;
+    let set_start = Instant::now();
+    let in_memory = sets.low_stock_products(threshold);
+    let set_elapsed = set_start.elapsed();
+    let db_ids: HashSet<Uuid> = products.iter().map(|p| p.id).collect();
+    let in_sync = in_memory.len() == db_ids.len() && in_memory.iter().all(|p| db_ids.contains(&p.id));
+    let in_memory_count = in_memory.len();
     drop(sets);
 
+    info!(
+        request_id = %request_id,
+        threshold,
+        count = products.len(),
+        in_memory_count,
+        in_sync,
+        "Listed low-stock products"
+    );
+
     let mut metrics = state.metrics.write().await;
-    metrics.record_raw("db_query:get", "DB", db_elapsed.as_nanos() as u64, 1);
-    metrics.record_raw("lookup", "HashSet", hs_elapsed.as_nanos() as u64, 1);
-    metrics.record_raw("lookup", "IndexSet", lh_elapsed.as_nanos() as u64, 1);
-    metrics.record_raw("lookup", "BTreeSet", bt_elapsed.as_nanos() as u64, 1);
+    metrics.record_raw("db_query:low_stock", "DB", elapsed.as_nanos() as u64, products.len());
+    metrics.record_raw("low_stock", "BTreeSet", set_elapsed.as_nanos() as u64, in_memory_count);
+
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "data": products,
+            "threshold": threshold,
+            "count": products.len(),
+            "in_memory_count": in_memory_count,
+            "sets_in_sync": in_sync,
+            "query_time_ms": elapsed.as_secs_f64() * 1000.0,
+            "set_scan_time_ms": set_elapsed.as_secs_f64() * 1000.0,
+        })),
+    ))
+}
+
+// ── Suggest ───────────────────────────────────────────────────────────────────
+
+/// Most suggestions `GET /api/products/search/suggest` returns.
+const MAX_SUGGESTIONS: usize = 10;
+
+/// How long the suggestion query may take before the handler gives up on it.
+const SUGGEST_TIMEOUT: Duration = Duration::from_millis(50);
+
+/// Typeahead over live product names in the DB. A query slower than
+/// [`SUGGEST_TIMEOUT`] yields an empty list rather than an error, so the input
+/// box never waits on it; answers are cached for five seconds.
+#[utoipa::path(
+    get,
+    path = "/api/products/search/suggest",
+    tag = "products",
+    params(SuggestParams),
+    responses(
+        (status = 200, description = "`data`: [name] starting with `q`, ignoring case, sorted and distinct, plus `cached`, `timed_out` and `query_time_ms`", body = Object),
+        (status = 400, description = "Empty `q`", body = ErrorResponse),
+    )
+)]
+pub async fn suggest_names(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    Query(params): Query<SuggestParams>,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    let q = params.q.trim();
+    if q.is_empty() {
+        return Err(crate::error::AppError::BadRequest("q must not be empty".to_string()));
+    }
+    let limit = params.limit.unwrap_or(MAX_SUGGESTIONS).clamp(1, MAX_SUGGESTIONS);
+    let key = format!("{}:{}", limit, q.to_lowercase());
+
+    let start = Instant::now();
+    let (names, cached, timed_out) = match state.name_suggestions.get(&key) {
+        Some(names) => (names, true, false),
+        None => match tokio::time::timeout(SUGGEST_TIMEOUT, db::fetch_name_suggestions(&state.db, q, limit)).await {
+            Ok(names) => {
+                let names = names?;
+                state.name_suggestions.insert(key, names.clone());
+                (names, false, false)
+            }
+            Err(_) => (Vec::new(), false, true),
+        },
+    };
+    let elapsed = start.elapsed();
+
+    info!(request_id = %request_id, prefix = %q, count = names.len(), cached, timed_out, "Suggested product names");
+
+    if !cached {
+        state.metrics.write().await.record_raw(
+            "db_query:suggest",
+            "DB",
+            elapsed.as_nanos() as u64,
+            names.len(),
+        );
+    }
+
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "query": q,
+            "data": names,
+            "count": names.len(),
+            "cached": cached,
+            "timed_out": timed_out,
+            "query_time_ms": elapsed.as_secs_f64() * 1000.0,
+        })),
+    ))
+}
+
+// ── Autocomplete ──────────────────────────────────────────────────────────────
+
+/// Name suggestions from the in-memory index: the patricia trie when
+/// `ENABLE_TRIE_INDEX` is on, timed against a `BTreeSet` range seek;
+/// otherwise the `BTreeSet` alone.
+#[utoipa::path(
+    get,
+    path = "/api/products/autocomplete",
+    tag = "products",
+    params(AutocompleteParams),
+    responses(
+        (status = 200, description = "`data`: [{ name, id }] sorted by name, plus `source` (`trie` or `btree`) and the timing of each index", body = Object),
+        (status = 400, description = "Empty `q`", body = ErrorResponse),
+    )
+)]
+pub async fn autocomplete(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    Query(params): Query<AutocompleteParams>,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    if params.q.is_empty() {
+        return Err(crate::error::AppError::BadRequest("q must not be empty".to_string()));
+    }
+    let limit = params.limit.unwrap_or(10).clamp(1, 100);
+
+    let sets = state.sets.read().await;
+    let trie = sets.name_trie.is_some().then(|| crate::sets::timed(|| sets.autocomplete(&params.q, limit)));
+    let (btree_found, btree_elapsed) = crate::sets::timed(|| sets.autocomplete_btree(&params.q, limit));
+    drop(sets);
+
+    let results_match = trie.as_ref().is_none_or(|(found, _)| *found == btree_found);
+    let trie_elapsed = trie.as_ref().map(|(_, elapsed)| *elapsed);
+    let (source, found) = match trie {
+        Some((found, _)) => ("trie", found),
+        None => ("btree", btree_found),
+    };
 
-    info!(id = %id, "Fetched product");
+    info!(request_id = %request_id, prefix = %params.q, source, count = found.len(), results_match, "Autocompleted product names");
+
+    let notes = request_id.to_string();
+    let mut metrics = state.metrics.write().await;
+    if let Some(elapsed) = trie_elapsed {
+        metrics.record_raw_with_notes("autocomplete", "PatriciaTrie", elapsed.as_nanos() as u64, found.len(), &notes);
+    }
+    metrics.record_raw_with_notes("autocomplete", "BTreeSet", btree_elapsed.as_nanos() as u64, found.len(), &notes);
 
+    let data: Vec<serde_json::Value> = found.iter().map(|(name, id)| serde_json::json!({"name": name, "id": id})).collect();
     Ok((
         StatusCode::OK,
         Json(serde_json::json!({
-            "data": product,
-            "set_presence": {
-                "hash_set": in_hash,
-                "index_set": in_linked,
-                "btree_set": in_btree,
-            },
-            "lookup_times_ns": {
-                "db": db_elapsed.as_nanos(),
-                "hash_set": hs_elapsed.as_nanos(),
-                "index_set": lh_elapsed.as_nanos(),
-                "btree_set": bt_elapsed.as_nanos(),
-            },
+            "data": data,
+            "count": data.len(),
+            "source": source,
+            "results_match": results_match,
+            "trie_time_us": trie_elapsed.map(|e| e.as_secs_f64() * 1_000_000.0),
+            "btree_time_us": btree_elapsed.as_secs_f64() * 1_000_000.0,
         })),
     ))
 }
 
-// ── Update ────────────────────────────────────────────────────────────────────
+// ── Create ────────────────────────────────────────────────────────────────────
 
-pub async fn update_product(
+#[utoipa::path(
+    post,
+    path = "/api/products",
+    tag = "products",
+    request_body = CreateProduct,
+    responses(
+        (status = 201, description = "`data`: the created Product, plus DB and set sync timings", body = Object),
+        (status = 422, description = "Validation failed", body = ErrorResponse),
+    )
+)]
+pub async fn create_product(
     State(state): State<AppState>,
-    Path(id): Path<Uuid>,
-    Json(payload): Json<UpdateProduct>,
+    request_id: RequestId,
+    Json(payload): Json<CreateProduct>,
 ) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    payload.validate().map_err(crate::error::AppError::Validation)?;
+
     let db_start = Instant::now();
-    let product = db::update_product(&state.db, id, &payload).await?;
+    let product = db::retry_with_backoff(&state.db_retry, "insert_product", || db::insert_product(&state.db, &payload)).await?;
     let db_elapsed = db_start.elapsed();
 
-    // Re-insert updated product into sets (remove old, insert new)
+    // Sync into every in-memory set and time the update as a whole
     let set_start = Instant::now();
     let mut sets = state.sets.write().await;
-    sets.remove_product(id);
     sets.insert_product(&product);
     let set_elapsed = set_start.elapsed();
+    let set_label = sets.label();
     drop(sets);
 
     let mut metrics = state.metrics.write().await;
-    metrics.record_raw("db_query:update", "DB", db_elapsed.as_nanos() as u64, 1);
-    metrics.record_raw(
-        "remove+insert",
-        "HashSet+LinkedHashSet+BTreeSet",
+    metrics.record_raw("db_query:insert", "DB", db_elapsed.as_nanos() as u64, 1);
+    metrics.record(MetricEntry::new(
+        "insert",
+        set_label,
         set_elapsed.as_nanos() as u64,
         1,
-    );
+        true,
+        Some("all sets updated atomically".to_string()),
+    ));
 
-    info!(id = %id, "Updated product");
+    info!(request_id = %request_id, id = %product.id, name = %product.name, "Created product");
 
     Ok((
-        StatusCode::OK,
+        StatusCode::CREATED,
         Json(serde_json::json!({
             "data": product,
             "db_time_ms": db_elapsed.as_secs_f64() * 1000.0,
@@ -192,38 +669,1975 @@ pub async fn update_product(
     ))
 }
 
-// ── Delete ────────────────────────────────────────────────────────────────────
+// ── Duplicate ─────────────────────────────────────────────────────────────────
 
-pub async fn delete_product(
+/// Copies a product under a fresh id. The copy's name is the first of
+/// [`DuplicateProduct::candidate_names`] no live product uses; 409 when all
+/// are taken.
+#[utoipa::path(
+    post,
+    path = "/api/products/{id}/duplicate",
+    tag = "products",
+    params(("id" = Uuid, Path, description = "Product id")),
+    request_body(content = Option<DuplicateProduct>, description = "Optional overrides for the copy"),
+    responses(
+        (status = 201, description = "`data`: the copy, plus `source_id` and timings", body = Object),
+        (status = 404, description = "No live product with this id", body = ErrorResponse),
+        (status = 409, description = "Every candidate name is taken", body = ErrorResponse),
+        (status = 422, description = "The copy fails validation", body = ErrorResponse),
+    )
+)]
+pub async fn duplicate_product(
     State(state): State<AppState>,
+    request_id: RequestId,
     Path(id): Path<Uuid>,
+    JsonOrDefault(payload): JsonOrDefault<DuplicateProduct>,
 ) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    let source = db::fetch_product_by_id(&state.db, id).await?;
+
+    let candidates = payload.candidate_names(&source.name);
+    let taken = db::fetch_taken_names(&state.db, &candidates).await?;
+    let name = candidates.into_iter().find(|n| !taken.contains(n)).ok_or_else(|| {
+        crate::error::AppError::Conflict(format!(
+            "No free name for a copy of '{}' after {} attempts",
+            payload.name_override.as_deref().unwrap_or(&source.name),
+            DuplicateProduct::MAX_NAME_ATTEMPTS
+        ))
+    })?;
+
+    let copy = CreateProduct {
+        name,
+        description: source.description.clone(),
+        price_cents: source.price_cents,
+        quantity: payload.quantity_override.unwrap_or(source.quantity),
+        // Rows from before the fixed category list can't be copied as they are
+        category: Category::try_from(source.category.clone())?,
+    };
+    copy.validate().map_err(crate::error::AppError::Validation)?;
+
     let db_start = Instant::now();
-    db::delete_product(&state.db, id).await?;
+    let product = db::retry_with_backoff(&state.db_retry, "insert_product", || db::insert_product(&state.db, &copy)).await?;
     let db_elapsed = db_start.elapsed();
 
     let set_start = Instant::now();
-    state.sets.write().await.remove_product(id);
+    let mut sets = state.sets.write().await;
+    sets.insert_product(&product);
     let set_elapsed = set_start.elapsed();
+    let set_label = sets.label();
+    drop(sets);
 
     let mut metrics = state.metrics.write().await;
-    metrics.record_raw("db_query:delete", "DB", db_elapsed.as_nanos() as u64, 1);
-    metrics.record_raw(
-        "remove",
-        "HashSet+LinkedHashSet+BTreeSet",
-        set_elapsed.as_nanos() as u64,
-        1,
-    );
+    metrics.record_raw("db_query:insert", "DB", db_elapsed.as_nanos() as u64, 1);
+    metrics.record_raw("insert", set_label, set_elapsed.as_nanos() as u64, 1);
 
-    info!(id = %id, "Deleted product");
+    info!(request_id = %request_id, source_id = %id, id = %product.id, name = %product.name, "Duplicated product");
 
     Ok((
-        StatusCode::OK,
+        StatusCode::CREATED,
         Json(serde_json::json!({
-            "message": "Product deleted",
-            "id": id,
+            "data": product,
+            "source_id": id,
             "db_time_ms": db_elapsed.as_secs_f64() * 1000.0,
             "set_sync_time_ms": set_elapsed.as_secs_f64() * 1000.0,
         })),
     ))
 }
+
+// ── Bulk create ───────────────────────────────────────────────────────────────
+
+#[utoipa::path(
+    post,
+    path = "/api/products/bulk",
+    tag = "products",
+    request_body = BulkCreateRequest,
+    responses(
+        (status = 201, description = "All created; `data`: [Product], `errors` empty", body = Object),
+        (status = 207, description = "Some items failed; they are listed in `errors` as [BulkError]", body = Object),
+        (status = 400, description = "Empty or oversized batch", body = ErrorResponse),
+    )
+)]
+pub async fn bulk_create_products(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    Json(payload): Json<BulkCreateRequest>,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    if payload.products.is_empty() {
+        return Err(crate::error::AppError::BadRequest(
+            "products must not be empty".to_string(),
+        ));
+    }
+    if payload.products.len() > BulkCreateRequest::MAX_ITEMS {
+        return Err(crate::error::AppError::BadRequest(format!(
+            "at most {} products per request",
+            BulkCreateRequest::MAX_ITEMS
+        )));
+    }
+
+    let db_start = Instant::now();
+    let result = db::bulk_insert_products(&state.db, &payload.products, payload.abort_on_error).await?;
+    let db_elapsed = db_start.elapsed();
+
+    // One write lock for the whole batch so readers never see half of it
+    let set_start = Instant::now();
+    let mut sets = state.sets.write().await;
+    sets.batch_insert(&result.created);
+    let set_elapsed = set_start.elapsed();
+    let set_label = sets.label();
+    drop(sets);
+
+    let mut metrics = state.metrics.write().await;
+    metrics.record_raw(
+        "db_query:bulk_insert",
+        "DB",
+        db_elapsed.as_nanos() as u64,
+        result.created.len(),
+    );
+    metrics.record_raw(
+        "bulk_insert",
+        set_label,
+        set_elapsed.as_nanos() as u64,
+        result.created.len(),
+    );
+
+    info!(
+        request_id = %request_id,
+        created = result.created.len(),
+        failed = result.errors.len(),
+        "Bulk-created products"
+    );
+
+    // 207 tells the client to look at `errors` even though some rows went in
+    let status = if result.errors.is_empty() {
+        StatusCode::CREATED
+    } else {
+        StatusCode::MULTI_STATUS
+    };
+
+    Ok((
+        status,
+        Json(serde_json::json!({
+            "created_count": result.created.len(),
+            "error_count": result.errors.len(),
+            "data": result.created,
+            "errors": result.errors,
+            "db_time_ms": db_elapsed.as_secs_f64() * 1000.0,
+            "set_sync_time_ms": set_elapsed.as_secs_f64() * 1000.0,
+        })),
+    ))
+}
+
+// ── CSV import ────────────────────────────────────────────────────────────────
+
+/// Parses an import file into the rows that passed validation plus one error
+/// per skipped row. Only a malformed header rejects the whole file.
+fn parse_product_csv(data: &[u8]) -> Result<(Vec<CreateProduct>, Vec<ImportRowError>), String> {
+    let mut reader = csv::ReaderBuilder::new().trim(csv::Trim::All).from_reader(data);
+    let headers = reader.headers().map_err(|e| format!("invalid CSV header: {}", e))?.clone();
+    let field_map = CsvFieldMap::from_headers(&headers)?;
+
+    let mut valid = Vec::new();
+    let mut errors = Vec::new();
+    // Product names aren't unique in the DB, but a repeat inside one file is
+    // almost always a copy-paste mistake
+    let mut first_seen: HashMap<String, usize> = HashMap::new();
+
+    for (i, record) in reader.records().enumerate() {
+        let row = i + 1;
+        let parsed = record
+            .map_err(|e| e.to_string())
+            .and_then(|r| CreateProduct::from_csv_record(&r, &field_map).map_err(|e| e.to_string()))
+            .and_then(|p| {
+                p.validate().map_err(|errors| join_validation_errors(&errors))?;
+                match first_seen.get(&p.name) {
+                    Some(first) => Err(format!("duplicate name '{}' (first seen on row {})", p.name, first)),
+                    None => Ok(p),
+                }
+            });
+
+        match parsed {
+            Ok(product) => {
+                first_seen.insert(product.name.clone(), row);
+                valid.push(product);
+            }
+            Err(message) => errors.push(ImportRowError { row, message }),
+        }
+    }
+
+    Ok((valid, errors))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/products/import/csv",
+    tag = "products",
+    params(ImportParams),
+    request_body(content = String, content_type = "multipart/form-data", description = "One file field holding the CSV"),
+    responses(
+        (status = 200, description = "Dry run; `report`: ImportReport", body = Object),
+        (status = 201, description = "Every row imported; `report`: ImportReport", body = Object),
+        (status = 207, description = "Some rows skipped; `report`: ImportReport", body = Object),
+        (status = 400, description = "Missing file or malformed header", body = ErrorResponse),
+    )
+)]
+pub async fn import_products_csv(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    Query(params): Query<ImportParams>,
+    mut multipart: Multipart,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    let field = multipart
+        .next_field()
+        .await
+        .map_err(|e| crate::error::AppError::BadRequest(format!("invalid multipart body: {}", e)))?
+        .ok_or_else(|| crate::error::AppError::BadRequest("multipart body must contain a CSV file".to_string()))?;
+    let data = field
+        .bytes()
+        .await
+        .map_err(|e| crate::error::AppError::BadRequest(format!("could not read uploaded file: {}", e)))?;
+
+    let parse_start = Instant::now();
+    let (valid, errors) = parse_product_csv(&data).map_err(crate::error::AppError::BadRequest)?;
+    let parse_elapsed = parse_start.elapsed();
+
+    let report = ImportReport {
+        total_rows: valid.len() + errors.len(),
+        imported: valid.len(),
+        skipped: errors.len(),
+        errors,
+        dry_run: params.dry_run,
+    };
+
+    if params.dry_run || valid.is_empty() {
+        info!(request_id = %request_id, rows = report.total_rows, skipped = report.skipped, dry_run = params.dry_run, "Validated CSV import");
+        return Ok((
+            StatusCode::OK,
+            Json(serde_json::json!({
+                "report": report,
+                "parse_time_ms": parse_elapsed.as_secs_f64() * 1000.0,
+            })),
+        ));
+    }
+
+    let db_start = Instant::now();
+    let created = db::import_products(&state.db, &valid).await?;
+    let db_elapsed = db_start.elapsed();
+
+    let set_start = Instant::now();
+    let mut sets = state.sets.write().await;
+    sets.batch_insert(&created);
+    let set_elapsed = set_start.elapsed();
+    let set_label = sets.label();
+    drop(sets);
+
+    let mut metrics = state.metrics.write().await;
+    metrics.record_raw("db_query:import_csv", "DB", db_elapsed.as_nanos() as u64, created.len());
+    metrics.record_raw("bulk_insert", set_label, set_elapsed.as_nanos() as u64, created.len());
+
+    info!(request_id = %request_id, imported = created.len(), skipped = report.skipped, "Imported products from CSV");
+
+    // Same convention as bulk create: 207 when some rows were skipped
+    let status = if report.errors.is_empty() {
+        StatusCode::CREATED
+    } else {
+        StatusCode::MULTI_STATUS
+    };
+
+    Ok((
+        status,
+        Json(serde_json::json!({
+            "report": report,
+            "parse_time_ms": parse_elapsed.as_secs_f64() * 1000.0,
+            "db_time_ms": db_elapsed.as_secs_f64() * 1000.0,
+            "set_sync_time_ms": set_elapsed.as_secs_f64() * 1000.0,
+        })),
+    ))
+}
+
+// ── CSV export ────────────────────────────────────────────────────────────────
+
+/// Streams the filtered product table as `products.csv`.
+///
+/// The DB stream borrows the pool, so it runs in its own task and hands lines
+/// to the response body through a small bounded channel — a slow client slows
+/// the query down instead of buffering rows. A DB error mid-export aborts the
+/// body, so the client sees a truncated download rather than a 500.
+#[utoipa::path(
+    get,
+    path = "/api/products/export/csv",
+    tag = "products",
+    params(ProductFilters),
+    responses(
+        (status = 200, description = "The filtered products as `products.csv`", content_type = "text/csv", body = String),
+    )
+)]
+pub async fn export_products_csv(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    Query(filters): Query<ProductFilters>,
+) -> AppResult<Response> {
+    let (tx, rx) = tokio::sync::mpsc::channel::<AppResult<String>>(64);
+
+    tokio::spawn(async move {
+        let start = Instant::now();
+        let mut rows: usize = 0;
+        let lines = db::stream_products_csv(&state.db, &filters);
+        futures_util::pin_mut!(lines);
+        while let Some(line) = lines.next().await {
+            let failed = line.is_err();
+            // A send error means the client hung up
+            if tx.send(line).await.is_err() || failed {
+                break;
+            }
+            rows += 1;
+        }
+        // The header line is not a product
+        let rows = rows.saturating_sub(1);
+        state.metrics.write().await.record_raw(
+            "db_query:export_csv",
+            "DB",
+            start.elapsed().as_nanos() as u64,
+            rows,
+        );
+        info!(request_id = %request_id, rows, "Exported products as CSV");
+    });
+
+    let body = stream::unfold(rx, |mut rx| async move { rx.recv().await.map(|line| (line, rx)) });
+
+    Ok(Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "text/csv")
+        .header(header::CONTENT_DISPOSITION, "attachment; filename=\"products.csv\"")
+        .body(Body::from_stream(body))
+        .unwrap())
+}
+
+// ── NDJSON export ─────────────────────────────────────────────────────────────
+
+/// Streams the filtered product table as newline-delimited JSON, one product
+/// per line, so clients can parse it incrementally. Streams the same way as
+/// the CSV export.
+#[utoipa::path(
+    get,
+    path = "/api/products/export/json",
+    tag = "products",
+    params(ProductFilters),
+    responses(
+        (status = 200, description = "One Product JSON object per line", content_type = "application/x-ndjson", body = String),
+    )
+)]
+pub async fn export_products_ndjson(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    Query(filters): Query<ProductFilters>,
+) -> AppResult<Response> {
+    let (tx, rx) = tokio::sync::mpsc::channel::<AppResult<String>>(64);
+
+    tokio::spawn(async move {
+        let start = Instant::now();
+        let mut rows: usize = 0;
+        let products = db::stream_products(&state.db, &filters);
+        futures_util::pin_mut!(products);
+        while let Some(product) = products.next().await {
+            let line = product.and_then(|p| Ok(serde_json::to_string(&p).map_err(anyhow::Error::from)? + "\n"));
+            let failed = line.is_err();
+            // A send error means the client hung up
+            if tx.send(line).await.is_err() || failed {
+                break;
+            }
+            rows += 1;
+        }
+        state.metrics.write().await.record_raw(
+            "db_query:export_ndjson",
+            "DB",
+            start.elapsed().as_nanos() as u64,
+            rows,
+        );
+        info!(request_id = %request_id, rows, "Exported products as NDJSON");
+    });
+
+    let body = stream::unfold(rx, |mut rx| async move { rx.recv().await.map(|line| (line, rx)) });
+
+    Ok(Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "application/x-ndjson")
+        .body(Body::from_stream(body))
+        .unwrap())
+}
+
+// ── Get by ID ─────────────────────────────────────────────────────────────────
+
+#[utoipa::path(
+    get,
+    path = "/api/products/{id}",
+    tag = "products",
+    params(("id" = Uuid, Path, description = "Product id")),
+    responses(
+        (status = 200, description = "`data`: TaggedProduct, plus `cache_hit`, `cache_age_ms`, `set_presence` and per-set `lookup_times_ns`", body = Object),
+        (status = 404, description = "No live product with this id", body = ErrorResponse),
+    )
+)]
+pub async fn get_product(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    Path(id): Path<Uuid>,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    let start = Instant::now();
+    let (product, cache_age) = db::fetch_product_cached(&state.db, &state.product_cache, id).await?;
+    let db_elapsed = start.elapsed();
+    let tags = db::fetch_tags_for_products(&state.db, &[id]).await?.remove(&id).unwrap_or_default();
+
+    // Show lookup time across every in-memory set
+    let sets = state.sets.read().await;
+
+    let hs_start = Instant::now();
+    let in_hash = sets.hash_set.contains(&product);
+    let hs_elapsed = hs_start.elapsed();
+
+    let lh_start = Instant::now();
+    let in_linked = sets.index_set.contains(&product);
+    let lh_elapsed = lh_start.elapsed();
+
+    let bt_start = Instant::now();
+    let in_btree = sets.btree_set.contains(&product);
+    let bt_elapsed = bt_start.elapsed();
+
+    let ah_start = Instant::now();
+    let in_ahash = sets.ahash_set.contains(&product);
+    let ah_elapsed = ah_start.elapsed();
+
+    let ds_start = Instant::now();
+    let in_dash = sets.dash_set.contains(&product);
+    let ds_elapsed = ds_start.elapsed();
+
+    // Keyed by id rather than by `Product`; not a set, but the usual way to find one
+    let hm_start = Instant::now();
+    let in_id_map = sets.id_map.contains_key(&id);
+    let hm_elapsed = hm_start.elapsed();
+
+    let fx_lookup = sets.fx_set.as_ref().map(|fx| {
+        let fx_start = Instant::now();
+        let in_fx = fx.contains(&product);
+        (in_fx, fx_start.elapsed())
+    });
+
+    drop(sets);
+
+    let mut metrics = state.metrics.write().await;
+    match cache_age {
+        Some(_) => metrics.record_raw("cache_lookup", "LruCache", db_elapsed.as_nanos() as u64, 1),
+        None => metrics.record_raw("db_query:get", "DB", db_elapsed.as_nanos() as u64, 1),
+    }
+    let notes = request_id.to_string();
+    metrics.record_raw_with_notes("lookup", "HashSet", hs_elapsed.as_nanos() as u64, 1, &notes);
+    metrics.record_raw_with_notes("lookup", "IndexSet", lh_elapsed.as_nanos() as u64, 1, &notes);
+    metrics.record_raw_with_notes("lookup", "BTreeSet", bt_elapsed.as_nanos() as u64, 1, &notes);
+    metrics.record_raw_with_notes("lookup", "AHashSet", ah_elapsed.as_nanos() as u64, 1, &notes);
+    metrics.record_raw_with_notes("lookup", "DashSet", ds_elapsed.as_nanos() as u64, 1, &notes);
+    metrics.record_raw_with_notes("lookup", "HashMap", hm_elapsed.as_nanos() as u64, 1, &notes);
+    if let Some((_, fx_elapsed)) = fx_lookup {
+        metrics.record_raw_with_notes("lookup", "FxHashSet", fx_elapsed.as_nanos() as u64, 1, &notes);
+    }
+
+    info!(request_id = %request_id, id = %id, cache_hit = cache_age.is_some(), "Fetched product");
+
+    let mut body = serde_json::json!({
+        "data": TaggedProduct { product, tags },
+        "cache_hit": cache_age.is_some(),
+        "cache_age_ms": cache_age.map(|age| age.as_secs_f64() * 1000.0),
+        "set_presence": {
+            "hash_set": in_hash,
+            "index_set": in_linked,
+            "btree_set": in_btree,
+            "ahash_set": in_ahash,
+            "dash_set": in_dash,
+            "id_map": in_id_map,
+        },
+        "lookup_times_ns": {
+            "db": db_elapsed.as_nanos(),
+            "hash_set": hs_elapsed.as_nanos(),
+            "index_set": lh_elapsed.as_nanos(),
+            "btree_set": bt_elapsed.as_nanos(),
+            "ahash_set": ah_elapsed.as_nanos(),
+            "dash_set": ds_elapsed.as_nanos(),
+            "id_map": hm_elapsed.as_nanos(),
+        },
+    });
+    if let Some((in_fx, fx_elapsed)) = fx_lookup {
+        body["set_presence"]["fx_set"] = in_fx.into();
+        body["lookup_times_ns"]["fx_set"] = serde_json::json!(fx_elapsed.as_nanos());
+    }
+
+    Ok((StatusCode::OK, Json(body)))
+}
+
+// ── Update ────────────────────────────────────────────────────────────────────
+
+#[utoipa::path(
+    put,
+    path = "/api/products/{id}",
+    tag = "products",
+    params(("id" = Uuid, Path, description = "Product id")),
+    request_body = UpdateProduct,
+    responses(
+        (status = 200, description = "`data`: the updated Product, plus `cache_hit` (the sets already held these values) and timings", body = Object),
+        (status = 404, description = "No live product with this id", body = ErrorResponse),
+        (status = 409, description = "`expected_version` no longer matches", body = ErrorResponse),
+        (status = 422, description = "Validation failed", body = ErrorResponse),
+    )
+)]
+pub async fn update_product(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<UpdateProduct>,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    payload.validate().map_err(crate::error::AppError::Validation)?;
+
+    let db_start = Instant::now();
+    let product = db::update_product(&state.db, id, &payload).await?;
+    let db_elapsed = db_start.elapsed();
+    state.product_cache.invalidate([id]).await;
+
+    // Only touch the sets when a field they hold actually changed
+    let set_start = Instant::now();
+    let mut sets = state.sets.write().await;
+    let changed = sets.upsert_product(&product);
+    let set_elapsed = set_start.elapsed();
+    let set_label = sets.label();
+    drop(sets);
+    let cache_hit = !changed;
+    if cache_hit {
+        state.set_cache_hits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    let mut metrics = state.metrics.write().await;
+    metrics.record_raw("db_query:update", "DB", db_elapsed.as_nanos() as u64, 1);
+    metrics.record_raw("upsert", set_label, set_elapsed.as_nanos() as u64, 1);
+
+    info!(request_id = %request_id, id = %id, cache_hit, "Updated product");
+
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "data": product,
+            "cache_hit": cache_hit,
+            "db_time_ms": db_elapsed.as_secs_f64() * 1000.0,
+            "set_sync_time_ms": set_elapsed.as_secs_f64() * 1000.0,
+        })),
+    ))
+}
+
+// ── Adjust quantity ───────────────────────────────────────────────────────────
+
+#[utoipa::path(
+    post,
+    path = "/api/products/{id}/adjust-quantity",
+    tag = "products",
+    params(("id" = Uuid, Path, description = "Product id")),
+    request_body = QuantityAdjustment,
+    responses(
+        (status = 200, description = "`data`: the updated Product, plus `delta`, `reason` and timings", body = Object),
+        (status = 400, description = "Zero delta, negative floor, or a result below `min_quantity` or above 1 000 000", body = ErrorResponse),
+        (status = 404, description = "No live product with this id", body = ErrorResponse),
+    )
+)]
+pub async fn adjust_quantity(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<QuantityAdjustment>,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    if payload.delta == 0 {
+        return Err(crate::error::AppError::BadRequest(
+            "delta must not be 0".to_string(),
+        ));
+    }
+    let min_quantity = payload.min_quantity.unwrap_or(0);
+    if min_quantity < 0 {
+        return Err(crate::error::AppError::BadRequest(
+            "min_quantity must be >= 0".to_string(),
+        ));
+    }
+
+    let db_start = Instant::now();
+    let product = db::adjust_quantity(&state.db, id, payload.delta, min_quantity).await?;
+    let db_elapsed = db_start.elapsed();
+    state.product_cache.invalidate([id]).await;
+
+    // Same UUID, so this replaces the stale copy in every set
+    let set_start = Instant::now();
+    let mut sets = state.sets.write().await;
+    sets.insert_product(&product);
+    let set_elapsed = set_start.elapsed();
+    let set_label = sets.label();
+    drop(sets);
+
+    let mut metrics = state.metrics.write().await;
+    metrics.record_raw("db_query:adjust_quantity", "DB", db_elapsed.as_nanos() as u64, 1);
+    metrics.record_raw("insert", set_label, set_elapsed.as_nanos() as u64, 1);
+
+    info!(
+        request_id = %request_id,
+        id = %id,
+        delta = payload.delta,
+        quantity = product.quantity,
+        reason = payload.reason.as_deref().unwrap_or(""),
+        "Adjusted product quantity"
+    );
+
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "data": product,
+            "delta": payload.delta,
+            "reason": payload.reason,
+            "db_time_ms": db_elapsed.as_secs_f64() * 1000.0,
+            "set_sync_time_ms": set_elapsed.as_secs_f64() * 1000.0,
+        })),
+    ))
+}
+
+// ── Restock ───────────────────────────────────────────────────────────────────
+
+#[utoipa::path(
+    post,
+    path = "/api/products/{id}/restock",
+    tag = "products",
+    params(("id" = Uuid, Path, description = "Product id")),
+    request_body = RestockRequest,
+    responses(
+        (status = 201, description = "`data`: the RestockEvent, plus the updated `product` and timings", body = Object),
+        (status = 400, description = "Non-positive quantity, or a stock level above the maximum", body = ErrorResponse),
+        (status = 404, description = "No live product with this id", body = ErrorResponse),
+    )
+)]
+pub async fn restock_product(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<RestockRequest>,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    if payload.quantity <= 0 {
+        return Err(crate::error::AppError::BadRequest(
+            "quantity must be > 0".to_string(),
+        ));
+    }
+
+    let db_start = Instant::now();
+    let event = db::create_restock_event(
+        &state.db,
+        id,
+        payload.quantity,
+        payload.supplier.as_deref(),
+        payload.notes.as_deref(),
+    )
+    .await?;
+    let product = db::fetch_product_by_id(&state.db, id).await?;
+    let db_elapsed = db_start.elapsed();
+    state.product_cache.invalidate([id]).await;
+
+    // Same UUID, so this replaces the stale copy in every set
+    let set_start = Instant::now();
+    let mut sets = state.sets.write().await;
+    sets.insert_product(&product);
+    let set_elapsed = set_start.elapsed();
+    let set_label = sets.label();
+    drop(sets);
+
+    let mut metrics = state.metrics.write().await;
+    metrics.record_raw("db_query:restock", "DB", db_elapsed.as_nanos() as u64, 1);
+    metrics.record_raw("insert", set_label, set_elapsed.as_nanos() as u64, 1);
+
+    info!(
+        request_id = %request_id,
+        id = %id,
+        quantity_added = payload.quantity,
+        quantity = product.quantity,
+        supplier = payload.supplier.as_deref().unwrap_or(""),
+        "Restocked product"
+    );
+
+    Ok((
+        StatusCode::CREATED,
+        Json(serde_json::json!({
+            "data": event,
+            "product": product,
+            "db_time_ms": db_elapsed.as_secs_f64() * 1000.0,
+            "set_sync_time_ms": set_elapsed.as_secs_f64() * 1000.0,
+        })),
+    ))
+}
+
+/// GET /api/products/:id/restock-history — past restocks, newest first
+#[utoipa::path(
+    get,
+    path = "/api/products/{id}/restock-history",
+    tag = "products",
+    params(("id" = Uuid, Path, description = "Product id"), RestockHistoryParams),
+    responses(
+        (status = 200, description = "`data`: [RestockEvent], newest first, plus `total_added`", body = Object),
+        (status = 404, description = "No live product with this id", body = ErrorResponse),
+    )
+)]
+pub async fn restock_history(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    Path(id): Path<Uuid>,
+    Query(params): Query<RestockHistoryParams>,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    let limit = params.limit.unwrap_or(50).clamp(1, 500);
+
+    let start = Instant::now();
+    // 404 for unknown or deleted products rather than an empty history
+    db::fetch_product_by_id(&state.db, id).await?;
+    let history = db::fetch_restock_history(&state.db, id, limit).await?;
+    let elapsed = start.elapsed();
+
+    info!(request_id = %request_id, id = %id, count = history.len(), "Fetched restock history");
+
+    state.metrics.write().await.record_raw(
+        "db_query:restock_history",
+        "DB",
+        elapsed.as_nanos() as u64,
+        history.len(),
+    );
+
+    let total_added: i64 = history.iter().map(|e| i64::from(e.quantity_added)).sum();
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "data": history,
+            "count": history.len(),
+            "total_added": total_added,
+            "query_time_ms": elapsed.as_secs_f64() * 1000.0,
+        })),
+    ))
+}
+
+// ── Delete ────────────────────────────────────────────────────────────────────
+
+#[utoipa::path(
+    delete,
+    path = "/api/products/{id}",
+    tag = "products",
+    params(("id" = Uuid, Path, description = "Product id")),
+    responses(
+        (status = 200, description = "Soft-deleted; `id` plus timings", body = Object),
+        (status = 404, description = "No live product with this id", body = ErrorResponse),
+    )
+)]
+pub async fn delete_product(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    Path(id): Path<Uuid>,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    let db_start = Instant::now();
+    db::delete_product(&state.db, id).await?;
+    let db_elapsed = db_start.elapsed();
+    state.product_cache.invalidate([id]).await;
+
+    let set_start = Instant::now();
+    let mut sets = state.sets.write().await;
+    sets.remove_product(id);
+    let set_elapsed = set_start.elapsed();
+    let set_label = sets.label();
+    drop(sets);
+
+    let mut metrics = state.metrics.write().await;
+    metrics.record_raw("db_query:delete", "DB", db_elapsed.as_nanos() as u64, 1);
+    metrics.record_raw(
+        "remove",
+        set_label,
+        set_elapsed.as_nanos() as u64,
+        1,
+    );
+
+    info!(request_id = %request_id, id = %id, "Soft-deleted product");
+
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "message": "Product deleted (restore with POST /api/products/:id/restore)",
+            "id": id,
+            "db_time_ms": db_elapsed.as_secs_f64() * 1000.0,
+            "set_sync_time_ms": set_elapsed.as_secs_f64() * 1000.0,
+        })),
+    ))
+}
+
+// ── Bulk delete ───────────────────────────────────────────────────────────────
+
+/// Soft-deletes a batch of products in one statement. Ids that are unknown or
+/// already deleted are listed in `not_found` rather than failing the request.
+#[utoipa::path(
+    delete,
+    path = "/api/products/bulk",
+    tag = "products",
+    request_body = BulkDeleteRequest,
+    responses(
+        (status = 200, description = "`deleted` count and the `not_found` ids", body = Object),
+        (status = 400, description = "Empty or oversized id list", body = ErrorResponse),
+    )
+)]
+pub async fn bulk_delete_products(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    Json(payload): Json<BulkDeleteRequest>,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    if payload.ids.is_empty() {
+        return Err(crate::error::AppError::BadRequest("ids must not be empty".to_string()));
+    }
+    if payload.ids.len() > BulkDeleteRequest::MAX_IDS {
+        return Err(crate::error::AppError::BadRequest(format!(
+            "at most {} ids per request",
+            BulkDeleteRequest::MAX_IDS
+        )));
+    }
+
+    let db_start = Instant::now();
+    let deleted: HashSet<Uuid> = if payload.dry_run {
+        db::fetch_live_product_ids(&state.db, &payload.ids).await?
+    } else {
+        db::bulk_delete_products(&state.db, &payload.ids).await?
+    }
+    .into_iter()
+    .collect();
+    let db_elapsed = db_start.elapsed();
+
+    let set_start = Instant::now();
+    let mut remove_strategy = None;
+    if !payload.dry_run && !deleted.is_empty() {
+        state.product_cache.invalidate(deleted.iter().copied()).await;
+        remove_strategy = Some(state.sets.write().await.remove_products(&deleted));
+    }
+    let set_elapsed = set_start.elapsed();
+
+    // Request order, each id once
+    let mut seen = HashSet::new();
+    let not_found: Vec<Uuid> = payload
+        .ids
+        .iter()
+        .copied()
+        .filter(|id| !deleted.contains(id) && seen.insert(*id))
+        .collect();
+
+    if !payload.dry_run {
+        let set_label = state.sets.read().await.label();
+        let mut metrics = state.metrics.write().await;
+        metrics.record_raw("db_query:bulk_delete", "DB", db_elapsed.as_nanos() as u64, deleted.len());
+        metrics.record_raw("bulk_remove", set_label, set_elapsed.as_nanos() as u64, deleted.len());
+    }
+
+    info!(
+        request_id = %request_id,
+        requested = payload.ids.len(),
+        deleted = deleted.len(),
+        not_found = not_found.len(),
+        dry_run = payload.dry_run,
+        "Bulk-deleted products"
+    );
+
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "requested": payload.ids.len(),
+            "deleted": deleted.len() as u64,
+            "not_found": not_found,
+            "dry_run": payload.dry_run,
+            "remove_strategy": remove_strategy,
+            "db_time_ms": db_elapsed.as_secs_f64() * 1000.0,
+            "set_sync_time_ms": set_elapsed.as_secs_f64() * 1000.0,
+        })),
+    ))
+}
+
+// ── Related products ──────────────────────────────────────────────────────────
+
+/// Other products in the same category, found two ways — a DB query and a
+/// `btree_set` scan — with both results and timings so they can be compared.
+#[utoipa::path(
+    get,
+    path = "/api/products/{id}/related",
+    tag = "products",
+    params(("id" = Uuid, Path, description = "Product id"), RelatedParams),
+    responses(
+        (status = 200, description = "`db_results` and `set_results`: [Product] in the same category, with timings", body = Object),
+        (status = 404, description = "No live product with this id", body = ErrorResponse),
+    )
+)]
+pub async fn related_products(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    Path(id): Path<Uuid>,
+    Query(params): Query<RelatedParams>,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    let limit = params.limit.unwrap_or(5).clamp(1, 100);
+    // 404 for unknown or deleted products rather than an empty list
+    let product = db::fetch_product_by_id(&state.db, id).await?;
+
+    let db_start = Instant::now();
+    let db_results = db::fetch_related_products(&state.db, id, limit).await?;
+    let db_elapsed = db_start.elapsed();
+
+    let set_start = Instant::now();
+    let set_results: Vec<Product> = state
+        .sets
+        .read()
+        .await
+        .products_by_category(&product.category)
+        .filter(|p| p.id != id)
+        .take(limit as usize)
+        .cloned()
+        .collect();
+    let set_elapsed = set_start.elapsed();
+
+    info!(
+        request_id = %request_id,
+        id = %id,
+        category = %product.category,
+        db_count = db_results.len(),
+        set_count = set_results.len(),
+        "Listed related products"
+    );
+
+    let mut metrics = state.metrics.write().await;
+    metrics.record_raw("db_query:related", "DB", db_elapsed.as_nanos() as u64, db_results.len());
+    metrics.record_raw("related", "BTreeSet", set_elapsed.as_nanos() as u64, set_results.len());
+
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "product_id": id,
+            "category": product.category,
+            "db_results": db_results,
+            "set_results": set_results,
+            "db_time_ms": db_elapsed.as_secs_f64() * 1000.0,
+            "set_time_ms": set_elapsed.as_secs_f64() * 1000.0,
+        })),
+    ))
+}
+
+// ── Duplicate names ───────────────────────────────────────────────────────────
+
+#[utoipa::path(
+    get,
+    path = "/api/products/duplicates",
+    tag = "products",
+    responses(
+        (status = 200, description = "`data`: [DuplicateGroup] by name, plus `count`, `redundant` (products deduplication would remove) and `query_time_ms`", body = Object),
+    )
+)]
+pub async fn list_duplicates(
+    State(state): State<AppState>,
+    request_id: RequestId,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    let start = Instant::now();
+    let groups = db::fetch_duplicate_names(&state.db).await?;
+    let elapsed = start.elapsed();
+    let redundant: i64 = groups.iter().map(|g| g.count - 1).sum();
+
+    info!(request_id = %request_id, groups = groups.len(), redundant, "Listed duplicate product names");
+
+    state.metrics.write().await.record_raw(
+        "db_query:duplicates",
+        "DB",
+        elapsed.as_nanos() as u64,
+        groups.len(),
+    );
+
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "data": groups,
+            "count": groups.len(),
+            "redundant": redundant,
+            "query_time_ms": elapsed.as_secs_f64() * 1000.0,
+        })),
+    ))
+}
+
+/// Keeps the oldest live product of each duplicated name and hard-deletes the
+/// others, along with their history.
+#[utoipa::path(
+    post,
+    path = "/api/products/deduplicate",
+    tag = "products",
+    responses(
+        (status = 200, description = "`removed` count and `removed_ids`, plus timings", body = Object),
+    )
+)]
+pub async fn deduplicate_products(
+    State(state): State<AppState>,
+    request_id: RequestId,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    let db_start = Instant::now();
+    let removed = db::deduplicate_products(&state.db).await?;
+    let db_elapsed = db_start.elapsed();
+    state.product_cache.invalidate(removed.iter().copied()).await;
+
+    let set_start = Instant::now();
+    let mut sets = state.sets.write().await;
+    sets.remove_product_batch(&removed);
+    let set_elapsed = set_start.elapsed();
+    let set_label = sets.label();
+    drop(sets);
+
+    let mut metrics = state.metrics.write().await;
+    metrics.record_raw("db_query:deduplicate", "DB", db_elapsed.as_nanos() as u64, removed.len());
+    metrics.record_raw("remove", set_label, set_elapsed.as_nanos() as u64, removed.len());
+
+    info!(request_id = %request_id, removed = removed.len(), "Removed duplicate products");
+
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "removed": removed.len(),
+            "removed_ids": removed,
+            "db_time_ms": db_elapsed.as_secs_f64() * 1000.0,
+            "set_sync_time_ms": set_elapsed.as_secs_f64() * 1000.0,
+        })),
+    ))
+}
+
+// ── Soft-deleted products ─────────────────────────────────────────────────────
+
+#[utoipa::path(
+    get,
+    path = "/api/products/deleted",
+    tag = "products",
+    responses(
+        (status = 200, description = "`data`: [DeletedProduct], plus `count` and `query_time_ms`", body = Object),
+    )
+)]
+pub async fn list_deleted_products(
+    State(state): State<AppState>,
+    request_id: RequestId,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    let start = Instant::now();
+    let products = db::fetch_deleted_products(&state.db).await?;
+    let elapsed = start.elapsed();
+
+    info!(request_id = %request_id, count = products.len(), "Listed deleted products");
+
+    state.metrics.write().await.record_raw(
+        "db_query:list_deleted",
+        "DB",
+        elapsed.as_nanos() as u64,
+        products.len(),
+    );
+
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "data": products,
+            "count": products.len(),
+            "query_time_ms": elapsed.as_secs_f64() * 1000.0,
+        })),
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/products/{id}/restore",
+    tag = "products",
+    params(("id" = Uuid, Path, description = "Product id")),
+    responses(
+        (status = 200, description = "`data`: the restored Product, plus timings", body = Object),
+        (status = 404, description = "No soft-deleted product with this id", body = ErrorResponse),
+        (status = 409, description = "A live product has taken the name since", body = ErrorResponse),
+    )
+)]
+pub async fn restore_product(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    Path(id): Path<Uuid>,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    let db_start = Instant::now();
+    let product = db::restore_product(&state.db, id).await?;
+    let db_elapsed = db_start.elapsed();
+
+    let set_start = Instant::now();
+    let mut sets = state.sets.write().await;
+    sets.insert_product(&product);
+    let set_elapsed = set_start.elapsed();
+    let set_label = sets.label();
+    drop(sets);
+
+    let mut metrics = state.metrics.write().await;
+    metrics.record_raw("db_query:restore", "DB", db_elapsed.as_nanos() as u64, 1);
+    metrics.record_raw("insert", set_label, set_elapsed.as_nanos() as u64, 1);
+
+    info!(request_id = %request_id, id = %id, "Restored product");
+
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "data": product,
+            "db_time_ms": db_elapsed.as_secs_f64() * 1000.0,
+            "set_sync_time_ms": set_elapsed.as_secs_f64() * 1000.0,
+        })),
+    ))
+}
+
+/// GET /api/products/:id/price-history — past price changes, newest first
+#[utoipa::path(
+    get,
+    path = "/api/products/{id}/price-history",
+    tag = "products",
+    params(("id" = Uuid, Path, description = "Product id"), PriceHistoryParams),
+    responses(
+        (status = 200, description = "`data`: [PriceHistoryEntry], newest first", body = Object),
+        (status = 404, description = "No live product with this id", body = ErrorResponse),
+    )
+)]
+pub async fn price_history(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    Path(id): Path<Uuid>,
+    Query(params): Query<PriceHistoryParams>,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    let limit = params.limit.unwrap_or(50).clamp(1, 500);
+
+    let start = Instant::now();
+    // 404 for unknown or deleted products rather than an empty history
+    db::fetch_product_by_id(&state.db, id).await?;
+    let history = db::fetch_price_history(&state.db, id, limit).await?;
+    let elapsed = start.elapsed();
+
+    info!(request_id = %request_id, id = %id, count = history.len(), "Fetched price history");
+
+    state.metrics.write().await.record_raw(
+        "db_query:price_history",
+        "DB",
+        elapsed.as_nanos() as u64,
+        history.len(),
+    );
+
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "data": history,
+            "count": history.len(),
+            "query_time_ms": elapsed.as_secs_f64() * 1000.0,
+        })),
+    ))
+}
+
+// ── Tags ──────────────────────────────────────────────────────────────────────
+
+#[utoipa::path(
+    post,
+    path = "/api/products/{id}/tags",
+    tag = "products",
+    params(("id" = Uuid, Path, description = "Product id")),
+    request_body = AddTag,
+    responses(
+        (status = 200, description = "`data`: the product's tags after the change", body = Object),
+        (status = 404, description = "No live product with this id", body = ErrorResponse),
+        (status = 422, description = "Blank or over-long tag", body = ErrorResponse),
+    )
+)]
+pub async fn add_product_tag(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<AddTag>,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    let tag = payload.validate().map_err(crate::error::AppError::Validation)?;
+
+    let start = Instant::now();
+    db::add_tag_to_product(&state.db, id, &tag).await?;
+    let tags = db::fetch_tags_for_products(&state.db, &[id]).await?.remove(&id).unwrap_or_default();
+    let elapsed = start.elapsed();
+
+    state.metrics.write().await.record_raw("db_query:add_tag", "DB", elapsed.as_nanos() as u64, 1);
+
+    info!(request_id = %request_id, id = %id, tag = %tag, "Tagged product");
+
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "data": tags,
+            "query_time_ms": elapsed.as_secs_f64() * 1000.0,
+        })),
+    ))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/products/{id}/tags/{tag}",
+    tag = "products",
+    params(
+        ("id" = Uuid, Path, description = "Product id"),
+        ("tag" = String, Path, description = "Tag name"),
+    ),
+    responses(
+        (status = 200, description = "`data`: the product's remaining tags", body = Object),
+        (status = 404, description = "The product doesn't carry this tag", body = ErrorResponse),
+    )
+)]
+pub async fn remove_product_tag(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    Path((id, tag)): Path<(Uuid, String)>,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    let start = Instant::now();
+    db::remove_tag_from_product(&state.db, id, &tag).await?;
+    let tags = db::fetch_tags_for_products(&state.db, &[id]).await?.remove(&id).unwrap_or_default();
+    let elapsed = start.elapsed();
+
+    state.metrics.write().await.record_raw("db_query:remove_tag", "DB", elapsed.as_nanos() as u64, 1);
+
+    info!(request_id = %request_id, id = %id, tag = %tag, "Untagged product");
+
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "data": tags,
+            "query_time_ms": elapsed.as_secs_f64() * 1000.0,
+        })),
+    ))
+}
+
+/// GET /api/tags/:tag/products — every live product carrying one tag, by name
+#[utoipa::path(
+    get,
+    path = "/api/tags/{tag}/products",
+    tag = "products",
+    params(("tag" = String, Path, description = "Tag name")),
+    responses(
+        (status = 200, description = "`data`: [Product], plus `count` and `query_time_ms`; empty for unknown tags", body = Object),
+    )
+)]
+pub async fn products_by_tag(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    Path(tag): Path<String>,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    let start = Instant::now();
+    let products = db::fetch_products_by_tag(&state.db, &tag).await?;
+    let elapsed = start.elapsed();
+
+    state.metrics.write().await.record_raw(
+        "db_query:by_tag",
+        "DB",
+        elapsed.as_nanos() as u64,
+        products.len(),
+    );
+
+    info!(request_id = %request_id, tag = %tag, count = products.len(), "Listed products by tag");
+
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "data": products,
+            "count": products.len(),
+            "query_time_ms": elapsed.as_secs_f64() * 1000.0,
+        })),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::extract::FromRequest;
+    use crate::handlers::offline_state;
+    use crate::sets::SetSizes;
+
+    const HEADER: &str = "name,description,price_cents,quantity,category";
+
+    async fn upload(csv: &str) -> Multipart {
+        let body = format!(
+            "--X\r\nContent-Disposition: form-data; name=\"file\"; filename=\"products.csv\"\r\n\
+             Content-Type: text/csv\r\n\r\n{}\r\n--X--\r\n",
+            csv
+        );
+        let req = axum::http::Request::builder()
+            .method("POST")
+            .header(axum::http::header::CONTENT_TYPE, "multipart/form-data; boundary=X")
+            .body(axum::body::Body::from(body))
+            .unwrap();
+        Multipart::from_request(req, &()).await.unwrap()
+    }
+
+    fn rows(errors: &[ImportRowError]) -> Vec<usize> {
+        errors.iter().map(|e| e.row).collect()
+    }
+
+    #[test]
+    fn csv_parse_skips_invalid_rows_with_their_row_number() {
+        let csv = format!(
+            "{}\nLamp,Desk lamp,1999,3,Home & Garden\n ,blank name,100,1,Home & Garden\nChair,,-5,1,Home & Garden\nDesk,,abc,1,Home & Garden\nRug,,500,-2,Home & Garden\n",
+            HEADER
+        );
+        let (valid, errors) = parse_product_csv(csv.as_bytes()).unwrap();
+
+        assert_eq!(valid.len(), 1);
+        assert_eq!(valid[0].description.as_deref(), Some("Desk lamp"));
+        assert_eq!(rows(&errors), vec![2, 3, 4, 5]);
+        assert_eq!(errors[0].message, "name must not be empty");
+        assert_eq!(errors[1].message, "price_cents must be between 0 and 9999999");
+        assert_eq!(errors[3].message, "quantity must be between 0 and 1000000");
+    }
+
+    #[test]
+    fn csv_parse_skips_repeated_names() {
+        let csv = format!("{}\nLamp,,100,1,Home & Garden\nLamp,,200,2,Home & Garden\nChair,,300,3,Home & Garden\n", HEADER);
+        let (valid, errors) = parse_product_csv(csv.as_bytes()).unwrap();
+
+        assert_eq!(valid.iter().map(|p| p.price_cents).collect::<Vec<_>>(), vec![100, 300]);
+        assert_eq!(
+            errors,
+            vec![ImportRowError { row: 2, message: "duplicate name 'Lamp' (first seen on row 1)".to_string() }]
+        );
+    }
+
+    #[test]
+    fn csv_parse_rejects_missing_columns_and_unknown_categories() {
+        let err = parse_product_csv(b"name,price_cents\nLamp,100\n").unwrap_err();
+        assert_eq!(err, "CSV header is missing column(s): quantity, category");
+
+        let (valid, errors) = parse_product_csv(b"category,quantity,price_cents,name\nBooks,1,100,Lamp\n").unwrap();
+        assert!(errors.is_empty());
+        assert_eq!(valid[0].description, None);
+
+        let (valid, errors) = parse_product_csv(b"category,quantity,price_cents,name\nbooks,1,100,Lamp\nGizmos,1,100,Rug\n").unwrap();
+        assert_eq!(valid[0].category, Category::Books, "category names ignore case");
+        assert_eq!(rows(&errors), vec![2]);
+        assert!(errors[0].message.contains("unknown category 'Gizmos'"), "{}", errors[0].message);
+    }
+
+    #[tokio::test]
+    async fn dry_run_validates_without_touching_the_database() {
+        let state = offline_state();
+        let csv = format!("{}\nLamp,,100,1,Home & Garden\n,,100,1,Home & Garden\n", HEADER);
+        let (status, Json(body)) = import_products_csv(
+            State(state.clone()),
+            RequestId::new(),
+            Query(ImportParams { dry_run: true }),
+            upload(&csv).await,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(status, StatusCode::OK);
+        let report = &body["report"];
+        assert_eq!(report["total_rows"], 2);
+        assert_eq!(report["imported"], 1);
+        assert_eq!(report["skipped"], 1);
+        assert_eq!(report["dry_run"], true);
+        assert_eq!(state.sets.read().await.hash_set.len(), 0);
+    }
+
+    #[tokio::test]
+    async fn autocomplete_uses_the_trie_when_enabled() {
+        let params = || Query(AutocompleteParams { q: "Pro".to_string(), limit: Some(2) });
+        let mut sets = crate::sets::SetManager::new().with_name_trie(true);
+        for name in ["Protractor", "Pro Drill", "Lamp", "Pro Saw"] {
+            sets.insert_product(&Product { name: name.to_string(), ..crate::seed::generate_products(1).remove(0) });
+        }
+        let state = AppState { sets: std::sync::Arc::new(tokio::sync::RwLock::new(sets)), ..offline_state() };
+
+        let (status, Json(body)) = autocomplete(State(state.clone()), RequestId::new(), params()).await.unwrap();
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!((body["source"].as_str(), body["results_match"].as_bool()), (Some("trie"), Some(true)));
+        let names: Vec<&str> = body["data"].as_array().unwrap().iter().map(|s| s["name"].as_str().unwrap()).collect();
+        assert_eq!(names, ["Pro Drill", "Pro Saw"]);
+        assert_eq!(state.metrics.read().await.entries.len(), 2);
+
+        let (_, Json(body)) = autocomplete(State(offline_state()), RequestId::new(), params()).await.unwrap();
+        assert_eq!((body["source"].as_str(), body["count"].as_u64()), (Some("btree"), Some(0)));
+        assert!(body["trie_time_us"].is_null());
+
+        let empty = Query(AutocompleteParams { q: String::new(), limit: None });
+        assert!(autocomplete(State(offline_state()), RequestId::new(), empty).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn price_range_is_served_from_memory() {
+        let state = offline_state();
+        {
+            let mut sets = state.sets.write().await;
+            for (name, price_cents) in [("Cheap", 50), ("Mid", 500), ("Dear", 5_000)] {
+                sets.insert_product(&Product {
+                    id: Uuid::new_v4(),
+                    name: name.to_string(),
+                    description: None,
+                    price_cents,
+                    quantity: 1,
+                    category: "Home & Garden".to_string(),
+                    created_at: chrono::Utc::now(),
+                    updated_at: chrono::Utc::now(),
+                    version: 1,
+                });
+            }
+        }
+
+        let params = PriceRangeParams { min_price: Some(50), max_price: Some(500) };
+        let (status, Json(body)) = products_in_price_range(State(state.clone()), RequestId::new(), Query(params)).await.unwrap();
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["count"], 2);
+        assert_eq!(body["data"][0]["name"], "Cheap");
+        assert_eq!(body["data"][1]["name"], "Mid");
+
+        let (_, Json(all)) = products_in_price_range(State(state.clone()), RequestId::new(), Query(PriceRangeParams::default())).await.unwrap();
+        assert_eq!(all["count"], 3);
+
+        let inverted = PriceRangeParams { min_price: Some(10), max_price: Some(1) };
+        let err = products_in_price_range(State(state), RequestId::new(), Query(inverted)).await.unwrap_err();
+        assert!(matches!(err, crate::error::AppError::BadRequest(_)));
+    }
+
+    #[tokio::test]
+    async fn invalid_payloads_are_422_before_the_database_is_touched() {
+        // offline_state has no reachable database, so getting past validation would be a 500
+        let bad = CreateProduct {
+            name: String::new(),
+            description: None,
+            price_cents: -1,
+            quantity: 5,
+            category: Category::Books,
+        };
+        let err = create_product(State(offline_state()), RequestId::new(), Json(bad)).await.unwrap_err();
+        let response = axum::response::IntoResponse::into_response(err);
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let fields: Vec<_> = body["errors"].as_array().unwrap().iter().map(|e| e["field"].clone()).collect();
+        assert_eq!(fields, ["name", "price_cents"]);
+
+        let update = UpdateProduct {
+            name: None,
+            description: None,
+            price_cents: None,
+            quantity: Some(-1),
+            category: None,
+            expected_version: None,
+        };
+        let err = update_product(State(offline_state()), RequestId::new(), Path(Uuid::new_v4()), Json(update))
+            .await
+            .unwrap_err();
+        assert!(matches!(&err, crate::error::AppError::Validation(errors) if errors.len() == 1));
+    }
+
+    #[tokio::test]
+    async fn import_rejects_an_empty_multipart_body() {
+        let req = axum::http::Request::builder()
+            .method("POST")
+            .header(axum::http::header::CONTENT_TYPE, "multipart/form-data; boundary=X")
+            .body(axum::body::Body::from("--X--\r\n"))
+            .unwrap();
+        let multipart = Multipart::from_request(req, &()).await.unwrap();
+        let err = import_products_csv(State(offline_state()), RequestId::new(), Query(ImportParams::default()), multipart)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, crate::error::AppError::BadRequest(_)));
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn import_inserts_valid_rows_and_syncs_sets(pool: sqlx::PgPool) {
+        let state = crate::AppState { db: pool, ..offline_state() };
+        let csv = format!(
+            "{}\nLamp,Desk lamp,1999,3,Home & Garden\nLamp,,1,1,Home & Garden\nChair,,-1,1,Home & Garden\nRug,,4500,2,Home & Garden\n",
+            HEADER
+        );
+        let (status, Json(body)) = import_products_csv(
+            State(state.clone()),
+            RequestId::new(),
+            Query(ImportParams::default()),
+            upload(&csv).await,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(status, StatusCode::MULTI_STATUS);
+        let report = &body["report"];
+        assert_eq!((report["total_rows"].as_u64(), report["imported"].as_u64()), (Some(4), Some(2)));
+        assert_eq!(report["errors"][0]["row"], 2);
+        assert_eq!(report["errors"][1]["row"], 3);
+
+        assert_eq!(db::count_products(&state.db).await.unwrap(), 2);
+        let sets = state.sets.read().await;
+        assert_eq!(sets.hash_set.len(), 2);
+        assert!(sets.symmetric_difference().is_empty());
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn list_sorted_by_price_ascending_starts_with_the_cheapest(pool: sqlx::PgPool) {
+        let state = crate::AppState { db: pool, ..offline_state() };
+        let csv = format!("{}\nLamp,,700,1,Home & Garden\nRug,,200,2,Home & Garden\nDrill,,900,3,Tools & Hardware\n", HEADER);
+        let (status, _) = import_products_csv(State(state.clone()), RequestId::new(), Query(ImportParams::default()), upload(&csv).await)
+            .await
+            .unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+
+        let uri: axum::http::Uri = "/api/products?sort_by=price_cents&sort_order=asc&limit=2".parse().unwrap();
+        let query = Query::<ProductFilters>::try_from_uri(&uri).unwrap();
+        let (_, _, Json(body)) = list_products(State(state.clone()), RequestId::new(), query).await.unwrap();
+        let prices: Vec<i64> = body["data"].as_array().unwrap().iter().map(|p| p["price_cents"].as_i64().unwrap()).collect();
+        assert_eq!(prices, vec![200, 700]);
+        assert!(body["next_cursor"].is_null(), "custom orders page by offset");
+
+        let bad: axum::http::Uri = "/api/products?sort_by=description".parse().unwrap();
+        assert!(Query::<ProductFilters>::try_from_uri(&bad).is_err(), "only whitelisted columns");
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn list_reports_the_filtered_total_in_body_and_header(pool: sqlx::PgPool) {
+        use axum::response::IntoResponse;
+
+        let state = crate::AppState { db: pool, ..offline_state() };
+        let csv = format!("{}\nLamp,,700,1,Home & Garden\nRug,,200,2,Home & Garden\nChair,,500,2,Home & Garden\nDrill,,900,3,Tools & Hardware\n", HEADER);
+        let (status, _) = import_products_csv(State(state.clone()), RequestId::new(), Query(ImportParams::default()), upload(&csv).await)
+            .await
+            .unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+
+        let list = |uri: &str| {
+            let query = Query::<ProductFilters>::try_from_uri(&uri.parse().unwrap()).unwrap();
+            list_products(State(state.clone()), RequestId::new(), query)
+        };
+        let response = list("/api/products?category=Home%20%26%20Garden&limit=2&offset=1").await.into_response();
+        let header = response.headers()["x-total-count"].to_str().unwrap().to_string();
+        let body: serde_json::Value =
+            serde_json::from_slice(&axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap()).unwrap();
+        assert_eq!(header, body["total_count"].to_string());
+        assert_eq!((body["total_count"].as_i64(), body["count"].as_u64()), (Some(3), Some(2)), "Drill is filtered out");
+        assert_eq!((body["has_next_page"].as_bool(), body["has_prev_page"].as_bool()), (Some(false), Some(true)));
+
+        let (_, [(_, total)], Json(body)) = list("/api/products?limit=3").await.unwrap();
+        assert_eq!((total.as_str(), body["has_next_page"].as_bool(), body["has_prev_page"].as_bool()), ("4", Some(true), Some(false)));
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn abc_analysis_classifies_every_live_product(pool: sqlx::PgPool) {
+        let state = crate::AppState { db: pool, ..offline_state() };
+        let csv = format!("{}\nDesk,,8000,1,Home & Garden\nChair,,500,2,Home & Garden\nLamp,,600,1,Home & Garden\nRug,,200,2,Home & Garden\n", HEADER);
+        let (status, _) = import_products_csv(State(state.clone()), RequestId::new(), Query(ImportParams::default()), upload(&csv).await)
+            .await
+            .unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+
+        let (status, Json(body)) = abc_analysis(State(state.clone()), RequestId::new()).await.unwrap();
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["results_match"], true);
+        let data = &body["data"];
+        assert_eq!((data["a_count"].as_u64(), data["b_count"].as_u64(), data["c_count"].as_u64()), (Some(1), Some(2), Some(1)));
+        assert_eq!(data["total_value_cents"].as_i64(), Some(10_000));
+        let items: Vec<(&str, &str)> = data["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|i| (i["name"].as_str().unwrap(), i["class"].as_str().unwrap()))
+            .collect();
+        assert_eq!(items, [("Desk", "A"), ("Chair", "B"), ("Lamp", "B"), ("Rug", "C")]);
+        let metrics = state.metrics.read().await;
+        let recorded: Vec<&str> = metrics.entries.iter().rev().take(2).map(|e| e.operation.as_str()).collect();
+        assert_eq!(recorded, ["abc_analysis", "db_query:abc_analysis"]);
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn price_distribution_counts_every_product_in_one_bucket(pool: sqlx::PgPool) {
+        let state = crate::AppState { db: pool, ..offline_state() };
+        let csv = format!("{}\nPen,,0,5,Books\nLamp,,999,1,Books\nRug,,1000,2,Books\nDesk,,60000,1,Books\n", HEADER);
+        let (status, _) = import_products_csv(State(state.clone()), RequestId::new(), Query(ImportParams::default()), upload(&csv).await)
+            .await
+            .unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+
+        let params = PriceDistributionParams { buckets: Some("1000,5000".to_string()) };
+        let (status, Json(body)) = price_distribution(State(state.clone()), RequestId::new(), Query(params)).await.unwrap();
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["results_match"], true);
+        assert_eq!(body["total_count"], 4);
+        assert_eq!(
+            body["data"],
+            serde_json::json!([
+                { "lower_cents": 0, "upper_cents": 1000, "count": 2, "total_value_cents": 999 },
+                { "lower_cents": 1000, "upper_cents": 5000, "count": 1, "total_value_cents": 2000 },
+                { "lower_cents": 5000, "upper_cents": null, "count": 1, "total_value_cents": 60000 },
+            ])
+        );
+
+        let unsorted = PriceDistributionParams { buckets: Some("5000,1000".to_string()) };
+        let err = price_distribution(State(state), RequestId::new(), Query(unsorted)).await.unwrap_err();
+        assert!(matches!(err, crate::error::AppError::BadRequest(_)));
+    }
+
+    #[tokio::test]
+    async fn suggest_rejects_a_blank_prefix() {
+        let params = SuggestParams { q: "  ".to_string(), limit: None };
+        let err = suggest_names(State(offline_state()), RequestId::new(), Query(params)).await.unwrap_err();
+        assert!(matches!(err, crate::error::AppError::BadRequest(_)));
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn suggest_matches_name_prefixes_ignoring_case(pool: sqlx::PgPool) {
+        let state = crate::AppState { db: pool, ..offline_state() };
+        let csv = format!("{}\nEco Lamp,,700,1,Books\neco mug,,200,2,Books\nDeco Rug,,900,3,Books\nEc%o,,100,1,Books\n", HEADER);
+        let (status, _) = import_products_csv(State(state.clone()), RequestId::new(), Query(ImportParams::default()), upload(&csv).await)
+            .await
+            .unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+
+        let params = SuggestParams { q: "ECO".to_string(), limit: None };
+        let (_, Json(body)) = suggest_names(State(state.clone()), RequestId::new(), Query(params)).await.unwrap();
+        assert_eq!(body["data"], serde_json::json!(["Eco Lamp", "eco mug"]));
+        assert_eq!((body["cached"].as_bool(), body["timed_out"].as_bool()), (Some(false), Some(false)));
+
+        // `%` is literal, and the second identical request is served from the cache
+        assert_eq!(db::fetch_name_suggestions(&state.db, "Ec%", 10).await.unwrap(), vec!["Ec%o"]);
+        let params = SuggestParams { q: "eco".to_string(), limit: None };
+        let (_, Json(body)) = suggest_names(State(state), RequestId::new(), Query(params)).await.unwrap();
+        assert_eq!(body["cached"], true);
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn suggest_returns_at_most_ten_names(pool: sqlx::PgPool) {
+        let state = crate::AppState { db: pool, ..offline_state() };
+        let rows: String = (1..=12).map(|i| format!("Eco {:02},,100,1,Books\n", i)).collect();
+        let (status, _) = import_products_csv(State(state.clone()), RequestId::new(), Query(ImportParams::default()), upload(&format!("{}\n{}", HEADER, rows)).await)
+            .await
+            .unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+
+        let params = SuggestParams { q: "eco".to_string(), limit: Some(50) };
+        let (_, Json(body)) = suggest_names(State(state), RequestId::new(), Query(params)).await.unwrap();
+        let names = body["data"].as_array().unwrap();
+        assert_eq!(names.len(), MAX_SUGGESTIONS);
+        assert_eq!((names[0].as_str(), names[9].as_str()), (Some("Eco 01"), Some("Eco 10")));
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn tagged_products_carry_their_tags_in_list_and_get(pool: sqlx::PgPool) {
+        let state = crate::AppState { db: pool, ..offline_state() };
+        let csv = format!("{}\nLamp,,700,1,Home & Garden\nRug,,200,2,Home & Garden\n", HEADER);
+        let (status, _) = import_products_csv(State(state.clone()), RequestId::new(), Query(ImportParams::default()), upload(&csv).await)
+            .await
+            .unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+        let lamp = db::fetch_all_products(&state.db, &ProductFilters::default()).await.unwrap().0.into_iter().find(|p| p.name == "Lamp").unwrap();
+
+        for tag in [" sale ", "home", "sale"] {
+            let payload = AddTag { tag: tag.to_string() };
+            let (_, Json(body)) = add_product_tag(State(state.clone()), RequestId::new(), Path(lamp.id), Json(payload)).await.unwrap();
+            assert!(body["data"].as_array().unwrap().contains(&"sale".into()), "trimmed before storing");
+        }
+        let blank = AddTag { tag: "  ".to_string() };
+        let err = add_product_tag(State(state.clone()), RequestId::new(), Path(lamp.id), Json(blank)).await.unwrap_err();
+        assert!(matches!(err, crate::error::AppError::Validation(_)));
+
+        let uri: axum::http::Uri = "/api/products?tags=sale,home".parse().unwrap();
+        let (_, _, Json(body)) = list_products(State(state.clone()), RequestId::new(), Query::try_from_uri(&uri).unwrap()).await.unwrap();
+        assert_eq!(body["count"], 1);
+        assert_eq!(body["data"][0]["name"], "Lamp");
+        assert_eq!(body["data"][0]["tags"], serde_json::json!(["home", "sale"]));
+
+        let (_, Json(body)) = remove_product_tag(State(state.clone()), RequestId::new(), Path((lamp.id, "sale".to_string()))).await.unwrap();
+        assert_eq!(body["data"], serde_json::json!(["home"]));
+        let (_, Json(body)) = get_product(State(state), RequestId::new(), Path(lamp.id)).await.unwrap();
+        assert_eq!(body["data"]["tags"], serde_json::json!(["home"]));
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn bulk_delete_reports_unknown_ids_and_shrinks_the_sets(pool: sqlx::PgPool) {
+        let state = crate::AppState { db: pool, ..offline_state() };
+        let products = crate::seed::seed_products(&state.db, 5).await.unwrap();
+        state.sets.write().await.sync_from_db(&products);
+        let stranger = Uuid::new_v4();
+        let ids = vec![products[0].id, stranger, products[3].id, products[0].id];
+
+        let dry = BulkDeleteRequest { ids: ids.clone(), dry_run: true };
+        let (_, Json(body)) = bulk_delete_products(State(state.clone()), RequestId::new(), Json(dry)).await.unwrap();
+        assert_eq!(body["deleted"], 2);
+        assert_eq!(state.sets.read().await.sizes(), SetSizes::uniform(5, None), "dry run leaves the sets alone");
+        assert_eq!(db::fetch_live_product_ids(&state.db, &ids).await.unwrap().len(), 2);
+
+        let req = BulkDeleteRequest { ids: ids.clone(), dry_run: false };
+        let (status, Json(body)) = bulk_delete_products(State(state.clone()), RequestId::new(), Json(req)).await.unwrap();
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["requested"], 4);
+        assert_eq!(body["deleted"], 2);
+        assert_eq!(body["not_found"], serde_json::json!([stranger]));
+        assert_eq!(state.sets.read().await.sizes(), SetSizes::uniform(3, None));
+
+        // Already soft-deleted counts as not found
+        let again = BulkDeleteRequest { ids: vec![products[3].id], dry_run: false };
+        let (_, Json(body)) = bulk_delete_products(State(state.clone()), RequestId::new(), Json(again)).await.unwrap();
+        assert_eq!((body["deleted"].as_u64(), body["not_found"][0].as_str()), (Some(0), Some(products[3].id.to_string().as_str())));
+
+        let empty = BulkDeleteRequest { ids: vec![], dry_run: false };
+        let err = bulk_delete_products(State(state), RequestId::new(), Json(empty)).await.unwrap_err();
+        assert!(matches!(err, crate::error::AppError::BadRequest(_)));
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn related_products_share_the_category_and_exclude_the_product(pool: sqlx::PgPool) {
+        let state = crate::AppState { db: pool, ..offline_state() };
+        let csv = format!("{}\nLamp,,100,1,Home & Garden\nRug,,200,2,Home & Garden\nSofa,,300,3,Home & Garden\nDrill,,400,4,Tools & Hardware\n", HEADER);
+        let (status, _) = import_products_csv(State(state.clone()), RequestId::new(), Query(ImportParams::default()), upload(&csv).await)
+            .await
+            .unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+        let (products, _) = db::fetch_all_products(&state.db, &ProductFilters::default()).await.unwrap();
+        let rug = products.iter().find(|p| p.name == "Rug").unwrap();
+
+        let params = RelatedParams { limit: Some(5) };
+        let (_, Json(body)) = related_products(State(state.clone()), RequestId::new(), Path(rug.id), Query(params)).await.unwrap();
+        for strategy in ["db_results", "set_results"] {
+            let found = body[strategy].as_array().unwrap();
+            let names: Vec<&str> = found.iter().map(|p| p["name"].as_str().unwrap()).collect();
+            assert_eq!(names, ["Lamp", "Sofa"], "{}", strategy);
+            assert!(found.iter().all(|p| p["id"] != rug.id.to_string()), "{} lists the product itself", strategy);
+            assert!(found.iter().all(|p| p["category"] == "Home & Garden"), "{}", strategy);
+        }
+
+        let params = RelatedParams { limit: Some(1) };
+        let (_, Json(body)) = related_products(State(state.clone()), RequestId::new(), Path(rug.id), Query(params)).await.unwrap();
+        assert_eq!(body["db_results"], body["set_results"]);
+        assert_eq!(body["db_results"].as_array().unwrap().len(), 1);
+
+        let missing = related_products(State(state), RequestId::new(), Path(Uuid::new_v4()), Query(RelatedParams::default())).await;
+        assert!(matches!(missing.unwrap_err(), crate::error::AppError::NotFound(_)));
+    }
+
+    fn lamp(name: &str) -> CreateProduct {
+        CreateProduct {
+            name: name.to_string(),
+            description: Some("Desk lamp".to_string()),
+            price_cents: 1999,
+            quantity: 3,
+            category: Category::HomeAndGarden,
+        }
+    }
+
+    async fn duplicate(state: &crate::AppState, id: Uuid, payload: DuplicateProduct) -> AppResult<serde_json::Value> {
+        let (status, Json(body)) = duplicate_product(State(state.clone()), RequestId::new(), Path(id), JsonOrDefault(payload)).await?;
+        assert_eq!(status, StatusCode::CREATED);
+        Ok(body)
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn duplicate_copies_under_a_new_id_and_leaves_the_original_alone(pool: sqlx::PgPool) {
+        let state = crate::AppState { db: pool, ..offline_state() };
+        let original = db::insert_product(&state.db, &lamp("Lamp")).await.unwrap();
+
+        let body = duplicate(&state, original.id, DuplicateProduct::default()).await.unwrap();
+        let copy: Product = serde_json::from_value(body["data"].clone()).unwrap();
+        assert_ne!(copy.id, original.id);
+        assert_eq!(copy.name, "Lamp (copy)");
+        assert_eq!(
+            (&copy.description, copy.price_cents, copy.quantity, &copy.category),
+            (&original.description, original.price_cents, original.quantity, &original.category)
+        );
+        assert!(state.sets.read().await.hash_set.contains(&copy));
+
+        let reread = db::fetch_product_by_id(&state.db, original.id).await.unwrap();
+        assert_eq!((reread.name.as_str(), reread.quantity, reread.version), ("Lamp", 3, original.version));
+
+        let payload = DuplicateProduct { name_override: Some("Floor lamp".to_string()), quantity_override: Some(0) };
+        let body = duplicate(&state, original.id, payload).await.unwrap();
+        assert_eq!((body["data"]["name"].as_str(), body["data"]["quantity"].as_i64()), (Some("Floor lamp"), Some(0)));
+
+        let missing = duplicate(&state, Uuid::new_v4(), DuplicateProduct::default()).await;
+        assert!(matches!(missing.unwrap_err(), crate::error::AppError::NotFound(_)));
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn duplicate_numbers_copies_and_gives_up_after_ten_names(pool: sqlx::PgPool) {
+        let state = crate::AppState { db: pool, ..offline_state() };
+        let original = db::insert_product(&state.db, &lamp("Lamp")).await.unwrap();
+
+        let mut names = vec![];
+        for _ in 0..DuplicateProduct::MAX_NAME_ATTEMPTS {
+            let body = duplicate(&state, original.id, DuplicateProduct::default()).await.unwrap();
+            names.push(body["data"]["name"].as_str().unwrap().to_string());
+        }
+        assert_eq!(names[..3], ["Lamp (copy)", "Lamp (copy 2)", "Lamp (copy 3)"]);
+        assert_eq!(names[9], "Lamp (copy 10)");
+
+        let err = duplicate(&state, original.id, DuplicateProduct::default()).await.unwrap_err();
+        assert!(matches!(err, crate::error::AppError::Conflict(_)), "{:?}", err);
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn updating_with_identical_fields_is_a_set_cache_hit(pool: sqlx::PgPool) {
+        let state = crate::AppState { db: pool, ..offline_state() };
+        let original = db::insert_product(&state.db, &lamp("Lamp")).await.unwrap();
+        state.sets.write().await.insert_product(&original);
+
+        let update = |price_cents: Option<i64>| UpdateProduct {
+            name: Some("Lamp".to_string()),
+            description: None,
+            price_cents,
+            quantity: None,
+            category: None,
+            expected_version: None,
+        };
+        for expected_hits in [1, 2] {
+            let (_, Json(body)) = update_product(State(state.clone()), RequestId::new(), Path(original.id), Json(update(None))).await.unwrap();
+            assert_eq!(body["cache_hit"], true);
+            assert_eq!(state.set_cache_hits.load(Ordering::Relaxed), expected_hits);
+        }
+
+        let (_, Json(body)) = update_product(State(state.clone()), RequestId::new(), Path(original.id), Json(update(Some(2499)))).await.unwrap();
+        assert_eq!(body["cache_hit"], false);
+        assert_eq!(state.set_cache_hits.load(Ordering::Relaxed), 2);
+        assert_eq!(state.sets.read().await.hash_set.get(&original).unwrap().price_cents, 2499);
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn second_fetch_is_served_from_the_product_cache_until_an_update(pool: sqlx::PgPool) {
+        let state = crate::AppState { db: pool, ..offline_state() };
+        let original = db::insert_product(&state.db, &lamp("Lamp")).await.unwrap();
+
+        let (_, Json(first)) = get_product(State(state.clone()), RequestId::new(), Path(original.id)).await.unwrap();
+        assert_eq!((first["cache_hit"].as_bool(), first["cache_age_ms"].is_null()), (Some(false), true));
+        let (_, Json(second)) = get_product(State(state.clone()), RequestId::new(), Path(original.id)).await.unwrap();
+        assert_eq!(second["cache_hit"], true);
+        assert!(second["cache_age_ms"].as_f64().unwrap() >= 0.0);
+        assert_eq!(second["data"], first["data"]);
+
+        let update = UpdateProduct {
+            name: None,
+            description: None,
+            price_cents: Some(2499),
+            quantity: None,
+            category: None,
+            expected_version: None,
+        };
+        let (status, _) = update_product(State(state.clone()), RequestId::new(), Path(original.id), Json(update)).await.unwrap();
+        assert_eq!(status, StatusCode::OK);
+        let (_, Json(after)) = get_product(State(state.clone()), RequestId::new(), Path(original.id)).await.unwrap();
+        assert_eq!((after["cache_hit"].as_bool(), after["data"]["price_cents"].as_i64()), (Some(false), Some(2499)));
+
+        let (status, _) = delete_product(State(state.clone()), RequestId::new(), Path(original.id)).await.unwrap();
+        assert!(status.is_success());
+        let err = get_product(State(state.clone()), RequestId::new(), Path(original.id)).await.unwrap_err();
+        assert!(matches!(err, crate::error::AppError::NotFound(_)), "a deleted product must not be served from the cache");
+
+        let stats = state.product_cache.stats().await;
+        assert_eq!((stats.hit_count, stats.miss_count, stats.evictions), (1, 3, 2));
+    }
+
+    fn restock(quantity: i32, supplier: Option<&str>) -> Json<RestockRequest> {
+        Json(RestockRequest { quantity, supplier: supplier.map(str::to_string), notes: None })
+    }
+
+    #[tokio::test]
+    async fn restock_rejects_a_non_positive_quantity_before_the_database_is_touched() {
+        for quantity in [0, -5] {
+            let err = restock_product(State(offline_state()), RequestId::new(), Path(Uuid::new_v4()), restock(quantity, None))
+                .await
+                .unwrap_err();
+            let response = axum::response::IntoResponse::into_response(err);
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        }
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn restock_adds_stock_records_history_and_syncs_sets(pool: sqlx::PgPool) {
+        let state = crate::AppState { db: pool, ..offline_state() };
+        let original = db::insert_product(&state.db, &lamp("Lamp")).await.unwrap();
+
+        for (quantity, supplier) in [(10, Some("Acme")), (4, None)] {
+            let (status, Json(body)) =
+                restock_product(State(state.clone()), RequestId::new(), Path(original.id), restock(quantity, supplier)).await.unwrap();
+            assert_eq!(status, StatusCode::CREATED);
+            assert_eq!(body["data"]["quantity_added"].as_i64(), Some(quantity as i64));
+        }
+
+        let reread = db::fetch_product_by_id(&state.db, original.id).await.unwrap();
+        assert_eq!((reread.quantity, reread.version), (17, original.version + 2));
+        let sets = state.sets.read().await;
+        let synced = sets.hash_set.iter().find(|p| p.id == original.id).expect("restocked product is in the sets");
+        assert_eq!(synced.quantity, 17);
+        drop(sets);
+
+        let (_, Json(body)) = restock_history(State(state.clone()), RequestId::new(), Path(original.id), Query(RestockHistoryParams::default()))
+            .await
+            .unwrap();
+        let added: Vec<_> = body["data"].as_array().unwrap().iter().map(|e| e["quantity_added"].as_i64().unwrap()).collect();
+        assert_eq!(added, [4, 10], "newest first");
+        assert_eq!((body["count"].as_u64(), body["total_added"].as_i64()), (Some(2), Some(14)));
+        assert_eq!(body["data"][1]["supplier"].as_str(), Some("Acme"));
+
+        let too_many = restock_product(State(state.clone()), RequestId::new(), Path(original.id), restock(i32::MAX, None)).await;
+        assert!(matches!(too_many.unwrap_err(), crate::error::AppError::BadRequest(_)));
+        let missing = restock_product(State(state.clone()), RequestId::new(), Path(Uuid::new_v4()), restock(1, None)).await;
+        assert!(matches!(missing.unwrap_err(), crate::error::AppError::NotFound(_)));
+        let missing = restock_history(State(state), RequestId::new(), Path(Uuid::new_v4()), Query(RestockHistoryParams::default())).await;
+        assert!(matches!(missing.unwrap_err(), crate::error::AppError::NotFound(_)));
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn export_streams_a_csv_attachment(pool: sqlx::PgPool) {
+        let state = crate::AppState { db: pool, ..offline_state() };
+        let csv = format!("{}\nLamp,,100,1,Home & Garden\nRug,,200,2,Home & Garden\nDrill,,300,3,Tools & Hardware\n", HEADER);
+        let (status, _) = import_products_csv(State(state.clone()), RequestId::new(), Query(ImportParams::default()), upload(&csv).await)
+            .await
+            .unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+
+        let filters = ProductFilters { category: Some("Home & Garden".to_string()), ..Default::default() };
+        let response = export_products_csv(State(state.clone()), RequestId::new(), Query(filters)).await.unwrap();
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "text/csv");
+        assert_eq!(
+            response.headers()[header::CONTENT_DISPOSITION],
+            "attachment; filename=\"products.csv\""
+        );
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        let mut lines = body.lines();
+        assert_eq!(lines.next(), Some("id,name,description,price_cents,quantity,category,created_at,updated_at"));
+        let mut names: Vec<&str> = lines.map(|l| l.split(',').nth(1).unwrap()).collect();
+        names.sort_unstable();
+        assert_eq!(names, vec!["Lamp", "Rug"]);
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn ndjson_export_has_one_valid_json_product_per_line(pool: sqlx::PgPool) {
+        crate::seed::seed_products(&pool, 1_200).await.unwrap();
+        let state = crate::AppState { db: pool, ..offline_state() };
+
+        let export = |filters: ProductFilters| {
+            let state = state.clone();
+            async move {
+                let response = export_products_ndjson(State(state), RequestId::new(), Query(filters)).await.unwrap();
+                assert_eq!(response.headers()[header::CONTENT_TYPE], "application/x-ndjson");
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                String::from_utf8(body.to_vec()).unwrap()
+            }
+        };
+
+        let body = export(ProductFilters::default()).await;
+        assert!(body.ends_with('\n'));
+        let products: Vec<Product> = body.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(products.len() as i64, db::count_products(&state.db).await.unwrap());
+        assert!(products.windows(2).all(|w| w[0].created_at >= w[1].created_at), "newest first");
+
+        let filters = ProductFilters { category: Some("Books".to_string()), ..Default::default() };
+        let books = export(filters).await;
+        let expected = products.iter().filter(|p| p.category == "Books").count();
+        assert_eq!(books.lines().count(), expected);
+        assert!(books.lines().all(|line| serde_json::from_str::<Product>(line).unwrap().category == "Books"));
+    }
+}
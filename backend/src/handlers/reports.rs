@@ -0,0 +1,85 @@
+use std::time::Instant;
+
+use axum::{extract::State, http::StatusCode, Json};
+use tracing::info;
+
+use crate::{db, error::AppResult, extract::RequestId, AppState};
+
+/// `GET /api/reports/inventory-value` — the value of every product's stock,
+/// most valuable first, computed by the database (`ORDER BY price_cents *
+/// quantity`) and from `btree_set` (collect and sort), with both timings.
+#[utoipa::path(
+    get,
+    path = "/api/reports/inventory-value",
+    tag = "reports",
+    responses(
+        (status = 200, description = "`db_results` and `set_results`: [InventoryReportRow], most valuable first, plus `total_inventory_value_cents` and timings", body = Object),
+    )
+)]
+pub async fn inventory_value(
+    State(state): State<AppState>,
+    request_id: RequestId,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    let db_start = Instant::now();
+    let db_results = db::fetch_inventory_report(&state.db).await?;
+    let db_elapsed = db_start.elapsed();
+
+    let set_start = Instant::now();
+    let set_results = state.sets.read().await.inventory_report();
+    let set_elapsed = set_start.elapsed();
+
+    let total_inventory_value_cents: i64 = db_results.iter().map(|r| r.total_value_cents).sum();
+    let results_match = db_results == set_results;
+
+    info!(
+        request_id = %request_id,
+        db_count = db_results.len(),
+        set_count = set_results.len(),
+        total_inventory_value_cents,
+        results_match,
+        "Built inventory value report"
+    );
+
+    let mut metrics = state.metrics.write().await;
+    metrics.record_raw("db_query:inventory_report", "DB", db_elapsed.as_nanos() as u64, db_results.len());
+    metrics.record_raw("inventory_report", "BTreeSet", set_elapsed.as_nanos() as u64, set_results.len());
+
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "total_inventory_value_cents": total_inventory_value_cents,
+            "count": db_results.len(),
+            "results_match": results_match,
+            "db_results": db_results,
+            "set_results": set_results,
+            "db_time_ms": db_elapsed.as_secs_f64() * 1000.0,
+            "set_time_ms": set_elapsed.as_secs_f64() * 1000.0,
+        })),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::offline_state;
+    use crate::models::{Category, CreateProduct};
+
+    #[sqlx::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn db_and_set_reports_agree_on_order_and_total(pool: sqlx::PgPool) {
+        let state = crate::AppState { db: pool, ..offline_state() };
+        for (name, price_cents, quantity) in [("Rug", 1000, 3), ("lamp", 500, 6), ("Desk", 20_000, 1), ("Chair", 1500, 2)] {
+            let payload = CreateProduct { name: name.to_string(), description: None, price_cents, quantity, category: Category::HomeAndGarden };
+            let product = db::insert_product(&state.db, &payload).await.unwrap();
+            state.sets.write().await.insert_product(&product);
+        }
+
+        let (status, Json(body)) = inventory_value(State(state), RequestId::new()).await.unwrap();
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["results_match"], true);
+        assert_eq!((body["count"].as_u64(), body["total_inventory_value_cents"].as_i64()), (Some(4), Some(29_000)));
+        // Ties compare byte-wise, so "Chair" and "Rug" come before "lamp"
+        let names: Vec<_> = body["db_results"].as_array().unwrap().iter().map(|r| r["name"].as_str().unwrap()).collect();
+        assert_eq!(names, ["Desk", "Chair", "Rug", "lamp"]);
+    }
+}
@@ -1,18 +1,43 @@
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use std::time::Instant;
-
-use axum::{extract::State, http::StatusCode, Json};
+use std::time::{Duration, Instant};
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use rand::distributions::Distribution;
 use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
 use rand::{Rng, SeedableRng};
+use rand_distr::Zipf;
 use serde::{Deserialize, Serialize};
 use tokio::task::JoinSet;
 use tracing::info;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::{
+    db,
+    error::{AppError, AppResult},
+    extract::RequestId,
+    seed, AppState,
+};
+
+/// How virtual users pick which existing product to read or update.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AccessPattern {
+    /// Every product is equally likely (default).
+    #[default]
+    Uniform,
+    /// Zipf-distributed: the earliest-created products receive most of the
+    /// traffic. Higher exponents concentrate traffic further.
+    Zipf { exponent: f64 },
+}
 
-use crate::{db, error::AppResult, seed, AppState};
-
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct StressParams {
     /// Number of concurrent "virtual users" (default: 20)
     pub concurrency: Option<usize>,
@@ -20,13 +45,78 @@ pub struct StressParams {
     pub ops_per_user: Option<usize>,
     /// Seed the DB with this many products before testing (default: 0 = use existing)
     pub seed_count: Option<usize>,
+    /// Simulate DB latency instead of touching the DB or the in-memory sets (default: false)
+    pub dry_run: Option<bool>,
+    /// Distribution used to pick products for reads and updates (default: uniform)
+    #[serde(default)]
+    pub access_pattern: AccessPattern,
+    /// Only delete products created during this run (default: true). When
+    /// false, deletes may also hit pre-existing products — those are soft
+    /// deletes, so the data can still be restored afterwards.
+    #[serde(default = "default_true")]
+    pub delete_own_creates_only: bool,
+}
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct StressHistoryParams {
+    /// Number of runs to return, newest first (default: 10, max: 200)
+    pub limit: Option<i64>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Upper bounds of [`StressReport::latency_histogram`]'s buckets, in ms.
+const LATENCY_BUCKETS_MS: [f64; 13] = [0.1, 0.5, 1.0, 2.0, 5.0, 10.0, 25.0, 50.0, 100.0, 250.0, 500.0, 1000.0, f64::INFINITY];
+
+/// One latency histogram bucket, Prometheus-style: `cumulative_count` is the
+/// number of ops that took at most `le_ms`, `count` only those above the
+/// previous bound.
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct HistogramBucket {
+    /// `"+Inf"` for the last bucket, as in Prometheus' `le` label
+    #[serde(serialize_with = "serialize_bound")]
+    #[schema(value_type = Object)]
+    pub le_ms: f64,
+    pub count: u64,
+    pub cumulative_count: u64,
+}
+
+fn serialize_bound<S: serde::Serializer>(le_ms: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+    if le_ms.is_infinite() {
+        serializer.serialize_str("+Inf")
+    } else {
+        serializer.serialize_f64(*le_ms)
+    }
 }
 
-#[derive(Debug, Serialize)]
+/// Buckets `sorted_ms` (ascending) by [`LATENCY_BUCKETS_MS`].
+fn latency_histogram(sorted_ms: &[f64]) -> Vec<HistogramBucket> {
+    let mut previous = 0;
+    LATENCY_BUCKETS_MS
+        .iter()
+        .map(|&le_ms| {
+            let cumulative = sorted_ms.partition_point(|&ms| ms <= le_ms);
+            let bucket = HistogramBucket {
+                le_ms,
+                count: (cumulative - previous) as u64,
+                cumulative_count: cumulative as u64,
+            };
+            previous = cumulative;
+            bucket
+        })
+        .collect()
+}
+
+#[derive(Debug, Serialize, ToSchema)]
 pub struct StressReport {
     pub concurrency: usize,
     pub ops_per_user: usize,
     pub total_ops: usize,
+    pub access_pattern: AccessPattern,
+    pub delete_own_creates_only: bool,
     pub product_count_before: i64,
     pub product_count_after: i64,
 
@@ -38,7 +128,10 @@ pub struct StressReport {
     pub reads: u64,
     pub creates: u64,
     pub updates: u64,
+    /// Every delete, hard or soft
     pub deletes: u64,
+    /// Deletes that permanently removed a product created during this run
+    pub true_deletes: u64,
     pub errors: u64,
 
     // Latency (across all ops)
@@ -47,6 +140,9 @@ pub struct StressReport {
     pub avg_latency_ms: f64,
     pub p95_latency_ms: f64,
     pub p99_latency_ms: f64,
+    pub p999_latency_ms: f64,
+    /// Every op's latency in fixed buckets, for CDF plots
+    pub latency_histogram: Vec<HistogramBucket>,
 
     // Per-op latency breakdown
     pub read_avg_ms: f64,
@@ -59,29 +155,67 @@ pub struct StressReport {
     pub set_lookup_total_ns: u64,
     pub set_remove_total_ns: u64,
 
+    pub was_dry_run: bool,
     pub ascii_summary: String,
 }
 
 // ── POST /api/stress-test ────────────────────────────────────────────────────
 
+#[tracing::instrument(
+    skip_all,
+    fields(
+        request_id = %request_id,
+        concurrency = params.concurrency,
+        ops_per_user = params.ops_per_user,
+        dry_run = params.dry_run,
+        total_ops = tracing::field::Empty,
+        elapsed_ms = tracing::field::Empty,
+    )
+)]
+#[utoipa::path(
+    post,
+    path = "/api/stress-test",
+    tag = "stress",
+    request_body = StressParams,
+    responses(
+        (status = 200, description = "`report`: StressReport", body = Object),
+        (status = 400, description = "Invalid access pattern, or no products to test against", body = ErrorResponse),
+        (status = 408, description = "Ran past `BENCHMARK_TIMEOUT_SECS`", body = ErrorResponse),
+    )
+)]
 pub async fn run_stress_test(
     State(state): State<AppState>,
+    request_id: RequestId,
     Json(params): Json<StressParams>,
 ) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
     let concurrency = params.concurrency.unwrap_or(20).clamp(1, 200);
     let ops_per_user = params.ops_per_user.unwrap_or(50).clamp(1, 1_000);
+    let dry_run = params.dry_run.unwrap_or(false);
+    let access_pattern = params.access_pattern;
+    let own_creates_only = params.delete_own_creates_only;
+    if let AccessPattern::Zipf { exponent } = access_pattern {
+        if !exponent.is_finite() || exponent <= 0.0 {
+            return Err(AppError::BadRequest(
+                "access_pattern.exponent must be > 0".to_string(),
+            ));
+        }
+    }
 
-    // Optional pre-seed
-    if let Some(n) = params.seed_count {
+    // Optional pre-seed (never in dry-run mode — nothing may touch the DB)
+    if let Some(n) = params.seed_count.filter(|_| !dry_run) {
         let n = n.min(10_000);
-        info!("Stress test: seeding {} products before run...", n);
+        info!(request_id = %request_id, "Stress test: seeding {} products before run...", n);
         let products = seed::seed_products(&state.db, n).await?;
         state.sets.write().await.sync_from_db(&products);
     }
 
-    let product_count_before = db::count_products(&state.db).await?;
+    let product_count_before = if dry_run {
+        state.sets.read().await.hash_set.len() as i64
+    } else {
+        db::count_products(&state.db).await?
+    };
 
-    if product_count_before == 0 {
+    if product_count_before == 0 && !dry_run {
         return Ok((
             StatusCode::BAD_REQUEST,
             Json(serde_json::json!({
@@ -91,9 +225,11 @@ pub async fn run_stress_test(
     }
 
     info!(
+        request_id = %request_id,
         concurrency,
         ops_per_user,
         product_count_before,
+        dry_run,
         "Starting stress test"
     );
 
@@ -102,6 +238,7 @@ pub async fn run_stress_test(
     let creates = Arc::new(AtomicU64::new(0));
     let updates = Arc::new(AtomicU64::new(0));
     let deletes = Arc::new(AtomicU64::new(0));
+    let true_deletes = Arc::new(AtomicU64::new(0));
     let errors = Arc::new(AtomicU64::new(0));
     let set_insert_ns = Arc::new(AtomicU64::new(0));
     let set_lookup_ns = Arc::new(AtomicU64::new(0));
@@ -117,22 +254,42 @@ pub async fn run_stress_test(
     let created_during_run: Arc<tokio::sync::Mutex<Vec<uuid::Uuid>>> =
         Arc::new(tokio::sync::Mutex::new(Vec::new()));
 
-    // Grab a snapshot of product IDs from the DB for reads/updates/deletes
-    let existing_products = db::fetch_all_products_unbounded(&state.db).await?;
-    let existing_ids: Arc<Vec<uuid::Uuid>> =
-        Arc::new(existing_products.iter().map(|p| p.id).collect());
+    // Grab a snapshot of product IDs from the DB for reads/updates/deletes.
+    // Dry runs never look the IDs up, so synthetic ones are fine.
+    let existing_ids: Arc<Vec<uuid::Uuid>> = if dry_run {
+        Arc::new((0..DRY_RUN_ID_POOL).map(|_| uuid::Uuid::new_v4()).collect())
+    } else {
+        let existing_products = db::fetch_all_products_unbounded(&state.db).await?;
+        Arc::new(existing_products.iter().map(|p| p.id).collect())
+    };
+
+    // IDs are ordered oldest-first, so Zipf rank 1 is the earliest-created product
+    let zipf = match access_pattern {
+        AccessPattern::Uniform => None,
+        AccessPattern::Zipf { exponent } => Some(
+            Zipf::new(existing_ids.len() as u64, exponent)
+                .map_err(|e| AppError::BadRequest(format!("access_pattern: {}", e)))?,
+        ),
+    };
+
+    // Live names are unique, so created names carry a per-run part; otherwise a
+    // second run against the same database would collide on every create
+    let run_tag: Arc<str> = Uuid::new_v4().simple().to_string()[..8].into();
 
     let total_start = Instant::now();
     let mut join_set: JoinSet<()> = JoinSet::new();
 
     for user_id in 0..concurrency {
         let pool = state.db.clone();
+        let retry = state.db_retry.clone();
+        let cache = state.product_cache.clone();
         let sets = Arc::clone(&state.sets);
         let ids = Arc::clone(&existing_ids);
         let reads_c = Arc::clone(&reads);
         let creates_c = Arc::clone(&creates);
         let updates_c = Arc::clone(&updates);
         let deletes_c = Arc::clone(&deletes);
+        let true_deletes_c = Arc::clone(&true_deletes);
         let errors_c = Arc::clone(&errors);
         let set_ins_c = Arc::clone(&set_insert_ns);
         let set_lk_c = Arc::clone(&set_lookup_ns);
@@ -143,6 +300,7 @@ pub async fn run_stress_test(
         let ul = Arc::clone(&update_lats);
         let dl = Arc::clone(&delete_lats);
         let created_c = Arc::clone(&created_during_run);
+        let run_tag = Arc::clone(&run_tag);
 
         join_set.spawn(async move {
             // StdRng is Send + Sync — safe to use across .await points in spawned tasks
@@ -156,80 +314,118 @@ pub async fn run_stress_test(
                 let result: Result<(), anyhow::Error> = async {
                     if roll < 50 {
                         // READ
-                        if let Some(&id) = ids.choose(&mut rng) {
-                            let start = Instant::now();
-                            let prod = db::fetch_product_by_id(&pool, id).await?;
-                            let db_ns = start.elapsed().as_nanos() as u64;
+                        if let Some(id) = pick_id(&ids, zipf.as_ref(), &mut rng) {
+                            if dry_run {
+                                simulate_db_latency(&mut rng).await;
+                            } else {
+                                let prod = db::fetch_product_by_id(&pool, id).await?;
 
-                            // Time lookup across sets
-                            let lk_start = Instant::now();
-                            let _ = sets.read().await.hash_set.contains(&prod);
-                            set_lk_c.fetch_add(lk_start.elapsed().as_nanos() as u64, Ordering::Relaxed);
+                                // Time lookup across sets
+                                let lk_start = Instant::now();
+                                let _ = sets.read().await.hash_set.contains(&prod);
+                                set_lk_c.fetch_add(lk_start.elapsed().as_nanos() as u64, Ordering::Relaxed);
+                            }
 
                             reads_c.fetch_add(1, Ordering::Relaxed);
-                            let _ = db_ns; // already timed
                             rl.lock().await.push(op_start.elapsed().as_secs_f64() * 1000.0);
                         }
                     } else if roll < 75 {
                         // CREATE
-                        use crate::models::CreateProduct;
-                        let adj = ["Pro", "Elite", "Standard", "Ultra"][rng.gen_range(0..4)];
-                        let noun = ["Widget", "Gadget", "Tool", "Device"][rng.gen_range(0..4)];
-                        let payload = CreateProduct {
-                            name: format!("{} {} #{}", adj, noun, op_i + user_id * 1000),
-                            description: Some(format!("Stress test item #{}", op_i)),
-                            price_cents: rng.gen_range(100..10_000),
-                            quantity: rng.gen_range(0..100),
-                            category: ["Electronics", "Clothing", "Books"][rng.gen_range(0..3)].to_string(),
-                        };
+                        let created_id = if dry_run {
+                            simulate_db_latency(&mut rng).await;
+                            uuid::Uuid::new_v4()
+                        } else {
+                            use crate::models::{Category, CreateProduct};
+                            let adj = ["Pro", "Elite", "Standard", "Ultra"][rng.gen_range(0..4)];
+                            let noun = ["Widget", "Gadget", "Tool", "Device"][rng.gen_range(0..4)];
+                            let payload = CreateProduct {
+                                name: format!("{} {} #{}-{}", adj, noun, run_tag, op_i + user_id * 1000),
+                                description: Some(format!("Stress test item #{}", op_i)),
+                                price_cents: rng.gen_range(100..10_000),
+                                quantity: rng.gen_range(0..100),
+                                category: [Category::Electronics, Category::Clothing, Category::Books][rng.gen_range(0..3)],
+                            };
 
-                        let prod = db::insert_product(&pool, &payload).await?;
-                        created_c.lock().await.push(prod.id);
+                            let prod = db::retry_with_backoff(&retry, "insert_product", || db::insert_product(&pool, &payload)).await?;
 
-                        let ins_start = Instant::now();
-                        sets.write().await.insert_product(&prod);
-                        set_ins_c.fetch_add(ins_start.elapsed().as_nanos() as u64, Ordering::Relaxed);
+                            let ins_start = Instant::now();
+                            sets.write().await.insert_product(&prod);
+                            set_ins_c.fetch_add(ins_start.elapsed().as_nanos() as u64, Ordering::Relaxed);
+                            prod.id
+                        };
+                        created_c.lock().await.push(created_id);
 
                         creates_c.fetch_add(1, Ordering::Relaxed);
                         cl.lock().await.push(op_start.elapsed().as_secs_f64() * 1000.0);
                     } else if roll < 90 {
                         // UPDATE
-                        if let Some(&id) = ids.choose(&mut rng) {
-                            use crate::models::UpdateProduct;
-                            let payload = UpdateProduct {
-                                name: None,
-                                description: Some(format!("Updated by stress test (op {})", op_i)),
-                                price_cents: Some(rng.gen_range(100..10_000)),
-                                quantity: Some(rng.gen_range(0..200)),
-                                category: None,
+                        if let Some(id) = pick_id(&ids, zipf.as_ref(), &mut rng) {
+                            let updated = if dry_run {
+                                simulate_db_latency(&mut rng).await;
+                                true
+                            } else {
+                                use crate::models::UpdateProduct;
+                                let payload = UpdateProduct {
+                                    name: None,
+                                    description: Some(format!("Updated by stress test (op {})", op_i)),
+                                    price_cents: Some(rng.gen_range(100..10_000)),
+                                    quantity: Some(rng.gen_range(0..200)),
+                                    category: None,
+                                    expected_version: None,
+                                };
+                                if let Ok(prod) = db::update_product(&pool, id, &payload).await {
+                                    cache.invalidate([id]).await;
+                                    let rm_start = Instant::now();
+                                    let mut s = sets.write().await;
+                                    s.remove_product(id);
+                                    s.insert_product(&prod);
+                                    set_rm_c.fetch_add(rm_start.elapsed().as_nanos() as u64, Ordering::Relaxed);
+                                    true
+                                } else {
+                                    false
+                                }
                             };
-                            if let Ok(prod) = db::update_product(&pool, id, &payload).await {
-                                let rm_start = Instant::now();
-                                let mut s = sets.write().await;
-                                s.remove_product(id);
-                                s.insert_product(&prod);
-                                set_rm_c.fetch_add(rm_start.elapsed().as_nanos() as u64, Ordering::Relaxed);
+                            if updated {
                                 updates_c.fetch_add(1, Ordering::Relaxed);
                                 ul.lock().await.push(op_start.elapsed().as_secs_f64() * 1000.0);
                             }
                         }
                     } else {
-                        // DELETE — only products created during this run; skip if none yet
-                        let id_to_delete = {
+                        // DELETE — products created during this run are hard-deleted;
+                        // pre-existing ones (only when allowed) are soft-deleted
+                        let target = {
                             let mut created = created_c.lock().await;
-                            if created.is_empty() {
+                            let take_own = !created.is_empty() && (own_creates_only || rng.gen_bool(0.5));
+                            if take_own {
+                                // Leaves the tracker here so no other worker can pick it too
+                                let idx = rng.gen_range(0..created.len());
+                                Some((created.swap_remove(idx), true))
+                            } else if own_creates_only {
                                 None
                             } else {
-                                let idx = rng.gen_range(0..created.len());
-                                Some(created.swap_remove(idx))
+                                pick_id(&ids, zipf.as_ref(), &mut rng).map(|id| (id, false))
                             }
                         };
-                        if let Some(id) = id_to_delete {
-                            db::delete_product(&pool, id).await?;
-                            let rm_start = Instant::now();
-                            sets.write().await.remove_product(id);
-                            set_rm_c.fetch_add(rm_start.elapsed().as_nanos() as u64, Ordering::Relaxed);
+                        if let Some((id, own)) = target {
+                            if dry_run {
+                                simulate_db_latency(&mut rng).await;
+                            } else {
+                                if own {
+                                    // Rows created by the run are throwaway: remove them for good
+                                    // rather than leaving soft-deleted tombstones behind
+                                    db::hard_delete_product(&pool, id).await?;
+                                } else {
+                                    db::delete_product(&pool, id).await?;
+                                }
+                                cache.invalidate([id]).await;
+                                let rm_start = Instant::now();
+                                sets.write().await.remove_product(id);
+                                set_rm_c.fetch_add(rm_start.elapsed().as_nanos() as u64, Ordering::Relaxed);
+                            }
                             deletes_c.fetch_add(1, Ordering::Relaxed);
+                            if own {
+                                true_deletes_c.fetch_add(1, Ordering::Relaxed);
+                            }
                             dl.lock().await.push(op_start.elapsed().as_secs_f64() * 1000.0);
                         }
                     }
@@ -252,7 +448,11 @@ pub async fn run_stress_test(
     while (join_set.join_next().await).is_some() {}
 
     let total_elapsed = total_start.elapsed();
-    let product_count_after = db::count_products(&state.db).await?;
+    let product_count_after = if dry_run {
+        state.sets.read().await.hash_set.len() as i64
+    } else {
+        db::count_products(&state.db).await?
+    };
 
     // Compute latency stats
     let mut all_lats = latencies_ms.lock().await.clone();
@@ -264,6 +464,7 @@ pub async fn run_stress_test(
     let avg_lat = if n > 0 { all_lats.iter().sum::<f64>() / n as f64 } else { 0.0 };
     let p95_lat = all_lats.get((n as f64 * 0.95) as usize).copied().unwrap_or(0.0);
     let p99_lat = all_lats.get((n as f64 * 0.99) as usize).copied().unwrap_or(0.0);
+    let p999_lat = all_lats.get((n as f64 * 0.999) as usize).copied().unwrap_or(0.0);
 
     let avg_of = |v: &[f64]| -> f64 {
         if v.is_empty() { 0.0 } else { v.iter().sum::<f64>() / v.len() as f64 }
@@ -271,6 +472,7 @@ pub async fn run_stress_test(
 
     let total_ops = concurrency * ops_per_user;
     let elapsed_ms = total_elapsed.as_secs_f64() * 1000.0;
+    tracing::Span::current().record("total_ops", total_ops).record("elapsed_ms", elapsed_ms);
     let ops_per_second = total_ops as f64 / total_elapsed.as_secs_f64();
 
     let r_lats = read_lats.lock().await;
@@ -293,6 +495,7 @@ pub async fn run_stress_test(
         creates.load(Ordering::Relaxed),
         updates.load(Ordering::Relaxed),
         deletes.load(Ordering::Relaxed),
+        true_deletes.load(Ordering::Relaxed),
         errors.load(Ordering::Relaxed),
     );
 
@@ -300,6 +503,8 @@ pub async fn run_stress_test(
         concurrency,
         ops_per_user,
         total_ops,
+        access_pattern,
+        delete_own_creates_only: own_creates_only,
         product_count_before,
         product_count_after,
         total_elapsed_ms: elapsed_ms,
@@ -308,12 +513,15 @@ pub async fn run_stress_test(
         creates: creates.load(Ordering::Relaxed),
         updates: updates.load(Ordering::Relaxed),
         deletes: deletes.load(Ordering::Relaxed),
+        true_deletes: true_deletes.load(Ordering::Relaxed),
         errors: errors.load(Ordering::Relaxed),
         min_latency_ms: min_lat,
         max_latency_ms: max_lat,
         avg_latency_ms: avg_lat,
         p95_latency_ms: p95_lat,
         p99_latency_ms: p99_lat,
+        p999_latency_ms: p999_lat,
+        latency_histogram: latency_histogram(&all_lats),
         read_avg_ms: avg_of(&r_lats),
         create_avg_ms: avg_of(&c_lats),
         update_avg_ms: avg_of(&u_lats),
@@ -321,26 +529,111 @@ pub async fn run_stress_test(
         set_insert_total_ns: set_insert_ns.load(Ordering::Relaxed),
         set_lookup_total_ns: set_lookup_ns.load(Ordering::Relaxed),
         set_remove_total_ns: set_remove_ns.load(Ordering::Relaxed),
+        was_dry_run: dry_run,
         ascii_summary: ascii.clone(),
     };
 
+    // Dry runs never touch the database, so only real runs enter the history
+    let run_id = if dry_run {
+        None
+    } else {
+        let report_json = serde_json::to_value(&report).map_err(anyhow::Error::from)?;
+        Some(db::insert_stress_run(&state.db, &report_json).await?)
+    };
+
     info!(
+        request_id = %request_id,
+        run_id = ?run_id,
         total_ops,
         ops_per_second = %format!("{:.1}", ops_per_second),
         avg_ms = %format!("{:.2}", avg_lat),
         p95_ms = %format!("{:.2}", p95_lat),
+        dry_run,
         "Stress test complete"
     );
 
     Ok((
         StatusCode::OK,
         Json(serde_json::json!({
+            "run_id": run_id,
             "report": report,
             "ascii_summary": ascii,
         })),
     ))
 }
 
+// ── GET /api/stress-test/history ─────────────────────────────────────────────
+
+#[utoipa::path(
+    get,
+    path = "/api/stress-test/history",
+    tag = "stress",
+    params(StressHistoryParams),
+    responses(
+        (status = 200, description = "`data`: [StressRunSummary], newest first", body = Object),
+    )
+)]
+pub async fn stress_history(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    Query(params): Query<StressHistoryParams>,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    let limit = params.limit.unwrap_or(10).clamp(1, 200);
+    let runs = db::fetch_stress_history(&state.db, limit).await?;
+
+    info!(request_id = %request_id, count = runs.len(), "Listed stress test history");
+
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "data": runs,
+            "count": runs.len(),
+        })),
+    ))
+}
+
+// ── GET /api/stress-test/history/:id ─────────────────────────────────────────
+
+#[utoipa::path(
+    get,
+    path = "/api/stress-test/history/{id}",
+    tag = "stress",
+    params(("id" = Uuid, Path, description = "Stress test run id")),
+    responses(
+        (status = 200, description = "`report`: the stored StressReport", body = Object),
+        (status = 404, description = "No stored run with this id", body = ErrorResponse),
+    )
+)]
+pub async fn stress_history_run(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    Path(id): Path<Uuid>,
+) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+    let report = db::fetch_stress_run(&state.db, id).await?;
+
+    info!(request_id = %request_id, id = %id, "Fetched stress test run");
+
+    Ok((StatusCode::OK, Json(serde_json::json!({ "report": report }))))
+}
+
+/// Number of synthetic product IDs handed to workers in dry-run mode.
+const DRY_RUN_ID_POOL: usize = 1_000;
+
+/// Picks an ID uniformly, or by Zipf rank when a distribution is given.
+fn pick_id(ids: &[Uuid], zipf: Option<&Zipf<f64>>, rng: &mut StdRng) -> Option<Uuid> {
+    match zipf {
+        None => ids.choose(rng).copied(),
+        // Zipf ranks are 1-based, sampled as whole-numbered floats
+        Some(dist) => ids.get(dist.sample(rng) as usize - 1).copied(),
+    }
+}
+
+/// Stand-in for a DB round trip in dry-run mode.
+async fn simulate_db_latency(rng: &mut StdRng) {
+    tokio::time::sleep(Duration::from_micros(rng.gen_range(100..1000))).await;
+}
+
+#[allow(clippy::too_many_arguments)]
 fn build_stress_ascii(
     concurrency: usize,
     ops_per_user: usize,
@@ -356,6 +649,7 @@ fn build_stress_ascii(
     creates: u64,
     updates: u64,
     deletes: u64,
+    true_deletes: u64,
     errors: u64,
 ) -> String {
     let w = 62;
@@ -388,9 +682,242 @@ fn build_stress_ascii(
         reads, creates, updates, deletes
     ));
     s.push_str(&format!(
-        "║  Errors: {:<4}                                             ║\n",
-        errors
+        "║{:<width$}║\n",
+        format!("  Errors: {:<8} True deletes: {}", errors, true_deletes),
+        width = w
     ));
     s.push_str(&format!("╚{}╝\n", divider));
     s
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::offline_state;
+    use crate::sets::SetSizes;
+
+    fn dry_params(concurrency: usize, ops_per_user: usize) -> StressParams {
+        StressParams {
+            concurrency: Some(concurrency),
+            ops_per_user: Some(ops_per_user),
+            seed_count: Some(100),
+            dry_run: Some(true),
+            access_pattern: AccessPattern::Uniform,
+            delete_own_creates_only: true,
+        }
+    }
+
+    #[tokio::test]
+    async fn dry_run_completes_without_database() {
+        let state = offline_state();
+        let (status, Json(body)) = run_stress_test(State(state.clone()), RequestId::new(), Json(dry_params(4, 25)))
+            .await
+            .unwrap();
+
+        assert_eq!(status, StatusCode::OK);
+        let report = &body["report"];
+        assert_eq!(report["was_dry_run"], true);
+        assert!(body["run_id"].is_null(), "dry runs are not stored");
+        assert_eq!(report["total_ops"], 100);
+        assert_eq!(report["errors"], 0);
+        assert_eq!(state.sets.read().await.sizes(), SetSizes::uniform(0, None), "dry run must not mutate sets");
+    }
+
+    #[tokio::test]
+    async fn dry_run_counts_every_completed_op() {
+        let (_, Json(body)) = run_stress_test(State(offline_state()), RequestId::new(), Json(dry_params(2, 50)))
+            .await
+            .unwrap();
+
+        let report = &body["report"];
+        let counted: u64 = ["reads", "creates", "updates", "deletes"]
+            .iter()
+            .map(|k| report[*k].as_u64().unwrap())
+            .sum();
+        // Deletes are skipped when nothing has been created yet, so counted <= total.
+        assert!(counted > 0 && counted <= 100, "counted {} ops", counted);
+        assert!(report["avg_latency_ms"].as_f64().unwrap() > 0.0);
+
+        let histogram = report["latency_histogram"].as_array().unwrap();
+        assert_eq!(histogram.len(), LATENCY_BUCKETS_MS.len());
+        assert_eq!(histogram.last().unwrap()["le_ms"], "+Inf");
+        // Skipped deletes are timed too, so every op lands in a bucket
+        assert_eq!(histogram.last().unwrap()["cumulative_count"], report["total_ops"]);
+    }
+
+    #[test]
+    fn histogram_buckets_are_inclusive_and_cumulative() {
+        let latencies = [0.05, 0.1, 0.3, 1.0, 1.5, 7.0, 7.0, 999.0, 1000.0, 4_000.0];
+        let histogram = latency_histogram(&latencies);
+
+        let counts: Vec<u64> = histogram.iter().map(|b| b.count).collect();
+        assert_eq!(counts, [2, 1, 1, 1, 0, 2, 0, 0, 0, 0, 0, 2, 1]);
+        let last = histogram.last().unwrap();
+        assert_eq!(last.cumulative_count, latencies.len() as u64);
+        assert!(histogram.windows(2).all(|w| w[1].cumulative_count == w[0].cumulative_count + w[1].count));
+        assert_eq!(serde_json::to_value(&histogram[3]).unwrap(), serde_json::json!({ "le_ms": 2.0, "count": 1, "cumulative_count": 5 }));
+
+        let empty = latency_histogram(&[]);
+        assert!(empty.iter().all(|b| b.count == 0 && b.cumulative_count == 0));
+    }
+
+    #[test]
+    fn access_pattern_deserializes_tagged() {
+        let p: StressParams =
+            serde_json::from_str(r#"{"access_pattern": {"type": "zipf", "exponent": 1.1}}"#).unwrap();
+        assert!(matches!(p.access_pattern, AccessPattern::Zipf { exponent } if exponent == 1.1));
+
+        let p: StressParams = serde_json::from_str("{}").unwrap();
+        assert!(matches!(p.access_pattern, AccessPattern::Uniform));
+        assert!(p.delete_own_creates_only);
+    }
+
+    #[tokio::test]
+    async fn own_creates_only_makes_every_delete_a_true_delete() {
+        let (_, Json(body)) = run_stress_test(State(offline_state()), RequestId::new(), Json(dry_params(4, 50)))
+            .await
+            .unwrap();
+        let report = &body["report"];
+        assert_eq!(report["deletes"], report["true_deletes"]);
+        assert!(report["true_deletes"].as_u64().unwrap() <= report["creates"].as_u64().unwrap());
+        assert!(body["ascii_summary"].as_str().unwrap().contains("True deletes:"));
+    }
+
+    fn live_params(delete_own_creates_only: bool) -> StressParams {
+        StressParams {
+            concurrency: Some(8),
+            ops_per_user: Some(40),
+            seed_count: Some(50),
+            dry_run: Some(false),
+            access_pattern: AccessPattern::Uniform,
+            delete_own_creates_only,
+        }
+    }
+
+    fn state_for(pool: sqlx::PgPool) -> AppState {
+        AppState { db: pool, ..offline_state() }
+    }
+
+    fn count(report: &serde_json::Value, key: &str) -> i64 {
+        report[key].as_i64().unwrap()
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn product_count_balances_creates_and_true_deletes(pool: sqlx::PgPool) {
+        let state = state_for(pool);
+        let (_, Json(body)) = run_stress_test(State(state.clone()), RequestId::new(), Json(live_params(true)))
+            .await
+            .unwrap();
+        let report = &body["report"];
+
+        assert_eq!(count(report, "errors"), 0);
+        assert!(count(report, "true_deletes") > 0, "run too short to delete anything");
+        assert_eq!(
+            count(report, "product_count_after"),
+            count(report, "product_count_before") + count(report, "creates") - count(report, "true_deletes"),
+        );
+        // Seeded products are untouched
+        assert_eq!(crate::db::fetch_deleted_products(&state.db).await.unwrap().len(), 0);
+        assert_eq!(state.sets.read().await.hash_set.len() as i64, count(report, "product_count_after"));
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn repeated_live_runs_do_not_collide_on_created_names(pool: sqlx::PgPool) {
+        let state = state_for(pool);
+        for run in 0..2 {
+            let (_, Json(body)) = run_stress_test(State(state.clone()), RequestId::new(), Json(live_params(true)))
+                .await
+                .unwrap();
+            let report = &body["report"];
+            assert!(count(report, "creates") > count(report, "true_deletes"), "run {} kept none of its creates", run);
+            assert_eq!(count(report, "errors"), 0, "run {}", run);
+        }
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn live_runs_are_stored_in_the_history(pool: sqlx::PgPool) {
+        let state = state_for(pool);
+        let mut ids = vec![];
+        for _ in 0..2 {
+            let (_, Json(body)) = run_stress_test(State(state.clone()), RequestId::new(), Json(live_params(true)))
+                .await
+                .unwrap();
+            ids.push((body["run_id"].as_str().unwrap().parse::<Uuid>().unwrap(), body["report"].clone()));
+        }
+
+        let (_, Json(body)) = stress_history(State(state.clone()), RequestId::new(), Query(StressHistoryParams::default()))
+            .await
+            .unwrap();
+        assert_eq!(body["count"], 2);
+        let newest = &body["data"][0];
+        let (id, report) = &ids[1];
+        assert_eq!(newest["id"].as_str(), Some(id.to_string().as_str()));
+        assert_eq!(newest["total_ops"], report["total_ops"]);
+        assert_eq!(newest["error_count"], report["errors"]);
+        assert_eq!(newest["concurrency"], 8);
+        // JSONB keeps numbers as `numeric`, so floats may come back off by an ulp
+        let p95 = newest["p95_latency_ms"].as_f64().unwrap();
+        assert!((p95 - report["p95_latency_ms"].as_f64().unwrap()).abs() < 1e-9);
+
+        let (_, Json(body)) = stress_history_run(State(state.clone()), RequestId::new(), Path(ids[0].0)).await.unwrap();
+        for key in ["total_ops", "reads", "creates", "updates", "deletes", "errors", "ascii_summary"] {
+            assert_eq!(body["report"][key], ids[0].1[key], "{}", key);
+        }
+
+        let missing = stress_history_run(State(state), RequestId::new(), Path(Uuid::new_v4())).await;
+        assert!(matches!(missing.unwrap_err(), AppError::NotFound(_)));
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn deleting_existing_products_soft_deletes_them(pool: sqlx::PgPool) {
+        let state = state_for(pool);
+        let (_, Json(body)) = run_stress_test(State(state.clone()), RequestId::new(), Json(live_params(false)))
+            .await
+            .unwrap();
+        let report = &body["report"];
+
+        let soft = count(report, "deletes") - count(report, "true_deletes");
+        assert!(soft >= 0);
+        assert_eq!(
+            crate::db::fetch_deleted_products(&state.db).await.unwrap().len() as i64,
+            soft,
+            "only pre-existing products become tombstones"
+        );
+        assert_eq!(
+            count(report, "product_count_after"),
+            count(report, "product_count_before") + count(report, "creates") - count(report, "deletes"),
+        );
+    }
+
+    #[test]
+    fn zipf_pick_favours_early_ids() {
+        let ids: Vec<Uuid> = (0..200).map(|_| Uuid::new_v4()).collect();
+        let dist = Zipf::new(ids.len() as u64, 1.0).unwrap();
+        let mut rng = StdRng::seed_from_u64(1);
+
+        let mut early = 0;
+        let mut late = 0;
+        for _ in 0..10_000 {
+            let id = pick_id(&ids, Some(&dist), &mut rng).unwrap();
+            let pos = ids.iter().position(|x| *x == id).unwrap();
+            if pos < 20 {
+                early += 1;
+            } else if pos >= 180 {
+                late += 1;
+            }
+        }
+        assert!(early > late * 5, "early {} vs late {}", early, late);
+    }
+
+    #[tokio::test]
+    async fn zipf_rejects_non_positive_exponent() {
+        let mut params = dry_params(1, 1);
+        params.access_pattern = AccessPattern::Zipf { exponent: 0.0 };
+        let err = run_stress_test(State(offline_state()), RequestId::new(), Json(params)).await.unwrap_err();
+        assert!(matches!(err, AppError::BadRequest(_)));
+    }
+}
@@ -0,0 +1,215 @@
+use std::{
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use axum::{
+    body::Body,
+    http::{header::CONTENT_TYPE, HeaderName, HeaderValue, Method, Request, StatusCode},
+    response::{IntoResponse, Response},
+};
+use futures_util::future::BoxFuture;
+use sqlx::PgPool;
+use tokio::task::JoinHandle;
+use tower::{Layer, Service};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::{db, error::AppError, models::CachedResponse};
+
+pub static IDEMPOTENCY_KEY: HeaderName = HeaderName::from_static("idempotency-key");
+
+/// Set on responses replayed from `idempotency_cache`.
+pub static IDEMPOTENT_REPLAYED: HeaderName = HeaderName::from_static("idempotent-replayed");
+
+/// How long a stored response is replayed for.
+pub const KEY_TTL_HOURS: i32 = 24;
+
+/// Replays the stored response for POST/PUT requests that repeat an
+/// `Idempotency-Key: <uuid>` header, without calling the handler again.
+///
+/// Only successful JSON responses are stored, so a request that failed can be
+/// fixed and retried under the same key. A key is tied to the method and path
+/// it was first used with; reusing it elsewhere is a 409. Two requests racing
+/// with a fresh key may both run. Requests without the header pass straight
+/// through, as do all other methods.
+#[derive(Debug, Clone)]
+pub struct IdempotencyLayer {
+    pool: PgPool,
+}
+
+impl IdempotencyLayer {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+impl<S> Layer<S> for IdempotencyLayer {
+    type Service = IdempotencyService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        IdempotencyService { inner, pool: self.pool.clone() }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct IdempotencyService<S> {
+    inner: S,
+    pool: PgPool,
+}
+
+impl<S> Service<Request<Body>> for IdempotencyService<S>
+where
+    S: Service<Request<Body>, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Response, S::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        // `poll_ready` was called on `self.inner`, so that is the one to use
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        if !matches!(*req.method(), Method::POST | Method::PUT) {
+            return Box::pin(inner.call(req));
+        }
+        let Some(header) = req.headers().get(&IDEMPOTENCY_KEY) else {
+            return Box::pin(inner.call(req));
+        };
+        let Some(key) = parse_key(header) else {
+            let err = AppError::BadRequest("Idempotency-Key must be a UUID".to_string());
+            return Box::pin(async move { Ok(err.into_response()) });
+        };
+
+        let pool = self.pool.clone();
+        Box::pin(async move {
+            let request_path = format!("{} {}", req.method(), req.uri().path());
+            match db::fetch_idempotent_response(&pool, &key, KEY_TTL_HOURS).await {
+                Ok(Some(cached)) if cached.request_path == request_path => return Ok(replay(cached)),
+                Ok(Some(cached)) => {
+                    let msg = format!("Idempotency-Key {} was already used for {}", key, cached.request_path);
+                    return Ok(AppError::Conflict(msg).into_response());
+                }
+                Ok(None) => {}
+                Err(e) => return Ok(e.into_response()),
+            }
+
+            let response = inner.call(req).await?;
+            if !response.status().is_success() || !is_json(&response) {
+                return Ok(response);
+            }
+
+            let (parts, body) = response.into_parts();
+            let bytes = match axum::body::to_bytes(body, usize::MAX).await {
+                Ok(bytes) => bytes,
+                Err(e) => return Ok(AppError::Internal(anyhow::Error::new(e)).into_response()),
+            };
+            if let Ok(text) = std::str::from_utf8(&bytes) {
+                let cached = CachedResponse {
+                    request_path,
+                    status_code: i32::from(parts.status.as_u16()),
+                    response_body: text.to_string(),
+                };
+                // The handler has already run; failing to store only means a retry runs it again
+                if let Err(e) = db::store_idempotent_response(&pool, &key, &cached, KEY_TTL_HOURS).await {
+                    warn!(error = %e, key = %key, path = %cached.request_path, "Failed to store idempotent response");
+                }
+            }
+            Ok(Response::from_parts(parts, Body::from(bytes)))
+        })
+    }
+}
+
+/// The key in its canonical hyphenated form, so differently-cased spellings
+/// of the same UUID share a cache entry.
+fn parse_key(header: &HeaderValue) -> Option<String> {
+    let key = Uuid::parse_str(header.to_str().ok()?.trim()).ok()?;
+    Some(key.to_string())
+}
+
+fn is_json(response: &Response) -> bool {
+    response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.starts_with("application/json"))
+}
+
+fn replay(cached: CachedResponse) -> Response {
+    let status = u16::try_from(cached.status_code)
+        .ok()
+        .and_then(|code| StatusCode::from_u16(code).ok())
+        .unwrap_or(StatusCode::OK);
+    (
+        status,
+        [
+            (CONTENT_TYPE, HeaderValue::from_static("application/json")),
+            (IDEMPOTENT_REPLAYED.clone(), HeaderValue::from_static("true")),
+        ],
+        cached.response_body,
+    )
+        .into_response()
+}
+
+/// Every `every`, deletes stored responses older than [`KEY_TTL_HOURS`].
+/// Runs until the returned handle is aborted or the runtime shuts down.
+pub fn spawn_expiry_task(pool: PgPool, every: Duration) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(every);
+        loop {
+            ticker.tick().await;
+            match db::purge_expired_idempotency_keys(&pool, KEY_TTL_HOURS).await {
+                Ok(0) => {}
+                Ok(purged) => info!(purged, "Purged expired idempotency keys"),
+                Err(e) => warn!(error = %e, "Failed to purge expired idempotency keys"),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::offline_state;
+    use axum::{routing::post, Router};
+    use tower::ServiceExt;
+
+    fn app(pool: PgPool) -> Router {
+        Router::new()
+            .route("/echo", post(|body: String| async move { body }))
+            .layer(IdempotencyLayer::new(pool))
+    }
+
+    fn request(key: Option<&str>) -> Request<Body> {
+        let mut builder = Request::builder().method(Method::POST).uri("/echo");
+        if let Some(key) = key {
+            builder = builder.header(&IDEMPOTENCY_KEY, key);
+        }
+        builder.body(Body::from("hello")).unwrap()
+    }
+
+    #[tokio::test]
+    async fn requests_without_a_key_skip_the_cache() {
+        // The offline pool can't reach a database, so any lookup would fail
+        let response = app(offline_state().db).oneshot(request(None)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(&body[..], b"hello");
+    }
+
+    #[tokio::test]
+    async fn keys_must_be_uuids() {
+        let response = app(offline_state().db).oneshot(request(Some("retry-1"))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let key = Uuid::new_v4();
+        let upper = HeaderValue::from_str(&key.to_string().to_uppercase()).unwrap();
+        assert_eq!(parse_key(&upper), Some(key.to_string()));
+    }
+}
@@ -1,26 +1,42 @@
-use std::sync::Arc;
+use std::{
+    sync::{atomic::AtomicU64, Arc},
+    time::Duration,
+};
 
 use axum::{
+    extract::DefaultBodyLimit,
     routing::{delete, get, post},
-    Router,
+    Json, Router,
 };
-use sqlx::postgres::PgPoolOptions;
 use tokio::sync::RwLock;
-use tower_http::{cors::CorsLayer, trace::TraceLayer};
+use tower_http::{compression::CompressionLayer, cors::CorsLayer, trace::TraceLayer};
 use tracing::info;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
+#[cfg(feature = "audit")]
+mod audit;
 mod config;
 mod db;
 mod error;
+mod extract;
 mod handlers;
+mod idempotency;
 mod metrics;
+mod middleware;
 mod models;
+mod openapi;
 mod seed;
 mod sets;
+mod shutdown;
+#[cfg(feature = "opentelemetry")]
+mod telemetry;
+mod util;
 
 use crate::config::Config;
-use crate::metrics::MetricsStore;
+use crate::metrics::{MetricSubscribers, MetricsStore};
+use crate::middleware::RequestIdLayer;
 use crate::sets::SetManager;
+use crate::shutdown::ShutdownOptions;
 
 /// Shared application state — cheap to clone (all heap behind Arc).
 #[derive(Clone)]
@@ -28,49 +44,95 @@ pub struct AppState {
     pub db: sqlx::PgPool,
     pub sets: Arc<RwLock<SetManager>>,
     pub metrics: Arc<RwLock<MetricsStore>>,
+    pub config: Arc<Config>,
+    /// Product updates that left the in-memory sets untouched because nothing changed
+    pub set_cache_hits: Arc<AtomicU64>,
+    /// `GET /api/ws/metrics` clients; the same list `metrics` broadcasts to
+    pub metric_subscribers: MetricSubscribers,
+    /// Applied to product listing and inserts when the pool is exhausted
+    pub db_retry: db::RetryPolicy,
+    /// Recently fetched products for `GET /api/products/:id`
+    pub product_cache: db::ProductCache,
+    /// Five-second cache of `GET /api/products/search/suggest` results
+    pub name_suggestions: db::NameSuggestionCache,
 }
 
+/// How often the retention task drops metrics older than `metrics_retention_secs`
+const METRICS_PURGE_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// How often stored idempotent responses past their 24 hours are deleted
+const IDEMPOTENCY_PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     // Load .env if present (ignored in production where env vars are injected)
     dotenv::dotenv().ok();
 
-    // Structured logging
-    tracing_subscriber::fmt()
-        .with_env_filter(
+    let config = Config::from_env()?;
+
+    #[cfg(feature = "opentelemetry")]
+    let tracer_provider = config.otlp_endpoint.as_deref().map(telemetry::init_tracer_provider).transpose()?;
+
+    // Structured logging, plus span export when a collector is configured
+    let subscriber = tracing_subscriber::registry()
+        .with(
             tracing_subscriber::EnvFilter::try_from_default_env()
                 .unwrap_or_else(|_| "info,inventory_service=debug".parse().unwrap()),
         )
-        .with_target(false)
-        .compact()
-        .init();
+        .with(tracing_subscriber::fmt::layer().with_target(false).compact());
+    #[cfg(feature = "opentelemetry")]
+    let subscriber = subscriber.with(tracer_provider.as_ref().map(telemetry::layer));
+    subscriber.init();
 
-    let config = Config::from_env()?;
+    #[cfg(feature = "opentelemetry")]
+    if let Some(endpoint) = &config.otlp_endpoint {
+        info!(endpoint = %endpoint, "Exporting trace spans over OTLP");
+    }
+    #[cfg(not(feature = "opentelemetry"))]
+    if config.otlp_endpoint.is_some() {
+        tracing::warn!("OTLP_ENDPOINT is set but this build lacks the `opentelemetry` feature; spans are not exported");
+    }
 
     info!("╔══════════════════════════════════════╗");
     info!("║  Inventory Service  — Rust + Axum    ║");
     info!("║  HashSet · LinkedHashSet · BTreeSet  ║");
     info!("╚══════════════════════════════════════╝");
 
-    // DB pool (up to 20 connections so stress tests can run concurrently)
+    // DB pool (20 connections by default so stress tests can run concurrently)
     info!("Connecting to PostgreSQL...");
-    let pool = PgPoolOptions::new()
-        .max_connections(20)
-        .connect(&config.database_url)
-        .await?;
-    info!("Database connection pool established.");
+    let pool = config.pool_options().connect(&config.database_url).await?;
+    info!(
+        max_connections = config.db_max_connections,
+        min_connections = config.db_min_connections,
+        "Database connection pool established."
+    );
 
     // Run pending migrations
     info!("Running migrations...");
     sqlx::migrate!("./migrations").run(&pool).await?;
     info!("Migrations complete.");
 
+    let metrics_store = MetricsStore::with_capacity(config.metrics_capacity);
+    let metric_subscribers = metrics_store.subscribers();
+    let metrics = Arc::new(RwLock::new(metrics_store));
     let state = AppState {
         db: pool,
-        sets: Arc::new(RwLock::new(SetManager::new())),
-        metrics: Arc::new(RwLock::new(MetricsStore::new())),
+        sets: Arc::new(RwLock::new(SetManager::with_fx_set(config.enable_fx_set).with_name_trie(config.enable_trie_index))),
+        metric_subscribers,
+        metrics: metrics.clone(),
+        config: Arc::new(config.clone()),
+        set_cache_hits: Arc::new(AtomicU64::new(0)),
+        db_retry: db::RetryPolicy::from_config(&config).with_metrics(metrics.clone()),
+        product_cache: db::ProductCache::new(config.product_cache_size),
+        name_suggestions: db::name_suggestion_cache(),
     };
 
+    metrics::spawn_retention_task(metrics.clone(), config.metrics_retention_secs, METRICS_PURGE_INTERVAL);
+    if config.metrics_persist_interval_secs > 0 {
+        let every = Duration::from_secs(config.metrics_persist_interval_secs);
+        metrics::spawn_persistence_task(metrics.clone(), state.db.clone(), every);
+    }
+    idempotency::spawn_expiry_task(state.db.clone(), IDEMPOTENCY_PURGE_INTERVAL);
     let app = build_router(state);
 
     let addr = format!("{}:{}", config.host, config.port);
@@ -78,64 +140,574 @@ async fn main() -> anyhow::Result<()> {
     info!("Quick-start: POST http://{}/api/seed?count=5000  →  then POST http://{}/api/benchmark/run", addr, addr);
 
     let listener = tokio::net::TcpListener::bind(&addr).await?;
-    axum::serve(listener, app).await?;
+    let options = ShutdownOptions {
+        timeout: Duration::from_secs(config.shutdown_timeout_secs),
+        metrics_dump_path: config.metrics_dump_path.map(Into::into),
+    };
+    shutdown::serve(listener, app, metrics, options, shutdown::signal_received()?).await?;
+
+    #[cfg(feature = "opentelemetry")]
+    if let Some(provider) = tracer_provider {
+        telemetry::shutdown_tracer(provider);
+    }
 
     Ok(())
 }
 
 fn build_router(state: AppState) -> Router {
-    Router::new()
+    // Generated once; every request gets a copy of the same document
+    let spec = Json(openapi::spec());
+
+    // Replays retried POST/PUT calls that carry an `Idempotency-Key`
+    let idempotent = idempotency::IdempotencyLayer::new(state.db.clone());
+
+    let router = Router::new()
         // ── Health ──────────────────────────────────────────────────────────
         .route("/health", get(handlers::health))
+        .route("/api/health/detailed", get(handlers::health_detailed))
+
+        // ── API docs ────────────────────────────────────────────────────────
+        .route("/api/openapi.json", get(move || async move { spec }))
+        .route("/api/docs", get(openapi::docs))
 
         // ── Products CRUD ───────────────────────────────────────────────────
         .route(
             "/api/products",
-            get(handlers::products::list_products).post(handlers::products::create_product),
+            get(handlers::products::list_products)
+                .post(handlers::products::create_product)
+                .layer(idempotent.clone()),
         )
+        .route("/api/products/search", get(handlers::products::search_products))
+        .route("/api/products/search/suggest", get(handlers::products::suggest_names))
+        .route("/api/products/autocomplete", get(handlers::products::autocomplete))
+        .route(
+            "/api/products/bulk",
+            post(handlers::products::bulk_create_products)
+                .delete(handlers::products::bulk_delete_products)
+                .layer(idempotent.clone()),
+        )
+        .route("/api/products/import/csv", post(handlers::products::import_products_csv))
+        .route("/api/products/export/csv", get(handlers::products::export_products_csv))
+        .route("/api/products/export/json", get(handlers::products::export_products_ndjson))
+        .route("/api/products/deleted", get(handlers::products::list_deleted_products))
+        .route("/api/products/duplicates", get(handlers::products::list_duplicates))
+        .route("/api/products/deduplicate", post(handlers::products::deduplicate_products))
+        .route("/api/products/stats", get(handlers::products::product_stats))
+        .route("/api/products/abc-analysis", get(handlers::products::abc_analysis))
+        .route("/api/products/price-distribution", get(handlers::products::price_distribution))
+        .route("/api/products/range", get(handlers::products::products_in_price_range))
+        .route("/api/products/by-category", get(handlers::products::products_by_category))
+        .route("/api/products/low-stock", get(handlers::products::low_stock_products))
         .route(
             "/api/products/:id",
             get(handlers::products::get_product)
                 .put(handlers::products::update_product)
-                .delete(handlers::products::delete_product),
+                .delete(handlers::products::delete_product)
+                .layer(idempotent.clone()),
+        )
+        .route(
+            "/api/products/:id/adjust-quantity",
+            post(handlers::products::adjust_quantity).layer(idempotent.clone()),
+        )
+        .route(
+            "/api/products/:id/restock",
+            post(handlers::products::restock_product).layer(idempotent.clone()),
+        )
+        .route(
+            "/api/products/:id/restock-history",
+            get(handlers::products::restock_history),
+        )
+        .route(
+            "/api/products/:id/restore",
+            post(handlers::products::restore_product),
+        )
+        .route(
+            "/api/products/:id/price-history",
+            get(handlers::products::price_history),
+        )
+        .route(
+            "/api/products/:id/related",
+            get(handlers::products::related_products),
+        )
+        .route(
+            "/api/products/:id/duplicate",
+            post(handlers::products::duplicate_product).layer(idempotent.clone()),
+        )
+        .route("/api/products/:id/tags", post(handlers::products::add_product_tag))
+        .route(
+            "/api/products/:id/tags/:tag",
+            delete(handlers::products::remove_product_tag),
         )
+        .route("/api/tags/:tag/products", get(handlers::products::products_by_tag))
 
         // ── Product Devolutions ─────────────────────────────────────────────
         .route(
             "/api/devolutions",
             get(handlers::devolutions::list_devolutions)
-                .post(handlers::devolutions::create_devolution),
+                .post(handlers::devolutions::create_devolution)
+                .layer(idempotent),
         )
+        .route("/api/devolutions/stats", get(handlers::devolutions::devolution_stats))
         .route(
             "/api/devolutions/:id",
             get(handlers::devolutions::get_devolution),
         )
+        .route(
+            "/api/devolutions/:id/approve",
+            post(handlers::devolutions::approve_devolution),
+        )
+        .route(
+            "/api/devolutions/:id/reject",
+            post(handlers::devolutions::reject_devolution),
+        )
+
+        // ── Reports ─────────────────────────────────────────────────────────
+        .route("/api/reports/inventory-value", get(handlers::reports::inventory_value))
+
+        // ── Stress test history ──────────────────────────────────────────────
+        .route("/api/stress-test/history", get(handlers::stress::stress_history))
+        .route("/api/stress-test/history/:id", get(handlers::stress::stress_history_run))
 
         // ── Seed / Reset ─────────────────────────────────────────────────────
         .route("/api/seed", post(handlers::benchmark::seed_data))
-        .route("/api/reset", delete(handlers::benchmark::reset_all))
+        .route("/api/seed/devolutions", post(handlers::benchmark::seed_devolutions))
 
         // ── Benchmark ───────────────────────────────────────────────────────
-        .route("/api/benchmark/run", post(handlers::benchmark::run_benchmark))
+        .route("/api/benchmark/range", post(handlers::benchmark::run_range_benchmark))
+        .route("/api/benchmark/range-delete", post(handlers::benchmark::run_range_delete_benchmark))
+        .route("/api/benchmark/clone-overhead", post(handlers::benchmark::run_clone_overhead_benchmark))
+        .route(
+            "/api/benchmark/concurrent",
+            post(handlers::benchmark::run_concurrent_benchmark),
+        )
+        .route(
+            "/api/benchmark/lock-contention",
+            post(handlers::benchmark::run_lock_contention_benchmark),
+        )
+        .route(
+            "/api/benchmark/serialization",
+            post(handlers::benchmark::run_serialization_benchmark),
+        )
         .route("/api/benchmark/report", get(handlers::benchmark::get_report))
+        .route("/api/benchmark/history", get(handlers::benchmark::benchmark_history))
+        .route("/api/benchmark/compare", get(handlers::benchmark::compare_benchmark_runs))
+        .route(
+            "/api/benchmark/history/:id",
+            get(handlers::benchmark::benchmark_history_run),
+        )
+        .route("/api/benchmark/sets", delete(handlers::benchmark::sets_reset))
         .route(
             "/api/benchmark/sets/status",
             get(handlers::benchmark::sets_status),
         )
+        .route(
+            "/api/benchmark/sets/search",
+            get(handlers::benchmark::sets_prefix_search),
+        )
+        .route(
+            "/api/benchmark/sets/consistency",
+            get(handlers::benchmark::sets_consistency),
+        )
+        .route(
+            "/api/benchmark/sets/top-n",
+            get(handlers::benchmark::sets_top_n),
+        )
+        .route(
+            "/api/benchmark/sets/partitions",
+            get(handlers::benchmark::sets_partitions),
+        )
+        .route("/api/benchmark/sets/stats", get(handlers::benchmark::sets_stats))
+        .route("/api/benchmark/sets/sync", post(handlers::benchmark::sets_sync))
+        .route("/api/benchmark/sets/diff", get(handlers::benchmark::sets_diff))
+        .route("/api/benchmark/sets/rebalance", post(handlers::benchmark::sets_rebalance))
+        .route("/api/benchmark/sets/batch-insert", post(handlers::benchmark::sets_batch_insert))
+        .route(
+            "/api/benchmark/sets/iteration-order-diff",
+            get(handlers::benchmark::sets_iteration_order_diff),
+        )
+        .route(
+            "/api/benchmark/sets/snapshot",
+            post(handlers::benchmark::sets_snapshot),
+        )
+        .route(
+            "/api/benchmark/sets/restore",
+            post(handlers::benchmark::sets_restore)
+                .layer(DefaultBodyLimit::max(handlers::benchmark::MAX_SNAPSHOT_BYTES)),
+        )
         .route(
             "/api/benchmark/export/csv",
             get(handlers::benchmark::export_csv),
         )
+        .route(
+            "/api/benchmark/export/influx",
+            get(handlers::benchmark::export_influx),
+        )
+        .route(
+            "/api/benchmark/export/msgpack",
+            get(handlers::benchmark::export_msgpack),
+        )
         .route(
             "/api/benchmark/export/json",
             get(handlers::benchmark::export_json),
         )
+        .route(
+            "/api/benchmark/schema",
+            get(handlers::benchmark::export_benchmark_json_schema),
+        )
 
-        // ── Stress test ─────────────────────────────────────────────────────
-        .route("/api/stress-test", post(handlers::stress::run_stress_test))
+        // ── Admin ───────────────────────────────────────────────────────────
+        .route("/api/admin/audit-log", get(handlers::admin::audit_log))
+        .route("/api/admin/config", get(handlers::admin::config))
+        .route("/api/admin/pool", get(handlers::admin::pool))
+        .route("/api/admin/cache/stats", get(handlers::admin::cache_stats))
+        .route("/api/admin/reset", delete(handlers::admin::reset_all))
+        .route("/api/admin/explain", post(handlers::admin::explain))
 
+        // ── Metrics store ───────────────────────────────────────────────────
+        .route("/api/metrics", delete(handlers::metrics::purge))
+        .route("/api/metrics/history", get(handlers::metrics::history))
+        .route("/api/ws/metrics", get(handlers::metrics::ws_metrics))
+        .route(
+            "/api/metrics/config",
+            get(handlers::metrics::get_config).put(handlers::metrics::update_config),
+        );
+
+    // ── Prometheus ──────────────────────────────────────────────────────────
+    #[cfg(feature = "prometheus")]
+    let router = router.route("/metrics", get(handlers::prometheus_metrics));
+
+    // ── Parquet export ──────────────────────────────────────────────────────
+    #[cfg(feature = "parquet")]
+    let router = router.route("/api/benchmark/export/parquet", get(handlers::benchmark::export_parquet));
+
+    // ── Timeouts ────────────────────────────────────────────────────────────
+    // Full benchmark runs and stress tests legitimately take minutes, so they
+    // get their own, longer limit
+    let router = middleware::with_timeout(router, Duration::from_secs(state.config.request_timeout_secs));
+    let long_running = Router::new()
+        .route("/api/benchmark/run", post(handlers::benchmark::run_benchmark))
+        .route("/api/stress-test", post(handlers::stress::run_stress_test));
+    let long_running = middleware::with_timeout(long_running, Duration::from_secs(state.config.benchmark_timeout_secs));
+    let router = router.merge(long_running);
+
+    // ── Audit log ───────────────────────────────────────────────────────────
+    // Inside RequestIdLayer so each entry can carry the request ID
+    #[cfg(feature = "audit")]
+    let router = router.layer(audit::AuditLayer::new(state.db.clone()));
+
+    // ── Compression ─────────────────────────────────────────────────────────
+    // gzip, brotli or deflate, whichever the client's Accept-Encoding prefers
+    let router = if state.config.disable_compression {
+        router
+    } else {
+        router.layer(CompressionLayer::new())
+    };
+
+    // ── Tracing ─────────────────────────────────────────────────────────────
+    // With `opentelemetry`, request spans join the caller's trace when it sends a `traceparent`
+    let trace = TraceLayer::new_for_http();
+    #[cfg(feature = "opentelemetry")]
+    let trace = trace.make_span_with(telemetry::make_request_span);
+
+    router
         // ── Middleware ──────────────────────────────────────────────────────
         .layer(CorsLayer::permissive())
-        .layer(TraceLayer::new_for_http())
+        .layer(trace)
+        .layer(RequestIdLayer)
         .with_state(state)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::offline_state;
+    use crate::middleware::X_REQUEST_ID;
+    use axum::{
+        body::Body,
+        http::{Method, Request, StatusCode},
+    };
+    use tower::ServiceExt;
+
+    async fn send(app: &Router, method: Method, uri: &str) -> (StatusCode, String) {
+        let request = Request::builder().method(method).uri(uri).body(Body::empty()).unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        let id = response
+            .headers()
+            .get(&X_REQUEST_ID)
+            .unwrap_or_else(|| panic!("{} has no X-Request-Id", uri))
+            .to_str()
+            .unwrap()
+            .to_string();
+        (response.status(), id)
+    }
+
+    mod ws {
+        use super::*;
+        use futures_util::StreamExt;
+        use tokio_tungstenite::{tungstenite, MaybeTlsStream, WebSocketStream};
+
+        type Client = WebSocketStream<MaybeTlsStream<tokio::net::TcpStream>>;
+
+        /// Serves `state` on a free local port and returns the stream URL.
+        async fn serve(state: AppState) -> String {
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let addr = listener.local_addr().unwrap();
+            tokio::spawn(async move { axum::serve(listener, build_router(state)).await.unwrap() });
+            format!("ws://{}/api/ws/metrics", addr)
+        }
+
+        async fn next_json(client: &mut Client) -> serde_json::Value {
+            let message = tokio::time::timeout(Duration::from_secs(10), client.next()).await.unwrap().unwrap().unwrap();
+            serde_json::from_str(message.to_text().unwrap()).unwrap()
+        }
+
+        #[tokio::test]
+        async fn clients_get_a_snapshot_then_each_new_entry() {
+            let state = offline_state();
+            state.metrics.write().await.record_raw("lookup", "HashSet", 500, 1);
+            let (mut client, _) = tokio_tungstenite::connect_async(serve(state.clone()).await).await.unwrap();
+
+            let snapshot = next_json(&mut client).await;
+            assert_eq!(snapshot["type"], "snapshot");
+            assert_eq!(snapshot["aggregated"][0]["operation"], "lookup");
+
+            state.metrics.write().await.record_raw("insert_all", "BTreeSet", 900, 3);
+            let entry = next_json(&mut client).await;
+            assert_eq!(entry["type"], "entry");
+            assert_eq!((entry["entry"]["operation"].as_str(), entry["entry"]["duration_ns"].as_u64()), (Some("insert_all"), Some(900)));
+
+            client.close(None).await.unwrap();
+            drop(client);
+            // The server drops its receiver once it sees the close; the next record prunes the sender
+            tokio::time::timeout(Duration::from_secs(10), async {
+                while !state.metric_subscribers.read().unwrap().iter().all(|tx| tx.is_closed()) {
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                }
+            })
+            .await
+            .unwrap();
+            state.metrics.write().await.record_raw("lookup", "HashSet", 1, 1);
+            assert!(state.metric_subscribers.read().unwrap().is_empty());
+        }
+
+        #[tokio::test]
+        async fn connections_past_the_limit_are_refused() {
+            let state = offline_state();
+            let receivers: Vec<_> = (0..handlers::metrics::MAX_WS_CLIENTS)
+                .map(|_| {
+                    let (tx, rx) = tokio::sync::mpsc::channel(1);
+                    state.metric_subscribers.write().unwrap().push(tx);
+                    rx
+                })
+                .collect();
+
+            let url = serve(state.clone()).await;
+            match tokio_tungstenite::connect_async(&url).await {
+                Err(tungstenite::Error::Http(response)) => assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE),
+                other => panic!("expected a 503, got {:?}", other.map(|(_, r)| r.status())),
+            }
+
+            // Disconnected clients no longer count
+            drop(receivers);
+            assert!(tokio_tungstenite::connect_async(&url).await.is_ok());
+        }
+
+        #[sqlx::test]
+        #[ignore = "requires DATABASE_URL"]
+        async fn running_a_benchmark_streams_its_metrics(pool: sqlx::PgPool) {
+            seed::seed_products(&pool, 20).await.unwrap();
+            let state = AppState { db: pool, ..offline_state() };
+            let (mut client, _) = tokio_tungstenite::connect_async(serve(state.clone()).await).await.unwrap();
+            assert_eq!(next_json(&mut client).await["type"], "snapshot");
+
+            let (status, _) = send(&build_router(state), Method::POST, "/api/benchmark/run").await;
+            assert_eq!(status, StatusCode::OK);
+            let entry = next_json(&mut client).await;
+            assert_eq!(entry["type"], "entry");
+            assert!(entry["entry"]["duration_ns"].is_u64());
+        }
+    }
+
+    #[tokio::test]
+    async fn every_response_carries_a_unique_request_id() {
+        let app = build_router(offline_state());
+        let cases = [
+            (Method::GET, "/health", StatusCode::OK),
+            (Method::GET, "/api/benchmark/schema", StatusCode::OK),
+            (Method::GET, "/api/metrics/config", StatusCode::OK),
+            // Offline pool: the handler runs and times out waiting for a connection
+            (Method::GET, "/api/products", StatusCode::SERVICE_UNAVAILABLE),
+            (Method::GET, "/api/products/not-a-uuid", StatusCode::BAD_REQUEST),
+            (Method::DELETE, "/health", StatusCode::METHOD_NOT_ALLOWED),
+            (Method::GET, "/no/such/route", StatusCode::NOT_FOUND),
+        ];
+
+        let mut seen = std::collections::HashSet::new();
+        for (method, uri, expected) in cases {
+            let (status, id) = send(&app, method, uri).await;
+            assert_eq!(status, expected, "{}", uri);
+            assert!(uuid::Uuid::parse_str(&id).is_ok(), "{} returned {:?}", uri, id);
+            assert!(seen.insert(id), "{} reused a request id", uri);
+        }
+    }
+
+    #[tokio::test]
+    async fn openapi_spec_lists_the_api() {
+        let app = build_router(offline_state());
+        let request = Request::builder().uri("/api/openapi.json").body(Body::empty()).unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let spec: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let paths = spec["paths"].as_object().unwrap();
+        assert!(paths.len() >= 10, "only {} paths", paths.len());
+        assert!(paths.contains_key("/api/products/{id}"));
+
+        let (status, _) = send(&app, Method::GET, "/api/docs").await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    /// `GET /api/benchmark/export/csv` over a store with enough rows to be worth compressing.
+    async fn export_csv(disable_compression: bool, accept_encoding: Option<&str>) -> (Option<String>, usize) {
+        let state = offline_state();
+        let state = AppState { config: Arc::new(Config { disable_compression, ..(*state.config).clone() }), ..state };
+        let mut metrics = state.metrics.write().await;
+        for i in 0..500 {
+            metrics.record_raw("lookup", "HashSet", 1_000 + i, 10_000);
+        }
+        drop(metrics);
+
+        let mut request = Request::builder().uri("/api/benchmark/export/csv");
+        if let Some(encoding) = accept_encoding {
+            request = request.header(axum::http::header::ACCEPT_ENCODING, encoding);
+        }
+        let response = build_router(state).oneshot(request.body(Body::empty()).unwrap()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let encoding = response
+            .headers()
+            .get(axum::http::header::CONTENT_ENCODING)
+            .map(|v| v.to_str().unwrap().to_string());
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (encoding, body.len())
+    }
+
+    #[tokio::test]
+    async fn csv_export_is_gzipped_when_the_client_accepts_it() {
+        let (encoding, plain_len) = export_csv(false, None).await;
+        assert_eq!(encoding, None);
+
+        let (encoding, gzip_len) = export_csv(false, Some("gzip")).await;
+        assert_eq!(encoding.as_deref(), Some("gzip"));
+        assert!(gzip_len * 4 < plain_len, "gzip {} bytes vs plain {} bytes", gzip_len, plain_len);
+
+        let (encoding, br_len) = export_csv(false, Some("br")).await;
+        assert_eq!(encoding.as_deref(), Some("br"));
+        assert!(br_len < plain_len);
+    }
+
+    #[tokio::test]
+    async fn disable_compression_sends_the_csv_export_as_is() {
+        let (plain_encoding, plain_len) = export_csv(false, None).await;
+        let (encoding, len) = export_csv(true, Some("gzip")).await;
+        assert_eq!((encoding, plain_encoding), (None, None));
+        // Timestamps vary in width between runs, so the sizes only roughly agree
+        assert!(len.abs_diff(plain_len) * 100 < plain_len, "{} vs {} bytes", len, plain_len);
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn benchmark_metrics_are_tagged_with_the_request_id(pool: sqlx::PgPool) {
+        seed::seed_products(&pool, 20).await.unwrap();
+        let state = AppState { db: pool, ..offline_state() };
+        let app = build_router(state.clone());
+
+        let (status, id) = send(&app, Method::POST, "/api/benchmark/run").await;
+        assert_eq!(status, StatusCode::OK);
+
+        let metrics = state.metrics.read().await;
+        assert!(!metrics.entries.is_empty());
+        assert!(metrics.entries.iter().all(|e| e.notes.as_deref() == Some(id.as_str())));
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn retried_create_with_the_same_idempotency_key_makes_one_product(pool: sqlx::PgPool) {
+        use axum::http::header::CONTENT_TYPE;
+        use crate::idempotency::{IDEMPOTENCY_KEY, IDEMPOTENT_REPLAYED};
+
+        let app = build_router(AppState { db: pool.clone(), ..offline_state() });
+        let key = uuid::Uuid::new_v4().to_string();
+        let create = |uri: &str| {
+            Request::builder()
+                .method(Method::POST)
+                .uri(uri)
+                .header(CONTENT_TYPE, "application/json")
+                .header(&IDEMPOTENCY_KEY, &key)
+                .body(Body::from(r#"{"name":"Retried Lamp","price_cents":1999,"quantity":3,"category":"Home & Garden"}"#))
+                .unwrap()
+        };
+
+        let mut bodies = vec![];
+        for _ in 0..2 {
+            let response = app.clone().oneshot(create("/api/products")).await.unwrap();
+            assert_eq!(response.status(), StatusCode::CREATED);
+            let replayed = response.headers().contains_key(&IDEMPOTENT_REPLAYED);
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            bodies.push((replayed, serde_json::from_slice::<serde_json::Value>(&bytes).unwrap()));
+        }
+        assert_eq!((bodies[0].0, bodies[1].0), (false, true));
+        assert_eq!(bodies[0].1, bodies[1].1);
+
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM products").fetch_one(&pool).await.unwrap();
+        assert_eq!(count, 1);
+
+        // A key belongs to the endpoint it was first sent to
+        let response = app.oneshot(create("/api/devolutions")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+    }
+
+    #[cfg(feature = "audit")]
+    #[sqlx::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn creating_a_product_writes_exactly_one_audit_row(pool: sqlx::PgPool) {
+        use axum::http::header::{CONTENT_LENGTH, CONTENT_TYPE, USER_AGENT};
+
+        let app = build_router(AppState { db: pool.clone(), ..offline_state() });
+        let body = r#"{"name":"Audited Lamp","price_cents":1999,"quantity":3,"category":"Home & Garden"}"#;
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri("/api/products")
+            .header(CONTENT_TYPE, "application/json")
+            .header(CONTENT_LENGTH, body.len())
+            .header(USER_AGENT, "audit-test")
+            .body(Body::from(body))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let id = response.headers()[&X_REQUEST_ID].to_str().unwrap().to_string();
+
+        // Reads are not audited
+        assert_eq!(send(&app, Method::GET, "/api/products").await.0, StatusCode::OK);
+        assert_eq!(send(&app, Method::GET, "/api/admin/audit-log?method=post").await.0, StatusCode::OK);
+
+        // The row is written after the response went out
+        for _ in 0..100 {
+            if !db::fetch_audit_log(&pool, 1, None).await.unwrap().is_empty() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        // Leave time for a stray second write to land before counting
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        let entries = db::fetch_audit_log(&pool, 10, None).await.unwrap();
+        assert_eq!(entries.len(), 1);
+
+        let entry = &entries[0];
+        assert_eq!(entry.request_id.map(|u| u.to_string()), Some(id));
+        assert_eq!((entry.method.as_str(), entry.path.as_str()), ("POST", "/api/products"));
+        assert_eq!(entry.user_agent.as_deref(), Some("audit-test"));
+        assert_eq!(entry.response_status, 201);
+        assert_eq!(entry.request_body_json.as_ref().unwrap()["name"], "Audited Lamp");
+        assert!(db::fetch_audit_log(&pool, 10, Some("DELETE")).await.unwrap().is_empty());
+    }
+}
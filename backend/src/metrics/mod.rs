@@ -1,6 +1,21 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, TimeDelta, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, PoisonError},
+    time::Duration,
+};
+use sqlx::PgPool;
+use tokio::{
+    sync::{
+        mpsc::{error::TrySendError, Sender},
+        RwLock,
+    },
+    task::JoinHandle,
+};
+use tracing::{debug, info, warn};
+
+use crate::{db, error::AppResult};
 
 /// One recorded operation timing.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,19 +54,78 @@ impl MetricEntry {
     }
 }
 
-/// In-memory store for all timing entries collected across requests.
-#[derive(Debug, Default)]
+/// Senders for the connected `GET /api/ws/metrics` clients. A std lock, since
+/// [`MetricsStore::record`] broadcasts without awaiting; it is only held long
+/// enough to push, send to or prune senders.
+pub type MetricSubscribers = Arc<std::sync::RwLock<Vec<Sender<MetricEntry>>>>;
+
+/// In-memory store for timing entries collected across requests.
+///
+/// Holds at most `capacity` entries: once full, recording a new entry evicts
+/// the oldest one, so memory stays bounded however long the service runs.
+#[derive(Debug)]
 pub struct MetricsStore {
-    pub entries: Vec<MetricEntry>,
+    pub entries: VecDeque<MetricEntry>,
+    capacity: usize,
+    subscribers: MetricSubscribers,
+}
+
+impl Default for MetricsStore {
+    fn default() -> Self {
+        Self::with_capacity(Self::DEFAULT_CAPACITY)
+    }
 }
 
 impl MetricsStore {
+    pub const DEFAULT_CAPACITY: usize = 100_000;
+    pub const MAX_CAPACITY: usize = 10_000_000;
+
     pub fn new() -> Self {
         Self::default()
     }
 
+    /// A store that keeps the most recent `capacity` entries (at least 1).
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: VecDeque::new(),
+            capacity: capacity.max(1),
+            subscribers: MetricSubscribers::default(),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Changes the capacity (at least 1), evicting the oldest entries if the
+    /// store is now over it. Returns how many entries were evicted.
+    pub fn set_capacity(&mut self, capacity: usize) -> usize {
+        self.capacity = capacity.max(1);
+        let excess = self.entries.len().saturating_sub(self.capacity);
+        self.entries.drain(..excess);
+        excess
+    }
+
+    /// Every entry passed to [`Self::record`] is also sent to these.
+    pub fn subscribers(&self) -> MetricSubscribers {
+        self.subscribers.clone()
+    }
+
     pub fn record(&mut self, entry: MetricEntry) {
-        self.entries.push(entry);
+        self.broadcast(&entry);
+        if self.entries.len() >= self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+
+    /// Sends `entry` to every subscriber. One whose buffer is full misses it;
+    /// one whose receiver is gone is dropped.
+    fn broadcast(&self, entry: &MetricEntry) {
+        let mut subscribers = self.subscribers.write().unwrap_or_else(PoisonError::into_inner);
+        if !subscribers.is_empty() {
+            subscribers.retain(|tx| !matches!(tx.try_send(entry.clone()), Err(TrySendError::Closed(_))));
+        }
     }
 
     pub fn record_raw(
@@ -71,23 +145,68 @@ impl MetricsStore {
         ));
     }
 
+    /// Like [`Self::record_raw`], tagging the entry with `notes` (e.g. the
+    /// request ID that produced it).
+    pub fn record_raw_with_notes(
+        &mut self,
+        operation: impl Into<String>,
+        set_type: impl Into<String>,
+        duration_ns: u64,
+        item_count: usize,
+        notes: impl Into<String>,
+    ) {
+        self.record(MetricEntry::new(
+            operation,
+            set_type,
+            duration_ns,
+            item_count,
+            true,
+            Some(notes.into()),
+        ));
+    }
+
     pub fn clear(&mut self) {
         self.entries.clear();
     }
 
+    /// Drops every entry recorded before `cutoff` and returns how many went.
+    /// Entries stamped exactly at `cutoff` are kept.
+    pub fn purge_before(&mut self, cutoff: DateTime<Utc>) -> usize {
+        let before = self.entries.len();
+        self.entries.retain(|e| e.timestamp >= cutoff);
+        before - self.entries.len()
+    }
+
+    /// Removes and returns every entry, oldest first.
+    pub fn drain(&mut self) -> Vec<MetricEntry> {
+        self.entries.drain(..).collect()
+    }
+
+    /// Puts `entries` taken by [`Self::drain`] back in front of anything
+    /// recorded since. If that overfills the store, the oldest are dropped.
+    pub fn requeue(&mut self, entries: Vec<MetricEntry>) {
+        let room = self.capacity.saturating_sub(self.entries.len());
+        let skip = entries.len().saturating_sub(room);
+        for entry in entries.into_iter().skip(skip).rev() {
+            self.entries.push_front(entry);
+        }
+    }
+
     /// Aggregate stats per (operation, set_type) pair.
     pub fn aggregated(&self) -> Vec<AggregatedMetric> {
-        let mut map: HashMap<(String, String), Vec<u64>> = HashMap::new();
+        let mut map: HashMap<(String, String), (Vec<u64>, Welford)> = HashMap::new();
 
         for e in &self.entries {
-            map.entry((e.operation.clone(), e.set_type.clone()))
-                .or_default()
-                .push(e.duration_ns);
+            let (durations, spread) = map
+                .entry((e.operation.clone(), e.set_type.clone()))
+                .or_default();
+            durations.push(e.duration_ns);
+            spread.push(e.duration_ns as f64);
         }
 
         let mut out: Vec<AggregatedMetric> = map
             .into_iter()
-            .map(|((op, st), durations)| {
+            .map(|((op, st), (durations, spread))| {
                 let count = durations.len();
                 let total: u64 = durations.iter().sum();
                 let avg = total / count as u64;
@@ -96,8 +215,10 @@ impl MetricsStore {
                 let min = *sorted.first().unwrap_or(&0);
                 let max = *sorted.last().unwrap_or(&0);
                 let p50 = sorted[count / 2];
-                let p95 = sorted[((count as f64 * 0.95) as usize).min(count.saturating_sub(1))];
-                let p99 = sorted[((count as f64 * 0.99) as usize).min(count.saturating_sub(1))];
+                let p95 = quantile(&sorted, 0.95);
+                let p99 = quantile(&sorted, 0.99);
+                let variance = spread.variance();
+                let std_dev = variance.sqrt();
 
                 AggregatedMetric {
                     operation: op,
@@ -109,8 +230,15 @@ impl MetricsStore {
                     p50_ns: p50,
                     p95_ns: p95,
                     p99_ns: p99,
+                    p999_ns: quantile(&sorted, 0.999),
+                    p9999_ns: quantile(&sorted, 0.9999),
+                    meaningful_tail: count >= MEANINGFUL_TAIL_SAMPLES,
                     avg_ms: avg as f64 / 1_000_000.0,
                     p95_ms: p95 as f64 / 1_000_000.0,
+                    variance_ns: variance,
+                    std_dev_ns: std_dev,
+                    std_dev_ms: std_dev / 1_000_000.0,
+                    cv_percent: if spread.mean > 0.0 { std_dev / spread.mean * 100.0 } else { 0.0 },
                 }
             })
             .collect();
@@ -152,6 +280,129 @@ impl MetricsStore {
         Ok(String::from_utf8(data)?)
     }
 
+    /// Export every entry as an InfluxDB line-protocol record: measurement
+    /// `inventory_operation`, tagged by `operation` and `set_type`, stamped with
+    /// the entry's timestamp in nanoseconds.
+    pub fn to_influx_line_protocol(&self) -> String {
+        use std::fmt::Write;
+
+        let mut out = String::new();
+        for e in &self.entries {
+            out.push_str("inventory_operation");
+            // Influx rejects empty tag values, so such tags are left out
+            for (key, value) in [("operation", &e.operation), ("set_type", &e.set_type)] {
+                if !value.is_empty() {
+                    let _ = write!(out, ",{}={}", key, escape_influx_tag(value));
+                }
+            }
+            let _ = write!(
+                out,
+                " duration_ns={}i,duration_us={},duration_ms={},item_count={}i",
+                e.duration_ns, e.duration_us, e.duration_ms, e.item_count
+            );
+            // Outside 1677–2262 there is no nanosecond timestamp; Influx then uses its own clock
+            if let Some(ns) = e.timestamp.timestamp_nanos_opt() {
+                let _ = write!(out, " {}", ns);
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Export every entry as a single-row-group Parquet file with one column
+    /// per `MetricEntry` field. `timestamp` is stored as UTC microseconds and
+    /// `notes` is the only nullable column.
+    #[cfg(feature = "parquet")]
+    pub fn to_parquet_bytes(&self) -> anyhow::Result<Vec<u8>> {
+        use arrow_array::{ArrayRef, BooleanArray, Float64Array, RecordBatch, StringArray, TimestampMicrosecondArray, UInt64Array};
+        use arrow_schema::{DataType, Field, Schema, TimeUnit};
+        use parquet::arrow::ArrowWriter;
+
+        let utc = Some("UTC".into());
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("timestamp", DataType::Timestamp(TimeUnit::Microsecond, utc.clone()), false),
+            Field::new("operation", DataType::Utf8, false),
+            Field::new("set_type", DataType::Utf8, false),
+            Field::new("duration_ns", DataType::UInt64, false),
+            Field::new("duration_us", DataType::Float64, false),
+            Field::new("duration_ms", DataType::Float64, false),
+            Field::new("item_count", DataType::UInt64, false),
+            Field::new("success", DataType::Boolean, false),
+            Field::new("notes", DataType::Utf8, true),
+        ]));
+
+        let e = &self.entries;
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(TimestampMicrosecondArray::from_iter_values(e.iter().map(|e| e.timestamp.timestamp_micros())).with_timezone_opt(utc)),
+            Arc::new(StringArray::from_iter_values(e.iter().map(|e| &e.operation))),
+            Arc::new(StringArray::from_iter_values(e.iter().map(|e| &e.set_type))),
+            Arc::new(UInt64Array::from_iter_values(e.iter().map(|e| e.duration_ns))),
+            Arc::new(Float64Array::from_iter_values(e.iter().map(|e| e.duration_us))),
+            Arc::new(Float64Array::from_iter_values(e.iter().map(|e| e.duration_ms))),
+            Arc::new(UInt64Array::from_iter_values(e.iter().map(|e| e.item_count as u64))),
+            Arc::new(e.iter().map(|e| Some(e.success)).collect::<BooleanArray>()),
+            Arc::new(e.iter().map(|e| e.notes.as_deref()).collect::<StringArray>()),
+        ];
+        let batch = RecordBatch::try_new(schema.clone(), columns)?;
+
+        let mut writer = ArrowWriter::try_new(Vec::new(), schema, None)?;
+        writer.write(&batch)?;
+        Ok(writer.into_inner()?)
+    }
+
+    /// Render aggregated stats in the Prometheus text exposition format: one
+    /// `summary` (p50/p95/p99 + sum/count) and min/max/avg gauges per
+    /// (operation, set_type) pair.
+    #[cfg(feature = "prometheus")]
+    pub fn to_prometheus(&self) -> String {
+        use std::fmt::Write;
+
+        let mut sums: HashMap<(&str, &str), u64> = HashMap::new();
+        for e in &self.entries {
+            *sums.entry((&e.operation, &e.set_type)).or_default() += e.duration_ns;
+        }
+
+        let agg = self.aggregated();
+        let labels = |m: &AggregatedMetric| {
+            format!(
+                "operation=\"{}\",set_type=\"{}\"",
+                escape_label(&m.operation),
+                escape_label(&m.set_type)
+            )
+        };
+
+        let mut out = String::new();
+        out.push_str("# HELP inventory_operation_duration_ns Operation latency in nanoseconds.\n");
+        out.push_str("# TYPE inventory_operation_duration_ns summary\n");
+        for m in &agg {
+            let l = labels(m);
+            for (q, v) in [("0.5", m.p50_ns), ("0.95", m.p95_ns), ("0.99", m.p99_ns)] {
+                let _ = writeln!(out, "inventory_operation_duration_ns{{{},quantile=\"{}\"}} {}", l, q, v);
+            }
+            let sum = sums.get(&(m.operation.as_str(), m.set_type.as_str())).copied().unwrap_or(0);
+            let _ = writeln!(out, "inventory_operation_duration_ns_sum{{{}}} {}", l, sum);
+            let _ = writeln!(out, "inventory_operation_duration_ns_count{{{}}} {}", l, m.sample_count);
+        }
+
+        for (name, help, value) in [
+            ("min", "Fastest", (|m: &AggregatedMetric| m.min_ns) as fn(&AggregatedMetric) -> u64),
+            ("max", "Slowest", |m| m.max_ns),
+            ("avg", "Mean", |m| m.avg_ns),
+        ] {
+            let _ = writeln!(
+                out,
+                "# HELP inventory_operation_duration_{}_ns {} observed operation latency in nanoseconds.",
+                name, help
+            );
+            let _ = writeln!(out, "# TYPE inventory_operation_duration_{}_ns gauge", name);
+            for m in &agg {
+                let _ = writeln!(out, "inventory_operation_duration_{}_ns{{{}}} {}", name, labels(m), value(m));
+            }
+        }
+
+        out
+    }
+
     /// Render a simple ASCII comparison table.
     pub fn ascii_table(&self) -> String {
         let agg = self.aggregated();
@@ -161,22 +412,24 @@ impl MetricsStore {
 
         let mut out = String::new();
         out.push_str(&format!(
-            "\n{:<20} {:<18} {:>12} {:>12} {:>12} {:>12} {:>12}\n",
-            "Operation", "Set Type", "Samples", "Avg (µs)", "P50 (µs)", "P95 (µs)", "P99 (µs)"
+            "\n{:<20} {:<18} {:>12} {:>12} {:>12} {:>12} {:>12} {:>12} {:>12}\n",
+            "Operation", "Set Type", "Samples", "Avg (µs)", "StdDev(µs)", "P50 (µs)", "P95 (µs)", "P99 (µs)", "P99.9(µs)"
         ));
-        out.push_str(&"-".repeat(102));
+        out.push_str(&"-".repeat(128));
         out.push('\n');
 
         for row in &agg {
             out.push_str(&format!(
-                "{:<20} {:<18} {:>12} {:>12.2} {:>12.2} {:>12.2} {:>12.2}\n",
+                "{:<20} {:<18} {:>12} {:>12.2} {:>12.2} {:>12.2} {:>12.2} {:>12.2} {:>12.2}\n",
                 row.operation,
                 row.set_type,
                 row.sample_count,
                 row.avg_ns as f64 / 1_000.0,
+                row.std_dev_ns / 1_000.0,
                 row.p50_ns as f64 / 1_000.0,
                 row.p95_ns as f64 / 1_000.0,
                 row.p99_ns as f64 / 1_000.0,
+                row.p999_ns as f64 / 1_000.0,
             ));
         }
 
@@ -184,6 +437,85 @@ impl MetricsStore {
     }
 }
 
+/// Escapes a Prometheus label value (`\\`, `"` and newlines).
+#[cfg(feature = "prometheus")]
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Escapes an InfluxDB tag value (`,`, `=`, spaces and `\`).
+fn escape_influx_tag(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, ',' | '=' | ' ' | '\\') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// The instant `max_age_secs` ago; ages too large to represent reach back to
+/// the earliest possible time, so nothing counts as older.
+pub fn cutoff_for_age(max_age_secs: u64) -> DateTime<Utc> {
+    i64::try_from(max_age_secs)
+        .ok()
+        .and_then(TimeDelta::try_seconds)
+        .and_then(|age| Utc::now().checked_sub_signed(age))
+        .unwrap_or(DateTime::<Utc>::MIN_UTC)
+}
+
+/// Every `every`, purges entries older than `retention_secs` from `store`.
+/// Runs until the returned handle is aborted or the runtime shuts down.
+pub fn spawn_retention_task(store: Arc<RwLock<MetricsStore>>, retention_secs: u64, every: Duration) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(every);
+        loop {
+            ticker.tick().await;
+            let purged = store.write().await.purge_before(cutoff_for_age(retention_secs));
+            if purged > 0 {
+                info!(purged, retention_secs, "Purged expired metrics entries");
+            }
+        }
+    })
+}
+
+/// Moves every entry in `store` to the `metrics` table and returns how many
+/// were written. The lock is released before the insert; on failure the
+/// entries are put back so the next flush can retry them.
+pub async fn persist(store: &RwLock<MetricsStore>, pool: &PgPool) -> AppResult<usize> {
+    let entries = store.write().await.drain();
+    if entries.is_empty() {
+        return Ok(0);
+    }
+    match db::bulk_insert_metrics(pool, &entries).await {
+        Ok(()) => Ok(entries.len()),
+        Err(e) => {
+            store.write().await.requeue(entries);
+            Err(e)
+        }
+    }
+}
+
+/// Every `every`, drains `store` into the `metrics` table with [`persist`].
+/// Runs until the returned handle is aborted or the runtime shuts down.
+pub fn spawn_persistence_task(store: Arc<RwLock<MetricsStore>>, pool: PgPool, every: Duration) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(every);
+        loop {
+            ticker.tick().await;
+            match persist(&store, &pool).await {
+                Ok(0) => {}
+                Ok(persisted) => debug!(persisted, "Persisted metrics entries"),
+                Err(e) => warn!(error = %e, "Failed to persist metrics entries; will retry"),
+            }
+        }
+    })
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AggregatedMetric {
     pub operation: String,
@@ -195,6 +527,415 @@ pub struct AggregatedMetric {
     pub p50_ns: u64,
     pub p95_ns: u64,
     pub p99_ns: u64,
+    pub p999_ns: u64,
+    pub p9999_ns: u64,
+    /// Whether there are enough samples (10 000+) for `p9999_ns` to be more
+    /// than the maximum under another name
+    pub meaningful_tail: bool,
     pub avg_ms: f64,
     pub p95_ms: f64,
+    /// Population variance of the durations (ns²)
+    pub variance_ns: f64,
+    pub std_dev_ns: f64,
+    pub std_dev_ms: f64,
+    /// Coefficient of variation: std dev as a percentage of the mean
+    pub cv_percent: f64,
+}
+
+/// Below this many samples, p99.99 falls on the last one or two and says
+/// nothing the maximum doesn't.
+const MEANINGFUL_TAIL_SAMPLES: usize = 10_000;
+
+/// The sample at position `⌊len · q⌋` of the ascending `sorted`, clamped to
+/// the last one. `sorted` must not be empty.
+fn quantile(sorted: &[u64], q: f64) -> u64 {
+    sorted[((sorted.len() as f64 * q) as usize).min(sorted.len() - 1)]
+}
+
+/// Welford's online mean/variance, updated one sample at a time so the spread
+/// is known without a second pass over the durations.
+#[derive(Debug, Default)]
+struct Welford {
+    count: u64,
+    mean: f64,
+    m2: f64,
+}
+
+impl Welford {
+    fn push(&mut self, x: f64) {
+        self.count += 1;
+        let delta = x - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (x - self.mean);
+    }
+
+    /// Population variance; 0 with fewer than two samples.
+    fn variance(&self) -> f64 {
+        if self.count < 2 {
+            0.0
+        } else {
+            self.m2 / self.count as f64
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store_with(capacity: usize, durations: impl IntoIterator<Item = u64>) -> MetricsStore {
+        let mut store = MetricsStore::with_capacity(capacity);
+        for ns in durations {
+            store.record_raw("insert", "HashSet", ns, 1);
+        }
+        store
+    }
+
+    #[test]
+    fn recording_past_capacity_evicts_oldest() {
+        // 1..=15 into a window of 10 keeps 6..=15
+        let store = store_with(10, 1..=15);
+        assert_eq!(store.entries.len(), 10);
+        assert_eq!(store.entries.front().unwrap().duration_ns, 6);
+        assert_eq!(store.entries.back().unwrap().duration_ns, 15);
+    }
+
+    #[test]
+    fn aggregation_only_sees_retained_entries() {
+        let store = store_with(4, [1_000_000, 1, 2, 3, 4]);
+        let agg = store.aggregated();
+        assert_eq!(agg.len(), 1);
+        let m = &agg[0];
+        assert_eq!(m.sample_count, 4);
+        assert_eq!((m.min_ns, m.max_ns, m.avg_ns), (1, 4, 2));
+        assert_eq!(store.to_csv().unwrap().lines().count(), 1 + 4);
+    }
+
+    #[test]
+    fn shrinking_capacity_evicts_immediately() {
+        let mut store = store_with(10, 1..=8);
+        assert_eq!(store.set_capacity(3), 5);
+        assert_eq!(store.capacity(), 3);
+        assert_eq!(store.entries.iter().map(|e| e.duration_ns).collect::<Vec<_>>(), vec![6, 7, 8]);
+
+        // Growing never evicts
+        assert_eq!(store.set_capacity(100), 0);
+        assert_eq!(store.entries.len(), 3);
+    }
+
+    #[test]
+    fn std_dev_of_known_sequence() {
+        let m = &store_with(10, [0, 2, 4]).aggregated()[0];
+        assert!((m.variance_ns - 8.0 / 3.0).abs() < 1e-9);
+        assert!((m.std_dev_ns - 1.632_993).abs() < 1e-6);
+        assert!((m.cv_percent - 81.649_658).abs() < 1e-5);
+        assert!((m.std_dev_ms - m.std_dev_ns / 1_000_000.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn tail_percentiles_of_10_001_known_samples() {
+        // 0, 1, …, 10 000 in a scrambled order, so the sort matters
+        let m = &store_with(20_000, (0..10_001u64).map(|i| i * 7_919 % 10_001)).aggregated()[0];
+        assert_eq!(m.sample_count, 10_001);
+        assert_eq!(m.max_ns, 10_000);
+        // Nearest-rank positions are 9 990 and 9 999; allow one position either way
+        assert!(m.p999_ns.abs_diff(9_990) <= 1, "{}", m.p999_ns);
+        assert!(m.p9999_ns.abs_diff(9_999) <= 1, "{}", m.p9999_ns);
+        assert!(m.p99_ns.abs_diff(9_900) <= 1, "{}", m.p99_ns);
+        assert!(m.p99_ns <= m.p999_ns && m.p999_ns <= m.p9999_ns && m.p9999_ns <= m.max_ns);
+        assert!(m.meaningful_tail);
+    }
+
+    #[test]
+    fn tail_is_not_meaningful_below_10_000_samples() {
+        let m = &store_with(10_000, 1..=9_999).aggregated()[0];
+        assert!(!m.meaningful_tail);
+        assert_eq!(m.p9999_ns, 9_999, "clamped to the last sample");
+        let m = &store_with(10, [42]).aggregated()[0];
+        assert_eq!((m.p999_ns, m.p9999_ns), (42, 42));
+    }
+
+    #[test]
+    fn constant_and_single_samples_have_no_spread() {
+        let m = &store_with(10, [7, 7, 7, 7]).aggregated()[0];
+        assert_eq!((m.variance_ns, m.std_dev_ns, m.cv_percent), (0.0, 0.0, 0.0));
+        let m = &store_with(10, [42]).aggregated()[0];
+        assert_eq!(m.std_dev_ns, 0.0);
+        let m = &store_with(10, [0, 0]).aggregated()[0];
+        assert_eq!(m.cv_percent, 0.0, "zero mean must not divide by zero");
+    }
+
+    #[test]
+    fn welford_matches_two_pass_on_large_values() {
+        let xs: Vec<f64> = (0..1_000).map(|i| 1e9 + (i % 17) as f64 * 1_000.0).collect();
+        let mut w = Welford::default();
+        xs.iter().for_each(|&x| w.push(x));
+        let mean = xs.iter().sum::<f64>() / xs.len() as f64;
+        let two_pass = xs.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / xs.len() as f64;
+        assert!((w.variance() - two_pass).abs() / two_pass < 1e-9);
+    }
+
+    #[test]
+    fn ascii_table_has_std_dev_column() {
+        let table = store_with(10, [1_000, 3_000]).ascii_table();
+        assert!(table.contains("StdDev(µs)"));
+        assert!(table.contains("P99.9(µs)"));
+        // σ of [1µs, 3µs] is 1µs
+        assert!(table.lines().any(|l| l.starts_with("insert") && l.contains("1.00")), "{}", table);
+    }
+
+    #[test]
+    fn capacity_is_at_least_one() {
+        let mut store = store_with(0, [5, 6]);
+        assert_eq!(store.capacity(), 1);
+        assert_eq!(store.entries.len(), 1);
+        assert_eq!(store.set_capacity(0), 0);
+        assert_eq!(MetricsStore::new().capacity(), MetricsStore::DEFAULT_CAPACITY);
+    }
+
+    #[test]
+    fn recorded_entries_reach_subscribers_and_closed_ones_are_pruned() {
+        let mut store = MetricsStore::new();
+        let (live_tx, mut live_rx) = tokio::sync::mpsc::channel(1);
+        let (gone_tx, gone_rx) = tokio::sync::mpsc::channel(1);
+        store.subscribers().write().unwrap().extend([live_tx, gone_tx]);
+        drop(gone_rx);
+
+        store.record_raw("lookup", "HashSet", 100, 1);
+        assert_eq!(live_rx.try_recv().unwrap().duration_ns, 100);
+        assert_eq!(store.subscribers().read().unwrap().len(), 1, "closed receiver pruned");
+
+        // A full buffer drops the entry for that client but keeps it subscribed
+        store.record_raw("lookup", "HashSet", 200, 1);
+        store.record_raw("lookup", "HashSet", 300, 1);
+        assert_eq!(live_rx.try_recv().unwrap().duration_ns, 200);
+        assert!(live_rx.try_recv().is_err());
+        assert_eq!(store.subscribers().read().unwrap().len(), 1);
+        assert_eq!(store.entries.len(), 3);
+    }
+
+    #[cfg(feature = "prometheus")]
+    mod prometheus {
+        use super::*;
+        use prometheus_parse::{Scrape, Value};
+
+        fn parse(text: &str) -> Scrape {
+            Scrape::parse(text.lines().map(|l| Ok(l.to_string()))).unwrap()
+        }
+
+        #[test]
+        fn prometheus_output_parses_and_carries_labels() {
+            let mut store = MetricsStore::new();
+            for ns in [100, 200, 300, 400] {
+                store.record_raw("insert_all", "HashSet", ns, 1);
+            }
+            store.record_raw("lookup", "BTreeSet", 50, 1);
+
+            let scrape = parse(&store.to_prometheus());
+
+            let summary = scrape
+                .samples
+                .iter()
+                .find(|s| {
+                    s.metric == "inventory_operation_duration_ns"
+                        && s.labels.get("operation") == Some("insert_all")
+                        && s.labels.get("set_type") == Some("HashSet")
+                })
+                .expect("summary for insert_all/HashSet");
+            match &summary.value {
+                Value::Summary(qs) => {
+                    assert_eq!(qs.len(), 3);
+                    assert!(qs.iter().any(|q| q.quantile == 0.5 && q.count == 300.0));
+                }
+                other => panic!("expected summary, got {:?}", other),
+            }
+
+            let max = scrape
+                .samples
+                .iter()
+                .find(|s| s.metric == "inventory_operation_duration_max_ns" && s.labels.get("set_type") == Some("BTreeSet"))
+                .expect("max gauge for BTreeSet");
+            assert_eq!(max.value, Value::Gauge(50.0));
+        }
+
+        #[test]
+        fn prometheus_sum_and_count_are_exact() {
+            let mut store = MetricsStore::new();
+            for ns in [1, 2, 4] {
+                store.record_raw("remove", "DB", ns, 1);
+            }
+            let text = store.to_prometheus();
+            assert!(text.contains("inventory_operation_duration_ns_sum{operation=\"remove\",set_type=\"DB\"} 7\n"));
+            assert!(text.contains("inventory_operation_duration_ns_count{operation=\"remove\",set_type=\"DB\"} 3\n"));
+        }
+
+        #[test]
+        fn prometheus_escapes_label_values() {
+            let mut store = MetricsStore::new();
+            store.record_raw("we\"ird", "a\\b", 1, 1);
+            let text = store.to_prometheus();
+            assert!(text.contains(r#"operation="we\"ird",set_type="a\\b""#), "{}", text);
+        }
+
+        #[test]
+        fn empty_store_renders_only_metadata() {
+            let text = MetricsStore::new().to_prometheus();
+            assert!(text.lines().all(|l| l.starts_with('#')));
+        }
+    }
+
+    #[test]
+    fn influx_line_protocol_is_exact() {
+        let at = |secs: i64| DateTime::from_timestamp(secs, 123).unwrap();
+        let mut store = MetricsStore::new();
+        for (secs, op, set_type, ns, count) in [
+            (1_700_000_000, "lookup", "HashSet", 1_500, 1),
+            (1_700_000_001, "insert_all", "BTreeSet", 2_000_000, 500),
+            (1_700_000_002, "range_by_price", "BTreeMap (price index)", 750, 3),
+        ] {
+            let mut entry = MetricEntry::new(op, set_type, ns, count, true, None);
+            entry.timestamp = at(secs);
+            store.record(entry);
+        }
+
+        assert_eq!(
+            store.to_influx_line_protocol(),
+            "inventory_operation,operation=lookup,set_type=HashSet \
+             duration_ns=1500i,duration_us=1.5,duration_ms=0.0015,item_count=1i 1700000000000000123\n\
+             inventory_operation,operation=insert_all,set_type=BTreeSet \
+             duration_ns=2000000i,duration_us=2000,duration_ms=2,item_count=500i 1700000001000000123\n\
+             inventory_operation,operation=range_by_price,set_type=BTreeMap\\ (price\\ index) \
+             duration_ns=750i,duration_us=0.75,duration_ms=0.00075,item_count=3i 1700000002000000123\n"
+        );
+    }
+
+    /// One entry per `secs`, stamped that many seconds after a fixed epoch.
+    fn store_at(secs: impl IntoIterator<Item = i64>) -> MetricsStore {
+        let mut store = MetricsStore::new();
+        for s in secs {
+            let mut entry = MetricEntry::new("lookup", "HashSet", s as u64, 1, true, None);
+            entry.timestamp = DateTime::from_timestamp(1_700_000_000 + s, 0).unwrap();
+            store.record(entry);
+        }
+        store
+    }
+
+    #[test]
+    fn purge_before_keeps_entries_at_or_after_the_cutoff() {
+        // Out of order on purpose: purging must not rely on insertion order
+        let mut store = store_at([5, 1, 10, 3, 7, 4]);
+        let cutoff = DateTime::from_timestamp(1_700_000_004, 0).unwrap();
+
+        assert_eq!(store.purge_before(cutoff), 2);
+        assert_eq!(store.entries.iter().map(|e| e.duration_ns).collect::<Vec<_>>(), vec![5, 10, 7, 4]);
+        assert_eq!(store.purge_before(cutoff), 0, "purging again removes nothing");
+    }
+
+    #[test]
+    fn purge_before_handles_the_extremes() {
+        let mut store = store_at(0..5);
+        assert_eq!(store.purge_before(DateTime::<Utc>::MIN_UTC), 0);
+        assert_eq!(store.purge_before(DateTime::<Utc>::MAX_UTC), 5);
+        assert!(store.entries.is_empty());
+    }
+
+    #[test]
+    fn cutoff_for_age_saturates_instead_of_overflowing() {
+        assert_eq!(cutoff_for_age(u64::MAX), DateTime::<Utc>::MIN_UTC);
+        let an_hour_ago = cutoff_for_age(3600);
+        let drift = (Utc::now() - TimeDelta::seconds(3600)) - an_hour_ago;
+        assert!(drift >= TimeDelta::zero() && drift < TimeDelta::seconds(5));
+    }
+
+    #[tokio::test]
+    async fn retention_task_purges_on_its_first_tick() {
+        let store = Arc::new(RwLock::new(MetricsStore::new()));
+        store.write().await.record_raw("lookup", "HashSet", 1, 1);
+        let mut stale = MetricEntry::new("lookup", "HashSet", 2, 1, true, None);
+        stale.timestamp = Utc::now() - TimeDelta::hours(2);
+        store.write().await.record(stale);
+
+        // The first tick fires immediately; the next is far beyond the test
+        let task = spawn_retention_task(store.clone(), 3600, Duration::from_secs(600));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        task.abort();
+
+        let store = store.read().await;
+        assert_eq!(store.entries.len(), 1);
+        assert_eq!(store.entries[0].duration_ns, 1);
+    }
+
+    #[test]
+    fn requeue_restores_drained_entries_ahead_of_newer_ones() {
+        let mut store = store_with(4, [1, 2, 3]);
+        let drained = store.drain();
+        assert!(store.entries.is_empty());
+        store.record_raw("insert", "HashSet", 4, 1);
+        store.record_raw("insert", "HashSet", 5, 1);
+
+        // Only two of the three drained entries still fit; the oldest goes
+        store.requeue(drained);
+        assert_eq!(store.entries.iter().map(|e| e.duration_ns).collect::<Vec<_>>(), [2, 3, 4, 5]);
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn persist_drains_the_store_into_the_database(pool: PgPool) {
+        let store = RwLock::new(store_with(10, [1, 2, 3]));
+        assert_eq!(persist(&store, &pool).await.unwrap(), 3);
+        assert!(store.read().await.entries.is_empty());
+        assert_eq!(persist(&store, &pool).await.unwrap(), 0);
+
+        let rows = db::fetch_metric_history(&pool, Some("insert"), 10).await.unwrap();
+        let mut durations: Vec<i64> = rows.iter().map(|m| m.duration_ns).collect();
+        durations.sort_unstable();
+        assert_eq!(durations, [1, 2, 3]);
+        assert!(rows.iter().all(|m| m.set_type == "HashSet" && m.success));
+    }
+
+    #[tokio::test]
+    async fn failed_persist_keeps_the_entries() {
+        let store = RwLock::new(store_with(10, [1, 2]));
+        // The offline pool can't reach a database
+        assert!(persist(&store, &crate::handlers::offline_state().db).await.is_err());
+        assert_eq!(store.read().await.entries.len(), 2);
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn parquet_export_reads_back_every_row() {
+        use arrow_array::cast::AsArray;
+        use arrow_array::Array;
+        use arrow_array::types::UInt64Type;
+        use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+        let mut store = store_with(100, [10, 2_000, 30_000_000]);
+        store.record_raw_with_notes("lookup", "BTreeSet", 7, 3, "req-1");
+
+        let bytes = store.to_parquet_bytes().unwrap();
+        assert_eq!(&bytes[..4], b"PAR1");
+        let reader = ParquetRecordBatchReaderBuilder::try_new(axum::body::Bytes::from(bytes)).unwrap().build().unwrap();
+        let batches: Vec<_> = reader.collect::<Result<_, _>>().unwrap();
+        assert_eq!(batches.iter().map(|b| b.num_rows()).sum::<usize>(), 4);
+
+        let durations: Vec<u64> = batches
+            .iter()
+            .flat_map(|b| b.column_by_name("duration_ns").unwrap().as_primitive::<UInt64Type>().values().to_vec())
+            .collect();
+        let expected: Vec<u64> = store.entries.iter().map(|e| e.duration_ns).collect();
+        assert_eq!(durations, expected);
+
+        let notes = batches[0].column_by_name("notes").unwrap().as_string::<i32>();
+        assert!(notes.is_null(0));
+        assert_eq!(notes.value(3), "req-1");
+    }
+
+    #[test]
+    fn influx_tags_escape_separators_and_skip_empty_values() {
+        assert_eq!(escape_influx_tag(r"a,b=c d\e"), r"a\,b\=c\ d\\e");
+
+        let mut store = MetricsStore::new();
+        store.record_raw("", "DB", 1, 1);
+        assert!(store.to_influx_line_protocol().starts_with("inventory_operation,set_type=DB duration_ns=1i,"));
+    }
 }
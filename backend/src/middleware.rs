@@ -0,0 +1,151 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use axum::{
+    error_handling::HandleErrorLayer,
+    http::{HeaderName, HeaderValue, Request, Response, StatusCode},
+    BoxError, Json, Router,
+};
+use tower::{timeout::error::Elapsed, Layer, Service, ServiceBuilder};
+
+use crate::extract::RequestId;
+
+pub static X_REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Gives every request a fresh [`RequestId`]: stored in the request extensions
+/// for handlers to extract, and returned to the client as `X-Request-Id`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RequestIdLayer;
+
+impl<S> Layer<S> for RequestIdLayer {
+    type Service = RequestIdService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequestIdService { inner }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RequestIdService<S> {
+    inner: S,
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for RequestIdService<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = ResponseFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<ReqBody>) -> Self::Future {
+        let request_id = RequestId::new();
+        req.extensions_mut().insert(request_id);
+        ResponseFuture { inner: self.inner.call(req), request_id }
+    }
+}
+
+pin_project_lite::pin_project! {
+    pub struct ResponseFuture<F> {
+        #[pin]
+        inner: F,
+        request_id: RequestId,
+    }
+}
+
+impl<F, ResBody, E> Future for ResponseFuture<F>
+where
+    F: Future<Output = Result<Response<ResBody>, E>>,
+{
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let mut response = std::task::ready!(this.inner.poll(cx))?;
+        // A hyphenated UUID is always a valid header value
+        let value = HeaderValue::from_str(&this.request_id.to_string()).expect("uuid header value");
+        response.headers_mut().insert(X_REQUEST_ID.clone(), value);
+        Poll::Ready(Ok(response))
+    }
+}
+
+/// Fails every route currently in `router` with 408 `{"error": "request timeout"}`
+/// once it has run for `timeout`. The handler's future is dropped, so work it
+/// hadn't awaited yet doesn't happen.
+pub fn with_timeout<S>(router: Router<S>, timeout: Duration) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    router.layer(
+        ServiceBuilder::new()
+            .layer(HandleErrorLayer::new(handle_timeout_error))
+            .layer(tower::timeout::TimeoutLayer::new(timeout)),
+    )
+}
+
+async fn handle_timeout_error(err: BoxError) -> (StatusCode, Json<serde_json::Value>) {
+    if err.is::<Elapsed>() {
+        (StatusCode::REQUEST_TIMEOUT, Json(serde_json::json!({ "error": "request timeout" })))
+    } else {
+        tracing::error!("Unhandled middleware error: {}", err);
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({ "error": err.to_string() })))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, routing::get, Router};
+    use tower::ServiceExt;
+
+    async fn echo(request_id: RequestId) -> String {
+        request_id.to_string()
+    }
+
+    #[tokio::test]
+    async fn handler_sees_the_id_sent_back_in_the_header() {
+        let app = Router::new().route("/", get(echo)).layer(RequestIdLayer);
+        let response = app.oneshot(Request::new(Body::empty())).await.unwrap();
+
+        let header = response.headers()[&X_REQUEST_ID].to_str().unwrap().to_string();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(header.as_bytes(), &body[..]);
+        assert!(uuid::Uuid::parse_str(&header).is_ok());
+    }
+
+    #[tokio::test]
+    async fn slow_handler_times_out_with_a_json_408() {
+        async fn slow() -> &'static str {
+            tokio::time::sleep(Duration::from_secs(10)).await;
+            "done"
+        }
+        let app = with_timeout(Router::new().route("/slow", get(slow)).route("/fast", get(|| async { "ok" })), Duration::from_millis(100));
+
+        let start = std::time::Instant::now();
+        let response = app.clone().oneshot(Request::builder().uri("/slow").body(Body::empty()).unwrap()).await.unwrap();
+        let elapsed = start.elapsed();
+        assert_eq!(response.status(), StatusCode::REQUEST_TIMEOUT);
+        assert!(elapsed >= Duration::from_millis(100) && elapsed < Duration::from_secs(2), "{:?}", elapsed);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(serde_json::from_slice::<serde_json::Value>(&body).unwrap(), serde_json::json!({ "error": "request timeout" }));
+
+        let response = app.oneshot(Request::builder().uri("/fast").body(Body::empty()).unwrap()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn extractor_without_the_layer_is_a_server_error() {
+        let app = Router::new().route("/", get(echo));
+        let response = app.oneshot(Request::new(Body::empty())).await.unwrap();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(response.headers().get(&X_REQUEST_ID).is_none());
+    }
+}
@@ -0,0 +1,42 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+/// A recorded mutating API call, as stored in `audit_log`.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct AuditLogEntry {
+    pub id: Uuid,
+    pub request_id: Option<Uuid>,
+    pub method: String,
+    /// Path including the query string
+    pub path: String,
+    pub user_agent: Option<String>,
+    /// `None` for non-JSON or oversized bodies
+    pub request_body_json: Option<serde_json::Value>,
+    pub response_status: i32,
+    pub duration_ms: f64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// What [`crate::audit::AuditLayer`] writes for one request.
+#[cfg(feature = "audit")]
+#[derive(Debug, Clone)]
+pub struct NewAuditEntry {
+    pub request_id: Option<Uuid>,
+    pub method: String,
+    pub path: String,
+    pub user_agent: Option<String>,
+    pub request_body_json: Option<serde_json::Value>,
+    pub response_status: i32,
+    pub duration_ms: f64,
+}
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AuditLogParams {
+    /// Entries to return, newest first (default: 50, max: 1000)
+    pub limit: Option<i64>,
+    /// Only this HTTP method (case-insensitive)
+    pub method: Option<String>,
+}
@@ -0,0 +1,40 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Which products a sampled `POST /api/benchmark/run` loads.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SampleStrategy {
+    /// Oldest first
+    #[default]
+    First,
+    /// Newest first
+    Last,
+    /// A different sample, in a different order, on every run
+    Random,
+}
+
+impl SampleStrategy {
+    /// ORDER BY clause for the sampling query; fixed strings only.
+    pub fn order_by(self) -> &'static str {
+        match self {
+            Self::First => "created_at ASC, id ASC",
+            Self::Last => "created_at DESC, id DESC",
+            Self::Random => "RANDOM()",
+        }
+    }
+}
+
+/// One row of `GET /api/benchmark/history`: when a run happened and who won,
+/// without the (large) per-set results.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct BenchmarkRunSummary {
+    pub id: Uuid,
+    pub run_at: DateTime<Utc>,
+    pub product_count: i32,
+    pub winner_insert: Option<String>,
+    pub winner_lookup: Option<String>,
+    pub winner_iterate: Option<String>,
+}
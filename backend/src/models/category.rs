@@ -0,0 +1,105 @@
+use serde::{Deserialize, Serialize};
+use strum::VariantArray;
+use strum_macros::{Display, EnumString, IntoStaticStr, VariantArray};
+use utoipa::openapi::{ObjectBuilder, RefOr, Schema, SchemaType};
+
+use crate::error::AppError;
+
+/// The fixed set of product categories. Serialized as its display name, e.g.
+/// `"Food & Beverage"`; parsing ignores ASCII case, so `"electronics"` is
+/// stored as `"Electronics"`. The `products.category` CHECK constraint lists
+/// the same names.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, Display, EnumString, IntoStaticStr, VariantArray, Serialize, Deserialize,
+)]
+#[strum(ascii_case_insensitive)]
+#[serde(try_from = "String", into = "&'static str")]
+pub enum Category {
+    Electronics,
+    Clothing,
+    #[strum(serialize = "Food & Beverage")]
+    FoodAndBeverage,
+    #[strum(serialize = "Home & Garden")]
+    HomeAndGarden,
+    #[strum(serialize = "Toys & Games")]
+    ToysAndGames,
+    #[strum(serialize = "Sports & Outdoors")]
+    SportsAndOutdoors,
+    Books,
+    Automotive,
+    #[strum(serialize = "Health & Beauty")]
+    HealthAndBeauty,
+    #[strum(serialize = "Office Supplies")]
+    OfficeSupplies,
+    #[strum(serialize = "Musical Instruments")]
+    MusicalInstruments,
+    #[strum(serialize = "Pet Supplies")]
+    PetSupplies,
+    Jewelry,
+    #[strum(serialize = "Tools & Hardware")]
+    ToolsAndHardware,
+    #[strum(serialize = "Baby Products")]
+    BabyProducts,
+}
+
+impl Category {
+    /// The name as stored in `products.category`.
+    pub fn as_str(self) -> &'static str {
+        self.into()
+    }
+
+    /// Whether `name` is a category exactly as stored (no case folding).
+    pub fn is_stored_name(name: &str) -> bool {
+        Self::VARIANTS.iter().any(|c| c.as_str() == name)
+    }
+}
+
+impl TryFrom<String> for Category {
+    type Error = AppError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.trim().parse().map_err(|_| {
+            let known: Vec<&str> = Self::VARIANTS.iter().map(|c| c.as_str()).collect();
+            AppError::BadRequest(format!("unknown category '{}'; expected one of: {}", value, known.join(", ")))
+        })
+    }
+}
+
+impl<'s> utoipa::ToSchema<'s> for Category {
+    fn schema() -> (&'s str, RefOr<Schema>) {
+        let names = Self::VARIANTS.iter().map(|c| c.as_str());
+        ("Category", ObjectBuilder::new().schema_type(SchemaType::String).enum_values(Some(names)).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn known_categories_round_trip_through_json() {
+        for &category in Category::VARIANTS {
+            let json = serde_json::to_string(&category).unwrap();
+            assert_eq!(json, format!("\"{}\"", category));
+            assert_eq!(serde_json::from_str::<Category>(&json).unwrap(), category);
+            assert!(Category::is_stored_name(category.as_str()));
+        }
+        assert_eq!(Category::VARIANTS.len(), 15);
+        assert_eq!(Category::FoodAndBeverage.to_string(), "Food & Beverage");
+    }
+
+    #[test]
+    fn parsing_ignores_case_and_surrounding_space() {
+        assert_eq!(Category::try_from(" electronics ".to_string()).unwrap(), Category::Electronics);
+        assert_eq!(Category::try_from("HOME & GARDEN".to_string()).unwrap(), Category::HomeAndGarden);
+        assert!(!Category::is_stored_name("electronics"), "only the canonical spelling is stored");
+    }
+
+    #[test]
+    fn unknown_categories_are_rejected() {
+        let err = Category::try_from("Gizmos".to_string()).unwrap_err();
+        assert!(matches!(&err, AppError::BadRequest(m) if m.starts_with("unknown category 'Gizmos'")), "{:?}", err);
+        assert!(serde_json::from_str::<Category>("\"Gizmos\"").is_err());
+        assert!(serde_json::from_str::<Category>("\"\"").is_err());
+    }
+}
@@ -1,8 +1,29 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
 
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+/// Review state of a return. Only `Pending` devolutions can be decided.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type, ToSchema)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "devolution_status", rename_all = "lowercase")]
+pub enum DevolutionStatus {
+    Pending,
+    Approved,
+    Rejected,
+}
+
+impl DevolutionStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Approved => "approved",
+            Self::Rejected => "rejected",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
 pub struct ProductDevolution {
     pub id: Uuid,
     pub product_id: Uuid,
@@ -10,9 +31,10 @@ pub struct ProductDevolution {
     pub reason: String,
     pub returned_at: DateTime<Utc>,
     pub created_at: DateTime<Utc>,
+    pub status: DevolutionStatus,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateDevolution {
     pub product_id: Uuid,
     pub quantity: i32,
@@ -21,8 +43,14 @@ pub struct CreateDevolution {
     pub returned_at: Option<DateTime<Utc>>,
 }
 
+/// Optional body for `POST /api/devolutions/:id/reject`.
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct RejectDevolution {
+    pub rejection_reason: Option<String>,
+}
+
 /// Devolution joined with product info for richer API responses.
-#[derive(Debug, Serialize, sqlx::FromRow)]
+#[derive(Debug, Serialize, sqlx::FromRow, ToSchema)]
 pub struct DevolutionWithProduct {
     pub id: Uuid,
     pub product_id: Uuid,
@@ -32,4 +60,91 @@ pub struct DevolutionWithProduct {
     pub reason: String,
     pub returned_at: DateTime<Utc>,
     pub created_at: DateTime<Utc>,
+    pub status: DevolutionStatus,
+    pub rejection_reason: Option<String>,
+    /// When the devolution was approved or rejected
+    pub decided_at: Option<DateTime<Utc>>,
+}
+
+/// Query parameters for `GET /api/devolutions`. Every filter is optional.
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DevolutionFilters {
+    /// Only returns at or after this RFC 3339 timestamp
+    pub start_date: Option<DateTime<Utc>>,
+    /// Only returns at or before this RFC 3339 timestamp
+    pub end_date: Option<DateTime<Utc>>,
+    /// Exact category of the returned product
+    pub category: Option<String>,
+    /// Page size (default and max: 1000)
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+impl DevolutionFilters {
+    pub const MAX_LIMIT: i64 = 1000;
+
+    /// Page size actually applied.
+    pub fn page_limit(&self) -> i64 {
+        self.limit.unwrap_or(Self::MAX_LIMIT).clamp(1, Self::MAX_LIMIT)
+    }
+
+    pub fn page_offset(&self) -> i64 {
+        self.offset.unwrap_or(0).max(0)
+    }
+}
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DevolutionStatsParams {
+    /// Only count returns from the last N days (all time when omitted)
+    pub period_days: Option<u32>,
+}
+
+impl DevolutionStatsParams {
+    /// About a century; longer windows overflow Postgres timestamps anyway.
+    pub const MAX_PERIOD_DAYS: u32 = 36_500;
+}
+
+/// Return analytics for `GET /api/devolutions/stats`.
+#[derive(Debug, Serialize, sqlx::FromRow, ToSchema)]
+pub struct DevolutionStats {
+    #[sqlx(skip)]
+    pub period_days: Option<u32>,
+    pub total_returns: i64,
+    pub total_returned_quantity: i64,
+    /// Up to five products with the most returns, most returned first
+    #[sqlx(json)]
+    pub top_products: Vec<ProductReturnCount>,
+    /// Up to five most common reasons, most common first
+    #[sqlx(json)]
+    pub top_reasons: Vec<ReasonCount>,
+    /// Every category with live products, highest return rate first
+    #[sqlx(json)]
+    pub category_return_rates: Vec<CategoryReturnRate>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ProductReturnCount {
+    pub product_id: Uuid,
+    pub product_name: String,
+    pub return_count: i64,
+    pub returned_quantity: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ReasonCount {
+    pub reason: String,
+    pub return_count: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CategoryReturnRate {
+    pub category: String,
+    pub product_count: i64,
+    /// Live products in the category with at least one return
+    pub products_returned: i64,
+    pub return_count: i64,
+    /// `products_returned / product_count`
+    pub return_rate: f64,
 }
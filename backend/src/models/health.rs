@@ -0,0 +1,48 @@
+use serde::Serialize;
+use sqlx::PgPool;
+use utoipa::ToSchema;
+
+/// Checks behind `GET /api/health/detailed`.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct HealthStatus {
+    pub db_ok: bool,
+    /// Round trip of `SELECT 1`; `None` when the database didn't answer
+    pub db_latency_ms: Option<f64>,
+    /// Every in-memory set holds the same number of products
+    pub sets_consistent: bool,
+    /// Seconds since the newest stored benchmark run; `None` if there is none
+    /// or the database is down
+    pub last_benchmark_age_secs: Option<i64>,
+    pub pool_connections: PoolConnections,
+}
+
+impl HealthStatus {
+    /// The critical checks. An old (or missing) benchmark run is reported but
+    /// doesn't make the instance unready.
+    pub fn is_ready(&self) -> bool {
+        self.db_ok && self.sets_consistent
+    }
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct PoolConnections {
+    /// `max_connections` the pool was built with
+    pub max: u32,
+    /// Connections currently open, idle or in use
+    pub open: u32,
+    pub idle: usize,
+    pub active: usize,
+}
+
+impl PoolConnections {
+    pub fn of(pool: &PgPool) -> Self {
+        let open = pool.size();
+        let idle = pool.num_idle();
+        Self {
+            max: pool.options().get_max_connections(),
+            open,
+            idle,
+            active: (open as usize).saturating_sub(idle),
+        }
+    }
+}
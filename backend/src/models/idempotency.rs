@@ -0,0 +1,11 @@
+/// A response stored in `idempotency_cache`, replayed for retries that carry
+/// the same `Idempotency-Key`.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct CachedResponse {
+    /// `"<METHOD> <path>"` of the request that produced it
+    pub request_path: String,
+    pub status_code: i32,
+    /// The JSON body as text. Replaying the text rather than a parsed value
+    /// keeps every number exactly as first sent.
+    pub response_body: String,
+}
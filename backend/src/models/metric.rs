@@ -0,0 +1,25 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+/// A metrics entry as stored in the `metrics` table.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct StoredMetric {
+    pub id: i64,
+    pub timestamp: DateTime<Utc>,
+    pub operation: String,
+    pub set_type: String,
+    pub duration_ns: i64,
+    pub item_count: i32,
+    pub success: bool,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct MetricHistoryParams {
+    /// Only this operation, e.g. `insert` or `db_query:list`
+    pub operation: Option<String>,
+    /// Entries to return, newest first (default: 100, max: 10 000)
+    pub limit: Option<i64>,
+}
@@ -1,5 +1,29 @@
+pub mod audit;
+pub mod benchmark;
+pub mod category;
 pub mod devolution;
+pub mod health;
+pub mod idempotency;
+pub mod metric;
 pub mod product;
+pub mod product_csv;
+pub mod report;
+pub mod restock;
+pub mod snapshot;
+pub mod stress;
+pub mod tag;
 
+pub use audit::*;
+pub use benchmark::*;
+pub use category::*;
 pub use devolution::*;
+pub use health::*;
+pub use idempotency::*;
+pub use metric::*;
 pub use product::*;
+pub use product_csv::*;
+pub use report::*;
+pub use restock::*;
+pub use snapshot::*;
+pub use stress::*;
+pub use tag::*;
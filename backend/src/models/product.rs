@@ -1,12 +1,17 @@
-use chrono::{DateTime, Utc};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, SecondsFormat, Utc};
 use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 use std::hash::{Hash, Hasher};
+use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
 
+use super::Category;
+use crate::util::parse_comma_list;
+
 /// Core product entity. Hash/Eq are by UUID so all three set types work correctly.
 /// Ord is by (name, id) so BTreeSet demonstrates automatic alphabetical sorting.
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
 pub struct Product {
     pub id: Uuid,
     pub name: String,
@@ -17,6 +22,8 @@ pub struct Product {
     pub category: String,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Bumped on every update; see [`UpdateProduct::expected_version`]
+    pub version: i64,
 }
 
 impl Hash for Product {
@@ -54,6 +61,42 @@ impl Product {
     }
 }
 
+/// A soft-deleted product as shown in the admin view.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct DeletedProduct {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub product: Product,
+    pub deleted_at: DateTime<Utc>,
+}
+
+/// Live products sharing one name, as found by `GET /api/products/duplicates`.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct DuplicateGroup {
+    pub name: String,
+    /// Oldest first; `POST /api/products/deduplicate` keeps the first
+    pub ids: Vec<Uuid>,
+    pub count: i64,
+}
+
+/// Aggregate price/quantity figures for dashboard widgets. The price fields are
+/// `None` when no products match.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct ProductStats {
+    /// The category filter the figures were computed for, `None` for all products
+    pub category: Option<String>,
+    pub count: i64,
+    pub min_price_cents: Option<i64>,
+    pub max_price_cents: Option<i64>,
+    pub avg_price_cents: Option<f64>,
+    pub median_price_cents: Option<f64>,
+    pub total_quantity: i64,
+    /// SUM(price_cents * quantity)
+    pub total_inventory_value: i64,
+    /// Rows in `product_price_history` for the matching products
+    pub price_change_count: i64,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -68,9 +111,10 @@ mod tests {
             description: None,
             price_cents: 100,
             quantity: 1,
-            category: "Test".to_string(),
+            category: "Electronics".to_string(),
             created_at: chrono::Utc::now(),
             updated_at: chrono::Utc::now(),
+            version: 1,
         }
     }
 
@@ -161,36 +205,693 @@ mod tests {
         let p = make(Uuid::new_v4(), "Test");
         assert!((p.price_dollars() - 1.0).abs() < f64::EPSILON);
     }
+
+    // ── LIKE patterns ──────────────────────────────────────────────────────────
+
+    #[test]
+    fn contains_pattern_wraps_plain_terms() {
+        assert_eq!(contains_pattern("Widget"), "%Widget%");
+        assert_eq!(contains_pattern(""), "%%");
+    }
+
+    #[test]
+    fn contains_pattern_escapes_wildcards() {
+        assert_eq!(contains_pattern("50%"), "%50\\%%");
+        assert_eq!(contains_pattern("a_b"), "%a\\_b%");
+        assert_eq!(contains_pattern("c:\\x"), "%c:\\\\x%");
+    }
+
+    #[test]
+    fn prefix_pattern_is_left_anchored() {
+        assert_eq!(prefix_pattern("Eco"), "Eco%");
+        assert_eq!(prefix_pattern("10%_off"), "10\\%\\_off%");
+    }
+
+    #[test]
+    fn list_filters_parse_from_the_query_string() {
+        let parse = |uri: &str| axum::extract::Query::<ProductFilters>::try_from_uri(&uri.parse().unwrap()).unwrap().0;
+        assert_eq!(parse("/api/products?tags=sale,%20outdoor,,&limit=5").tags, vec!["sale", "outdoor"]);
+        assert!(parse("/api/products?limit=5").tags.is_empty());
+        let multi = parse("/api/products?categories=Electronics,%20Clothing&category=Books");
+        assert_eq!(multi.categories.as_deref(), Some(&["Electronics".to_string(), "Clothing".to_string()][..]));
+        assert_eq!(multi.category_list().unwrap(), ["Electronics", "Clothing"], "categories wins over category");
+        assert_eq!(parse("/api/products?category=Books&categories=").category_list().unwrap(), ["Books"]);
+        assert_eq!(parse("/api/products").category_list(), None);
+        assert_eq!(parse("/api/products?name_search=mug").name_contains.as_deref(), Some("mug"), "old name still works");
+    }
+
+    // ── Validation ─────────────────────────────────────────────────────────────
+
+    fn payload(name: &str, price_cents: i64) -> CreateProduct {
+        CreateProduct {
+            name: name.to_string(),
+            description: None,
+            price_cents,
+            quantity: 1,
+            category: Category::Electronics,
+        }
+    }
+
+    fn fields(result: Result<(), Vec<ValidationError>>) -> Vec<(String, String)> {
+        result.unwrap_err().into_iter().map(|e| (e.field, e.code)).collect()
+    }
+
+    fn pair(field: &str, code: &str) -> (String, String) {
+        (field.to_string(), code.to_string())
+    }
+
+    #[test]
+    fn validate_rejects_blank_name_and_negative_price() {
+        assert!(payload("Widget", 0).validate().is_ok());
+        assert_eq!(payload("   ", 100).validate().unwrap_err()[0].message, "name must not be empty");
+        assert_eq!(fields(payload("Widget", -1).validate()), vec![pair("price_cents", "out_of_range")]);
+    }
+
+    #[test]
+    fn validate_enforces_upper_bounds() {
+        assert!(payload(&"n".repeat(MAX_NAME_CHARS), MAX_PRICE_CENTS).validate().is_ok());
+        assert_eq!(fields(payload(&"n".repeat(MAX_NAME_CHARS + 1), 0).validate()), vec![pair("name", "too_long")]);
+        assert_eq!(fields(payload("Widget", MAX_PRICE_CENTS + 1).validate()), vec![pair("price_cents", "out_of_range")]);
+        // Characters, not bytes
+        assert!(payload(&"é".repeat(MAX_NAME_CHARS), 0).validate().is_ok());
+    }
+
+    #[test]
+    fn validate_reports_every_failing_field() {
+        let bad = CreateProduct {
+            name: "".to_string(),
+            description: None,
+            price_cents: -5,
+            quantity: MAX_QUANTITY + 1,
+            category: Category::Books,
+        };
+        assert_eq!(
+            fields(bad.validate()),
+            vec![
+                pair("name", "required"),
+                pair("price_cents", "out_of_range"),
+                pair("quantity", "out_of_range"),
+            ]
+        );
+
+        let mut product = make(Uuid::new_v4(), "");
+        product.quantity = -1;
+        assert_eq!(fields(product.validate()), vec![pair("name", "required"), pair("quantity", "out_of_range")]);
+        product.category = "electronics".to_string();
+        assert_eq!(fields(product.validate())[2], pair("category", "unknown"), "stored names are case-sensitive");
+    }
+
+    #[test]
+    fn update_validation_skips_omitted_fields() {
+        let empty = UpdateProduct {
+            name: None,
+            description: None,
+            price_cents: None,
+            quantity: None,
+            category: None,
+            expected_version: None,
+        };
+        assert!(empty.validate().is_ok());
+
+        let bad = UpdateProduct { name: Some(" ".to_string()), quantity: Some(-3), ..empty };
+        assert_eq!(fields(bad.validate()), vec![pair("name", "required"), pair("quantity", "out_of_range")]);
+    }
+
+    #[test]
+    fn bulk_request_defaults_to_partial_success() {
+        let req: BulkCreateRequest = serde_json::from_str(r#"{ "products": [] }"#).unwrap();
+        assert!(!req.abort_on_error);
+    }
+
+    // ── Pagination cursor ──────────────────────────────────────────────────────
+
+    #[test]
+    fn cursor_round_trips_with_full_precision() {
+        let p = make(Uuid::new_v4(), "Test");
+        let cursor = ProductCursor::after(&p);
+        let decoded = ProductCursor::decode(&cursor.encode()).unwrap();
+        assert_eq!(decoded, cursor);
+        assert_eq!(decoded.created_at, p.created_at, "sub-second precision must survive");
+    }
+
+    #[test]
+    fn cursor_is_url_safe() {
+        let token = ProductCursor::after(&make(Uuid::new_v4(), "Test")).encode();
+        assert!(token.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+    }
+
+    #[test]
+    fn cursor_decode_rejects_garbage() {
+        assert!(ProductCursor::decode("").is_none());
+        assert!(ProductCursor::decode("not base64!").is_none());
+        assert!(ProductCursor::decode(&URL_SAFE_NO_PAD.encode("no-separator")).is_none());
+        assert!(ProductCursor::decode(&URL_SAFE_NO_PAD.encode("2024-01-01T00:00:00Z|nope")).is_none());
+    }
+
+    // ── Duplicate names ────────────────────────────────────────────────────────
+
+    #[test]
+    fn duplicate_candidates_start_with_copy_then_number() {
+        let names = DuplicateProduct::default().candidate_names("Lamp");
+        assert_eq!(names.len(), DuplicateProduct::MAX_NAME_ATTEMPTS);
+        assert_eq!(names[..3], ["Lamp (copy)", "Lamp (copy 2)", "Lamp (copy 3)"]);
+        assert_eq!(names.last().unwrap(), "Lamp (copy 10)");
+
+        let renamed = DuplicateProduct { name_override: Some("Torch".to_string()), ..Default::default() };
+        assert_eq!(renamed.candidate_names("Lamp")[..2], ["Torch", "Torch (copy 2)"]);
+    }
+}
+
+// ── Validation ───────────────────────────────────────────────────────────────
+
+pub const MAX_NAME_CHARS: usize = 200;
+/// $99 999.99
+pub const MAX_PRICE_CENTS: i64 = 9_999_999;
+pub const MAX_QUANTITY: i32 = 1_000_000;
+
+/// One broken rule, reported per field in a 422 response.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct ValidationError {
+    pub field: String,
+    /// Machine-readable: `required`, `too_long`, `out_of_range` or `unknown`
+    pub code: String,
+    pub message: String,
+}
+
+impl ValidationError {
+    pub(crate) fn new(field: &str, code: &str, message: String) -> Self {
+        Self { field: field.to_string(), code: code.to_string(), message }
+    }
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+fn check_name(name: &str, errors: &mut Vec<ValidationError>) {
+    let chars = name.trim().chars().count();
+    if chars == 0 {
+        errors.push(ValidationError::new("name", "required", "name must not be empty".to_string()));
+    } else if chars > MAX_NAME_CHARS {
+        errors.push(ValidationError::new(
+            "name",
+            "too_long",
+            format!("name must be at most {} characters", MAX_NAME_CHARS),
+        ));
+    }
+}
+
+fn check_price(price_cents: i64, errors: &mut Vec<ValidationError>) {
+    if !(0..=MAX_PRICE_CENTS).contains(&price_cents) {
+        errors.push(ValidationError::new(
+            "price_cents",
+            "out_of_range",
+            format!("price_cents must be between 0 and {}", MAX_PRICE_CENTS),
+        ));
+    }
+}
+
+fn check_quantity(quantity: i32, errors: &mut Vec<ValidationError>) {
+    if !(0..=MAX_QUANTITY).contains(&quantity) {
+        errors.push(ValidationError::new(
+            "quantity",
+            "out_of_range",
+            format!("quantity must be between 0 and {}", MAX_QUANTITY),
+        ));
+    }
+}
+
+/// Request payloads carry a [`Category`]; this catches stored rows whose
+/// category predates the fixed list.
+fn check_category(category: &str, errors: &mut Vec<ValidationError>) {
+    if category.trim().is_empty() {
+        errors.push(ValidationError::new("category", "required", "category must not be empty".to_string()));
+    } else if !Category::is_stored_name(category) {
+        errors.push(ValidationError::new("category", "unknown", format!("unknown category '{}'", category)));
+    }
+}
+
+/// All messages on one line, for places that report a single string per item.
+pub fn join_validation_errors(errors: &[ValidationError]) -> String {
+    errors.iter().map(|e| e.message.as_str()).collect::<Vec<_>>().join("; ")
+}
+
+fn into_result(errors: Vec<ValidationError>) -> Result<(), Vec<ValidationError>> {
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+impl Product {
+    /// The business rules every stored product must satisfy. Request payloads
+    /// check the same rules through [`CreateProduct::validate`] and
+    /// [`UpdateProduct::validate`].
+    pub fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        let mut errors = Vec::new();
+        check_name(&self.name, &mut errors);
+        check_price(self.price_cents, &mut errors);
+        check_quantity(self.quantity, &mut errors);
+        check_category(&self.category, &mut errors);
+        into_result(errors)
+    }
 }
 
 // ── Request payloads ─────────────────────────────────────────────────────────
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateProduct {
     pub name: String,
     pub description: Option<String>,
     /// Price in cents
     pub price_cents: i64,
     pub quantity: i32,
-    pub category: String,
+    pub category: Category,
+}
+
+impl CreateProduct {
+    /// Checks every field against the product rules, reporting all failures at once.
+    pub fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        let mut errors = Vec::new();
+        check_name(&self.name, &mut errors);
+        check_price(self.price_cents, &mut errors);
+        check_quantity(self.quantity, &mut errors);
+        into_result(errors)
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct BulkCreateRequest {
+    pub products: Vec<CreateProduct>,
+    /// `true`: all-or-nothing in one transaction. `false`: insert what can be
+    /// inserted and report the rest in [`BulkResult::errors`].
+    #[serde(default)]
+    pub abort_on_error: bool,
+}
+
+impl BulkCreateRequest {
+    pub const MAX_ITEMS: usize = 10_000;
+}
+
+/// Body of `DELETE /api/products/bulk`.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct BulkDeleteRequest {
+    pub ids: Vec<Uuid>,
+    /// Report what would be deleted without deleting anything
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+impl BulkDeleteRequest {
+    pub const MAX_IDS: usize = 10_000;
+}
+
+/// Optional body for `POST /api/products/:id/duplicate`.
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct DuplicateProduct {
+    /// Name for the copy instead of `"<source name> (copy)"`
+    pub name_override: Option<String>,
+    /// Quantity for the copy instead of the source's
+    pub quantity_override: Option<i32>,
 }
 
-#[derive(Debug, Deserialize)]
+impl DuplicateProduct {
+    /// Names tried before giving up with a 409
+    pub const MAX_NAME_ATTEMPTS: usize = 10;
+
+    /// Names to try for a copy of `source_name`, in order: the override (or
+    /// `"<source> (copy)"`), then `"<base> (copy 2)"` up to `(copy 10)`.
+    pub fn candidate_names(&self, source_name: &str) -> Vec<String> {
+        let base = self.name_override.as_deref().unwrap_or(source_name);
+        let first = match &self.name_override {
+            Some(name) => name.clone(),
+            None => format!("{} (copy)", source_name),
+        };
+        std::iter::once(first)
+            .chain((2..=Self::MAX_NAME_ATTEMPTS).map(|n| format!("{} (copy {})", base, n)))
+            .collect()
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BulkResult {
+    pub created: Vec<Product>,
+    pub errors: Vec<BulkError>,
+}
+
+/// Why the item at `index` in the request was not created.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BulkError {
+    pub index: usize,
+    pub error: String,
+}
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ImportParams {
+    /// Validate the file and report what would be imported, without writing
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Result of `POST /api/products/import/csv`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ImportReport {
+    pub total_rows: usize,
+    /// Rows inserted — or, on a dry run, rows that would be inserted
+    pub imported: usize,
+    pub skipped: usize,
+    pub errors: Vec<ImportRowError>,
+    pub dry_run: bool,
+}
+
+/// Why a CSV row was skipped. `row` is 1-based and excludes the header line.
+#[derive(Debug, Serialize, PartialEq, ToSchema)]
+pub struct ImportRowError {
+    pub row: usize,
+    pub message: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdateProduct {
     pub name: Option<String>,
     pub description: Option<String>,
     pub price_cents: Option<i64>,
     pub quantity: Option<i32>,
-    pub category: Option<String>,
+    pub category: Option<Category>,
+    /// When set, the update only applies if the stored version still matches;
+    /// otherwise it fails with 409 Conflict.
+    pub expected_version: Option<i64>,
+}
+
+impl UpdateProduct {
+    /// Checks the fields that are present; omitted fields keep their stored value.
+    pub fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        let mut errors = Vec::new();
+        if let Some(name) = &self.name {
+            check_name(name, &mut errors);
+        }
+        if let Some(price_cents) = self.price_cents {
+            check_price(price_cents, &mut errors);
+        }
+        if let Some(quantity) = self.quantity {
+            check_quantity(quantity, &mut errors);
+        }
+        into_result(errors)
+    }
+}
+
+/// Body of `POST /api/products/:id/adjust-quantity`.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct QuantityAdjustment {
+    /// Added to the current quantity; negative to take stock out
+    pub delta: i32,
+    /// Floor the resulting quantity must not drop below (default: 0)
+    pub min_quantity: Option<i32>,
+    /// Free-text note for the log, e.g. "cycle count correction"
+    pub reason: Option<String>,
 }
 
 // ── Query parameters ──────────────────────────────────────────────────────────
 
-#[derive(Debug, Deserialize, Default)]
+#[derive(Debug, Deserialize, Default, IntoParams, ToSchema)]
+#[into_params(parameter_in = Query)]
 pub struct ProductFilters {
+    /// Exact category name; ignored when `categories` is given
     pub category: Option<String>,
+    /// Comma-separated category names; products in any of them are listed
+    #[serde(default, deserialize_with = "comma_list")]
+    #[param(value_type = Option<String>, example = "Electronics,Clothing")]
+    #[schema(value_type = Option<String>)]
+    pub categories: Option<Vec<String>>,
     pub min_price_cents: Option<i64>,
     pub max_price_cents: Option<i64>,
     pub limit: Option<i64>,
     pub offset: Option<i64>,
+    /// Opaque `next_cursor` token from a previous page. When present, keyset
+    /// pagination is used and `offset` is ignored.
+    pub cursor: Option<String>,
+    /// Case-insensitive substring match on `name` (plain `ILIKE`, no index
+    /// needed). `%`, `_` and `\` match literally. Also accepted as `name_search`.
+    #[serde(alias = "name_search")]
+    pub name_contains: Option<String>,
+    /// Column to order by (default: `created_at`)
+    pub sort_by: Option<SortField>,
+    /// Default: `desc` when `sort_by` is absent, `asc` otherwise
+    pub sort_order: Option<SortOrder>,
+    /// Comma-separated tag names; only products carrying every one are listed
+    #[serde(default, deserialize_with = "comma_separated")]
+    #[param(value_type = Option<String>, example = "sale,outdoor")]
+    #[schema(value_type = Option<String>)]
+    pub tags: Vec<String>,
+}
+
+/// `"a, b,,c"` → `["a", "b", "c"]`; a missing parameter is an empty list.
+fn comma_separated<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    Ok(comma_list(deserializer)?.unwrap_or_default())
+}
+
+/// Like [`comma_separated`], but a missing or blank parameter is `None`.
+fn comma_list<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Option<Vec<String>>, D::Error> {
+    Ok(parse_comma_list(Option::<String>::deserialize(deserializer)?))
+}
+
+/// Sortable product columns for `GET /api/products`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SortField {
+    Name,
+    PriceCents,
+    Quantity,
+    CreatedAt,
+    UpdatedAt,
+    Category,
+}
+
+impl SortField {
+    /// Column name as written in SQL. These fixed strings are the only part of
+    /// a client's sort choice that reaches the query text.
+    pub fn column(self) -> &'static str {
+        match self {
+            Self::Name => "name",
+            Self::PriceCents => "price_cents",
+            Self::Quantity => "quantity",
+            Self::CreatedAt => "created_at",
+            Self::UpdatedAt => "updated_at",
+            Self::Category => "category",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+impl SortOrder {
+    pub fn keyword(self) -> &'static str {
+        match self {
+            Self::Asc => "ASC",
+            Self::Desc => "DESC",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SearchParams {
+    /// Free-text query, matched against name, category and description
+    pub q: String,
+    /// Max results (default: 50, max: 500)
+    pub limit: Option<i64>,
+}
+
+/// Per-category totals over live products.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct CategorySummary {
+    pub category: String,
+    pub product_count: i64,
+    pub total_quantity: i64,
+    pub total_value_cents: i64,
+}
+
+/// One recorded price change, newest first in API responses.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct PriceHistoryEntry {
+    pub id: Uuid,
+    pub product_id: Uuid,
+    pub old_price_cents: i64,
+    pub new_price_cents: i64,
+    pub changed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PriceHistoryParams {
+    /// Entries to return, newest first (default: 50, max: 500)
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct StatsParams {
+    pub category: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct RelatedParams {
+    /// Products per strategy (default: 5, max: 100)
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct LowStockParams {
+    /// Products with `quantity <= threshold` are listed (default: 10)
+    pub threshold: Option<i32>,
+}
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AutocompleteParams {
+    /// Name prefix, matched case-sensitively
+    pub q: String,
+    /// Suggestions to return (default: 10, max: 100)
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PriceRangeParams {
+    /// Inclusive lower bound in cents (default: 0)
+    pub min_price: Option<i64>,
+    /// Inclusive upper bound in cents (default: no limit)
+    pub max_price: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SuggestParams {
+    /// Name prefix, matched case-insensitively
+    pub q: String,
+    /// Suggestions to return (default and max: 10)
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PriceDistributionParams {
+    /// Comma-separated ascending bucket boundaries in cents, e.g.
+    /// `0,1000,5000,10000`; the last bucket is open-ended (default:
+    /// `0,1000,5000,10000,50000`, at most 50 buckets)
+    pub buckets: Option<String>,
+}
+
+impl ProductFilters {
+    /// Page size actually applied (default 1 000, capped at 10 000).
+    pub fn page_limit(&self) -> i64 {
+        self.limit.unwrap_or(1000).min(10_000)
+    }
+
+    /// Sort column and direction actually applied.
+    pub fn sort(&self) -> (SortField, SortOrder) {
+        match (self.sort_by, self.sort_order) {
+            (None, order) => (SortField::CreatedAt, order.unwrap_or(SortOrder::Desc)),
+            (Some(field), order) => (field, order.unwrap_or(SortOrder::Asc)),
+        }
+    }
+
+    /// Keyset cursors encode `(created_at, id)`, so they only work with the
+    /// default newest-first order.
+    pub fn supports_cursor(&self) -> bool {
+        self.sort() == (SortField::CreatedAt, SortOrder::Desc)
+    }
+
+    /// `ORDER BY` body for [`Self::sort`], with `id` as the tiebreak so pages
+    /// are stable.
+    pub fn order_by_clause(&self) -> String {
+        let (field, order) = self.sort();
+        format!("{} {}, id ASC", field.column(), order.keyword())
+    }
+
+    /// Categories to match, as a ready-to-bind array: `categories` when given,
+    /// otherwise the single `category`. `None` means any category.
+    pub fn category_list(&self) -> Option<Vec<String>> {
+        self.categories.clone().or_else(|| self.category.clone().map(|c| vec![c]))
+    }
+
+    /// `name_contains` as a ready-to-bind `ILIKE` pattern.
+    pub fn name_pattern(&self) -> Option<String> {
+        self.name_contains.as_deref().map(contains_pattern)
+    }
+}
+
+/// Wraps `term` in `%…%` for `ILIKE`, escaping `\`, `%` and `_` so they match
+/// literally (Postgres' default LIKE escape character is `\`).
+pub fn contains_pattern(term: &str) -> String {
+    let mut out = String::with_capacity(term.len() + 2);
+    out.push('%');
+    push_escaped(&mut out, term);
+    out.push('%');
+    out
+}
+
+/// Like [`contains_pattern`], but anchored: matches names starting with `prefix`.
+pub fn prefix_pattern(prefix: &str) -> String {
+    let mut out = String::with_capacity(prefix.len() + 1);
+    push_escaped(&mut out, prefix);
+    out.push('%');
+    out
+}
+
+fn push_escaped(out: &mut String, term: &str) {
+    for c in term.chars() {
+        if matches!(c, '\\' | '%' | '_') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+}
+
+/// Keyset pagination position: `(created_at, id)` of the last product served.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProductCursor {
+    pub created_at: DateTime<Utc>,
+    pub id: Uuid,
+}
+
+impl ProductCursor {
+    pub fn after(product: &Product) -> Self {
+        Self {
+            created_at: product.created_at,
+            id: product.id,
+        }
+    }
+
+    /// URL-safe base64 of `"<rfc3339 created_at>|<id>"`.
+    pub fn encode(&self) -> String {
+        let raw = format!(
+            "{}|{}",
+            self.created_at.to_rfc3339_opts(SecondsFormat::Nanos, true),
+            self.id
+        );
+        URL_SAFE_NO_PAD.encode(raw)
+    }
+
+    /// Returns `None` for anything that isn't a token produced by `encode`.
+    pub fn decode(token: &str) -> Option<Self> {
+        let bytes = URL_SAFE_NO_PAD.decode(token).ok()?;
+        let raw = String::from_utf8(bytes).ok()?;
+        let (ts, id) = raw.split_once('|')?;
+        Some(Self {
+            created_at: DateTime::parse_from_rfc3339(ts).ok()?.with_timezone(&Utc),
+            id: Uuid::parse_str(id).ok()?,
+        })
+    }
 }
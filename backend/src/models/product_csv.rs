@@ -0,0 +1,248 @@
+use chrono::{DateTime, Utc};
+use csv::StringRecord;
+use serde::Serialize;
+use std::str::FromStr;
+use uuid::Uuid;
+
+use super::{Category, CreateProduct, Product};
+
+/// Export column order: [`Product::to_csv_row`] writes it and
+/// `TryFrom<StringRecord>` for [`Product`] reads it back.
+pub const PRODUCT_CSV_COLUMNS: [&str; 8] =
+    ["id", "name", "description", "price_cents", "quantity", "category", "created_at", "updated_at"];
+
+/// One field of one CSV record that could not be turned into a product.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CsvParseError {
+    /// 1-based data row; the header row is not counted
+    pub row: usize,
+    pub field: String,
+    pub message: String,
+}
+
+impl CsvParseError {
+    fn new(row: usize, field: &str, message: String) -> Self {
+        Self { row, field: field.to_string(), message }
+    }
+}
+
+impl std::fmt::Display for CsvParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for CsvParseError {}
+
+/// Where each [`CreateProduct`] field sits in an import record. Columns not
+/// listed here are ignored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CsvFieldMap {
+    pub name_col: usize,
+    /// `None` when the file has no description column
+    pub description_col: Option<usize>,
+    pub price_col: usize,
+    pub quantity_col: usize,
+    pub category_col: usize,
+}
+
+impl CsvFieldMap {
+    /// Header names the import columns are looked up by, in error-report order.
+    pub const REQUIRED_COLUMNS: [&'static str; 4] = ["name", "price_cents", "quantity", "category"];
+
+    /// Finds the import columns by name in `headers`, in any order. Fails with
+    /// every missing required column listed.
+    pub fn from_headers(headers: &StringRecord) -> Result<Self, String> {
+        let position = |col: &str| headers.iter().position(|h| h == col);
+        let missing: Vec<&str> = Self::REQUIRED_COLUMNS.into_iter().filter(|col| position(col).is_none()).collect();
+        match Self::REQUIRED_COLUMNS.map(position) {
+            [Some(name_col), Some(price_col), Some(quantity_col), Some(category_col)] => Ok(Self {
+                name_col,
+                description_col: position("description"),
+                price_col,
+                quantity_col,
+                category_col,
+            }),
+            _ => Err(format!("CSV header is missing column(s): {}", missing.join(", "))),
+        }
+    }
+}
+
+/// 1-based data row of `record`, taken from its position in the reader.
+fn data_row(record: &StringRecord) -> usize {
+    // The header is record 0, so the first data record is already row 1
+    record.position().map_or(0, |p| p.record() as usize)
+}
+
+fn required<'r>(record: &'r StringRecord, row: usize, col: usize, field: &str) -> Result<&'r str, CsvParseError> {
+    record
+        .get(col)
+        .ok_or_else(|| CsvParseError::new(row, field, format!("{} is missing", field)))
+}
+
+fn optional(record: &StringRecord, col: usize) -> Option<String> {
+    record.get(col).filter(|v| !v.is_empty()).map(str::to_string)
+}
+
+fn number<T: FromStr>(record: &StringRecord, row: usize, col: usize, field: &str) -> Result<T, CsvParseError> {
+    let value = required(record, row, col, field)?;
+    value
+        .trim()
+        .parse()
+        .map_err(|_| CsvParseError::new(row, field, format!("{} must be a whole number, got '{}'", field, value)))
+}
+
+fn category(record: &StringRecord, row: usize, col: usize) -> Result<Category, CsvParseError> {
+    let value = required(record, row, col, "category")?;
+    Category::try_from(value.to_string()).map_err(|e| CsvParseError::new(row, "category", e.to_string()))
+}
+
+fn timestamp(record: &StringRecord, row: usize, col: usize, field: &str) -> Result<DateTime<Utc>, CsvParseError> {
+    let value = required(record, row, col, field)?;
+    DateTime::parse_from_rfc3339(value.trim())
+        .map(|t| t.with_timezone(&Utc))
+        .map_err(|e| CsvParseError::new(row, field, format!("{} is not an RFC 3339 timestamp: {}", field, e)))
+}
+
+impl Product {
+    /// This product as one export record, in [`PRODUCT_CSV_COLUMNS`] order.
+    pub fn to_csv_row(&self) -> Vec<String> {
+        vec![
+            self.id.to_string(),
+            self.name.clone(),
+            self.description.clone().unwrap_or_default(),
+            self.price_cents.to_string(),
+            self.quantity.to_string(),
+            self.category.clone(),
+            self.created_at.to_rfc3339(),
+            self.updated_at.to_rfc3339(),
+        ]
+    }
+}
+
+/// Reads a record written by [`Product::to_csv_row`]. The export carries no
+/// version, so the product comes back at version 1.
+impl TryFrom<StringRecord> for Product {
+    type Error = CsvParseError;
+
+    fn try_from(record: StringRecord) -> Result<Self, Self::Error> {
+        let row = data_row(&record);
+        let id = required(&record, row, 0, "id")?;
+        let id = Uuid::parse_str(id.trim())
+            .map_err(|_| CsvParseError::new(row, "id", format!("id must be a UUID, got '{}'", id)))?;
+        let product = Product {
+            id,
+            name: required(&record, row, 1, "name")?.to_string(),
+            description: optional(&record, 2),
+            price_cents: number(&record, row, 3, "price_cents")?,
+            quantity: number(&record, row, 4, "quantity")?,
+            category: category(&record, row, 5)?.as_str().to_string(),
+            created_at: timestamp(&record, row, 6, "created_at")?,
+            updated_at: timestamp(&record, row, 7, "updated_at")?,
+            version: 1,
+        };
+        match product.validate() {
+            Ok(()) => Ok(product),
+            Err(errors) => Err(CsvParseError::new(row, &errors[0].field, errors[0].message.clone())),
+        }
+    }
+}
+
+impl CreateProduct {
+    /// Reads the fields `field_map` points at. Values are only parsed here;
+    /// the business rules are still up to [`CreateProduct::validate`].
+    pub fn from_csv_record(record: &StringRecord, field_map: &CsvFieldMap) -> Result<CreateProduct, CsvParseError> {
+        let row = data_row(record);
+        Ok(CreateProduct {
+            name: required(record, row, field_map.name_col, "name")?.to_string(),
+            description: field_map.description_col.and_then(|col| optional(record, col)),
+            price_cents: number(record, row, field_map.price_col, "price_cents")?,
+            quantity: number(record, row, field_map.quantity_col, "quantity")?,
+            category: category(record, row, field_map.category_col)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn records(data: &str) -> (StringRecord, Vec<StringRecord>) {
+        let mut reader = csv::ReaderBuilder::new().trim(csv::Trim::All).from_reader(data.as_bytes());
+        let headers = reader.headers().unwrap().clone();
+        (headers, reader.records().map(Result::unwrap).collect())
+    }
+
+    #[test]
+    fn field_map_finds_columns_in_any_order_and_ignores_extras() {
+        let (headers, rows) = records("sku,category,quantity,price_cents,name,warehouse\nA-1,books,3,1250,Atlas,North\n");
+        let map = CsvFieldMap::from_headers(&headers).unwrap();
+        assert_eq!(
+            map,
+            CsvFieldMap { name_col: 4, description_col: None, price_col: 3, quantity_col: 2, category_col: 1 }
+        );
+
+        let product = CreateProduct::from_csv_record(&rows[0], &map).unwrap();
+        assert_eq!((product.name.as_str(), product.price_cents, product.quantity), ("Atlas", 1250, 3));
+        assert_eq!(product.category, Category::Books);
+        assert_eq!(product.description, None);
+    }
+
+    #[test]
+    fn missing_required_columns_and_fields_are_reported() {
+        let (headers, _) = records("name,price_cents\nLamp,100\n");
+        assert_eq!(
+            CsvFieldMap::from_headers(&headers).unwrap_err(),
+            "CSV header is missing column(s): quantity, category"
+        );
+
+        // A short record leaves the mapped column out entirely
+        let (headers, rows) = {
+            let mut reader = csv::ReaderBuilder::new().flexible(true).from_reader(&b"name,price_cents,quantity,category\nLamp,100\n"[..]);
+            let headers = reader.headers().unwrap().clone();
+            (headers, reader.records().map(Result::unwrap).collect::<Vec<_>>())
+        };
+        let map = CsvFieldMap::from_headers(&headers).unwrap();
+        let err = CreateProduct::from_csv_record(&rows[0], &map).unwrap_err();
+        assert_eq!(err, CsvParseError::new(1, "quantity", "quantity is missing".to_string()));
+    }
+
+    #[test]
+    fn non_numeric_price_names_the_field_and_row() {
+        let (headers, rows) = records("name,price_cents,quantity,category\nLamp,100,1,Books\nRug,abc,1,Books\n");
+        let map = CsvFieldMap::from_headers(&headers).unwrap();
+        assert!(CreateProduct::from_csv_record(&rows[0], &map).is_ok());
+
+        let err = CreateProduct::from_csv_record(&rows[1], &map).unwrap_err();
+        assert_eq!((err.row, err.field.as_str()), (2, "price_cents"));
+        assert_eq!(err.message, "price_cents must be a whole number, got 'abc'");
+    }
+
+    #[test]
+    fn products_round_trip_through_export_records() {
+        let now = Utc::now();
+        let product = Product {
+            id: Uuid::new_v4(),
+            name: "Desk, oak".to_string(),
+            description: None,
+            price_cents: 24_900,
+            quantity: 7,
+            category: "Books".to_string(),
+            created_at: now,
+            updated_at: now,
+            version: 4,
+        };
+        let row = product.to_csv_row();
+        assert_eq!(row.len(), PRODUCT_CSV_COLUMNS.len());
+
+        let back = Product::try_from(StringRecord::from(row)).unwrap();
+        assert_eq!(back.id, product.id);
+        assert_eq!((back.name.as_str(), back.description, back.price_cents), ("Desk, oak", None, 24_900));
+        assert_eq!((back.created_at, back.version), (now, 1));
+
+        let mut bad = product.to_csv_row();
+        bad[4] = "-3".to_string();
+        let err = Product::try_from(StringRecord::from(bad)).unwrap_err();
+        assert_eq!(err.field, "quantity");
+    }
+}
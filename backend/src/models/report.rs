@@ -0,0 +1,249 @@
+use serde::Serialize;
+use sqlx::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::Product;
+use crate::util::parse_comma_list;
+
+/// One line of `GET /api/reports/inventory-value`: a product and the value of
+/// its stock on hand.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, FromRow, ToSchema)]
+pub struct InventoryReportRow {
+    pub product_id: Uuid,
+    pub name: String,
+    pub category: String,
+    pub price_cents: i64,
+    pub quantity: i32,
+    /// `price_cents * quantity`
+    pub total_value_cents: i64,
+}
+
+impl From<&Product> for InventoryReportRow {
+    fn from(p: &Product) -> Self {
+        Self {
+            product_id: p.id,
+            name: p.name.clone(),
+            category: p.category.clone(),
+            price_cents: p.price_cents,
+            quantity: p.quantity,
+            total_value_cents: p.price_cents.saturating_mul(i64::from(p.quantity)),
+        }
+    }
+}
+
+/// Cumulative value share, in percent, up to which products are class A.
+pub const ABC_A_THRESHOLD_PCT: f64 = 80.0;
+/// Cumulative value share, in percent, up to which products are class B.
+pub const ABC_B_THRESHOLD_PCT: f64 = 95.0;
+
+/// ABC class of a product by its share of total stock value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+pub enum AbcClass {
+    A,
+    B,
+    C,
+}
+
+/// A product with the value of its stock and its ABC class.
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct AbcItem {
+    #[serde(flatten)]
+    pub product: Product,
+    /// `price_cents * quantity`
+    pub value_cents: i64,
+    /// Share of the total value held by this product and every more valuable one
+    pub cumulative_pct: f64,
+    pub class: AbcClass,
+}
+
+/// `GET /api/products/abc-analysis`: every product classified by how much of
+/// the total stock value it holds, most valuable first.
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct AbcAnalysisResult {
+    pub total_value_cents: i64,
+    pub a_count: usize,
+    pub b_count: usize,
+    pub c_count: usize,
+    pub a_value_pct: f64,
+    pub b_value_pct: f64,
+    pub c_value_pct: f64,
+    pub items: Vec<AbcItem>,
+}
+
+impl AbcAnalysisResult {
+    /// Classifies `products`, which must already be sorted most valuable
+    /// first. A product is class A while the value before it is under
+    /// [`ABC_A_THRESHOLD_PCT`] of the total, B while under
+    /// [`ABC_B_THRESHOLD_PCT`], and C after that, so the product that crosses
+    /// a threshold still belongs to the higher class. With no stock value at
+    /// all, everything is C.
+    pub fn classify(products: Vec<Product>) -> Self {
+        let value_of = |p: &Product| p.price_cents.saturating_mul(i64::from(p.quantity));
+        let total_value_cents: i64 = products.iter().map(value_of).sum();
+        let pct = |value: i64| if total_value_cents > 0 { value as f64 * 100.0 / total_value_cents as f64 } else { 0.0 };
+
+        let mut result = Self {
+            total_value_cents,
+            a_count: 0,
+            b_count: 0,
+            c_count: 0,
+            a_value_pct: 0.0,
+            b_value_pct: 0.0,
+            c_value_pct: 0.0,
+            items: Vec::with_capacity(products.len()),
+        };
+        let mut cumulative = 0;
+        for product in products {
+            let value_cents = value_of(&product);
+            let before_pct = pct(cumulative);
+            cumulative += value_cents;
+            let class = match before_pct {
+                _ if total_value_cents <= 0 => AbcClass::C,
+                p if p < ABC_A_THRESHOLD_PCT => AbcClass::A,
+                p if p < ABC_B_THRESHOLD_PCT => AbcClass::B,
+                _ => AbcClass::C,
+            };
+            let (count, value_pct) = match class {
+                AbcClass::A => (&mut result.a_count, &mut result.a_value_pct),
+                AbcClass::B => (&mut result.b_count, &mut result.b_value_pct),
+                AbcClass::C => (&mut result.c_count, &mut result.c_value_pct),
+            };
+            *count += 1;
+            *value_pct += pct(value_cents);
+            result.items.push(AbcItem { product, value_cents, cumulative_pct: pct(cumulative), class });
+        }
+        result
+    }
+}
+
+/// Bucket boundaries `GET /api/products/price-distribution` uses when none are given.
+pub const DEFAULT_PRICE_BUCKETS: [i64; 5] = [0, 1_000, 5_000, 10_000, 50_000];
+/// Most buckets one price distribution may have.
+pub const MAX_PRICE_BUCKETS: usize = 50;
+
+/// Products priced within `[lower_cents, upper_cents)`, and the value of
+/// their stock. The last bucket has no upper bound.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct PriceBucket {
+    pub lower_cents: i64,
+    /// `None` for the last bucket
+    pub upper_cents: Option<i64>,
+    pub count: i64,
+    /// Sum of `price_cents * quantity` over the bucket
+    pub total_value_cents: i64,
+}
+
+impl PriceBucket {
+    /// One empty bucket per boundary in `buckets` (ascending), each reaching
+    /// up to the next boundary.
+    pub fn empty_histogram(buckets: &[i64]) -> Vec<PriceBucket> {
+        buckets
+            .iter()
+            .enumerate()
+            .map(|(i, &lower_cents)| PriceBucket {
+                lower_cents,
+                upper_cents: buckets.get(i + 1).copied(),
+                count: 0,
+                total_value_cents: 0,
+            })
+            .collect()
+    }
+}
+
+/// Parses `?buckets=0,1000,5000` into ascending boundaries in cents. The
+/// first bucket always starts at 0, so a list that doesn't start there gets
+/// it added; with no list, [`DEFAULT_PRICE_BUCKETS`] apply.
+pub fn parse_price_buckets(raw: Option<&str>) -> Result<Vec<i64>, String> {
+    let Some(items) = parse_comma_list(raw.map(str::to_string)) else {
+        return Ok(DEFAULT_PRICE_BUCKETS.to_vec());
+    };
+    let mut buckets = Vec::with_capacity(items.len() + 1);
+    for item in &items {
+        let bound: i64 = item.parse().map_err(|_| format!("bucket boundary '{}' is not a whole number of cents", item))?;
+        if bound < 0 {
+            return Err(format!("bucket boundary {} must not be negative", bound));
+        }
+        if buckets.last().is_some_and(|&prev| bound <= prev) {
+            return Err("bucket boundaries must be strictly ascending".to_string());
+        }
+        buckets.push(bound);
+    }
+    if buckets[0] != 0 {
+        buckets.insert(0, 0);
+    }
+    if buckets.len() > MAX_PRICE_BUCKETS {
+        return Err(format!("at most {} buckets are allowed, got {}", MAX_PRICE_BUCKETS, buckets.len()));
+    }
+    Ok(buckets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn valued(name: &str, price_cents: i64, quantity: i32) -> Product {
+        Product {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            description: None,
+            price_cents,
+            quantity,
+            category: "Books".to_string(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            version: 1,
+        }
+    }
+
+    #[test]
+    fn abc_classes_follow_the_cumulative_value_share() {
+        // 70% + 15% + 10% + 4% + 1% of 10_000
+        let products = [("W", 7000), ("X", 1500), ("Y", 1000), ("Z", 400), ("Q", 100)]
+            .iter()
+            .map(|&(name, price)| valued(name, price, 1))
+            .collect();
+        let result = AbcAnalysisResult::classify(products);
+
+        let classes: Vec<AbcClass> = result.items.iter().map(|i| i.class).collect();
+        assert_eq!(classes, [AbcClass::A, AbcClass::A, AbcClass::B, AbcClass::C, AbcClass::C]);
+        assert_eq!((result.a_count, result.b_count, result.c_count), (2, 1, 2));
+        assert_eq!(result.a_count + result.b_count + result.c_count, result.items.len());
+        assert_eq!(result.total_value_cents, 10_000);
+        assert!((result.a_value_pct - 85.0).abs() < 1e-9 && (result.c_value_pct - 5.0).abs() < 1e-9);
+        assert!((result.items[4].cumulative_pct - 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn abc_analysis_without_stock_value_puts_everything_in_c() {
+        let result = AbcAnalysisResult::classify(vec![valued("Empty", 500, 0), valued("Free", 0, 3)]);
+        assert_eq!((result.a_count, result.b_count, result.c_count), (0, 0, 2));
+        assert_eq!(result.a_value_pct + result.b_value_pct + result.c_value_pct, 0.0);
+
+        let empty = AbcAnalysisResult::classify(Vec::new());
+        assert!(empty.items.is_empty() && empty.total_value_cents == 0);
+    }
+
+    #[test]
+    fn price_buckets_parse_sorted_boundaries_from_zero() {
+        assert_eq!(parse_price_buckets(None).unwrap(), DEFAULT_PRICE_BUCKETS);
+        assert_eq!(parse_price_buckets(Some("0, 1000,5000")).unwrap(), [0, 1_000, 5_000]);
+        assert_eq!(parse_price_buckets(Some("500,2500")).unwrap(), [0, 500, 2_500], "0 is added in front");
+
+        assert!(parse_price_buckets(Some("100,abc")).unwrap_err().contains("'abc'"));
+        assert!(parse_price_buckets(Some("-5,10")).unwrap_err().contains("negative"));
+        assert!(parse_price_buckets(Some("100,100")).unwrap_err().contains("ascending"));
+        let too_many: Vec<String> = (0..=MAX_PRICE_BUCKETS).map(|i| (i * 100).to_string()).collect();
+        assert!(parse_price_buckets(Some(&too_many.join(","))).unwrap_err().starts_with("at most 50 buckets"));
+    }
+
+    #[test]
+    fn empty_histogram_covers_every_price_without_gaps() {
+        let histogram = PriceBucket::empty_histogram(&[0, 1_000, 5_000]);
+        assert_eq!(
+            histogram.iter().map(|b| (b.lower_cents, b.upper_cents)).collect::<Vec<_>>(),
+            [(0, Some(1_000)), (1_000, Some(5_000)), (5_000, None)]
+        );
+    }
+}
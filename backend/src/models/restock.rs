@@ -0,0 +1,31 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+/// One recorded delivery, newest first in API responses.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct RestockEvent {
+    pub id: Uuid,
+    pub product_id: Uuid,
+    pub quantity_added: i32,
+    pub supplier: Option<String>,
+    pub notes: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Body of `POST /api/products/:id/restock`.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RestockRequest {
+    /// Units received; must be positive
+    pub quantity: i32,
+    pub supplier: Option<String>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct RestockHistoryParams {
+    /// Events to return, newest first (default: 50, max: 500)
+    pub limit: Option<i64>,
+}
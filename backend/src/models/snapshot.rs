@@ -0,0 +1,14 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::Product;
+
+/// Every product a `SetManager` held at `captured_at`, in `index_set`
+/// (insertion) order. Returned by `POST /api/benchmark/sets/snapshot` and
+/// accepted unchanged by `POST /api/benchmark/sets/restore`.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SetSnapshot {
+    pub products: Vec<Product>,
+    pub captured_at: DateTime<Utc>,
+}
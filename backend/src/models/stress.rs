@@ -0,0 +1,18 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// One row of `GET /api/stress-test/history`: the headline numbers of a stored
+/// run, without the latency breakdown and ASCII summary.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct StressRunSummary {
+    pub id: Uuid,
+    pub run_at: DateTime<Utc>,
+    pub concurrency: Option<i64>,
+    pub total_ops: Option<i64>,
+    pub ops_per_second: Option<f64>,
+    pub p95_latency_ms: Option<f64>,
+    /// The report's `errors`
+    pub error_count: Option<i64>,
+}
@@ -0,0 +1,57 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::{Product, ValidationError};
+
+pub const MAX_TAG_CHARS: usize = 50;
+
+/// A product with the names of its tags, alphabetically.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct TaggedProduct {
+    #[serde(flatten)]
+    pub product: Product,
+    pub tags: Vec<String>,
+}
+
+impl TaggedProduct {
+    /// Pairs each product with its entry in `tags` (from
+    /// `db::fetch_tags_for_products`); untagged products get an empty list.
+    pub fn attach(products: Vec<Product>, tags: &mut HashMap<Uuid, Vec<String>>) -> Vec<Self> {
+        products
+            .into_iter()
+            .map(|product| {
+                let tags = tags.remove(&product.id).unwrap_or_default();
+                Self { product, tags }
+            })
+            .collect()
+    }
+}
+
+/// Body of `POST /api/products/:id/tags`.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct AddTag {
+    /// Created on first use; surrounding whitespace is trimmed
+    pub tag: String,
+}
+
+impl AddTag {
+    /// The tag name as stored, or why it can't be.
+    pub fn validate(&self) -> Result<String, Vec<ValidationError>> {
+        let tag = self.tag.trim();
+        let chars = tag.chars().count();
+        if chars == 0 {
+            Err(vec![ValidationError::new("tag", "required", "tag must not be empty".to_string())])
+        } else if chars > MAX_TAG_CHARS {
+            Err(vec![ValidationError::new(
+                "tag",
+                "too_long",
+                format!("tag must be at most {} characters", MAX_TAG_CHARS),
+            )])
+        } else {
+            Ok(tag.to_string())
+        }
+    }
+}
@@ -0,0 +1,296 @@
+use axum::response::Html;
+use utoipa::OpenApi;
+
+use crate::{db, error, handlers, models, sets};
+
+/// Swagger UI page for `GET /api/docs`. The page itself ships in the binary;
+/// the Swagger UI scripts it loads come from a CDN.
+const DOCS_HTML: &str = include_str!("docs.html");
+
+/// Every documented route and the schemas they refer to. Responses wrapped in
+/// an ad-hoc JSON envelope are typed as `object`, with the payload schema
+/// named in the response description.
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Inventory Service API",
+        description = "Product inventory backed by PostgreSQL, mirrored into in-memory Rust sets for benchmarking.",
+    ),
+    paths(
+        handlers::health,
+        handlers::health_detailed,
+        handlers::products::list_products,
+        handlers::products::create_product,
+        handlers::products::search_products,
+        handlers::products::suggest_names,
+        handlers::products::autocomplete,
+        handlers::products::bulk_create_products,
+        handlers::products::bulk_delete_products,
+        handlers::products::import_products_csv,
+        handlers::products::export_products_csv,
+        handlers::products::export_products_ndjson,
+        handlers::products::list_deleted_products,
+        handlers::products::list_duplicates,
+        handlers::products::deduplicate_products,
+        handlers::products::product_stats,
+        handlers::products::abc_analysis,
+        handlers::products::price_distribution,
+        handlers::products::products_in_price_range,
+        handlers::products::products_by_category,
+        handlers::products::low_stock_products,
+        handlers::products::get_product,
+        handlers::products::update_product,
+        handlers::products::delete_product,
+        handlers::products::adjust_quantity,
+        handlers::products::restock_product,
+        handlers::products::restock_history,
+        handlers::products::restore_product,
+        handlers::products::price_history,
+        handlers::products::related_products,
+        handlers::products::duplicate_product,
+        handlers::products::add_product_tag,
+        handlers::products::remove_product_tag,
+        handlers::products::products_by_tag,
+        handlers::devolutions::list_devolutions,
+        handlers::devolutions::create_devolution,
+        handlers::devolutions::devolution_stats,
+        handlers::devolutions::get_devolution,
+        handlers::devolutions::approve_devolution,
+        handlers::devolutions::reject_devolution,
+        handlers::reports::inventory_value,
+        handlers::benchmark::seed_data,
+        handlers::benchmark::seed_devolutions,
+        handlers::benchmark::sets_reset,
+        handlers::benchmark::run_benchmark,
+        handlers::benchmark::run_range_benchmark,
+        handlers::benchmark::run_range_delete_benchmark,
+        handlers::benchmark::run_clone_overhead_benchmark,
+        handlers::benchmark::run_concurrent_benchmark,
+        handlers::benchmark::run_lock_contention_benchmark,
+        handlers::benchmark::run_serialization_benchmark,
+        handlers::benchmark::get_report,
+        handlers::benchmark::benchmark_history,
+        handlers::benchmark::benchmark_history_run,
+        handlers::benchmark::compare_benchmark_runs,
+        handlers::benchmark::sets_status,
+        handlers::benchmark::sets_prefix_search,
+        handlers::benchmark::sets_consistency,
+        handlers::benchmark::sets_top_n,
+        handlers::benchmark::sets_partitions,
+        handlers::benchmark::sets_sync,
+        handlers::benchmark::sets_diff,
+        handlers::benchmark::sets_rebalance,
+        handlers::benchmark::sets_batch_insert,
+        handlers::benchmark::sets_stats,
+        handlers::benchmark::sets_iteration_order_diff,
+        handlers::benchmark::sets_snapshot,
+        handlers::benchmark::sets_restore,
+        handlers::benchmark::export_csv,
+        handlers::benchmark::export_influx,
+        handlers::benchmark::export_msgpack,
+        handlers::benchmark::export_json,
+        handlers::benchmark::export_benchmark_json_schema,
+        handlers::stress::run_stress_test,
+        handlers::stress::stress_history,
+        handlers::stress::stress_history_run,
+        handlers::admin::audit_log,
+        handlers::admin::config,
+        handlers::admin::pool,
+        handlers::admin::cache_stats,
+        handlers::admin::reset_all,
+        handlers::admin::explain,
+        handlers::metrics::purge,
+        handlers::metrics::history,
+        handlers::metrics::ws_metrics,
+        handlers::metrics::get_config,
+        handlers::metrics::update_config,
+    ),
+    components(schemas(
+        error::ErrorResponse,
+        models::ValidationError,
+        models::Category,
+        models::Product,
+        models::TaggedProduct,
+        models::AddTag,
+        models::DeletedProduct,
+        models::DuplicateGroup,
+        models::ProductStats,
+        models::CategorySummary,
+        models::PriceHistoryEntry,
+        models::CreateProduct,
+        models::UpdateProduct,
+        models::DuplicateProduct,
+        models::QuantityAdjustment,
+        models::RestockRequest,
+        models::RestockEvent,
+        models::BulkCreateRequest,
+        models::BulkDeleteRequest,
+        models::BulkResult,
+        models::BulkError,
+        models::ImportReport,
+        models::ImportRowError,
+        models::ProductFilters,
+        models::SortField,
+        models::SortOrder,
+        models::DevolutionStatus,
+        models::ProductDevolution,
+        models::DevolutionWithProduct,
+        models::CreateDevolution,
+        models::RejectDevolution,
+        models::DevolutionStats,
+        models::ProductReturnCount,
+        models::ReasonCount,
+        models::CategoryReturnRate,
+        models::InventoryReportRow,
+        models::AbcClass,
+        models::AbcItem,
+        models::AbcAnalysisResult,
+        models::PriceBucket,
+        models::BenchmarkRunSummary,
+        models::SampleStrategy,
+        models::AuditLogEntry,
+        models::StoredMetric,
+        models::HealthStatus,
+        models::PoolConnections,
+        models::SetSnapshot,
+        sets::BenchmarkReport,
+        sets::SetBenchmarkResult,
+        sets::SetBenchmarkConfig,
+        sets::SetDiff,
+        sets::RemoveStrategy,
+        sets::OpTiming,
+        sets::SummaryRow,
+        sets::PriceRangeBenchmark,
+        sets::PrefixSearchBenchmark,
+        sets::PrefixSearchTiming,
+        sets::RangeBenchmarkResult,
+        sets::RangeDeleteResult,
+        sets::BatchInsertResult,
+        sets::SetStats,
+        sets::SetSizes,
+        sets::CloneOverheadResult,
+        sets::RangeDeleteSample,
+        sets::BatchRemoveStrategy,
+        sets::ConcurrentBenchmarkResult,
+        sets::LockContentionResult,
+        db::ProductCacheStats,
+        sets::SerializationBenchmarkResult,
+        sets::BenchmarkDelta,
+        sets::SetDelta,
+        sets::RegressionAlert,
+        sets::Severity,
+        sets::SetMemoryEstimate,
+        handlers::benchmark::RunBenchmarkParams,
+        handlers::admin::ExplainRequest,
+        handlers::benchmark::RangeBenchmarkParams,
+        handlers::benchmark::RangeDeleteParams,
+        handlers::benchmark::ConcurrentBenchmarkParams,
+        handlers::benchmark::LockContentionParams,
+        handlers::benchmark::SerializationParams,
+        handlers::benchmark::TopNField,
+        handlers::stress::StressParams,
+        handlers::stress::StressReport,
+        handlers::stress::HistogramBucket,
+        handlers::stress::AccessPattern,
+        models::StressRunSummary,
+        handlers::metrics::MetricsConfigUpdate,
+    )),
+    tags(
+        (name = "health", description = "Liveness and readiness probes"),
+        (name = "products", description = "Product CRUD, search, import and export"),
+        (name = "devolutions", description = "Product returns and their review"),
+        (name = "reports", description = "Inventory reports from the DB and the in-memory sets"),
+        (name = "benchmark", description = "Seeding, set benchmarks and their exports"),
+        (name = "sets", description = "Inspecting the in-memory sets"),
+        (name = "stress", description = "Concurrent load against the DB and the sets"),
+        (name = "admin", description = "Audit log, configuration and pool state"),
+        (name = "metrics", description = "The in-memory metrics store"),
+    ),
+)]
+pub struct ApiDoc;
+
+#[cfg(feature = "prometheus")]
+#[derive(OpenApi)]
+#[openapi(paths(handlers::prometheus_metrics))]
+struct PrometheusDoc;
+
+#[cfg(feature = "parquet")]
+#[derive(OpenApi)]
+#[openapi(paths(handlers::benchmark::export_parquet))]
+struct ParquetDoc;
+
+/// The spec for this build; `GET /metrics` is only listed with the
+/// `prometheus` feature and `GET /api/benchmark/export/parquet` with `parquet`.
+pub fn spec() -> utoipa::openapi::OpenApi {
+    let spec = ApiDoc::openapi();
+    #[cfg(feature = "prometheus")]
+    let spec = {
+        let mut spec = spec;
+        spec.merge(PrometheusDoc::openapi());
+        spec
+    };
+    #[cfg(feature = "parquet")]
+    let spec = {
+        let mut spec = spec;
+        spec.merge(ParquetDoc::openapi());
+        spec
+    };
+    spec
+}
+
+/// `GET /api/docs` — Swagger UI for the spec at `/api/openapi.json`.
+pub async fn docs() -> Html<&'static str> {
+    Html(DOCS_HTML)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every `$ref` in `value`, e.g. `#/components/schemas/Product`.
+    fn refs(value: &serde_json::Value, out: &mut Vec<String>) {
+        match value {
+            serde_json::Value::Object(map) => {
+                for (key, v) in map {
+                    match (key.as_str(), v.as_str()) {
+                        ("$ref", Some(r)) => out.push(r.to_string()),
+                        _ => refs(v, out),
+                    }
+                }
+            }
+            serde_json::Value::Array(items) => items.iter().for_each(|v| refs(v, out)),
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn every_schema_reference_resolves() {
+        let json = serde_json::to_value(spec()).unwrap();
+        let schemas = json["components"]["schemas"].as_object().unwrap();
+        let mut found = Vec::new();
+        refs(&json, &mut found);
+        assert!(!found.is_empty());
+        for r in found {
+            let name = r.strip_prefix("#/components/schemas/").unwrap_or(&r);
+            assert!(schemas.contains_key(name), "dangling reference {}", r);
+        }
+    }
+
+    #[test]
+    fn required_schemas_are_registered() {
+        let json = serde_json::to_value(spec()).unwrap();
+        for name in ["CreateProduct", "UpdateProduct", "ProductFilters", "BenchmarkReport", "StressReport", "ErrorResponse", "ValidationError"] {
+            assert!(json["components"]["schemas"].get(name).is_some(), "missing schema {}", name);
+        }
+    }
+
+    #[test]
+    fn product_list_documents_its_filters() {
+        let json = serde_json::to_value(spec()).unwrap();
+        let params = json["paths"]["/api/products"]["get"]["parameters"].as_array().unwrap();
+        let names: Vec<&str> = params.iter().filter_map(|p| p["name"].as_str()).collect();
+        for name in ["category", "categories", "name_contains", "sort_by", "tags", "cursor"] {
+            assert!(names.contains(&name), "missing query parameter {}", name);
+        }
+    }
+}
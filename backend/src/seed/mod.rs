@@ -1,29 +1,18 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use chrono::{Duration, Utc};
+use rand::distributions::{Distribution, WeightedIndex};
 use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
 use rand::{Rng, SeedableRng};
 use sqlx::PgPool;
+use strum::VariantArray;
 use tracing::info;
+use uuid::Uuid;
 
-use crate::error::AppResult;
-use crate::models::Product;
-
-static CATEGORIES: &[&str] = &[
-    "Electronics",
-    "Clothing",
-    "Food & Beverage",
-    "Home & Garden",
-    "Toys & Games",
-    "Sports & Outdoors",
-    "Books",
-    "Automotive",
-    "Health & Beauty",
-    "Office Supplies",
-    "Musical Instruments",
-    "Pet Supplies",
-    "Jewelry",
-    "Tools & Hardware",
-    "Baby Products",
-];
+use crate::error::{AppError, AppResult};
+use crate::models::{Category, CreateProduct, DevolutionWithProduct, Product};
 
 static ADJECTIVES: &[&str] = &[
     "Premium", "Deluxe", "Ultra", "Pro", "Classic", "Elite", "Smart", "Eco",
@@ -41,7 +30,6 @@ static NOUNS: &[&str] = &[
     "Converter", "Regulator", "Indicator",
 ];
 
-#[allow(dead_code)]
 static REASONS: &[&str] = &[
     "Defective on arrival",
     "Wrong item received",
@@ -62,16 +50,150 @@ fn random_product_name(rng: &mut impl Rng, serial: usize) -> String {
     format!("{} {} #{:05}", adj, noun, serial)
 }
 
+/// Relative category weights for seeding, normalized to sum to 1.0.
+/// Categories without a weight are never picked.
+#[derive(Debug, Clone)]
+pub struct CategoryWeights {
+    categories: Vec<Category>,
+    shares: Vec<f64>,
+    index: WeightedIndex<f64>,
+}
+
+impl CategoryWeights {
+    /// Keys are category names, matched like [`Category`]'s parser (ASCII
+    /// case-insensitive). Weights must be finite and non-negative, with at
+    /// least one above zero.
+    pub fn new(weights: &HashMap<String, f64>) -> AppResult<Self> {
+        let mut pairs: Vec<(Category, f64)> = Vec::with_capacity(weights.len());
+        for (name, &weight) in weights {
+            let category = Category::try_from(name.clone())?;
+            if pairs.iter().any(|&(c, _)| c == category) {
+                return Err(AppError::BadRequest(format!("category '{}' is weighted more than once", category)));
+            }
+            pairs.push((category, weight));
+        }
+        // Fixed order, so a seeded RNG picks the same categories whatever the map's order
+        pairs.sort_by_key(|&(c, _)| Category::VARIANTS.iter().position(|&v| v == c));
+
+        let (shares, index) = normalize_weights(&pairs, "category_weights", "category")?;
+        Ok(Self { categories: pairs.into_iter().map(|(c, _)| c).collect(), shares, index })
+    }
+
+    /// Each weighted category's share of the products, summing to 1.0.
+    pub fn normalized(&self) -> HashMap<&'static str, f64> {
+        self.categories.iter().map(|c| c.as_str()).zip(self.shares.iter().copied()).collect()
+    }
+
+    fn sample(&self, rng: &mut impl Rng) -> Category {
+        self.categories[self.index.sample(rng)]
+    }
+}
+
+/// Relative devolution reason weights, normalized to sum to 1.0. Reasons
+/// without a weight are never picked.
+#[derive(Debug, Clone)]
+pub struct ReasonWeights {
+    reasons: Vec<&'static str>,
+    shares: Vec<f64>,
+    index: WeightedIndex<f64>,
+}
+
+impl ReasonWeights {
+    /// Keys must be one of the seeding reasons, ignoring ASCII case. Weights
+    /// follow the same rules as [`CategoryWeights::new`].
+    pub fn new(weights: &HashMap<String, f64>) -> AppResult<Self> {
+        let mut pairs: Vec<(&'static str, f64)> = Vec::with_capacity(weights.len());
+        for (name, &weight) in weights {
+            let reason = REASONS.iter().copied().find(|r| r.eq_ignore_ascii_case(name.trim())).ok_or_else(|| {
+                AppError::BadRequest(format!("unknown reason '{}'; expected one of: {}", name, REASONS.join(", ")))
+            })?;
+            if pairs.iter().any(|&(r, _)| r == reason) {
+                return Err(AppError::BadRequest(format!("reason '{}' is weighted more than once", reason)));
+            }
+            pairs.push((reason, weight));
+        }
+        pairs.sort_by_key(|&(r, _)| REASONS.iter().position(|&v| v == r));
+
+        let (shares, index) = normalize_weights(&pairs, "reason_weights", "reason")?;
+        Ok(Self { reasons: pairs.into_iter().map(|(r, _)| r).collect(), shares, index })
+    }
+
+    /// Each weighted reason's share of the devolutions, summing to 1.0.
+    pub fn normalized(&self) -> HashMap<&'static str, f64> {
+        self.reasons.iter().copied().zip(self.shares.iter().copied()).collect()
+    }
+
+    fn sample(&self, rng: &mut impl Rng) -> &'static str {
+        self.reasons[self.index.sample(rng)]
+    }
+}
+
+/// Checks `(name, weight)` pairs, already in a fixed order, and returns each
+/// one's share of the total with a sampler over them. `param` and `kind`
+/// name the query parameter and its keys in error messages.
+fn normalize_weights<T: Display>(pairs: &[(T, f64)], param: &str, kind: &str) -> AppResult<(Vec<f64>, WeightedIndex<f64>)> {
+    if let Some((name, _)) = pairs.iter().find(|(_, w)| !w.is_finite() || *w < 0.0) {
+        return Err(AppError::BadRequest(format!("weight for '{}' must be a non-negative number", name)));
+    }
+    let total: f64 = pairs.iter().map(|(_, w)| w).sum();
+    if total <= 0.0 {
+        return Err(AppError::BadRequest(format!("{} must give at least one {} a positive weight", param, kind)));
+    }
+    let shares: Vec<f64> = pairs.iter().map(|(_, w)| w / total).collect();
+    let index = WeightedIndex::new(&shares).map_err(|e| AppError::BadRequest(format!("invalid {}: {}", param, e)))?;
+    Ok((shares, index))
+}
+
 /// Seed the database with `count` random products in batches.
 pub async fn seed_products(pool: &PgPool, count: usize) -> AppResult<Vec<Product>> {
+    seed_with_rng(pool, count, StdRng::from_entropy(), None).await
+}
+
+/// [`seed_products`] with options. With a fixed `rng_seed`, the same seed,
+/// `count` and weights always generate the same products, in the same order.
+/// Without `weights`, categories are picked uniformly.
+pub async fn seed_products_with(
+    pool: &PgPool,
+    count: usize,
+    rng_seed: Option<u64>,
+    weights: Option<&CategoryWeights>,
+) -> AppResult<Vec<Product>> {
+    let rng = rng_seed.map_or_else(StdRng::from_entropy, StdRng::seed_from_u64);
+    seed_with_rng(pool, count, rng, weights).await
+}
+
+/// One generated row. Draws from `rng` in a fixed order, so a seeded RNG
+/// reproduces the same sequence of rows.
+fn random_product(rng: &mut impl Rng, serial: usize, weights: Option<&CategoryWeights>) -> CreateProduct {
+    let name = random_product_name(rng, serial);
+    let description = rng
+        .gen_bool(0.7)
+        .then(|| format!("High-quality {} for professional use. Serial: {}", name, serial));
+    CreateProduct {
+        name,
+        description,
+        price_cents: rng.gen_range(99..=99_999), // $0.99 – $999.99
+        quantity: rng.gen_range(0..=500),
+        category: match weights {
+            Some(weights) => weights.sample(rng),
+            None => *Category::VARIANTS.choose(rng).unwrap(),
+        },
+    }
+}
+
+// StdRng is Send + Sync — safe to hold across async await points
+async fn seed_with_rng(
+    pool: &PgPool,
+    count: usize,
+    mut rng: StdRng,
+    weights: Option<&CategoryWeights>,
+) -> AppResult<Vec<Product>> {
     info!("Seeding {} products...", count);
 
-    // StdRng is Send + Sync — safe to hold across async await points
-    let mut rng = StdRng::from_entropy();
     let batch_size = 500_usize;
     let mut all_products: Vec<Product> = Vec::with_capacity(count);
 
-    let chunks = (count + batch_size - 1) / batch_size;
+    let chunks = count.div_ceil(batch_size);
 
     for chunk in 0..chunks {
         let start = chunk * batch_size;
@@ -86,19 +208,12 @@ pub async fn seed_products(pool: &PgPool, count: usize) -> AppResult<Vec<Product
         let mut categories: Vec<String> = Vec::with_capacity(this_batch);
 
         for i in 0..this_batch {
-            names.push(random_product_name(&mut rng, start + i));
-            descriptions.push(if rng.gen_bool(0.7) {
-                Some(format!(
-                    "High-quality {} for professional use. Serial: {}",
-                    names.last().unwrap(),
-                    start + i
-                ))
-            } else {
-                None
-            });
-            prices.push(rng.gen_range(99..=999_99)); // $0.99 – $999.99
-            quantities.push(rng.gen_range(0..=500));
-            categories.push(CATEGORIES.choose(&mut rng).unwrap().to_string());
+            let row = random_product(&mut rng, start + i, weights);
+            names.push(row.name);
+            descriptions.push(row.description);
+            prices.push(row.price_cents);
+            quantities.push(row.quantity);
+            categories.push(row.category.to_string());
         }
 
         // Use unnest for bulk insert (much faster than individual INSERTs)
@@ -107,7 +222,7 @@ pub async fn seed_products(pool: &PgPool, count: usize) -> AppResult<Vec<Product
             INSERT INTO products (name, description, price_cents, quantity, category)
             SELECT * FROM UNNEST($1::text[], $2::text[], $3::bigint[], $4::int[], $5::text[])
             ON CONFLICT DO NOTHING
-            RETURNING id, name, description, price_cents, quantity, category, created_at, updated_at
+            RETURNING id, name, description, price_cents, quantity, category, created_at, updated_at, version
             "#,
         )
         .bind(&names)
@@ -127,8 +242,221 @@ pub async fn seed_products(pool: &PgPool, count: usize) -> AppResult<Vec<Product
     Ok(all_products)
 }
 
-/// Generate a random devolution reason.
-#[allow(dead_code)]
-pub fn random_reason(rng: &mut StdRng) -> String {
-    REASONS.choose(rng).unwrap_or(&"Other").to_string()
+/// Random products built in memory only — fresh IDs, nothing written to the DB.
+pub fn generate_products(count: usize) -> Vec<Product> {
+    let mut rng = StdRng::from_entropy();
+    let now = Utc::now();
+    (0..count)
+        .map(|i| Product {
+            id: Uuid::new_v4(),
+            name: random_product_name(&mut rng, i),
+            description: None,
+            price_cents: rng.gen_range(99..=99_999),
+            quantity: rng.gen_range(0..=500),
+            category: Category::VARIANTS.choose(&mut rng).unwrap().to_string(),
+            created_at: now,
+            updated_at: now,
+            version: 1,
+        })
+        .collect()
+}
+
+/// Generate a random devolution reason, from `weights` when given.
+pub fn random_reason(rng: &mut StdRng, weights: Option<&ReasonWeights>) -> String {
+    match weights {
+        Some(weights) => weights.sample(rng),
+        None => REASONS.choose(rng).unwrap_or(&"Other"),
+    }
+    .to_string()
+}
+
+/// Inserts `count` devolutions against randomly picked live products, with
+/// 1–5 units each, a random reason and a `returned_at` within the last 90
+/// days. All start `pending`. With a fixed `rng_seed`, the same seed, weights
+/// and set of live products always produce the same rows.
+pub async fn seed_devolutions(
+    pool: &PgPool,
+    count: usize,
+    rng_seed: Option<u64>,
+    weights: Option<&ReasonWeights>,
+) -> AppResult<Vec<DevolutionWithProduct>> {
+    // Ordered, so a seeded RNG picks the same products on every run
+    let product_ids: Vec<Uuid> = sqlx::query_scalar("SELECT id FROM products WHERE deleted_at IS NULL ORDER BY id")
+        .fetch_all(pool)
+        .await?;
+    if product_ids.is_empty() {
+        return Err(AppError::BadRequest("no live products to return; seed products first".to_string()));
+    }
+
+    info!("Seeding {} devolutions...", count);
+    let mut rng = rng_seed.map_or_else(StdRng::from_entropy, StdRng::seed_from_u64);
+    let now = Utc::now();
+    let batch_size = 500_usize;
+    let mut all_devolutions: Vec<DevolutionWithProduct> = Vec::with_capacity(count);
+
+    for start in (0..count).step_by(batch_size) {
+        let this_batch = batch_size.min(count - start);
+        let mut ids: Vec<Uuid> = Vec::with_capacity(this_batch);
+        let mut quantities: Vec<i32> = Vec::with_capacity(this_batch);
+        let mut reasons: Vec<String> = Vec::with_capacity(this_batch);
+        let mut returned_at = Vec::with_capacity(this_batch);
+        for _ in 0..this_batch {
+            ids.push(*product_ids.choose(&mut rng).unwrap());
+            quantities.push(rng.gen_range(1..=5));
+            reasons.push(random_reason(&mut rng, weights));
+            returned_at.push(now - Duration::seconds(rng.gen_range(0..90 * 24 * 60 * 60)));
+        }
+
+        let devolutions = sqlx::query_as::<_, DevolutionWithProduct>(
+            r#"
+            WITH inserted AS (
+                INSERT INTO product_devolutions (product_id, quantity, reason, returned_at)
+                SELECT * FROM UNNEST($1::uuid[], $2::int[], $3::text[], $4::timestamptz[])
+                RETURNING *
+            )
+            SELECT d.id, d.product_id, p.name AS product_name, p.category AS product_category,
+                   d.quantity, d.reason, d.returned_at, d.created_at,
+                   d.status, d.rejection_reason, d.decided_at
+            FROM inserted d
+            JOIN products p ON p.id = d.product_id
+            ORDER BY d.returned_at DESC
+            "#,
+        )
+        .bind(&ids)
+        .bind(&quantities)
+        .bind(&reasons)
+        .bind(&returned_at)
+        .fetch_all(pool)
+        .await?;
+        all_devolutions.extend(devolutions);
+    }
+
+    info!("Seeding complete. Total: {} devolutions", all_devolutions.len());
+    Ok(all_devolutions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(rng_seed: u64, count: usize) -> Vec<String> {
+        let mut rng = StdRng::seed_from_u64(rng_seed);
+        (0..count).map(|i| random_product(&mut rng, i, None).name).collect()
+    }
+
+    fn weights(pairs: &[(&str, f64)]) -> AppResult<CategoryWeights> {
+        CategoryWeights::new(&pairs.iter().map(|&(name, w)| (name.to_string(), w)).collect())
+    }
+
+    /// Share of `count` generated products per category.
+    fn observed_shares(weights: &CategoryWeights, count: usize) -> HashMap<Category, f64> {
+        let mut rng = StdRng::seed_from_u64(11);
+        let mut counts: HashMap<Category, usize> = HashMap::new();
+        for i in 0..count {
+            *counts.entry(random_product(&mut rng, i, Some(weights)).category).or_default() += 1;
+        }
+        counts.into_iter().map(|(c, n)| (c, n as f64 / count as f64)).collect()
+    }
+
+    #[test]
+    fn skewed_weights_are_followed_within_five_percent() {
+        for (pairs, count) in [
+            (&[("Electronics", 50.0), ("Books", 10.0), ("Toys & Games", 40.0)][..], 1_000),
+            (&[("electronics", 0.95), ("Books", 0.05)][..], 2_000),
+            (&[("Jewelry", 1.0), ("Automotive", 99.0), ("Clothing", 0.0)][..], 5_000),
+        ] {
+            let weights = weights(pairs).unwrap();
+            let expected = weights.normalized();
+            assert!((expected.values().sum::<f64>() - 1.0).abs() < 1e-9);
+
+            let observed = observed_shares(&weights, count);
+            assert!(observed.keys().all(|c| expected.get(c.as_str()).is_some_and(|&s| s > 0.0)), "{:?}", observed);
+            for (name, share) in expected {
+                let got = observed.iter().find(|(c, _)| c.as_str() == name).map_or(0.0, |(_, &s)| s);
+                assert!((got - share).abs() <= 0.05, "{}: wanted {:.3}, got {:.3}", name, share, got);
+            }
+        }
+    }
+
+    #[test]
+    fn invalid_weights_are_rejected() {
+        let bad_request = |r: AppResult<CategoryWeights>| matches!(r, Err(AppError::BadRequest(_)));
+        assert!(bad_request(weights(&[("Gizmos", 1.0)])));
+        assert!(bad_request(weights(&[("Books", -1.0)])));
+        assert!(bad_request(weights(&[("Books", f64::NAN)])));
+        assert!(bad_request(weights(&[("Books", 0.0)])));
+        assert!(bad_request(weights(&[("Books", 1.0), ("books", 2.0)])));
+        assert!(bad_request(weights(&[])));
+    }
+
+    #[test]
+    fn seeded_weighted_picks_do_not_depend_on_map_order() {
+        let pairs = [("Books", 1.0), ("Clothing", 2.0), ("Jewelry", 3.0)];
+        let a = weights(&pairs).unwrap();
+        let reversed: Vec<_> = pairs.iter().rev().copied().collect();
+        let b = weights(&reversed).unwrap();
+        let categories = |w: &CategoryWeights| {
+            let mut rng = StdRng::seed_from_u64(5);
+            (0..50).map(|i| random_product(&mut rng, i, Some(w)).category).collect::<Vec<_>>()
+        };
+        assert_eq!(categories(&a), categories(&b));
+    }
+
+    #[test]
+    fn reason_weights_match_known_reasons_and_are_followed() {
+        let pairs = [("changed mind", 3.0), ("Missing parts", 1.0)];
+        let weights = ReasonWeights::new(&pairs.iter().map(|&(r, w)| (r.to_string(), w)).collect()).unwrap();
+        assert_eq!(weights.normalized(), HashMap::from([("Changed mind", 0.75), ("Missing parts", 0.25)]));
+
+        let mut rng = StdRng::seed_from_u64(3);
+        let picks: Vec<String> = (0..2_000).map(|_| random_reason(&mut rng, Some(&weights))).collect();
+        let changed = picks.iter().filter(|r| *r == "Changed mind").count() as f64 / picks.len() as f64;
+        assert!((changed - 0.75).abs() <= 0.05, "{}", changed);
+        assert!(picks.iter().all(|r| r == "Changed mind" || r == "Missing parts"));
+
+        let reason_weights = |pairs: &[(&str, f64)]| ReasonWeights::new(&pairs.iter().map(|&(r, w)| (r.to_string(), w)).collect());
+        assert!(matches!(reason_weights(&[("Too shiny", 1.0)]), Err(AppError::BadRequest(_))));
+        assert!(matches!(reason_weights(&[("Missing parts", -2.0)]), Err(AppError::BadRequest(_))));
+        assert!(matches!(reason_weights(&[("Missing parts", 1.0), ("missing parts", 1.0)]), Err(AppError::BadRequest(_))));
+    }
+
+    #[test]
+    fn fixed_seed_always_generates_the_same_names() {
+        assert_eq!(names(42, 3), vec!["Classic Controller #00000", "Silent Gadget #00001", "Standard Sensor #00002"]);
+        assert_eq!(names(42, 50), names(42, 50));
+        assert_ne!(names(42, 50), names(43, 50));
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn reseeding_with_the_same_seed_repeats_the_products(pool: PgPool) {
+        let first = seed_products_with(&pool, 600, Some(7), None).await.unwrap();
+        sqlx::query("DELETE FROM products").execute(&pool).await.unwrap();
+        let second = seed_products_with(&pool, 600, Some(7), None).await.unwrap();
+
+        let rows = |v: &[Product]| {
+            v.iter()
+                .map(|p| (p.name.clone(), p.description.clone(), p.price_cents, p.quantity, p.category.clone()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(rows(&first), rows(&second), "spans two batches, same rows in the same order");
+        assert_eq!(first.iter().map(|p| &p.name).take(3).collect::<Vec<_>>(), names(7, 3).iter().collect::<Vec<_>>());
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn seeded_devolutions_reference_live_products(pool: PgPool) {
+        let err = seed_devolutions(&pool, 10, Some(1), None).await.unwrap_err();
+        assert!(matches!(err, AppError::BadRequest(_)), "no products yet: {:?}", err);
+
+        let products = seed_products_with(&pool, 20, Some(1), None).await.unwrap();
+        let devolutions = seed_devolutions(&pool, 600, Some(1), None).await.unwrap();
+        assert_eq!(devolutions.len(), 600, "spans two batches");
+
+        let ids: std::collections::HashSet<Uuid> = products.iter().map(|p| p.id).collect();
+        assert!(devolutions.iter().all(|d| ids.contains(&d.product_id)));
+        assert!(devolutions.iter().all(|d| (1..=5).contains(&d.quantity) && REASONS.contains(&d.reason.as_str())));
+        let stored: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM product_devolutions").fetch_one(&pool).await.unwrap();
+        assert_eq!(stored, 600);
+    }
 }
@@ -1,18 +1,37 @@
-use std::collections::{BTreeSet, HashSet};
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet};
+use std::ops::{Bound, RangeInclusive};
 use std::hint::black_box;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
-/// How many evenly-spread elements are used for every lookup measurement.
+/// Default number of evenly-spread elements used for every lookup measurement.
 /// Averaging 1 000 samples eliminates single-call noise and exercises
 /// different positions in each set's internal structure.
 const LOOKUP_SAMPLES: usize = 1_000;
 
-use chrono::Utc;
+/// Default number of products inserted into a throwaway set before timing starts.
+const WARMUP_COUNT: usize = 1_000;
+
+use ahash::AHashSet;
+use chrono::{DateTime, Utc};
+#[cfg(feature = "skiplist")]
+use crossbeam_skiplist::SkipSet;
+use dashmap::DashSet;
 use indexmap::IndexSet;
+use patricia_tree::PatriciaMap;
+use rand::Rng;
+use rayon::prelude::*;
+use rustc_hash::FxHashSet;
 use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use tokio::sync::RwLock;
+use utoipa::ToSchema;
 use uuid::Uuid;
 
-use crate::models::Product;
+use crate::db;
+use crate::error::AppResult;
+use crate::models::{AbcAnalysisResult, InventoryReportRow, PriceBucket, Product, ProductStats, SetSnapshot};
 
 // ── Timing helpers ────────────────────────────────────────────────────────────
 
@@ -26,30 +45,117 @@ where
     (result, start.elapsed())
 }
 
+/// Per-run durations from [`timed_multi`] and their summary statistics.
+/// Percentiles are nearest-rank, as in the metrics store.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MultiTimingResult {
+    pub runs: Vec<Duration>,
+    pub min: Duration,
+    pub max: Duration,
+    pub mean: Duration,
+    /// Population standard deviation of `runs`
+    pub std_dev_ns: f64,
+    pub p50: Duration,
+    pub p95: Duration,
+    pub p99: Duration,
+}
+
+impl MultiTimingResult {
+    fn from_runs(runs: Vec<Duration>) -> Self {
+        let mut sorted = runs.clone();
+        sorted.sort_unstable();
+        let count = sorted.len();
+        let pick = |q: f64| sorted[((count as f64 * q) as usize).min(count - 1)];
+
+        let mean_ns = runs.iter().map(|d| d.as_nanos() as f64).sum::<f64>() / count as f64;
+        let variance = runs.iter().map(|d| (d.as_nanos() as f64 - mean_ns).powi(2)).sum::<f64>() / count as f64;
+        Self {
+            min: sorted[0],
+            max: sorted[count - 1],
+            mean: Duration::from_nanos(mean_ns.round() as u64),
+            std_dev_ns: variance.sqrt(),
+            p50: pick(0.50),
+            p95: pick(0.95),
+            p99: pick(0.99),
+            runs,
+        }
+    }
+}
+
+/// Runs `f` `n` times (at least once) and times each call separately. Results
+/// go through `black_box` so the calls can't be optimized away.
+pub fn timed_multi<F, R>(f: F, n: usize) -> MultiTimingResult
+where
+    F: Fn() -> R,
+{
+    let runs = (0..n.max(1))
+        .map(|_| {
+            let start = Instant::now();
+            black_box(f());
+            start.elapsed()
+        })
+        .collect();
+    MultiTimingResult::from_runs(runs)
+}
+
 // ── Per-operation result ──────────────────────────────────────────────────────
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct OpTiming {
     /// Nanoseconds elapsed
     pub duration_ns: u64,
     pub duration_us: f64,
     pub duration_ms: f64,
+    /// Fastest run in nanoseconds — only set when `repeat_runs > 1`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_ns: Option<u64>,
+    /// Slowest run in nanoseconds — only set when `repeat_runs > 1`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_ns: Option<u64>,
 }
 
-impl From<Duration> for OpTiming {
-    fn from(d: Duration) -> Self {
-        let ns = d.as_nanos() as u64;
+impl OpTiming {
+    fn from_ns(ns: u64) -> Self {
         Self {
             duration_ns: ns,
             duration_us: ns as f64 / 1_000.0,
             duration_ms: ns as f64 / 1_000_000.0,
+            min_ns: None,
+            max_ns: None,
+        }
+    }
+
+    /// Median of per-run durations (mean of the two middle values for an even
+    /// count), with the min and max kept alongside.
+    pub fn across_runs(runs_ns: &[u64]) -> Self {
+        let mut sorted = runs_ns.to_vec();
+        sorted.sort_unstable();
+        let Some((&min, &max)) = sorted.first().zip(sorted.last()) else {
+            return Self::from_ns(0);
+        };
+        let mid = sorted.len() / 2;
+        let median = if sorted.len().is_multiple_of(2) {
+            (sorted[mid - 1] + sorted[mid]) / 2
+        } else {
+            sorted[mid]
+        };
+        Self {
+            min_ns: Some(min),
+            max_ns: Some(max),
+            ..Self::from_ns(median)
         }
     }
 }
 
+impl From<Duration> for OpTiming {
+    fn from(d: Duration) -> Self {
+        Self::from_ns(d.as_nanos() as u64)
+    }
+}
+
 // ── Benchmark result for one set type ────────────────────────────────────────
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct SetBenchmarkResult {
     pub set_type: String,
     /// Description of what makes this set unique
@@ -65,12 +171,138 @@ pub struct SetBenchmarkResult {
     /// Is the iteration order deterministic / meaningful?
     pub order_guaranteed: bool,
     pub order_type: String,
+    /// Worker threads used — only set for concurrent benchmarks
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub threads: Option<usize>,
+    /// Insert + lookup + remove operations per second, per worker thread
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub per_thread_ops_per_sec: Option<f64>,
+    /// Structure-specific notes, e.g. the top-k extraction time for `BinaryHeap`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extra_info: Option<String>,
+    /// How `remove_half` removed elements — only set for `IndexSet`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remove_strategy: Option<RemoveStrategy>,
+}
+
+/// How the `IndexSet` benchmark removes elements.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum RemoveStrategy {
+    /// `swap_remove`: O(1), moves the last element into the gap
+    Swap,
+    /// `shift_remove`: O(n), shifts everything after the gap down one place
+    Shift,
+}
+
+/// How [`SetManager::remove_products`] drops a batch from the `BTreeSet`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum BatchRemoveStrategy {
+    /// `retain` in place: one O(n) pass that rebalances after each removal
+    Retain,
+    /// Filter the survivors into a `Vec` and bulk-build a new set from them
+    Rebuild,
+}
+
+impl BatchRemoveStrategy {
+    /// Share of the set above which rebuilding beats `retain`
+    pub const REBUILD_THRESHOLD: f64 = 0.3;
+
+    /// `Rebuild` once a batch of `batch` ids covers more than
+    /// [`REBUILD_THRESHOLD`](Self::REBUILD_THRESHOLD) of a set of `set_len`.
+    pub fn for_batch(batch: usize, set_len: usize) -> Self {
+        if set_len > 0 && batch as f64 / set_len as f64 > Self::REBUILD_THRESHOLD {
+            Self::Rebuild
+        } else {
+            Self::Retain
+        }
+    }
+
+    fn remove_from(self, set: &mut BTreeSet<Product>, ids: &HashSet<Uuid>) {
+        match self {
+            Self::Retain => set.retain(|p| !ids.contains(&p.id)),
+            Self::Rebuild => {
+                let kept: Vec<Product> = std::mem::take(set).into_iter().filter(|p| !ids.contains(&p.id)).collect();
+                *set = BTreeSet::from_iter(kept);
+            }
+        }
+    }
+}
+
+// ── Benchmark configuration ──────────────────────────────────────────────────
+
+/// Tunables for `SetManager::run_benchmark`, accepted in the body of
+/// `POST /api/benchmark/run`. Missing fields fall back to the defaults.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(default)]
+pub struct SetBenchmarkConfig {
+    /// Rayon worker threads for the concurrent (`DashSet`) benchmark
+    pub threads: usize,
+    /// How many of the most expensive products the `BinaryHeap` benchmark extracts
+    #[serde(alias = "k")]
+    pub top_k: usize,
+    /// Products inserted into a throwaway set before each benchmark starts timing
+    pub warmup_count: usize,
+    /// Elements averaged for each lookup-hit / lookup-miss measurement
+    pub lookup_samples: usize,
+    /// Full benchmark passes; timings are the median across passes. The
+    /// `HashSet`, `IndexSet` and `BTreeSet` passes also repeat their lookups
+    /// and iteration this many times each, via [`timed_multi`].
+    pub repeat_runs: u32,
+    /// Benchmark `IndexSet` with only this removal strategy. When `None`, both
+    /// run, except that `shift_remove` is skipped above
+    /// [`Self::SHIFT_REMOVE_AUTO_LIMIT`] products.
+    pub index_remove_strategy: Option<RemoveStrategy>,
+}
+
+impl SetBenchmarkConfig {
+    pub const MAX_THREADS: usize = 64;
+    pub const MAX_TOP_K: usize = 10_000;
+    pub const MAX_WARMUP_COUNT: usize = 100_000;
+    pub const MAX_LOOKUP_SAMPLES: usize = 100_000;
+    pub const MAX_REPEAT_RUNS: u32 = 20;
+    /// Largest product count the O(n²) `shift_remove` pass runs for unasked
+    pub const SHIFT_REMOVE_AUTO_LIMIT: usize = 20_000;
+
+    /// Clamps every field into its supported range.
+    pub fn normalized(mut self) -> Self {
+        self.threads = self.threads.clamp(1, Self::MAX_THREADS);
+        self.top_k = self.top_k.clamp(1, Self::MAX_TOP_K);
+        self.warmup_count = self.warmup_count.min(Self::MAX_WARMUP_COUNT);
+        self.lookup_samples = self.lookup_samples.clamp(1, Self::MAX_LOOKUP_SAMPLES);
+        self.repeat_runs = self.repeat_runs.clamp(1, Self::MAX_REPEAT_RUNS);
+        self
+    }
+
+    /// Whether the `IndexSet (shift_remove)` variant runs for `product_count` products.
+    pub fn runs_shift_remove(&self, product_count: usize) -> bool {
+        match self.index_remove_strategy {
+            Some(strategy) => strategy == RemoveStrategy::Shift,
+            None => product_count <= Self::SHIFT_REMOVE_AUTO_LIMIT,
+        }
+    }
+}
+
+impl Default for SetBenchmarkConfig {
+    fn default() -> Self {
+        Self {
+            threads: std::thread::available_parallelism().map_or(4, |n| n.get()),
+            top_k: 10,
+            warmup_count: WARMUP_COUNT,
+            lookup_samples: LOOKUP_SAMPLES,
+            repeat_runs: 1,
+            index_remove_strategy: None,
+        }
+    }
 }
 
 // ── Full benchmark comparison ─────────────────────────────────────────────────
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct BenchmarkReport {
+    /// Primary key of this run in `benchmark_runs`
+    pub run_id: Uuid,
     pub run_at: String,
     pub product_count: usize,
     pub results: Vec<SetBenchmarkResult>,
@@ -78,9 +310,112 @@ pub struct BenchmarkReport {
     pub winner_lookup: String,
     pub winner_iterate: String,
     pub summary_table: Vec<SummaryRow>,
+    /// Price-range query: full `BTreeSet` scan vs. the price index. `None` for
+    /// reports stored before it existed or runs with no products.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub price_range: Option<PriceRangeBenchmark>,
+    /// Name-prefix search: `IndexSet` scan vs. `BTreeSet` range, for a short
+    /// and a long prefix. `None` for older reports or runs with no products.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefix_search: Option<PrefixSearchBenchmark>,
+}
+
+impl BenchmarkReport {
+    /// MessagePack encoding of the report, with field names kept as map keys.
+    /// The positional (array) form would break on the optional fields that are
+    /// skipped when `None`.
+    pub fn to_msgpack(&self) -> anyhow::Result<Vec<u8>> {
+        Ok(rmp_serde::to_vec_named(self)?)
+    }
+
+    pub fn from_msgpack(bytes: &[u8]) -> anyhow::Result<BenchmarkReport> {
+        Ok(rmp_serde::from_slice(bytes)?)
+    }
+}
+
+/// Timings for selecting every product priced within `[min_cents, max_cents]`.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PriceRangeBenchmark {
+    pub min_cents: i64,
+    pub max_cents: i64,
+    pub matches: usize,
+    /// Iterate the whole name-ordered `BTreeSet` and filter by price
+    pub full_scan: OpTiming,
+    /// `range()` over a `BTreeMap` keyed by `(price_cents, id)`
+    pub price_index: OpTiming,
+}
+
+/// Result of [`benchmark_btree_range`]: one price-range selection timed with
+/// the price index and with a filtered scan of the name-ordered `BTreeSet`.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RangeBenchmarkResult {
+    pub low_pct: f64,
+    pub high_pct: f64,
+    /// Price at the `low_pct` percentile
+    pub min_cents: i64,
+    /// Price at the `high_pct` percentile
+    pub max_cents: i64,
+    pub matches: usize,
+    /// `range()` over the `(price_cents, id)` index
+    pub range_query_ns: u64,
+    /// `btree_set.iter().filter(..)` over every product
+    pub filter_scan_ns: u64,
+    /// Both approaches selected exactly the same products
+    pub results_match: bool,
+}
+
+/// Result of [`benchmark_btree_range_delete`]: one batch removal from a
+/// `BTreeSet` timed with `retain` and with a rebuild, plus a sweep over batch
+/// sizes to find where rebuilding starts to win.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RangeDeleteResult {
+    pub remove_fraction: f64,
+    pub set_size: usize,
+    pub removed: usize,
+    /// `set.retain(|p| !ids.contains(&p.id))`
+    pub retain_ns: u64,
+    /// Filter into a `Vec`, then `BTreeSet::from_iter`
+    pub rebuild_ns: u64,
+    /// `retain` or `rebuild`, whichever was quicker at `remove_fraction`
+    pub faster: BatchRemoveStrategy,
+    /// What [`SetManager::remove_products`] would pick for this batch
+    pub chosen: BatchRemoveStrategy,
+    /// Smallest swept fraction at which rebuilding was quicker; `None` if it never was
+    pub crossover_fraction: Option<f64>,
+    pub sweep: Vec<RangeDeleteSample>,
+    /// Both strategies left exactly the same products
+    pub results_match: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RangeDeleteSample {
+    pub remove_fraction: f64,
+    pub retain_ns: u64,
+    pub rebuild_ns: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PrefixSearchBenchmark {
+    /// One-character prefix: matches a large slice of the set
+    pub short: PrefixSearchTiming,
+    /// Eight-character prefix: matches a handful of products
+    pub long: PrefixSearchTiming,
+}
+
+/// Timings for one case-insensitive name-prefix search.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PrefixSearchTiming {
+    pub prefix: String,
+    pub matches: usize,
+    /// Filter every element of the insertion-ordered `IndexSet`
+    pub index_scan: OpTiming,
+    /// Seek into the name-ordered `BTreeSet` and walk only the matching range
+    pub btree_range: OpTiming,
+    /// `"IndexSet scan"` or `"BTreeSet range"`
+    pub faster: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct SummaryRow {
     pub set_type: String,
     pub insert_ms: f64,
@@ -91,206 +426,1324 @@ pub struct SummaryRow {
     pub order: String,
 }
 
-// ── SetManager: holds all three sets ─────────────────────────────────────────
+// ── Run-to-run comparison ────────────────────────────────────────────────────
+
+/// Changes smaller than this are treated as noise and get no verdict line.
+pub const VERDICT_THRESHOLD_PCT: f64 = 5.0;
+
+/// How every set type's timings moved from run `a` to run `b`. Positive
+/// deltas mean `b` was slower.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct BenchmarkDelta {
+    pub run_a: Uuid,
+    pub run_b: Uuid,
+    /// Set types present in both runs, in run `b`'s order
+    pub sets: Vec<SetDelta>,
+    /// Set types that only one of the runs benchmarked
+    pub unmatched_set_types: Vec<String>,
+    /// e.g. "BTreeSet insert improved by 12%" — only changes of at least
+    /// `VERDICT_THRESHOLD_PCT`
+    pub verdicts: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SetDelta {
+    pub set_type: String,
+    pub insert_delta_ms: f64,
+    pub insert_delta_pct: f64,
+    pub lookup_hit_delta_us: f64,
+    pub lookup_hit_delta_pct: f64,
+    pub lookup_miss_delta_us: f64,
+    pub lookup_miss_delta_pct: f64,
+    pub iterate_delta_ms: f64,
+    pub iterate_delta_pct: f64,
+    pub remove_delta_ms: f64,
+    pub remove_delta_pct: f64,
+}
+
+/// Compares two reports set type by set type.
+pub fn compare_reports(a: &BenchmarkReport, b: &BenchmarkReport) -> BenchmarkDelta {
+    let mut sets = Vec::new();
+    let mut verdicts = Vec::new();
+
+    for rb in &b.results {
+        let Some(ra) = a.results.iter().find(|r| r.set_type == rb.set_type) else {
+            continue;
+        };
+        let ops = [
+            ("insert", &ra.insert_all, &rb.insert_all),
+            ("lookup hit", &ra.lookup_hit, &rb.lookup_hit),
+            ("lookup miss", &ra.lookup_miss, &rb.lookup_miss),
+            ("iterate", &ra.iterate_all, &rb.iterate_all),
+            ("remove", &ra.remove_half, &rb.remove_half),
+        ];
+        for (op, ta, tb) in ops {
+            let pct = pct_change(ta, tb);
+            if pct.abs() >= VERDICT_THRESHOLD_PCT {
+                let direction = if pct < 0.0 { "improved" } else { "regressed" };
+                verdicts.push(format!("{} {} {} by {:.0}%", rb.set_type, op, direction, pct.abs()));
+            }
+        }
+
+        sets.push(SetDelta {
+            set_type: rb.set_type.clone(),
+            insert_delta_ms: rb.insert_all.duration_ms - ra.insert_all.duration_ms,
+            insert_delta_pct: pct_change(&ra.insert_all, &rb.insert_all),
+            lookup_hit_delta_us: rb.lookup_hit.duration_us - ra.lookup_hit.duration_us,
+            lookup_hit_delta_pct: pct_change(&ra.lookup_hit, &rb.lookup_hit),
+            lookup_miss_delta_us: rb.lookup_miss.duration_us - ra.lookup_miss.duration_us,
+            lookup_miss_delta_pct: pct_change(&ra.lookup_miss, &rb.lookup_miss),
+            iterate_delta_ms: rb.iterate_all.duration_ms - ra.iterate_all.duration_ms,
+            iterate_delta_pct: pct_change(&ra.iterate_all, &rb.iterate_all),
+            remove_delta_ms: rb.remove_half.duration_ms - ra.remove_half.duration_ms,
+            remove_delta_pct: pct_change(&ra.remove_half, &rb.remove_half),
+        });
+    }
+
+    let in_both = |t: &String| sets.iter().any(|d: &SetDelta| &d.set_type == t);
+    let unmatched_set_types = a
+        .results
+        .iter()
+        .chain(&b.results)
+        .map(|r| &r.set_type)
+        .filter(|t| !in_both(t))
+        .cloned()
+        .collect();
+
+    BenchmarkDelta {
+        run_a: a.run_id,
+        run_b: b.run_id,
+        sets,
+        unmatched_set_types,
+        verdicts,
+    }
+}
+
+/// Percentage change from `a` to `b`; 0 when `a` took no measurable time.
+fn pct_change(a: &OpTiming, b: &OpTiming) -> f64 {
+    if a.duration_ns == 0 {
+        return 0.0;
+    }
+    (b.duration_ns as f64 - a.duration_ns as f64) / a.duration_ns as f64 * 100.0
+}
+
+// ── Regression check against a baseline ─────────────────────────────────────
+
+/// Default slowdown (in %) that earns a `warning` in `POST /api/benchmark/run`.
+pub const REGRESSION_WARN_PCT: f64 = 10.0;
+/// Default slowdown (in %) that earns a `regression`.
+pub const REGRESSION_FAIL_PCT: f64 = 25.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Ok,
+    Warning,
+    Regression,
+}
+
+/// One operation of one set type, measured against the same operation in a
+/// baseline run.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct RegressionAlert {
+    pub set_type: String,
+    /// Metric name, e.g. `insert_all` or `lookup_hit`
+    pub operation: String,
+    pub baseline_ns: u64,
+    pub current_ns: u64,
+    /// Positive means `current` was slower
+    pub pct_change: f64,
+    pub severity: Severity,
+}
+
+/// Grades every operation of every set type found in both reports. A slowdown
+/// of more than `warn_pct` is a `warning`, more than `fail_pct` a `regression`;
+/// speedups are always `ok`. Set types only one report has are skipped.
+pub fn regression_check(
+    baseline: &BenchmarkReport,
+    current: &BenchmarkReport,
+    warn_pct: f64,
+    fail_pct: f64,
+) -> Vec<RegressionAlert> {
+    let mut alerts = Vec::new();
+    for rc in &current.results {
+        let Some(rb) = baseline.results.iter().find(|r| r.set_type == rc.set_type) else {
+            continue;
+        };
+        let ops = [
+            ("insert_all", &rb.insert_all, &rc.insert_all),
+            ("lookup_hit", &rb.lookup_hit, &rc.lookup_hit),
+            ("lookup_miss", &rb.lookup_miss, &rc.lookup_miss),
+            ("iterate_all", &rb.iterate_all, &rc.iterate_all),
+            ("remove_half", &rb.remove_half, &rc.remove_half),
+        ];
+        for (op, tb, tc) in ops {
+            let pct = pct_change(tb, tc);
+            let severity = if pct > fail_pct {
+                Severity::Regression
+            } else if pct > warn_pct {
+                Severity::Warning
+            } else {
+                Severity::Ok
+            };
+            alerts.push(RegressionAlert {
+                set_type: rc.set_type.clone(),
+                operation: op.to_string(),
+                baseline_ns: tb.duration_ns,
+                current_ns: tc.duration_ns,
+                pct_change: pct,
+                severity,
+            });
+        }
+    }
+    alerts
+}
+
+// ── Stats ────────────────────────────────────────────────────────────────────
+
+/// Output of [`SetManager::stats`]. With no products every figure is 0.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, ToSchema)]
+pub struct SetStats {
+    pub min_price_cents: i64,
+    pub max_price_cents: i64,
+    pub avg_price_cents: f64,
+    pub total_quantity: i64,
+    /// Sum of `price_cents * quantity`
+    pub total_value_cents: i64,
+    pub product_count: usize,
+}
+
+impl SetStats {
+    pub fn inventory_value_dollars(&self) -> f64 {
+        self.total_value_cents as f64 / 100.0
+    }
+
+    /// The same figures from the DB's aggregates, with the empty-table
+    /// `NULL`s read as 0.
+    pub fn from_product_stats(stats: &ProductStats) -> Self {
+        Self {
+            min_price_cents: stats.min_price_cents.unwrap_or(0),
+            max_price_cents: stats.max_price_cents.unwrap_or(0),
+            avg_price_cents: stats.avg_price_cents.unwrap_or(0.0),
+            total_quantity: stats.total_quantity,
+            total_value_cents: stats.total_inventory_value,
+            product_count: stats.count as usize,
+        }
+    }
+
+    /// Fields on which `self` and `other` disagree. Averages are compared to
+    /// within a millionth of a cent, since the DB sums in `numeric`.
+    pub fn discrepancies(&self, other: &SetStats) -> Vec<&'static str> {
+        [
+            ("min_price_cents", self.min_price_cents == other.min_price_cents),
+            ("max_price_cents", self.max_price_cents == other.max_price_cents),
+            ("avg_price_cents", (self.avg_price_cents - other.avg_price_cents).abs() < 1e-6),
+            ("total_quantity", self.total_quantity == other.total_quantity),
+            ("total_value_cents", self.total_value_cents == other.total_value_cents),
+            ("product_count", self.product_count == other.product_count),
+        ]
+        .into_iter()
+        .filter(|(_, same)| !same)
+        .map(|(field, _)| field)
+        .collect()
+    }
+}
+
+// ── Memory estimate ──────────────────────────────────────────────────────────
+
+/// Output of [`SetManager::memory_estimate_bytes`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+pub struct SetMemoryEstimate {
+    pub hash_set_bytes: usize,
+    pub index_set_bytes: usize,
+    pub btree_set_bytes: usize,
+    pub total_bytes: usize,
+}
+
+/// A SwissTable (std/hashbrown) sized for `capacity`: `len` occupied slots at
+/// `elem + 8` bytes each, the empty slots, and one control byte per bucket.
+fn hash_table_bytes(len: usize, capacity: usize, elem: usize) -> usize {
+    if capacity == 0 {
+        return 0;
+    }
+    // Tables are kept at most 7/8 full and have a power-of-two bucket count
+    let buckets = (capacity * 8 / 7).next_power_of_two().max(4);
+    len * (elem + 8) + buckets.saturating_sub(len) * elem + buckets
+}
+
+/// std's B-tree stores up to 11 elements per node. Nodes built by repeated
+/// inserts settle around two thirds full; each one also carries a parent
+/// pointer and lengths, and internal nodes (about 1 in 7) hold 12 child
+/// pointers on top.
+fn btree_bytes(len: usize, elem: usize) -> usize {
+    const NODE_CAPACITY: usize = 11;
+    const TYPICAL_FILL: usize = 7;
+    const NODE_HEADER: usize = 16;
+    if len == 0 {
+        return 0;
+    }
+    let nodes = len.div_ceil(TYPICAL_FILL);
+    let internal = nodes.div_ceil(TYPICAL_FILL) - 1;
+    nodes * (NODE_CAPACITY * elem + NODE_HEADER) + internal * (NODE_CAPACITY + 1) * 8
+}
+
+// ── Category partitions ──────────────────────────────────────────────────────
+
+/// One category's products, from [`SetManager::partition_by_category`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CategoryPartition<'a> {
+    /// In `btree_set`'s `(name, id)` order
+    pub products: Vec<&'a Product>,
+    /// Saturates at `i32::MAX` rather than wrapping
+    pub total_quantity: i32,
+    pub total_value_cents: i64,
+}
+
+// ── Drift from the database ──────────────────────────────────────────────────
+
+/// Where `hash_set` and the live rows in `products` disagree, from
+/// [`diff_with_db`]. Both id lists are sorted.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, ToSchema)]
+pub struct SetDiff {
+    pub in_db_not_in_sets: Vec<Uuid>,
+    pub in_sets_not_in_db: Vec<Uuid>,
+    /// `hash_set` and the DB hold different numbers of products
+    pub count_mismatch: bool,
+}
+
+impl SetDiff {
+    pub fn has_drift(&self) -> bool {
+        self.count_mismatch || !self.in_db_not_in_sets.is_empty() || !self.in_sets_not_in_db.is_empty()
+    }
+}
+
+/// Compares the ids in `sets.hash_set` with every live product id in the DB.
+/// Field values are not compared, only which products are present.
+pub async fn diff_with_db(sets: &SetManager, pool: &PgPool) -> AppResult<SetDiff> {
+    let db_ids = db::fetch_all_live_product_ids(pool).await?;
+    Ok(sets.diff_ids(&db_ids))
+}
+
+// ── SetManager: holds all sets ───────────────────────────────────────────────
+
+/// Length of every set a [`SetManager`] holds, keyed by field name. Sets
+/// that are disabled are `None` and left out of the JSON.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+pub struct SetSizes {
+    pub hash_set: usize,
+    pub index_set: usize,
+    pub btree_set: usize,
+    pub ahash_set: usize,
+    pub dash_set: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fx_set: Option<usize>,
+    #[cfg(feature = "skiplist")]
+    pub skip_set: usize,
+}
+
+impl SetSizes {
+    /// Whether every set holds as many products as `hash_set`.
+    pub fn consistent(&self) -> bool {
+        let others = [self.index_set, self.btree_set, self.ahash_set, self.dash_set].into_iter().chain(self.fx_set);
+        #[cfg(feature = "skiplist")]
+        let others = others.chain([self.skip_set]);
+        let mut others = others;
+        others.all(|len| len == self.hash_set)
+    }
+
+    /// Every set holding `len` products, with `fx_set` as given.
+    #[cfg(test)]
+    pub fn uniform(len: usize, fx_set: Option<usize>) -> Self {
+        Self {
+            hash_set: len,
+            index_set: len,
+            btree_set: len,
+            ahash_set: len,
+            dash_set: len,
+            fx_set,
+            #[cfg(feature = "skiplist")]
+            skip_set: len,
+        }
+    }
+}
 
-/// Manages the three in-memory sets that are compared during benchmarks.
+/// Manages the in-memory sets that are compared during benchmarks.
 ///
 /// - `hash_set`         → `std::collections::HashSet`  — unordered, O(1) ops
 /// - `index_set`        → `indexmap::IndexSet`          — insertion-ordered, O(1) ops
-///                         (equivalent to the `linked-hash-set` concept:
-///                          a hash set backed by a contiguous array that preserves
-///                          the insertion order of elements)
+///   (equivalent to the `linked-hash-set` concept: a hash set backed by a
+///   contiguous array that preserves the insertion order of elements)
 /// - `btree_set`        → `std::collections::BTreeSet` — sorted by (name, id), O(log n) ops
+/// - `ahash_set`        → `ahash::AHashSet`             — unordered, O(1) ops, AES-based hasher
+/// - `dash_set`         → `dashmap::DashSet`            — unordered, sharded, safe for concurrent writers
+/// - `fx_set`           → `rustc_hash::FxHashSet`       — unordered, O(1) ops, FxHash (opt-in via `ENABLE_FX_SET`)
+/// - `skip_set`         → `crossbeam_skiplist::SkipSet` — sorted by (name, id), O(log n) ops (`skiplist` feature)
+///
+/// `name_trie` is a secondary index for name autocomplete (opt-in via `ENABLE_TRIE_INDEX`).
 pub struct SetManager {
     pub hash_set: HashSet<Product>,
     /// IndexSet is the idiomatic Rust `LinkedHashSet` equivalent:
     /// O(1) average insert/lookup, deterministic insertion-order iteration.
     pub index_set: IndexSet<Product>,
     pub btree_set: BTreeSet<Product>,
+    /// `HashSet` with `ahash`'s faster (still DoS-resistant) hasher in place of SipHash.
+    pub ahash_set: AHashSet<Product>,
+    /// Sharded concurrent set: many threads can insert/remove through `&self`.
+    pub dash_set: DashSet<Product>,
+    /// Secondary index ordered by `(price_cents, id)` for [`range_by_price`](Self::range_by_price).
+    /// Holds keys only; products are resolved through `id_map`. Not one of the
+    /// compared sets, so it stays out of sizes and consistency checks.
+    price_index: BTreeSet<(i64, Uuid)>,
+    /// Products keyed by id, the way an inventory service usually looks them
+    /// up. Kept in step with the sets but, like `price_index`, not one of them.
+    pub id_map: HashMap<Uuid, Product>,
+    /// `HashSet` with rustc's FxHash: very fast on small keys, not DoS-resistant.
+    /// `None` unless enabled with [`with_fx_set`](Self::with_fx_set).
+    pub fx_set: Option<FxHashSet<Product>>,
+    /// Lock-free skip list: same `(name, id)` order as `btree_set`.
+    #[cfg(feature = "skiplist")]
+    pub skip_set: SkipSet<Product>,
+    /// Patricia trie over product names for [`autocomplete`](Self::autocomplete).
+    /// Keys are [`trie_key`]s, so products sharing a name each keep an entry.
+    /// `None` unless enabled with [`with_name_trie`](Self::with_name_trie).
+    pub name_trie: Option<PatriciaMap<Uuid>>,
     pub last_report: Option<BenchmarkReport>,
+    /// When the sets last matched the database, as recorded by
+    /// `POST /api/benchmark/sets/sync`. Cleared whenever the contents are
+    /// replaced wholesale, so the next incremental sync falls back to a full one.
+    pub last_sync_at: Option<DateTime<Utc>>,
 }
 
 impl SetManager {
     pub fn new() -> Self {
+        Self::with_fx_set(false)
+    }
+
+    /// Like [`new`](Self::new), but also tracks `fx_set` when `enabled`.
+    pub fn with_fx_set(enabled: bool) -> Self {
         Self {
             hash_set: HashSet::new(),
             index_set: IndexSet::new(),
             btree_set: BTreeSet::new(),
+            ahash_set: AHashSet::new(),
+            dash_set: DashSet::new(),
+            price_index: BTreeSet::new(),
+            id_map: HashMap::new(),
+            fx_set: enabled.then(FxHashSet::default),
+            #[cfg(feature = "skiplist")]
+            skip_set: SkipSet::new(),
+            name_trie: None,
             last_report: None,
+            last_sync_at: None,
         }
     }
 
-    /// Sync all three sets from a DB product list (replacing existing contents).
+    /// Also maintains `name_trie` when `enabled`, built from whatever the sets
+    /// already hold.
+    pub fn with_name_trie(mut self, enabled: bool) -> Self {
+        self.name_trie = enabled.then(|| self.btree_set.iter().map(|p| (trie_key(&p.name, p.id), p.id)).collect());
+        self
+    }
+
+    /// Sync all sets from a DB product list (replacing existing contents).
     pub fn sync_from_db(&mut self, products: &[Product]) {
+        self.last_sync_at = None;
         self.hash_set.clear();
         self.index_set.clear();
         self.btree_set.clear();
+        self.ahash_set.clear();
+        self.dash_set.clear();
+        self.price_index.clear();
+        self.id_map.clear();
+        if let Some(fx) = &mut self.fx_set {
+            fx.clear();
+        }
+        #[cfg(feature = "skiplist")]
+        self.skip_set.clear();
+        if let Some(trie) = &mut self.name_trie {
+            trie.clear();
+        }
+
+        self.batch_insert(products);
+    }
 
+    /// Adds `products` to every set in one call, keeping what is already
+    /// there. Products new to the sets are appended with `extend`; ids already
+    /// stored, or repeated within the batch, go through
+    /// [`insert_product`](Self::insert_product) afterwards so the last copy wins.
+    pub fn batch_insert(&mut self, products: &[Product]) {
+        let mut fresh = Vec::with_capacity(products.len());
+        let mut known = Vec::new();
         for p in products {
-            self.hash_set.insert(p.clone());
-            self.index_set.insert(p.clone());
-            self.btree_set.insert(p.clone());
+            match self.id_map.entry(p.id) {
+                std::collections::hash_map::Entry::Occupied(_) => known.push(p),
+                std::collections::hash_map::Entry::Vacant(slot) => {
+                    slot.insert(p.clone());
+                    fresh.push(p);
+                }
+            }
+        }
+
+        self.hash_set.extend(fresh.iter().map(|&p| p.clone()));
+        self.index_set.extend(fresh.iter().map(|&p| p.clone()));
+        self.btree_set.extend(fresh.iter().map(|&p| p.clone()));
+        self.ahash_set.extend(fresh.iter().map(|&p| p.clone()));
+        for &p in &fresh {
+            self.dash_set.insert(p.clone());
+        }
+        self.price_index.extend(fresh.iter().map(|&p| (p.price_cents, p.id)));
+        if let Some(fx) = &mut self.fx_set {
+            fx.extend(fresh.iter().map(|&p| p.clone()));
+        }
+        #[cfg(feature = "skiplist")]
+        for &p in &fresh {
+            self.skip_set.insert(p.clone());
+        }
+        if let Some(trie) = &mut self.name_trie {
+            for &p in &fresh {
+                trie.insert(trie_key(&p.name, p.id), p.id);
+            }
+        }
+
+        for p in known {
+            self.insert_product(p);
         }
     }
 
-    /// Insert a product into all three sets.
+    /// Insert a product into all sets, replacing any stored copy with the
+    /// same UUID.
     ///
-    /// `HashSet` and `IndexSet` deduplicate by `Eq` (UUID), so re-inserting a
-    /// product with the same UUID naturally replaces the old entry.
+    /// The hash-based sets deduplicate by `Eq` (UUID), but a plain `insert` of
+    /// an equal value keeps the *old* value, so they use `replace` (or
+    /// remove + insert for `DashSet`) to store the new field values.
     /// `BTreeSet` deduplicates by `Ord` (`(name, id)`), so a name change would
-    /// leave a stale entry behind.  We evict by ID first to keep all three sets
+    /// leave a stale entry behind.  We evict by ID first to keep all sets
     /// consistent.
     pub fn insert_product(&mut self, product: &Product) {
-        self.hash_set.insert(product.clone());
-        self.index_set.insert(product.clone());
+        // The old price is the index key, so drop it before `id_map` forgets it
+        if let Some(old) = self.id_map.get(&product.id) {
+            self.price_index.remove(&(old.price_cents, old.id));
+            // Keyed by name too, so a rename would otherwise leave the old entry
+            if let Some(trie) = &mut self.name_trie {
+                trie.remove(trie_key(&old.name, old.id));
+            }
+        }
+        if let Some(trie) = &mut self.name_trie {
+            trie.insert(trie_key(&product.name, product.id), product.id);
+        }
+        self.price_index.insert((product.price_cents, product.id));
+        self.id_map.insert(product.id, product.clone());
+        self.hash_set.replace(product.clone());
+        self.index_set.replace(product.clone());
         self.btree_set.retain(|p| p.id != product.id);
         self.btree_set.insert(product.clone());
+        self.ahash_set.replace(product.clone());
+        self.dash_set.remove(product);
+        self.dash_set.insert(product.clone());
+        if let Some(fx) = &mut self.fx_set {
+            fx.replace(product.clone());
+        }
+        // Ordered like `btree_set`, so it needs the same evict-by-ID treatment
+        #[cfg(feature = "skiplist")]
+        {
+            self.remove_from_skip_set(&HashSet::from([product.id]));
+            self.skip_set.insert(product.clone());
+        }
+    }
+
+    /// Like [`insert_product`](Self::insert_product), but a no-op when the
+    /// stored copy already has the same content. Returns whether anything was
+    /// written.
+    ///
+    /// `updated_at` and `version` are ignored: every DB update bumps both, even
+    /// one that changes nothing else, so after a no-op the sets keep the older
+    /// values for those two fields.
+    pub fn upsert_product(&mut self, product: &Product) -> bool {
+        if self.hash_set.get(product).is_some_and(|stored| same_content(stored, product)) {
+            return false;
+        }
+        self.insert_product(product);
+        true
+    }
+
+    /// Applies changed rows on top of the current contents, each through
+    /// [`insert_product`](Self::insert_product) so updates replace the stale
+    /// copy. Products absent from `updates` are left as they are.
+    pub fn incremental_sync(&mut self, updates: &[Product]) {
+        for p in updates {
+            self.insert_product(p);
+        }
     }
 
-    /// Remove a product from all three sets by ID.
+    /// Remove a product from all sets by ID.
     pub fn remove_product(&mut self, id: Uuid) {
-        self.hash_set.retain(|p| p.id != id);
-        self.index_set.retain(|p| p.id != id);
-        self.btree_set.retain(|p| p.id != id);
+        self.remove_products_with(&HashSet::from([id]), BatchRemoveStrategy::Retain);
+    }
+
+    /// [`remove_products`](Self::remove_products) for a list of ids.
+    pub fn remove_product_batch(&mut self, ids: &[Uuid]) -> BatchRemoveStrategy {
+        self.remove_products(&ids.iter().copied().collect())
+    }
+
+    /// Removes every product in `ids` with one pass over each set, so a batch
+    /// costs the same as a single removal. Unknown ids are ignored. The
+    /// `BTreeSet` is rebuilt instead of retained when the batch is a large
+    /// share of it; the strategy used is returned.
+    pub fn remove_products(&mut self, ids: &HashSet<Uuid>) -> BatchRemoveStrategy {
+        let strategy = BatchRemoveStrategy::for_batch(ids.len(), self.btree_set.len());
+        self.remove_products_with(ids, strategy);
+        strategy
+    }
+
+    fn remove_products_with(&mut self, ids: &HashSet<Uuid>, strategy: BatchRemoveStrategy) {
+        self.hash_set.retain(|p| !ids.contains(&p.id));
+        self.index_set.retain(|p| !ids.contains(&p.id));
+        strategy.remove_from(&mut self.btree_set, ids);
+        self.ahash_set.retain(|p| !ids.contains(&p.id));
+        self.dash_set.retain(|p| !ids.contains(&p.id));
+        for id in ids {
+            if let Some(old) = self.id_map.remove(id) {
+                self.price_index.remove(&(old.price_cents, old.id));
+                if let Some(trie) = &mut self.name_trie {
+                    trie.remove(trie_key(&old.name, old.id));
+                }
+            }
+        }
+        if let Some(fx) = &mut self.fx_set {
+            fx.retain(|p| !ids.contains(&p.id));
+        }
+        #[cfg(feature = "skiplist")]
+        self.remove_from_skip_set(ids);
+    }
+
+    /// `SkipSet` has no `retain`; collect the matching entries, then remove them.
+    #[cfg(feature = "skiplist")]
+    fn remove_from_skip_set(&self, ids: &HashSet<Uuid>) {
+        let stale: Vec<Product> = self
+            .skip_set
+            .iter()
+            .filter(|e| ids.contains(&e.value().id))
+            .map(|e| e.value().clone())
+            .collect();
+        for p in &stale {
+            self.skip_set.remove(p);
+        }
     }
 
-    /// Clear all three sets and the cached benchmark report.
+    /// Clear all sets and the cached benchmark report.
     pub fn reset(&mut self) {
         self.hash_set.clear();
         self.index_set.clear();
         self.btree_set.clear();
+        self.ahash_set.clear();
+        self.dash_set.clear();
+        self.price_index.clear();
+        self.id_map.clear();
+        if let Some(fx) = &mut self.fx_set {
+            fx.clear();
+        }
+        #[cfg(feature = "skiplist")]
+        self.skip_set.clear();
+        if let Some(trie) = &mut self.name_trie {
+            trie.clear();
+        }
         self.last_report = None;
+        self.last_sync_at = None;
     }
 
-    pub fn sizes(&self) -> (usize, usize, usize) {
-        (
-            self.hash_set.len(),
-            self.index_set.len(),
-            self.btree_set.len(),
-        )
+    /// Copies every product out, in `index_set` order so that restoring also
+    /// brings back the insertion order.
+    pub fn snapshot(&self) -> SetSnapshot {
+        SetSnapshot {
+            products: self.index_set.iter().cloned().collect(),
+            captured_at: Utc::now(),
+        }
     }
 
-    // ── Benchmark runner ──────────────────────────────────────────────────────
+    /// Replaces the contents of every set with `snap`'s products. The cached
+    /// benchmark report is left alone.
+    pub fn restore_from_snapshot(&mut self, snap: &SetSnapshot) {
+        self.sync_from_db(&snap.products);
+    }
 
-    pub fn run_benchmark(&mut self, products: Vec<Product>) -> BenchmarkReport {
-        let count = products.len();
+    /// Picks a product from `index_set` by inverse-CDF sampling, where
+    /// `weights[i]` is the relative probability of the i-th inserted product.
+    ///
+    /// Returns `None` if `weights.len() != index_set.len()` or the weights
+    /// don't sum to a positive number.
+    pub fn weighted_sample<'a>(&'a self, weights: &[f64]) -> Option<&'a Product> {
+        self.weighted_sample_with(weights, &mut rand::thread_rng())
+    }
 
-        let hash_result = benchmark_hash_set(&products);
-        let index_result = benchmark_index_set(&products);
-        let btree_result = benchmark_btree_set(&products);
+    /// [`weighted_sample`](Self::weighted_sample) with a caller-supplied RNG.
+    pub fn weighted_sample_with<'a, R: Rng + ?Sized>(
+        &'a self,
+        weights: &[f64],
+        rng: &mut R,
+    ) -> Option<&'a Product> {
+        if weights.len() != self.index_set.len() {
+            return None;
+        }
+        let total: f64 = weights.iter().sum();
+        if !total.is_finite() || total <= 0.0 {
+            return None;
+        }
 
-        // Re-sync manager sets after benchmark
-        self.sync_from_db(&products);
+        let target = rng.gen::<f64>() * total;
+        let mut cumulative = 0.0;
+        for (i, w) in weights.iter().enumerate() {
+            cumulative += w;
+            if target < cumulative {
+                return self.index_set.get_index(i);
+            }
+        }
+        // Floating-point rounding can leave `target` just past the last bucket
+        self.index_set.last()
+    }
+
+    /// Products whose IDs are present in every set. When the sets are
+    /// consistent this is every product.
+    pub fn intersection(&self) -> Vec<&Product> {
+        let index_ids = ids_of(self.index_set.iter());
+        let btree_ids = ids_of(self.btree_set.iter());
+        let ahash_ids = ids_of(self.ahash_set.iter());
+        let fx_ids = self.fx_set.as_ref().map(|fx| ids_of(fx.iter()));
+        #[cfg(feature = "skiplist")]
+        let skip_ids: Option<HashSet<Uuid>> = Some(self.skip_set.iter().map(|e| e.value().id).collect());
+        #[cfg(not(feature = "skiplist"))]
+        let skip_ids: Option<HashSet<Uuid>> = None;
 
-        let winner_insert = fastest_insert(&hash_result, &index_result, &btree_result);
-        let winner_lookup = fastest_lookup(&hash_result, &index_result, &btree_result);
-        let winner_iterate = fastest_iterate(&hash_result, &index_result, &btree_result);
+        self.hash_set
+            .iter()
+            .filter(|p| {
+                index_ids.contains(&p.id)
+                    && btree_ids.contains(&p.id)
+                    && ahash_ids.contains(&p.id)
+                    && self.dash_set.contains(*p)
+                    && fx_ids.as_ref().is_none_or(|ids| ids.contains(&p.id))
+                    && skip_ids.as_ref().is_none_or(|ids| ids.contains(&p.id))
+            })
+            .collect()
+    }
 
-        let summary_table = vec![
-            summary_row(&hash_result),
-            summary_row(&index_result),
-            summary_row(&btree_result),
+    /// IDs present in some sets but not all of them (sorted). Non-empty means
+    /// the sets have diverged.
+    pub fn symmetric_difference(&self) -> Vec<Uuid> {
+        let mut all = vec![
+            ids_of(self.hash_set.iter()),
+            ids_of(self.index_set.iter()),
+            ids_of(self.btree_set.iter()),
+            ids_of(self.ahash_set.iter()),
+            self.dash_set.iter().map(|p| p.id).collect(),
         ];
+        if let Some(fx) = &self.fx_set {
+            all.push(ids_of(fx.iter()));
+        }
+        #[cfg(feature = "skiplist")]
+        all.push(self.skip_set.iter().map(|e| e.value().id).collect());
 
-        let report = BenchmarkReport {
-            run_at: Utc::now().to_rfc3339(),
-            product_count: count,
-            results: vec![hash_result, index_result, btree_result],
-            winner_insert,
-            winner_lookup,
-            winner_iterate,
-            summary_table,
-        };
+        let union: HashSet<Uuid> = all.iter().flatten().copied().collect();
+        let mut diverging: Vec<Uuid> = union
+            .into_iter()
+            .filter(|id| !all.iter().all(|set| set.contains(id)))
+            .collect();
+        diverging.sort_unstable();
+        diverging
+    }
 
-        self.last_report = Some(report.clone());
-        report
+    /// Products priced within `[min_cents, max_cents]`, cheapest first, in
+    /// O(log n + k).
+    ///
+    /// `btree_set` is ordered by `(name, id)`, so a price range can't be cut
+    /// out of it without a full scan; the price-ordered index serves it instead.
+    pub fn range_by_price(&self, min_cents: i64, max_cents: i64) -> Vec<&Product> {
+        if min_cents > max_cents {
+            return vec![];
+        }
+        self.price_index.range(price_bounds(min_cents, max_cents)).filter_map(|(_, id)| self.id_map.get(id)).collect()
     }
-}
 
-// ── Individual set benchmarks ─────────────────────────────────────────────────
+    /// Products whose name starts with `prefix` (ASCII case-insensitive), in
+    /// insertion order. Scans all of `index_set`; an empty prefix matches everything.
+    pub fn find_by_name_prefix<'a>(&'a self, prefix: &str) -> Vec<&'a Product> {
+        filter_by_name_prefix(self.index_set.iter(), prefix)
+    }
 
-/// Builds evenly-spread lookup targets (LOOKUP_SAMPLES indices across the slice).
-fn lookup_targets(products: &[Product]) -> Vec<&Product> {
-    if products.is_empty() {
-        return vec![];
+    /// Same matches as [`find_by_name_prefix`](Self::find_by_name_prefix), in
+    /// `(name, id)` order, reading only the matching ranges of `btree_set`.
+    pub fn find_by_name_prefix_btree<'a>(&'a self, prefix: &str) -> Vec<&'a Product> {
+        btree_prefix_search(&self.btree_set, prefix)
     }
-    let step = (products.len() / LOOKUP_SAMPLES).max(1);
-    products.iter().step_by(step).take(LOOKUP_SAMPLES).collect()
-}
 
-/// Pre-generates LOOKUP_SAMPLES fake products for miss benchmarks.
-fn miss_targets() -> Vec<Product> {
-    (0..LOOKUP_SAMPLES).map(|_| make_fake_product()).collect()
-}
+    /// Up to `limit` `(name, id)` pairs whose name starts with `prefix`
+    /// (case-sensitive), sorted by `(name, id)`. Walks only the matching
+    /// subtree of `name_trie`, or falls back to
+    /// [`autocomplete_btree`](Self::autocomplete_btree) when the trie is off.
+    pub fn autocomplete(&self, prefix: &str, limit: usize) -> Vec<(String, Uuid)> {
+        let Some(trie) = &self.name_trie else {
+            return self.autocomplete_btree(prefix, limit);
+        };
+        trie.iter_prefix(prefix.as_bytes())
+            .take(limit)
+            .map(|(key, id)| {
+                let name = &key[..key.len() - TRIE_KEY_SUFFIX_LEN];
+                (String::from_utf8_lossy(name).into_owned(), *id)
+            })
+            .collect()
+    }
 
-fn benchmark_hash_set(products: &[Product]) -> SetBenchmarkResult {
-    // Warmup: prime the allocator so this benchmark doesn't pay OS page-fault
-    // costs that the second/third benchmark would otherwise avoid for free.
-    {
-        let mut w: HashSet<Product> = HashSet::with_capacity(1_000);
-        for p in products.iter().take(1_000) { w.insert(p.clone()); }
+    /// Same results as [`autocomplete`](Self::autocomplete), from one range
+    /// seek into `btree_set`.
+    pub fn autocomplete_btree(&self, prefix: &str, limit: usize) -> Vec<(String, Uuid)> {
+        btree_prefix_range(&self.btree_set, prefix).take(limit).map(|p| (p.name.clone(), p.id)).collect()
     }
 
-    let mut set: HashSet<Product> = HashSet::with_capacity(products.len());
+    /// The `n` most expensive products, most expensive first, from one pass
+    /// over `hash_set` that keeps the best `n` so far in a min-heap. Ties on
+    /// price are broken by `Product`'s `(name, id)` order, highest first.
+    pub fn top_n_by_price(&self, n: usize) -> Vec<&Product> {
+        top_n_by_heap(self.hash_set.iter(), n, |p| p.price_cents)
+    }
 
-    // Insert all
-    let (_, insert_dur) = timed(|| {
-        for p in products { set.insert(p.clone()); }
-    });
+    /// Like [`top_n_by_price`](Self::top_n_by_price), by `quantity`.
+    pub fn top_n_by_quantity(&self, n: usize) -> Vec<&Product> {
+        top_n_by_heap(self.hash_set.iter(), n, |p| i64::from(p.quantity))
+    }
 
-    // Lookup hit — average of LOOKUP_SAMPLES evenly-spread elements
-    let hits = lookup_targets(products);
-    let (_, lookup_hit_total) = timed(|| {
-        for p in hits.iter().copied() { black_box(set.contains(black_box(p))); }
-    });
-    let lookup_hit_dur = if hits.is_empty() {
-        Duration::ZERO
-    } else {
-        lookup_hit_total / hits.len() as u32
-    };
+    /// Products with `quantity <= threshold`, in `btree_set`'s `(name, id)` order.
+    /// The set isn't ordered by quantity, so this is a full scan.
+    pub fn low_stock_products(&self, threshold: i32) -> Vec<&Product> {
+        self.btree_set.iter().filter(|p| p.quantity <= threshold).collect()
+    }
 
-    // Lookup miss — average of LOOKUP_SAMPLES fresh UUIDs not in the set
-    let misses = miss_targets();
-    let (_, lookup_miss_total) = timed(|| {
-        for f in misses.iter() { black_box(set.contains(black_box(f))); }
-    });
-    let lookup_miss_dur = lookup_miss_total / LOOKUP_SAMPLES as u32;
+    /// Every product with the value of its stock, most valuable first; equal
+    /// values keep `btree_set`'s `(name, id)` order. A full scan plus an
+    /// O(n log n) sort, since no set is ordered by value.
+    pub fn inventory_report(&self) -> Vec<InventoryReportRow> {
+        let mut rows: Vec<InventoryReportRow> = self.btree_set.iter().map(InventoryReportRow::from).collect();
+        // Stable, so ties stay in name order
+        rows.sort_by_key(|r| Reverse(r.total_value_cents));
+        rows
+    }
 
-    // Iterate all — time the full traversal, then slice 10 for the sample
-    let (all_names, iterate_dur) = timed(|| {
-        set.iter().map(|p| p.name.clone()).collect::<Vec<_>>()
-    });
-    let order_sample: Vec<String> = all_names.into_iter().take(10).collect();
+    /// [`AbcAnalysisResult`] over `btree_set`, ordered like
+    /// [`inventory_report`](Self::inventory_report).
+    pub fn abc_analysis_from_sets(&self) -> AbcAnalysisResult {
+        let mut products: Vec<Product> = self.btree_set.iter().cloned().collect();
+        products.sort_by_key(|p| Reverse(p.price_cents.saturating_mul(i64::from(p.quantity))));
+        AbcAnalysisResult::classify(products)
+    }
 
-    // Remove half
-    let half: Vec<Product> = set.iter().take(products.len() / 2).cloned().collect();
-    let (_, remove_dur) = timed(|| {
-        for p in &half { set.remove(p); }
+    /// [`db::fetch_price_histogram`](crate::db::fetch_price_histogram) over
+    /// the price index: one range scan per bucket.
+    pub fn price_histogram(&self, buckets: &[i64]) -> Vec<PriceBucket> {
+        let mut histogram = PriceBucket::empty_histogram(buckets);
+        for bucket in &mut histogram {
+            let lower = Bound::Included((bucket.lower_cents, Uuid::nil()));
+            let upper = bucket.upper_cents.map_or(Bound::Unbounded, |u| Bound::Excluded((u, Uuid::nil())));
+            for p in self.price_index.range((lower, upper)).filter_map(|(_, id)| self.id_map.get(id)) {
+                bucket.count += 1;
+                bucket.total_value_cents += p.price_cents.saturating_mul(i64::from(p.quantity));
+            }
+        }
+        histogram
+    }
+
+    /// Price and quantity aggregates in one pass over `hash_set`. Sums
+    /// saturate at `i64::MAX` instead of overflowing.
+    pub fn stats(&self) -> SetStats {
+        let mut stats = SetStats {
+            min_price_cents: i64::MAX,
+            max_price_cents: i64::MIN,
+            avg_price_cents: 0.0,
+            total_quantity: 0,
+            total_value_cents: 0,
+            product_count: self.hash_set.len(),
+        };
+        let mut price_sum = 0i64;
+        for p in &self.hash_set {
+            stats.min_price_cents = stats.min_price_cents.min(p.price_cents);
+            stats.max_price_cents = stats.max_price_cents.max(p.price_cents);
+            price_sum = price_sum.saturating_add(p.price_cents);
+            stats.total_quantity = stats.total_quantity.saturating_add(i64::from(p.quantity));
+            stats.total_value_cents = stats
+                .total_value_cents
+                .saturating_add(p.price_cents.saturating_mul(i64::from(p.quantity)));
+        }
+        if stats.product_count == 0 {
+            return SetStats { min_price_cents: 0, max_price_cents: 0, ..stats };
+        }
+        stats.avg_price_cents = price_sum as f64 / stats.product_count as f64;
+        stats
+    }
+
+    /// Rough RAM used by the hash, index and B-tree sets: their tables plus
+    /// the inline `Product` values. The heap buffers behind each product's
+    /// strings are not counted.
+    pub fn memory_estimate_bytes(&self) -> SetMemoryEstimate {
+        let product = std::mem::size_of::<Product>();
+        let hash_set_bytes = hash_table_bytes(self.hash_set.len(), self.hash_set.capacity(), product);
+        // Entries live in a Vec of (hash, value); the table only holds indices into it
+        let index_set_bytes = self.index_set.capacity() * (product + 8)
+            + hash_table_bytes(self.index_set.len(), self.index_set.capacity(), std::mem::size_of::<usize>());
+        let btree_set_bytes = btree_bytes(self.btree_set.len(), product);
+        SetMemoryEstimate {
+            hash_set_bytes,
+            index_set_bytes,
+            btree_set_bytes,
+            total_bytes: hash_set_bytes + index_set_bytes + btree_set_bytes,
+        }
+    }
+
+    /// Products in `category`, lazily, in `btree_set`'s `(name, id)` order.
+    /// A full scan; stop early with `take` when only a few are needed.
+    pub fn products_by_category<'a>(&'a self, category: &'a str) -> impl Iterator<Item = &'a Product> + 'a {
+        self.btree_set.iter().filter(move |p| p.category == category)
+    }
+
+    /// Number of in-memory products per category, taken from `hash_set`.
+    pub fn category_counts(&self) -> HashMap<String, usize> {
+        let mut counts = HashMap::new();
+        for p in &self.hash_set {
+            *counts.entry(p.category.clone()).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Splits `btree_set` by category in one pass, so each partition lists its
+    /// products by name, with the stock and stock value summed per category.
+    pub fn partition_by_category(&self) -> HashMap<String, CategoryPartition<'_>> {
+        let mut partitions: HashMap<String, CategoryPartition<'_>> = HashMap::new();
+        for p in &self.btree_set {
+            let partition = partitions.entry(p.category.clone()).or_default();
+            partition.products.push(p);
+            partition.total_quantity = partition.total_quantity.saturating_add(p.quantity);
+            partition.total_value_cents = partition
+                .total_value_cents
+                .saturating_add(p.price_cents.saturating_mul(i64::from(p.quantity)));
+        }
+        partitions
+    }
+
+    /// The category with the most products; ties go to the name that sorts
+    /// first. `None` when the sets are empty.
+    pub fn largest_category(&self) -> Option<(&str, usize)> {
+        self.category_sizes().into_iter().max_by_key(|&(name, count)| (count, Reverse(name)))
+    }
+
+    /// The category with the fewest products; ties go to the name that sorts
+    /// first. `None` when the sets are empty.
+    pub fn smallest_category(&self) -> Option<(&str, usize)> {
+        self.category_sizes().into_iter().min_by_key(|&(name, count)| (count, name))
+    }
+
+    fn category_sizes(&self) -> HashMap<&str, usize> {
+        let mut sizes = HashMap::new();
+        for p in &self.hash_set {
+            *sizes.entry(p.category.as_str()).or_insert(0) += 1;
+        }
+        sizes
+    }
+
+    /// Length of every set, `fx_set` only when enabled.
+    pub fn sizes(&self) -> SetSizes {
+        SetSizes {
+            hash_set: self.hash_set.len(),
+            index_set: self.index_set.len(),
+            btree_set: self.btree_set.len(),
+            ahash_set: self.ahash_set.len(),
+            dash_set: self.dash_set.len(),
+            fx_set: self.fx_set.as_ref().map(|fx| fx.len()),
+            #[cfg(feature = "skiplist")]
+            skip_set: self.skip_set.len(),
+        }
+    }
+
+    /// Metric label for operations applied to every set at once, e.g.
+    /// `HashSet+LinkedHashSet+BTreeSet+AHashSet+DashSet`, plus whichever
+    /// optional sets are enabled.
+    pub fn label(&self) -> String {
+        let mut names = vec!["HashSet", "LinkedHashSet", "BTreeSet", "AHashSet", "DashSet"];
+        if self.fx_set.is_some() {
+            names.push("FxHashSet");
+        }
+        #[cfg(feature = "skiplist")]
+        names.push("SkipSet");
+        names.join("+")
+    }
+
+    /// [`SetDiff`] between `hash_set` and `db_ids`.
+    pub fn diff_ids(&self, db_ids: &[Uuid]) -> SetDiff {
+        let db_ids: HashSet<Uuid> = db_ids.iter().copied().collect();
+        let set_ids: HashSet<Uuid> = self.hash_set.iter().map(|p| p.id).collect();
+        let mut in_db_not_in_sets: Vec<Uuid> = db_ids.difference(&set_ids).copied().collect();
+        let mut in_sets_not_in_db: Vec<Uuid> = set_ids.difference(&db_ids).copied().collect();
+        in_db_not_in_sets.sort_unstable();
+        in_sets_not_in_db.sort_unstable();
+        SetDiff { in_db_not_in_sets, in_sets_not_in_db, count_mismatch: db_ids.len() != self.hash_set.len() }
+    }
+
+    /// Whether `index_set` iterates oldest first, i.e. `created_at` never
+    /// decreases (ties broken by ID, like [`rebalance_index_set`](Self::rebalance_index_set)).
+    pub fn index_set_is_fifo(&self) -> bool {
+        let products: Vec<&Product> = self.index_set.iter().collect();
+        products.windows(2).all(|w| (w[0].created_at, w[0].id) <= (w[1].created_at, w[1].id))
+    }
+
+    /// Reorders `index_set` by `created_at`, restoring the FIFO order that a
+    /// `swap_remove` breaks by moving the last element into the gap. The other
+    /// sets are untouched.
+    pub fn rebalance_index_set(&mut self) {
+        let mut products: Vec<Product> = self.index_set.drain(..).collect();
+        products.sort_by_key(|p| (p.created_at, p.id));
+        self.index_set.extend(products);
+    }
+
+    // ── Benchmark runner ──────────────────────────────────────────────────────
+
+    #[tracing::instrument(
+        skip_all,
+        fields(product_count = products.len(), repeat_runs = config.repeat_runs, elapsed_ms = tracing::field::Empty)
+    )]
+    pub fn run_benchmark(&mut self, products: Vec<Product>, config: &SetBenchmarkConfig) -> AppResult<BenchmarkReport> {
+        let start = Instant::now();
+        let count = products.len();
+        // Shared by every repeat run of the concurrent benchmarks
+        let threads = config.threads.max(1);
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .map_err(|e| anyhow::anyhow!("failed to build a {}-thread rayon pool: {}", threads, e))?;
+
+        let with_fx = self.fx_set.is_some();
+        let mut results = repeat_runs(config.repeat_runs, || {
+            let mut pass = vec![benchmark_hash_set(&products, config)];
+            if config.index_remove_strategy != Some(RemoveStrategy::Shift) {
+                pass.push(benchmark_index_set(&products, config));
+            }
+            if config.runs_shift_remove(count) {
+                pass.push(benchmark_index_set_shift_remove(&products, config));
+            }
+            pass.extend([
+                benchmark_btree_set(&products, config),
+                benchmark_ahash_set(&products, config),
+                benchmark_dashmap_set(&products, config, &pool),
+            ]);
+            if with_fx {
+                pass.push(benchmark_fx_hash_set(&products, config));
+            }
+            #[cfg(feature = "skiplist")]
+            pass.push(benchmark_skiplist(&products, config));
+            pass
+        });
+
+        // Re-sync manager sets after benchmark
+        self.sync_from_db(&products);
+
+        let winner_insert = fastest_by(&results, |r| r.insert_all.duration_ns);
+        let winner_lookup = fastest_by(&results, |r| r.lookup_hit.duration_ns);
+        let winner_iterate = fastest_by(&results, |r| r.iterate_all.duration_ns);
+
+        // Reported alongside the sets but kept out of the running for winner: two
+        // aren't sets, and the locked HashSet is only a baseline for DashSet
+        results.extend(repeat_runs(config.repeat_runs, || {
+            vec![
+                benchmark_binary_heap(&products, config),
+                benchmark_hashmap(&products, config),
+                benchmark_rwlock_hash_set(&products, config, &pool),
+            ]
+        }));
+
+        let summary_table = results.iter().map(summary_row).collect();
+        let price_range = benchmark_price_range(&products, config.repeat_runs);
+        let prefix_search = benchmark_prefix_search(&products, config.repeat_runs);
+
+        let report = BenchmarkReport {
+            run_id: Uuid::new_v4(),
+            run_at: Utc::now().to_rfc3339(),
+            product_count: count,
+            results,
+            winner_insert,
+            winner_lookup,
+            winner_iterate,
+            summary_table,
+            price_range,
+            prefix_search,
+        };
+
+        self.last_report = Some(report.clone());
+        tracing::Span::current().record("elapsed_ms", start.elapsed().as_secs_f64() * 1000.0);
+        Ok(report)
+    }
+}
+
+/// `(price_cents, id)` keys covering every product priced within `[min_cents, max_cents]`.
+fn price_bounds(min_cents: i64, max_cents: i64) -> RangeInclusive<(i64, Uuid)> {
+    (min_cents, Uuid::nil())..=(max_cents, Uuid::max())
+}
+
+/// Every field except `updated_at` and `version`; see [`SetManager::upsert_product`].
+fn same_content(a: &Product, b: &Product) -> bool {
+    a.id == b.id
+        && a.name == b.name
+        && a.description == b.description
+        && a.price_cents == b.price_cents
+        && a.quantity == b.quantity
+        && a.category == b.category
+        && a.created_at == b.created_at
+}
+
+/// Kendall rank correlation between two orderings of the same items: 1.0 when
+/// they agree on every pair, -1.0 when one is the other reversed, near 0 when
+/// the orders are unrelated. Items missing from either side are ignored; with
+/// fewer than two shared items there are no pairs to disagree on, so it is 1.0.
+pub fn kendall_tau<T: Eq + std::hash::Hash>(a: &[T], b: &[T]) -> f64 {
+    let rank_in_b: HashMap<&T, usize> = b.iter().enumerate().map(|(i, item)| (item, i)).collect();
+    let ranks: Vec<usize> = a.iter().filter_map(|item| rank_in_b.get(item).copied()).collect();
+    let n = ranks.len();
+    if n < 2 {
+        return 1.0;
+    }
+    // Ranks are distinct, so every pair is one or the other
+    let mut concordant = 0i64;
+    let mut discordant = 0i64;
+    for (i, &r) in ranks.iter().enumerate() {
+        for &later in &ranks[i + 1..] {
+            if r < later {
+                concordant += 1;
+            } else {
+                discordant += 1;
+            }
+        }
+    }
+    (concordant - discordant) as f64 / (n * (n - 1) / 2) as f64
+}
+
+/// Products from `products` whose name starts with `prefix`, ignoring ASCII case.
+pub fn filter_by_name_prefix<'a>(products: impl Iterator<Item = &'a Product>, prefix: &str) -> Vec<&'a Product> {
+    products
+        .filter(|p| {
+            p.name.len() >= prefix.len() && p.name.as_bytes()[..prefix.len()].eq_ignore_ascii_case(prefix.as_bytes())
+        })
+        .collect()
+}
+
+/// Case-insensitive prefix search over a case-sensitive `BTreeSet`.
+///
+/// The set is ordered by the raw name, so "pro" and "Pro" live in different
+/// ranges. The prefix is grown one character at a time in every case variant,
+/// dropping variants no name starts with; each surviving variant is then one
+/// `range()` seek plus a walk over its matches.
+fn btree_prefix_search<'a>(set: &'a BTreeSet<Product>, prefix: &str) -> Vec<&'a Product> {
+    let mut stems = vec![String::new()];
+    for c in prefix.chars() {
+        let mut variants = vec![c.to_ascii_lowercase(), c.to_ascii_uppercase()];
+        variants.dedup();
+        stems = stems
+            .iter()
+            .flat_map(|stem| variants.iter().map(move |v| format!("{}{}", stem, v)))
+            .filter(|stem| btree_prefix_range(set, stem).next().is_some())
+            .collect();
+    }
+
+    let mut found: Vec<&Product> = stems.iter().flat_map(|stem| btree_prefix_range(set, stem)).collect();
+    // Each variant's range is sorted, but the variants interleave
+    found.sort_unstable();
+    found
+}
+
+/// Products whose name starts with `stem` (case-sensitive), in set order.
+fn btree_prefix_range<'a: 's, 's>(
+    set: &'a BTreeSet<Product>,
+    stem: &'s str,
+) -> impl Iterator<Item = &'a Product> + 's {
+    // Nil is the smallest UUID, so the probe sorts before every product named `stem`
+    let probe = Product { id: Uuid::nil(), name: stem.to_string(), ..make_fake_product() };
+    set.range((Bound::Included(probe), Bound::Unbounded))
+        .take_while(move |p| p.name.starts_with(stem))
+}
+
+/// Bytes after the name in a [`trie_key`]: the NUL separator and the UUID.
+const TRIE_KEY_SUFFIX_LEN: usize = 1 + 16;
+
+/// `name`, a NUL, then the UUID's bytes. Unique per product even when names
+/// repeat, and since NUL sorts first, byte order matches `btree_set`'s
+/// `(name, id)` order.
+fn trie_key(name: &str, id: Uuid) -> Vec<u8> {
+    let mut key = Vec::with_capacity(name.len() + TRIE_KEY_SUFFIX_LEN);
+    key.extend_from_slice(name.as_bytes());
+    key.push(0);
+    key.extend_from_slice(id.as_bytes());
+    key
+}
+
+fn ids_of<'a>(products: impl Iterator<Item = &'a Product>) -> HashSet<Uuid> {
+    products.map(|p| p.id).collect()
+}
+
+/// Calls `run` `runs` times and merges the passes result by result: every
+/// `OpTiming` becomes the median across passes with min/max attached. The
+/// other fields (order sample, throughput, notes) come from the first pass.
+fn repeat_runs<F>(runs: u32, mut run: F) -> Vec<SetBenchmarkResult>
+where
+    F: FnMut() -> Vec<SetBenchmarkResult>,
+{
+    let passes: Vec<Vec<SetBenchmarkResult>> = (0..runs.max(1)).map(|_| run()).collect();
+    if passes.len() == 1 {
+        return passes.into_iter().next().unwrap_or_default();
+    }
+
+    let merge = |i: usize, op: fn(&SetBenchmarkResult) -> &OpTiming| {
+        let ns: Vec<u64> = passes.iter().map(|pass| op(&pass[i]).duration_ns).collect();
+        OpTiming::across_runs(&ns)
+    };
+    (0..passes[0].len())
+        .map(|i| SetBenchmarkResult {
+            insert_all: merge(i, |r| &r.insert_all),
+            lookup_hit: merge(i, |r| &r.lookup_hit),
+            lookup_miss: merge(i, |r| &r.lookup_miss),
+            iterate_all: merge(i, |r| &r.iterate_all),
+            remove_half: merge(i, |r| &r.remove_half),
+            ..passes[0][i].clone()
+        })
+        .collect()
+}
+
+// ── Individual set benchmarks ─────────────────────────────────────────────────
+
+/// Builds evenly-spread lookup targets (`samples` indices across the slice).
+fn lookup_targets(products: &[Product], samples: usize) -> Vec<&Product> {
+    if products.is_empty() || samples == 0 {
+        return vec![];
+    }
+    let step = (products.len() / samples).max(1);
+    products.iter().step_by(step).take(samples).collect()
+}
+
+/// Pre-generates `samples` fake products for miss benchmarks.
+fn miss_targets(samples: usize) -> Vec<Product> {
+    (0..samples).map(|_| make_fake_product()).collect()
+}
+
+/// Average duration of one of `samples` lookups timed together.
+fn per_sample(total: Duration, samples: usize) -> Duration {
+    if samples == 0 {
+        Duration::ZERO
+    } else {
+        total / samples as u32
+    }
+}
+
+/// Times a step that leaves the set unchanged, divided by `samples` as in
+/// [`per_sample`]. With `repeat_runs > 1` the step runs that many times via
+/// [`timed_multi`] and the median run is reported with min/max attached.
+fn timed_read_only<F, R>(config: &SetBenchmarkConfig, samples: usize, f: F) -> OpTiming
+where
+    F: Fn() -> R,
+{
+    if config.repeat_runs <= 1 {
+        let (result, total) = timed(f);
+        black_box(result);
+        return per_sample(total, samples).into();
+    }
+    let multi = timed_multi(f, config.repeat_runs as usize);
+    let ns: Vec<u64> = multi.runs.iter().map(|&d| per_sample(d, samples).as_nanos() as u64).collect();
+    OpTiming::across_runs(&ns)
+}
+
+fn benchmark_hash_set(products: &[Product], config: &SetBenchmarkConfig) -> SetBenchmarkResult {
+    // Warmup: prime the allocator so this benchmark doesn't pay OS page-fault
+    // costs that the second/third benchmark would otherwise avoid for free.
+    {
+        let mut w: HashSet<Product> = HashSet::with_capacity(config.warmup_count);
+        for p in products.iter().take(config.warmup_count) { w.insert(p.clone()); }
+    }
+
+    let mut set: HashSet<Product> = HashSet::with_capacity(products.len());
+
+    // Insert all
+    let (_, insert_dur) = timed(|| {
+        for p in products { set.insert(p.clone()); }
+    });
+
+    // Lookup hit — average of `lookup_samples` evenly-spread elements
+    let hits = lookup_targets(products, config.lookup_samples);
+    let lookup_hit = timed_read_only(config, hits.len(), || {
+        for p in hits.iter().copied() { black_box(set.contains(black_box(p))); }
+    });
+
+    // Lookup miss — average of `lookup_samples` fresh UUIDs not in the set
+    let misses = miss_targets(config.lookup_samples);
+    let lookup_miss = timed_read_only(config, misses.len(), || {
+        for f in misses.iter() { black_box(set.contains(black_box(f))); }
+    });
+
+    // Iterate all — time the full traversal, then take 10 for the sample
+    let iterate_all = timed_read_only(config, 1, || {
+        set.iter().map(|p| p.name.clone()).collect::<Vec<_>>()
+    });
+    let order_sample: Vec<String> = set.iter().take(10).map(|p| p.name.clone()).collect();
+
+    // Remove half
+    let half: Vec<Product> = set.iter().take(products.len() / 2).cloned().collect();
+    let (_, remove_dur) = timed(|| {
+        for p in &half { set.remove(p); }
     });
 
     SetBenchmarkResult {
         set_type: "HashSet".to_string(),
-        description: "Unordered. O(1) avg insert/lookup/remove. Lookup = avg of 1 000 samples.".to_string(),
+        description: format!("Unordered. O(1) avg insert/lookup/remove. Lookup = avg of {} samples.", config.lookup_samples),
         product_count: products.len(),
         insert_all: insert_dur.into(),
-        lookup_hit: lookup_hit_dur.into(),
-        lookup_miss: lookup_miss_dur.into(),
-        iterate_all: iterate_dur.into(),
+        lookup_hit,
+        lookup_miss,
+        iterate_all,
         remove_half: remove_dur.into(),
         iteration_order_sample: order_sample,
         order_guaranteed: false,
         order_type: "Arbitrary (hash-based)".to_string(),
+        threads: None,
+        per_thread_ops_per_sec: None,
+        extra_info: None,
+        remove_strategy: None,
     }
 }
 
 /// `IndexSet` (from the `indexmap` crate) is the idiomatic Rust equivalent of
 /// a `LinkedHashSet`: it stores elements in a flat array (preserving insertion
 /// order) while maintaining a hash-map index for O(1) average lookups.
-fn benchmark_index_set(products: &[Product]) -> SetBenchmarkResult {
+///
+/// `remove_half` uses `swap_remove`, which is O(1) but fills each gap with the
+/// last element and so breaks insertion order; see
+/// [`benchmark_index_set_shift_remove`] for the order-preserving variant.
+fn benchmark_index_set(products: &[Product], config: &SetBenchmarkConfig) -> SetBenchmarkResult {
+    benchmark_index_set_with(products, config, RemoveStrategy::Swap)
+}
+
+/// Like [`benchmark_index_set`], removing with `shift_remove`: insertion order
+/// survives, but every removal shifts the elements after it, so removing half
+/// of n products costs O(n²) overall.
+fn benchmark_index_set_shift_remove(products: &[Product], config: &SetBenchmarkConfig) -> SetBenchmarkResult {
+    benchmark_index_set_with(products, config, RemoveStrategy::Shift)
+}
+
+/// Removes each of `items` from `set` using `strategy`.
+fn index_set_remove(set: &mut IndexSet<Product>, items: &[Product], strategy: RemoveStrategy) {
+    match strategy {
+        RemoveStrategy::Swap => {
+            for p in items { set.swap_remove(p); }
+        }
+        RemoveStrategy::Shift => {
+            for p in items { set.shift_remove(p); }
+        }
+    }
+}
+
+fn benchmark_index_set_with(products: &[Product], config: &SetBenchmarkConfig, strategy: RemoveStrategy) -> SetBenchmarkResult {
     // Warmup
     {
-        let mut w: IndexSet<Product> = IndexSet::with_capacity(1_000);
-        for p in products.iter().take(1_000) { w.insert(p.clone()); }
+        let mut w: IndexSet<Product> = IndexSet::with_capacity(config.warmup_count);
+        for p in products.iter().take(config.warmup_count) { w.insert(p.clone()); }
     }
 
     let mut set: IndexSet<Product> = IndexSet::with_capacity(products.len());
@@ -299,37 +1752,155 @@ fn benchmark_index_set(products: &[Product]) -> SetBenchmarkResult {
         for p in products { set.insert(p.clone()); }
     });
 
-    // Lookup hit — average of LOOKUP_SAMPLES evenly-spread elements
-    let hits = lookup_targets(products);
-    let (_, lookup_hit_total) = timed(|| {
+    // Lookup hit — average of `lookup_samples` evenly-spread elements
+    let hits = lookup_targets(products, config.lookup_samples);
+    let lookup_hit = timed_read_only(config, hits.len(), || {
         for p in hits.iter().copied() { black_box(set.contains(black_box(p))); }
     });
-    let lookup_hit_dur = if hits.is_empty() {
-        Duration::ZERO
-    } else {
-        lookup_hit_total / hits.len() as u32
+
+    // Lookup miss — average of `lookup_samples` fresh UUIDs not in the set
+    let misses = miss_targets(config.lookup_samples);
+    let lookup_miss = timed_read_only(config, misses.len(), || {
+        for f in misses.iter() { black_box(set.contains(black_box(f))); }
+    });
+
+    let iterate_all = timed_read_only(config, 1, || {
+        set.iter().map(|p| p.name.clone()).collect::<Vec<_>>()
+    });
+    let order_sample: Vec<String> = set.iter().take(10).map(|p| p.name.clone()).collect();
+
+    let half: Vec<Product> = set.iter().take(products.len() / 2).cloned().collect();
+    let (_, remove_dur) = timed(|| index_set_remove(&mut set, &half, strategy));
+
+    let (set_type, remove_note) = match strategy {
+        RemoveStrategy::Swap => (
+            "IndexSet (LinkedHashSet)",
+            "swap_remove: O(1), but the last element fills each gap, so insertion order is lost",
+        ),
+        RemoveStrategy::Shift => (
+            "IndexSet (shift_remove)",
+            "shift_remove: keeps insertion order, but each removal is O(n)",
+        ),
     };
+    SetBenchmarkResult {
+        set_type: set_type.to_string(),
+        description: format!(
+            "Insertion-ordered. O(1) avg insert/lookup. Remove = {}. Lookup = avg of {} samples.",
+            remove_note, config.lookup_samples
+        ),
+        product_count: products.len(),
+        insert_all: insert_dur.into(),
+        lookup_hit,
+        lookup_miss,
+        iterate_all,
+        remove_half: remove_dur.into(),
+        iteration_order_sample: order_sample,
+        order_guaranteed: true,
+        order_type: "Insertion order (FIFO)".to_string(),
+        threads: None,
+        per_thread_ops_per_sec: None,
+        extra_info: None,
+        remove_strategy: Some(strategy),
+    }
+}
+
+fn benchmark_btree_set(products: &[Product], config: &SetBenchmarkConfig) -> SetBenchmarkResult {
+    // Warmup
+    {
+        let mut w: BTreeSet<Product> = BTreeSet::new();
+        for p in products.iter().take(config.warmup_count) { w.insert(p.clone()); }
+    }
+
+    let mut set: BTreeSet<Product> = BTreeSet::new();
+
+    let (_, insert_dur) = timed(|| {
+        for p in products { set.insert(p.clone()); }
+    });
+
+    // Lookup hit — average of `lookup_samples` evenly-spread elements
+    let hits = lookup_targets(products, config.lookup_samples);
+    let lookup_hit = timed_read_only(config, hits.len(), || {
+        for p in hits.iter().copied() { black_box(set.contains(black_box(p))); }
+    });
+
+    // Lookup miss — average of `lookup_samples` fresh UUIDs not in the set
+    let misses = miss_targets(config.lookup_samples);
+    let lookup_miss = timed_read_only(config, misses.len(), || {
+        for f in misses.iter() { black_box(set.contains(black_box(f))); }
+    });
+
+    let iterate_all = timed_read_only(config, 1, || {
+        set.iter().map(|p| p.name.clone()).collect::<Vec<_>>()
+    });
+    let order_sample: Vec<String> = set.iter().take(10).map(|p| p.name.clone()).collect();
+
+    let half: Vec<Product> = set.iter().take(products.len() / 2).cloned().collect();
+    let (_, remove_dur) = timed(|| {
+        for p in &half { set.remove(p); }
+    });
+
+    SetBenchmarkResult {
+        set_type: "BTreeSet".to_string(),
+        description: format!("Sorted by (name, id). O(log n) insert/lookup/remove. Lookup = avg of {} samples.", config.lookup_samples),
+        product_count: products.len(),
+        insert_all: insert_dur.into(),
+        lookup_hit,
+        lookup_miss,
+        iterate_all,
+        remove_half: remove_dur.into(),
+        iteration_order_sample: order_sample,
+        order_guaranteed: true,
+        order_type: "Sorted alphabetically by name".to_string(),
+        threads: None,
+        per_thread_ops_per_sec: None,
+        extra_info: None,
+        remove_strategy: None,
+    }
+}
+
+/// `SkipSet` keeps the same `(name, id)` order as `BTreeSet` (via `Product: Ord`)
+/// but links nodes in towers instead of packing them into B-tree pages.
+#[cfg(feature = "skiplist")]
+fn benchmark_skiplist(products: &[Product], config: &SetBenchmarkConfig) -> SetBenchmarkResult {
+    // Warmup
+    {
+        let w: SkipSet<Product> = SkipSet::new();
+        for p in products.iter().take(config.warmup_count) { w.insert(p.clone()); }
+    }
+
+    let set: SkipSet<Product> = SkipSet::new();
+
+    let (_, insert_dur) = timed(|| {
+        for p in products { set.insert(p.clone()); }
+    });
 
-    // Lookup miss — average of LOOKUP_SAMPLES fresh UUIDs not in the set
-    let misses = miss_targets();
+    // Lookup hit — average of `lookup_samples` evenly-spread elements
+    let hits = lookup_targets(products, config.lookup_samples);
+    let (_, lookup_hit_total) = timed(|| {
+        for p in hits.iter().copied() { black_box(set.contains(black_box(p))); }
+    });
+    let lookup_hit_dur = per_sample(lookup_hit_total, hits.len());
+
+    // Lookup miss — average of `lookup_samples` fresh UUIDs not in the set
+    let misses = miss_targets(config.lookup_samples);
     let (_, lookup_miss_total) = timed(|| {
         for f in misses.iter() { black_box(set.contains(black_box(f))); }
     });
-    let lookup_miss_dur = lookup_miss_total / LOOKUP_SAMPLES as u32;
+    let lookup_miss_dur = per_sample(lookup_miss_total, misses.len());
 
     let (all_names, iterate_dur) = timed(|| {
-        set.iter().map(|p| p.name.clone()).collect::<Vec<_>>()
+        set.iter().map(|e| e.value().name.clone()).collect::<Vec<_>>()
     });
     let order_sample: Vec<String> = all_names.into_iter().take(10).collect();
 
-    let half: Vec<Product> = set.iter().take(products.len() / 2).cloned().collect();
+    let half: Vec<Product> = set.iter().take(products.len() / 2).map(|e| e.value().clone()).collect();
     let (_, remove_dur) = timed(|| {
-        for p in &half { set.swap_remove(p); }
+        for p in &half { set.remove(p); }
     });
 
     SetBenchmarkResult {
-        set_type: "IndexSet (LinkedHashSet)".to_string(),
-        description: "Insertion-ordered. O(1) avg insert/lookup. Lookup = avg of 1 000 samples.".to_string(),
+        set_type: "SkipSet".to_string(),
+        description: format!("Lock-free skip list sorted by (name, id). O(log n) insert/lookup/remove. Lookup = avg of {} samples.", config.lookup_samples),
         product_count: products.len(),
         insert_all: insert_dur.into(),
         lookup_hit: lookup_hit_dur.into(),
@@ -338,40 +1909,42 @@ fn benchmark_index_set(products: &[Product]) -> SetBenchmarkResult {
         remove_half: remove_dur.into(),
         iteration_order_sample: order_sample,
         order_guaranteed: true,
-        order_type: "Insertion order (FIFO)".to_string(),
+        order_type: "Sorted by (name, id)".to_string(),
+        threads: None,
+        per_thread_ops_per_sec: None,
+        extra_info: None,
+        remove_strategy: None,
     }
 }
 
-fn benchmark_btree_set(products: &[Product]) -> SetBenchmarkResult {
+/// `AHashSet` is a `std::collections::HashSet` with `ahash`'s hasher swapped
+/// in for SipHash-1-3. Same structure, same semantics — only hashing differs.
+fn benchmark_ahash_set(products: &[Product], config: &SetBenchmarkConfig) -> SetBenchmarkResult {
     // Warmup
     {
-        let mut w: BTreeSet<Product> = BTreeSet::new();
-        for p in products.iter().take(1_000) { w.insert(p.clone()); }
+        let mut w: AHashSet<Product> = AHashSet::with_capacity(config.warmup_count);
+        for p in products.iter().take(config.warmup_count) { w.insert(p.clone()); }
     }
 
-    let mut set: BTreeSet<Product> = BTreeSet::new();
+    let mut set: AHashSet<Product> = AHashSet::with_capacity(products.len());
 
     let (_, insert_dur) = timed(|| {
         for p in products { set.insert(p.clone()); }
     });
 
-    // Lookup hit — average of LOOKUP_SAMPLES evenly-spread elements
-    let hits = lookup_targets(products);
+    // Lookup hit — average of `lookup_samples` evenly-spread elements
+    let hits = lookup_targets(products, config.lookup_samples);
     let (_, lookup_hit_total) = timed(|| {
         for p in hits.iter().copied() { black_box(set.contains(black_box(p))); }
     });
-    let lookup_hit_dur = if hits.is_empty() {
-        Duration::ZERO
-    } else {
-        lookup_hit_total / hits.len() as u32
-    };
+    let lookup_hit_dur = per_sample(lookup_hit_total, hits.len());
 
-    // Lookup miss — average of LOOKUP_SAMPLES fresh UUIDs not in the set
-    let misses = miss_targets();
+    // Lookup miss — average of `lookup_samples` fresh UUIDs not in the set
+    let misses = miss_targets(config.lookup_samples);
     let (_, lookup_miss_total) = timed(|| {
         for f in misses.iter() { black_box(set.contains(black_box(f))); }
     });
-    let lookup_miss_dur = lookup_miss_total / LOOKUP_SAMPLES as u32;
+    let lookup_miss_dur = per_sample(lookup_miss_total, misses.len());
 
     let (all_names, iterate_dur) = timed(|| {
         set.iter().map(|p| p.name.clone()).collect::<Vec<_>>()
@@ -384,8 +1957,8 @@ fn benchmark_btree_set(products: &[Product]) -> SetBenchmarkResult {
     });
 
     SetBenchmarkResult {
-        set_type: "BTreeSet".to_string(),
-        description: "Sorted by (name, id). O(log n) insert/lookup/remove. Lookup = avg of 1 000 samples.".to_string(),
+        set_type: "AHashSet".to_string(),
+        description: format!("Unordered. O(1) avg insert/lookup/remove with the aHash hasher. Lookup = avg of {} samples.", config.lookup_samples),
         product_count: products.len(),
         insert_all: insert_dur.into(),
         lookup_hit: lookup_hit_dur.into(),
@@ -393,283 +1966,2837 @@ fn benchmark_btree_set(products: &[Product]) -> SetBenchmarkResult {
         iterate_all: iterate_dur.into(),
         remove_half: remove_dur.into(),
         iteration_order_sample: order_sample,
-        order_guaranteed: true,
-        order_type: "Sorted alphabetically by name".to_string(),
+        order_guaranteed: false,
+        order_type: "Arbitrary (hash-based)".to_string(),
+        threads: None,
+        per_thread_ops_per_sec: None,
+        extra_info: None,
+        remove_strategy: None,
     }
 }
 
-// ── Helpers ───────────────────────────────────────────────────────────────────
+/// `DashSet` is benchmarked concurrently: every phase except iteration is split
+/// across the workers of `pool` that hit the same set at once, so timings are
+/// wall-clock for the whole team rather than single-threaded.
+fn benchmark_dashmap_set(products: &[Product], config: &SetBenchmarkConfig, pool: &rayon::ThreadPool) -> SetBenchmarkResult {
+    let threads = pool.current_num_threads();
+    let chunk = products.len().div_ceil(threads).max(1);
 
-fn make_fake_product() -> Product {
-    Product {
-        id: Uuid::new_v4(),
-        name: "zzz_nonexistent_product".to_string(),
-        description: None,
-        price_cents: 0,
-        quantity: 0,
-        category: "none".to_string(),
-        created_at: Utc::now(),
-        updated_at: Utc::now(),
+    // Warmup
+    {
+        let w: DashSet<Product> = DashSet::with_capacity(config.warmup_count);
+        for p in products.iter().take(config.warmup_count) { w.insert(p.clone()); }
     }
-}
 
-fn fastest_insert(h: &SetBenchmarkResult, l: &SetBenchmarkResult, b: &SetBenchmarkResult) -> String {
-    [
-        (&h.set_type, h.insert_all.duration_ns),
-        (&l.set_type, l.insert_all.duration_ns),
-        (&b.set_type, b.insert_all.duration_ns),
-    ]
-    .iter()
-    .min_by_key(|x| x.1)
-    .map(|x| x.0.as_str())
-    .unwrap_or("N/A")
-    .to_string()
-}
-
-fn fastest_lookup(h: &SetBenchmarkResult, l: &SetBenchmarkResult, b: &SetBenchmarkResult) -> String {
-    [
-        (&h.set_type, h.lookup_hit.duration_ns),
-        (&l.set_type, l.lookup_hit.duration_ns),
-        (&b.set_type, b.lookup_hit.duration_ns),
-    ]
-    .iter()
-    .min_by_key(|x| x.1)
-    .map(|x| x.0.as_str())
-    .unwrap_or("N/A")
-    .to_string()
-}
-
-fn fastest_iterate(h: &SetBenchmarkResult, l: &SetBenchmarkResult, b: &SetBenchmarkResult) -> String {
-    [
-        (&h.set_type, h.iterate_all.duration_ns),
-        (&l.set_type, l.iterate_all.duration_ns),
-        (&b.set_type, b.iterate_all.duration_ns),
-    ]
-    .iter()
-    .min_by_key(|x| x.1)
-    .map(|x| x.0.as_str())
-    .unwrap_or("N/A")
-    .to_string()
-}
+    let set: DashSet<Product> = DashSet::with_capacity(products.len());
 
-fn summary_row(r: &SetBenchmarkResult) -> SummaryRow {
-    SummaryRow {
-        set_type: r.set_type.clone(),
-        insert_ms: r.insert_all.duration_ms,
-        lookup_hit_us: r.lookup_hit.duration_us,
-        lookup_miss_us: r.lookup_miss.duration_us,
-        iterate_ms: r.iterate_all.duration_ms,
-        remove_ms: r.remove_half.duration_ms,
-        order: r.order_type.clone(),
+    let (_, insert_dur) = timed(|| {
+        pool.install(|| {
+            products.par_chunks(chunk).for_each(|part| {
+                for p in part { set.insert(p.clone()); }
+            })
+        })
+    });
+
+    // Lookup hit — average of `lookup_samples` evenly-spread elements
+    let hits = lookup_targets(products, config.lookup_samples);
+    let (_, lookup_hit_total) = timed(|| {
+        pool.install(|| {
+            hits.par_iter().for_each(|p| { black_box(set.contains(black_box(*p))); })
+        })
+    });
+    let lookup_hit_dur = per_sample(lookup_hit_total, hits.len());
+
+    // Lookup miss — average of `lookup_samples` fresh UUIDs not in the set
+    let misses = miss_targets(config.lookup_samples);
+    let (_, lookup_miss_total) = timed(|| {
+        pool.install(|| {
+            misses.par_iter().for_each(|f| { black_box(set.contains(black_box(f))); })
+        })
+    });
+    let lookup_miss_dur = per_sample(lookup_miss_total, misses.len());
+
+    let (all_names, iterate_dur) = timed(|| {
+        set.iter().map(|p| p.name.clone()).collect::<Vec<_>>()
+    });
+    let order_sample: Vec<String> = all_names.into_iter().take(10).collect();
+
+    let half: Vec<Product> = set.iter().take(products.len() / 2).map(|p| p.clone()).collect();
+    let (_, remove_dur) = timed(|| {
+        pool.install(|| {
+            half.par_chunks(chunk).for_each(|part| {
+                for p in part { set.remove(p); }
+            })
+        })
+    });
+
+    let total_ops = products.len() + hits.len() + misses.len() + half.len();
+    let busy_secs = (insert_dur + lookup_hit_total + lookup_miss_total + remove_dur).as_secs_f64();
+    let per_thread_ops_per_sec = if busy_secs > 0.0 {
+        total_ops as f64 / busy_secs / threads as f64
+    } else {
+        0.0
+    };
+
+    SetBenchmarkResult {
+        set_type: "DashSet".to_string(),
+        description: format!(
+            "Sharded concurrent hash set. Insert/lookup/remove run on {} Rayon threads (wall-clock). Lookup = avg of {} samples.",
+            threads, config.lookup_samples
+        ),
+        product_count: products.len(),
+        insert_all: insert_dur.into(),
+        lookup_hit: lookup_hit_dur.into(),
+        lookup_miss: lookup_miss_dur.into(),
+        iterate_all: iterate_dur.into(),
+        remove_half: remove_dur.into(),
+        iteration_order_sample: order_sample,
+        order_guaranteed: false,
+        order_type: "Arbitrary (sharded hash)".to_string(),
+        threads: Some(threads),
+        per_thread_ops_per_sec: Some(per_thread_ops_per_sec),
+        extra_info: None,
+        remove_strategy: None,
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use chrono::Utc;
-    use uuid::Uuid;
+/// Baseline for [`benchmark_dashmap_set`]: the same concurrent phases against
+/// a `std` `HashSet` behind one `RwLock`. Lookups share the read lock, but
+/// every insert and remove takes the whole set, which is what `DashSet`'s
+/// sharding avoids.
+fn benchmark_rwlock_hash_set(products: &[Product], config: &SetBenchmarkConfig, pool: &rayon::ThreadPool) -> SetBenchmarkResult {
+    let threads = pool.current_num_threads();
+    let chunk = products.len().div_ceil(threads).max(1);
 
-    fn make(id: Uuid, name: &str) -> Product {
-        Product {
-            id,
-            name: name.to_string(),
-            description: None,
-            price_cents: 500,
-            quantity: 10,
-            category: "Test".to_string(),
-            created_at: Utc::now(),
-            updated_at: Utc::now(),
+    // Warmup
+    {
+        let w = std::sync::RwLock::new(HashSet::with_capacity(config.warmup_count));
+        for p in products.iter().take(config.warmup_count) { w.write().unwrap().insert(p.clone()); }
+    }
+
+    let set = std::sync::RwLock::new(HashSet::with_capacity(products.len()));
+
+    let (_, insert_dur) = timed(|| {
+        pool.install(|| {
+            products.par_chunks(chunk).for_each(|part| {
+                for p in part { set.write().unwrap().insert(p.clone()); }
+            })
+        })
+    });
+
+    // Lookup hit — average of `lookup_samples` evenly-spread elements
+    let hits = lookup_targets(products, config.lookup_samples);
+    let (_, lookup_hit_total) = timed(|| {
+        pool.install(|| {
+            hits.par_iter().for_each(|p| { black_box(set.read().unwrap().contains(black_box(*p))); })
+        })
+    });
+    let lookup_hit_dur = per_sample(lookup_hit_total, hits.len());
+
+    // Lookup miss — average of `lookup_samples` fresh UUIDs not in the set
+    let misses = miss_targets(config.lookup_samples);
+    let (_, lookup_miss_total) = timed(|| {
+        pool.install(|| {
+            misses.par_iter().for_each(|f| { black_box(set.read().unwrap().contains(black_box(f))); })
+        })
+    });
+    let lookup_miss_dur = per_sample(lookup_miss_total, misses.len());
+
+    let (all_names, iterate_dur) = timed(|| {
+        set.read().unwrap().iter().map(|p| p.name.clone()).collect::<Vec<_>>()
+    });
+    let order_sample: Vec<String> = all_names.into_iter().take(10).collect();
+
+    let half: Vec<Product> = set.read().unwrap().iter().take(products.len() / 2).cloned().collect();
+    let (_, remove_dur) = timed(|| {
+        pool.install(|| {
+            half.par_chunks(chunk).for_each(|part| {
+                for p in part { set.write().unwrap().remove(p); }
+            })
+        })
+    });
+
+    let total_ops = products.len() + hits.len() + misses.len() + half.len();
+    let busy_secs = (insert_dur + lookup_hit_total + lookup_miss_total + remove_dur).as_secs_f64();
+    let per_thread_ops_per_sec = if busy_secs > 0.0 {
+        total_ops as f64 / busy_secs / threads as f64
+    } else {
+        0.0
+    };
+
+    SetBenchmarkResult {
+        set_type: "RwLock<HashSet>".to_string(),
+        description: format!(
+            "Baseline for DashSet: one HashSet behind a single RwLock, hit by {} Rayon threads (wall-clock). Lookup = avg of {} samples.",
+            threads, config.lookup_samples
+        ),
+        product_count: products.len(),
+        insert_all: insert_dur.into(),
+        lookup_hit: lookup_hit_dur.into(),
+        lookup_miss: lookup_miss_dur.into(),
+        iterate_all: iterate_dur.into(),
+        remove_half: remove_dur.into(),
+        iteration_order_sample: order_sample,
+        order_guaranteed: false,
+        order_type: "Arbitrary (hash-based)".to_string(),
+        threads: Some(threads),
+        per_thread_ops_per_sec: Some(per_thread_ops_per_sec),
+        extra_info: None,
+        remove_strategy: None,
+    }
+}
+
+/// `FxHashSet` is a `std` `HashSet` with the Fx hasher from `rustc-hash`: a
+/// multiply-and-rotate hash that is very fast on small keys like UUIDs, but
+/// offers no protection against crafted collisions.
+fn benchmark_fx_hash_set(products: &[Product], config: &SetBenchmarkConfig) -> SetBenchmarkResult {
+    // Warmup
+    {
+        let mut w: FxHashSet<Product> = FxHashSet::with_capacity_and_hasher(config.warmup_count, Default::default());
+        for p in products.iter().take(config.warmup_count) { w.insert(p.clone()); }
+    }
+
+    let mut set: FxHashSet<Product> = FxHashSet::with_capacity_and_hasher(products.len(), Default::default());
+
+    let (_, insert_dur) = timed(|| {
+        for p in products { set.insert(p.clone()); }
+    });
+
+    // Lookup hit — average of `lookup_samples` evenly-spread elements
+    let hits = lookup_targets(products, config.lookup_samples);
+    let (_, lookup_hit_total) = timed(|| {
+        for p in hits.iter().copied() { black_box(set.contains(black_box(p))); }
+    });
+    let lookup_hit_dur = per_sample(lookup_hit_total, hits.len());
+
+    // Lookup miss — average of `lookup_samples` fresh UUIDs not in the set
+    let misses = miss_targets(config.lookup_samples);
+    let (_, lookup_miss_total) = timed(|| {
+        for f in misses.iter() { black_box(set.contains(black_box(f))); }
+    });
+    let lookup_miss_dur = per_sample(lookup_miss_total, misses.len());
+
+    let (all_names, iterate_dur) = timed(|| {
+        set.iter().map(|p| p.name.clone()).collect::<Vec<_>>()
+    });
+    let order_sample: Vec<String> = all_names.into_iter().take(10).collect();
+
+    let half: Vec<Product> = set.iter().take(products.len() / 2).cloned().collect();
+    let (_, remove_dur) = timed(|| {
+        for p in &half { set.remove(p); }
+    });
+
+    SetBenchmarkResult {
+        set_type: "FxHashSet".to_string(),
+        description: format!(
+            "Unordered. O(1) avg insert/lookup/remove with FxHash (non-cryptographic, not DoS-resistant). Lookup = avg of {} samples.",
+            config.lookup_samples
+        ),
+        product_count: products.len(),
+        insert_all: insert_dur.into(),
+        lookup_hit: lookup_hit_dur.into(),
+        lookup_miss: lookup_miss_dur.into(),
+        iterate_all: iterate_dur.into(),
+        remove_half: remove_dur.into(),
+        iteration_order_sample: order_sample,
+        order_guaranteed: false,
+        order_type: "Arbitrary (FxHash-based)".to_string(),
+        threads: None,
+        per_thread_ops_per_sec: None,
+        extra_info: None,
+        remove_strategy: None,
+    }
+}
+
+// ── Top-K by price ────────────────────────────────────────────────────────────
+
+/// Orders products by `price_cents` (then `id` as a tiebreak) instead of
+/// `Product`'s name-based `Ord`, so a `BinaryHeap` pops the most expensive first.
+#[derive(Debug, Clone)]
+pub struct ProductByPrice(pub Product);
+
+impl PartialEq for ProductByPrice {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for ProductByPrice {}
+
+impl Ord for ProductByPrice {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0
+            .price_cents
+            .cmp(&other.0.price_cents)
+            .then_with(|| self.0.id.cmp(&other.0.id))
+    }
+}
+
+impl PartialOrd for ProductByPrice {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Pops up to `k` products off the heap — the `k` most expensive, most expensive first.
+fn pop_top_k(heap: &mut BinaryHeap<ProductByPrice>, k: usize) -> Vec<Product> {
+    std::iter::from_fn(|| heap.pop()).take(k).map(|p| p.0).collect()
+}
+
+/// The `n` products with the largest `key`, largest first, in O(len · log n).
+///
+/// The heap never holds more than `n` entries: `Reverse` makes it a min-heap,
+/// so the smallest of the current best sits on top and is replaced whenever a
+/// larger one turns up.
+pub fn top_n_by_heap<'a>(
+    products: impl Iterator<Item = &'a Product>,
+    n: usize,
+    key: impl Fn(&Product) -> i64,
+) -> Vec<&'a Product> {
+    if n == 0 {
+        return vec![];
+    }
+    let mut heap: BinaryHeap<Reverse<(i64, &Product)>> = BinaryHeap::with_capacity(n);
+    for p in products {
+        let entry = Reverse((key(p), p));
+        if heap.len() < n {
+            heap.push(entry);
+        } else if let Some(mut smallest) = heap.peek_mut() {
+            if entry < *smallest {
+                *smallest = entry;
+            }
+        }
+    }
+    // Ascending `Reverse` order is descending key order
+    heap.into_sorted_vec().into_iter().map(|Reverse((_, p))| p).collect()
+}
+
+/// Same result as [`top_n_by_heap`] by collecting and sorting every product,
+/// which is what finding the top N costs in a set not ordered by `key`.
+pub fn top_n_by_sort<'a>(
+    products: impl Iterator<Item = &'a Product>,
+    n: usize,
+    key: impl Fn(&Product) -> i64,
+) -> Vec<&'a Product> {
+    let mut all: Vec<&Product> = products.collect();
+    all.sort_unstable_by(|a, b| (key(b), *b).cmp(&(key(a), *a)));
+    all.truncate(n);
+    all
+}
+
+/// `BinaryHeap` is not a set: it has no keyed lookup (`contains` is a linear
+/// scan) and only the maximum is cheap to reach. It is benchmarked for what it
+/// is good at — building a max-heap by price and popping the top `k`.
+pub fn benchmark_binary_heap(products: &[Product], config: &SetBenchmarkConfig) -> SetBenchmarkResult {
+    // Warmup
+    {
+        let mut w: BinaryHeap<ProductByPrice> = BinaryHeap::with_capacity(config.warmup_count);
+        for p in products.iter().take(config.warmup_count) { w.push(ProductByPrice(p.clone())); }
+    }
+
+    let mut heap: BinaryHeap<ProductByPrice> = BinaryHeap::with_capacity(products.len());
+
+    let (_, insert_dur) = timed(|| {
+        for p in products { heap.push(ProductByPrice(p.clone())); }
+    });
+
+    // Lookup hit — linear scan, averaged over the same samples as the sets
+    let hits = lookup_targets(products, config.lookup_samples);
+    let (_, lookup_hit_total) = timed(|| {
+        for p in hits.iter().copied() {
+            black_box(heap.iter().any(|h| h.0.id == black_box(p).id));
+        }
+    });
+    let lookup_hit_dur = per_sample(lookup_hit_total, hits.len());
+
+    let misses = miss_targets(config.lookup_samples);
+    let (_, lookup_miss_total) = timed(|| {
+        for f in misses.iter() {
+            black_box(heap.iter().any(|h| h.0.id == black_box(f).id));
+        }
+    });
+    let lookup_miss_dur = per_sample(lookup_miss_total, misses.len());
+
+    let (_, iterate_dur) = timed(|| {
+        heap.iter().map(|p| p.0.name.clone()).collect::<Vec<_>>()
+    });
+
+    // Top-k extraction on a copy so remove_half still starts from the full heap
+    let mut scratch = heap.clone();
+    let k = config.top_k.min(products.len());
+    let (top, top_k_dur) = timed(|| pop_top_k(&mut scratch, k));
+    let order_sample: Vec<String> = top.iter().take(10).map(|p| p.name.clone()).collect();
+
+    let half = products.len() / 2;
+    let (_, remove_dur) = timed(|| {
+        for _ in 0..half { black_box(heap.pop()); }
+    });
+
+    let top_k_timing = OpTiming::from(top_k_dur);
+    SetBenchmarkResult {
+        set_type: "BinaryHeap".to_string(),
+        description: "Max-heap by price_cents. O(log n) push/pop, O(1) peek, O(n) lookup (linear scan). Remove = pop the n/2 most expensive.".to_string(),
+        product_count: products.len(),
+        insert_all: insert_dur.into(),
+        lookup_hit: lookup_hit_dur.into(),
+        lookup_miss: lookup_miss_dur.into(),
+        iterate_all: iterate_dur.into(),
+        remove_half: remove_dur.into(),
+        iteration_order_sample: order_sample,
+        order_guaranteed: true,
+        order_type: "Descending price (pop order)".to_string(),
+        threads: None,
+        per_thread_ops_per_sec: None,
+        extra_info: Some(format!(
+            "top-{} by price extracted in {:.3} µs; top price = {} cents",
+            k,
+            top_k_timing.duration_us,
+            top.first().map_or(0, |p| p.price_cents)
+        )),
+        remove_strategy: None,
+    }
+}
+
+// ── HashMap by id ─────────────────────────────────────────────────────────────
+
+/// `HashMap<Uuid, Product>`, the shape of [`SetManager::id_map`]. `Product`'s
+/// `Hash`/`Eq` already look only at the id, so the hashing work matches
+/// `HashSet<Product>`; the difference is that the map is asked with a bare
+/// `Uuid`, while `HashSet::contains` needs a whole `Product` in hand.
+/// `extra_info` times `HashSet::contains` on the same targets side by side.
+pub fn benchmark_hashmap(products: &[Product], config: &SetBenchmarkConfig) -> SetBenchmarkResult {
+    // Warmup
+    {
+        let mut w: HashMap<Uuid, Product> = HashMap::with_capacity(config.warmup_count);
+        for p in products.iter().take(config.warmup_count) { w.insert(p.id, p.clone()); }
+    }
+
+    let mut map: HashMap<Uuid, Product> = HashMap::with_capacity(products.len());
+
+    let (_, insert_dur) = timed(|| {
+        for p in products { map.insert(p.id, p.clone()); }
+    });
+
+    let hits = lookup_targets(products, config.lookup_samples);
+    let hit_ids: Vec<Uuid> = hits.iter().map(|p| p.id).collect();
+    let lookup_hit = timed_read_only(config, hit_ids.len(), || {
+        for id in &hit_ids { black_box(map.get(black_box(id))); }
+    });
+
+    let miss_ids: Vec<Uuid> = miss_targets(config.lookup_samples).iter().map(|p| p.id).collect();
+    let lookup_miss = timed_read_only(config, miss_ids.len(), || {
+        for id in &miss_ids { black_box(map.get(black_box(id))); }
+    });
+
+    let iterate_all = timed_read_only(config, 1, || {
+        map.values().map(|p| p.name.clone()).collect::<Vec<_>>()
+    });
+    let order_sample: Vec<String> = map.values().take(10).map(|p| p.name.clone()).collect();
+
+    // The same hits through `HashSet::contains`, for comparison
+    let set: HashSet<Product> = products.iter().cloned().collect();
+    let set_lookup = timed_read_only(config, hits.len(), || {
+        for p in hits.iter().copied() { black_box(set.contains(black_box(p))); }
+    });
+
+    let comparison = format!(
+        "get by id {} ns vs HashSet::contains {} ns per lookup",
+        lookup_hit.duration_ns, set_lookup.duration_ns
+    );
+
+    let half: Vec<Uuid> = map.keys().take(products.len() / 2).copied().collect();
+    let (_, remove_dur) = timed(|| {
+        for id in &half { map.remove(id); }
+    });
+
+    SetBenchmarkResult {
+        set_type: "HashMap<Uuid, Product>".to_string(),
+        description: format!(
+            "Keyed by id. O(1) avg insert/get/remove by Uuid. Lookup = avg of {} samples.",
+            config.lookup_samples
+        ),
+        product_count: products.len(),
+        insert_all: insert_dur.into(),
+        lookup_hit,
+        lookup_miss,
+        iterate_all,
+        remove_half: remove_dur.into(),
+        iteration_order_sample: order_sample,
+        order_guaranteed: false,
+        order_type: "Arbitrary (hash-based)".to_string(),
+        threads: None,
+        per_thread_ops_per_sec: None,
+        extra_info: Some(comparison),
+        remove_strategy: None,
+    }
+}
+
+// ── Price range ───────────────────────────────────────────────────────────────
+
+/// Times selecting the middle ~10% of products by price two ways: scanning the
+/// name-ordered `BTreeSet` and filtering, vs. `range()` on a `(price, id)`-keyed
+/// `BTreeMap`. Building both structures is not timed.
+fn benchmark_price_range(products: &[Product], runs: u32) -> Option<PriceRangeBenchmark> {
+    let (min_cents, max_cents) = price_percentiles(products, 45.0, 55.0)?;
+
+    let set: BTreeSet<&Product> = products.iter().collect();
+    let index: BTreeMap<(i64, Uuid), &Product> = products.iter().map(|p| ((p.price_cents, p.id), p)).collect();
+
+    let mut scan_ns = Vec::new();
+    let mut index_ns = Vec::new();
+    let mut matches = 0;
+    for _ in 0..runs.max(1) {
+        let (found, scan_dur) = timed(|| {
+            set.iter()
+                .filter(|p| (min_cents..=max_cents).contains(&p.price_cents))
+                .count()
+        });
+        let (_, index_dur) = timed(|| black_box(index.range(price_bounds(min_cents, max_cents)).count()));
+        matches = found;
+        scan_ns.push(scan_dur.as_nanos() as u64);
+        index_ns.push(index_dur.as_nanos() as u64);
+    }
+
+    let timing = |ns: &[u64]| {
+        if ns.len() == 1 {
+            OpTiming::from_ns(ns[0])
+        } else {
+            OpTiming::across_runs(ns)
+        }
+    };
+    Some(PriceRangeBenchmark {
+        min_cents,
+        max_cents,
+        matches,
+        full_scan: timing(&scan_ns),
+        price_index: timing(&index_ns),
+    })
+}
+
+/// Prices at the `low_pct` and `high_pct` percentiles (0–100) of `products`;
+/// `None` when there are no products.
+fn price_percentiles(products: &[Product], low_pct: f64, high_pct: f64) -> Option<(i64, i64)> {
+    let mut prices: Vec<i64> = products.iter().map(|p| p.price_cents).collect();
+    prices.sort_unstable();
+    let at = |pct: f64| prices[((prices.len() as f64 * pct / 100.0) as usize).min(prices.len() - 1)];
+    (!prices.is_empty()).then(|| (at(low_pct), at(high_pct)))
+}
+
+/// Products priced within `[min_cents, max_cents]`, read off the price index.
+fn range_via_index<'a>(index: &BTreeMap<(i64, Uuid), &'a Product>, min_cents: i64, max_cents: i64) -> Vec<&'a Product> {
+    // `range()` panics on inverted bounds
+    if min_cents > max_cents {
+        return vec![];
+    }
+    index.range(price_bounds(min_cents, max_cents)).map(|(_, p)| *p).collect()
+}
+
+/// The same products found by filtering every element of the name-ordered set.
+fn range_via_scan<'a>(set: &BTreeSet<&'a Product>, min_cents: i64, max_cents: i64) -> Vec<&'a Product> {
+    set.iter().filter(|p| p.price_cents >= min_cents && p.price_cents <= max_cents).copied().collect()
+}
+
+/// Selects the products priced between the `low_pct` and `high_pct`
+/// percentiles (0–100, `low_pct <= high_pct`) two ways and times each:
+/// `range()` over a `(price_cents, id)` index, and a filtered iteration of a
+/// `BTreeSet` that, being ordered by name, has to look at every product.
+pub fn benchmark_btree_range(products: &[Product], low_pct: f64, high_pct: f64) -> RangeBenchmarkResult {
+    let (min_cents, max_cents) = price_percentiles(products, low_pct, high_pct).unwrap_or((0, -1));
+
+    let set: BTreeSet<&Product> = products.iter().collect();
+    let index: BTreeMap<(i64, Uuid), &Product> = products.iter().map(|p| ((p.price_cents, p.id), p)).collect();
+
+    let (by_range, range_dur) = timed(|| range_via_index(&index, min_cents, max_cents));
+    let (by_scan, scan_dur) = timed(|| range_via_scan(&set, min_cents, max_cents));
+
+    RangeBenchmarkResult {
+        low_pct,
+        high_pct,
+        min_cents,
+        max_cents,
+        matches: by_range.len(),
+        range_query_ns: range_dur.as_nanos() as u64,
+        filter_scan_ns: scan_dur.as_nanos() as u64,
+        results_match: ids_of(by_range.into_iter()) == ids_of(by_scan.into_iter()),
+    }
+}
+
+/// Batch sizes, as a share of the set, swept for the retain/rebuild crossover.
+const RANGE_DELETE_SWEEP: [f64; 9] = [0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8, 0.9];
+
+/// Removes the first `remove_fraction` of `products` from a fresh `BTreeSet`
+/// with each strategy, returning both timings and what each left behind.
+/// Building the sets and the id lookup is not timed.
+fn time_range_delete(set: &BTreeSet<Product>, products: &[Product], remove_fraction: f64) -> ((u64, u64), (BTreeSet<Product>, BTreeSet<Product>)) {
+    let count = (products.len() as f64 * remove_fraction).round() as usize;
+    let ids: HashSet<Uuid> = products.iter().take(count).map(|p| p.id).collect();
+
+    let mut retained = set.clone();
+    let ((), retain_dur) = timed(|| BatchRemoveStrategy::Retain.remove_from(&mut retained, &ids));
+    let mut rebuilt = set.clone();
+    let ((), rebuild_dur) = timed(|| BatchRemoveStrategy::Rebuild.remove_from(&mut rebuilt, &ids));
+    ((retain_dur.as_nanos() as u64, rebuild_dur.as_nanos() as u64), (retained, rebuilt))
+}
+
+/// Times removing `remove_fraction` (0–1) of `products` from a `BTreeSet`
+/// by `retain` against a filtered rebuild, then repeats at each of
+/// [`RANGE_DELETE_SWEEP`] to report the crossover.
+pub fn benchmark_btree_range_delete(products: &[Product], remove_fraction: f64) -> RangeDeleteResult {
+    let set: BTreeSet<Product> = products.iter().cloned().collect();
+    let ((retain_ns, rebuild_ns), (retained, rebuilt)) = time_range_delete(&set, products, remove_fraction);
+
+    let sweep: Vec<RangeDeleteSample> = RANGE_DELETE_SWEEP
+        .iter()
+        .map(|&fraction| {
+            let ((retain_ns, rebuild_ns), _) = time_range_delete(&set, products, fraction);
+            RangeDeleteSample { remove_fraction: fraction, retain_ns, rebuild_ns }
+        })
+        .collect();
+    let crossover_fraction = sweep.iter().find(|s| s.rebuild_ns < s.retain_ns).map(|s| s.remove_fraction);
+
+    let removed = set.len() - retained.len();
+    RangeDeleteResult {
+        remove_fraction,
+        set_size: set.len(),
+        removed,
+        retain_ns,
+        rebuild_ns,
+        faster: if rebuild_ns < retain_ns { BatchRemoveStrategy::Rebuild } else { BatchRemoveStrategy::Retain },
+        chosen: BatchRemoveStrategy::for_batch(removed, set.len()),
+        crossover_fraction,
+        sweep,
+        results_match: retained == rebuilt,
+    }
+}
+
+/// Times a short and a long prefix search, both lowercased so the `BTreeSet`
+/// side has to fan out over case variants the way user input would make it.
+fn benchmark_prefix_search(products: &[Product], runs: u32) -> Option<PrefixSearchBenchmark> {
+    let sample = &products.get(products.len() / 2)?.name;
+    let prefix_of = |n: usize| sample.chars().take(n).collect::<String>().to_ascii_lowercase();
+
+    let index: IndexSet<Product> = products.iter().cloned().collect();
+    let set: BTreeSet<Product> = products.iter().cloned().collect();
+
+    let time = |prefix: String| {
+        let mut scan_ns = Vec::new();
+        let mut range_ns = Vec::new();
+        let mut matches = 0;
+        for _ in 0..runs.max(1) {
+            let (found, scan_dur) = timed(|| filter_by_name_prefix(index.iter(), &prefix).len());
+            let (_, range_dur) = timed(|| black_box(btree_prefix_search(&set, &prefix).len()));
+            matches = found;
+            scan_ns.push(scan_dur.as_nanos() as u64);
+            range_ns.push(range_dur.as_nanos() as u64);
         }
+        let timing = |ns: &[u64]| {
+            if ns.len() == 1 {
+                OpTiming::from_ns(ns[0])
+            } else {
+                OpTiming::across_runs(ns)
+            }
+        };
+        let (index_scan, btree_range) = (timing(&scan_ns), timing(&range_ns));
+        let faster = if btree_range.duration_ns < index_scan.duration_ns { "BTreeSet range" } else { "IndexSet scan" };
+        PrefixSearchTiming { prefix, matches, index_scan, btree_range, faster: faster.to_string() }
+    };
+
+    Some(PrefixSearchBenchmark { short: time(prefix_of(1)), long: time(prefix_of(8)) })
+}
+
+// ── Concurrent access ─────────────────────────────────────────────────────────
+
+/// `hash_set.contains` calls made under each read guard in [`benchmark_concurrent`],
+/// so a read op does some work while holding the lock rather than just toggling it.
+const LOOKUPS_PER_READ: usize = 32;
+
+/// Throughput and lock contention for readers and writers sharing one
+/// `RwLock<SetManager>`.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ConcurrentBenchmarkResult {
+    pub product_count: usize,
+    pub readers: usize,
+    pub writers: usize,
+    pub ops_per_task: usize,
+    /// `hash_set.contains` calls per read-lock acquisition
+    pub lookups_per_read: usize,
+    pub total_reads: usize,
+    pub total_writes: usize,
+    /// From spawning the first task until the last one finished
+    pub total_wall_ns: u64,
+    /// Read acquisitions that were not granted on the first try
+    pub read_contention_waits: usize,
+    /// Write acquisitions that were not granted on the first try
+    pub write_contention_waits: usize,
+    /// Time the contended read acquisitions spent waiting, summed over tasks
+    pub read_wait_ns: u64,
+    /// Time the contended write acquisitions spent waiting, summed over tasks
+    pub write_wait_ns: u64,
+    pub reads_per_sec: f64,
+    pub writes_per_sec: f64,
+    /// One reader doing `ops_per_task` reads with the lock to itself, measured first
+    pub single_reader_reads_per_sec: f64,
+    /// `reads_per_sec / single_reader_reads_per_sec`
+    pub read_speedup: f64,
+}
+
+/// Per-task tally of lock acquisitions.
+#[derive(Debug, Default)]
+struct LockStats {
+    ops: usize,
+    waits: usize,
+    wait_ns: u64,
+    max_wait_ns: u64,
+}
+
+impl LockStats {
+    fn add(&mut self, other: LockStats) {
+        self.ops += other.ops;
+        self.waits += other.waits;
+        self.wait_ns += other.wait_ns;
+        self.max_wait_ns = self.max_wait_ns.max(other.max_wait_ns);
+    }
+
+    fn record_wait(&mut self, wait_start: Instant) {
+        let ns = wait_start.elapsed().as_nanos() as u64;
+        self.waits += 1;
+        self.wait_ns += ns;
+        self.max_wait_ns = self.max_wait_ns.max(ns);
+    }
+}
+
+/// When a lock loop stops.
+#[derive(Debug, Clone, Copy)]
+enum LoopUntil {
+    /// After this many acquisitions
+    Ops(usize),
+    /// At the first acquisition after this instant. The task also yields after
+    /// every acquisition, so tasks sharing a thread still take turns.
+    Deadline(Instant),
+}
+
+impl LoopUntil {
+    fn done(self, op: usize) -> bool {
+        match self {
+            LoopUntil::Ops(ops) => op >= ops,
+            LoopUntil::Deadline(deadline) => Instant::now() >= deadline,
+        }
+    }
+
+    async fn pause(self) {
+        if let LoopUntil::Deadline(_) = self {
+            tokio::task::yield_now().await;
+        }
+    }
+}
+
+/// Read acquisitions, each looking up `LOOKUPS_PER_READ` products starting
+/// at `offset` so concurrent readers walk different parts of `targets`.
+async fn read_loop(sets: Arc<RwLock<SetManager>>, targets: Arc<Vec<Product>>, offset: usize, until: LoopUntil) -> LockStats {
+    let mut stats = LockStats::default();
+    let mut op = 0;
+    while !until.done(op) {
+        let guard = match sets.try_read() {
+            Ok(guard) => guard,
+            Err(_) => {
+                let wait_start = Instant::now();
+                let guard = sets.read().await;
+                stats.record_wait(wait_start);
+                guard
+            }
+        };
+        for i in 0..LOOKUPS_PER_READ {
+            let target = &targets[(offset + op * LOOKUPS_PER_READ + i) % targets.len()];
+            black_box(guard.hash_set.contains(black_box(target)));
+        }
+        drop(guard);
+        stats.ops += 1;
+        op += 1;
+        until.pause().await;
+    }
+    stats
+}
+
+/// Write acquisitions, each re-inserting a product from `targets` with a new
+/// price so the set sizes stay put while every set is actually rewritten.
+async fn write_loop(sets: Arc<RwLock<SetManager>>, targets: Arc<Vec<Product>>, offset: usize, until: LoopUntil) -> LockStats {
+    let mut stats = LockStats::default();
+    let mut op = 0;
+    while !until.done(op) {
+        let mut product = targets[(offset + op) % targets.len()].clone();
+        product.price_cents += 1;
+
+        let mut guard = match sets.try_write() {
+            Ok(guard) => guard,
+            Err(_) => {
+                let wait_start = Instant::now();
+                let guard = sets.write().await;
+                stats.record_wait(wait_start);
+                guard
+            }
+        };
+        guard.insert_product(&product);
+        drop(guard);
+        stats.ops += 1;
+        op += 1;
+        until.pause().await;
+    }
+    stats
+}
+
+/// Spawns `readers` then `writers` loops against `sets`, spreading their
+/// starting offsets over `targets`, and sums their stats.
+async fn run_lock_loops(
+    sets: &Arc<RwLock<SetManager>>,
+    targets: &Arc<Vec<Product>>,
+    readers: usize,
+    writers: usize,
+    until: LoopUntil,
+) -> (LockStats, LockStats) {
+    let spread = targets.len() / (readers + writers).max(1);
+    let read_tasks: Vec<_> = (0..readers)
+        .map(|i| tokio::spawn(read_loop(sets.clone(), targets.clone(), i * spread, until)))
+        .collect();
+    let write_tasks: Vec<_> = (0..writers)
+        .map(|i| tokio::spawn(write_loop(sets.clone(), targets.clone(), (readers + i) * spread, until)))
+        .collect();
+
+    let mut reads = LockStats::default();
+    for task in read_tasks {
+        reads.add(task.await.expect("read task panicked"));
+    }
+    let mut writes = LockStats::default();
+    for task in write_tasks {
+        writes.add(task.await.expect("write task panicked"));
+    }
+    (reads, writes)
+}
+
+fn per_sec(count: usize, ns: u64) -> f64 {
+    if ns == 0 {
+        0.0
+    } else {
+        count as f64 * 1e9 / ns as f64
+    }
+}
+
+/// Runs `readers` lookup tasks and `writers` update tasks against a fresh
+/// `Arc<RwLock<SetManager>>` holding `products`, each task doing `ops_per_task`
+/// lock acquisitions. The tasks are spawned onto the current Tokio runtime, so
+/// readers only run in parallel on a multi-threaded one.
+///
+/// An acquisition counts as a contention wait when `try_read`/`try_write`
+/// fails and the task has to await the lock; the time spent in that await is
+/// summed into `read_wait_ns`/`write_wait_ns`.
+pub async fn benchmark_concurrent(
+    products: &[Product],
+    readers: usize,
+    writers: usize,
+    ops_per_task: usize,
+) -> ConcurrentBenchmarkResult {
+    let mut manager = SetManager::new();
+    manager.sync_from_db(products);
+    let sets = Arc::new(RwLock::new(manager));
+    // With nothing seeded, readers miss and writers keep re-adding one product
+    let targets = Arc::new(if products.is_empty() { vec![make_fake_product()] } else { products.to_vec() });
+
+    let single_start = Instant::now();
+    read_loop(sets.clone(), targets.clone(), 0, LoopUntil::Ops(ops_per_task)).await;
+    let single_dur = single_start.elapsed();
+
+    let start = Instant::now();
+    let (reads, writes) = run_lock_loops(&sets, &targets, readers, writers, LoopUntil::Ops(ops_per_task)).await;
+    let total_wall_ns = start.elapsed().as_nanos() as u64;
+
+    let reads_per_sec = per_sec(reads.ops, total_wall_ns);
+    let single_reader_reads_per_sec = per_sec(ops_per_task, single_dur.as_nanos() as u64);
+    ConcurrentBenchmarkResult {
+        product_count: products.len(),
+        readers,
+        writers,
+        ops_per_task,
+        lookups_per_read: LOOKUPS_PER_READ,
+        total_reads: reads.ops,
+        total_writes: writes.ops,
+        total_wall_ns,
+        read_contention_waits: reads.waits,
+        write_contention_waits: writes.waits,
+        read_wait_ns: reads.wait_ns,
+        write_wait_ns: writes.wait_ns,
+        reads_per_sec,
+        writes_per_sec: per_sec(writes.ops, total_wall_ns),
+        single_reader_reads_per_sec,
+        read_speedup: if single_reader_reads_per_sec > 0.0 { reads_per_sec / single_reader_reads_per_sec } else { 0.0 },
+    }
+}
+
+/// How long readers and writers wait for one `RwLock<SetManager>` when they
+/// compete for a fixed time.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct LockContentionResult {
+    pub product_count: usize,
+    pub readers: usize,
+    pub writers: usize,
+    pub duration_secs: u64,
+    pub total_reads: usize,
+    pub total_writes: usize,
+    /// From spawning the first task until the last one finished
+    pub total_wall_ns: u64,
+    /// Mean time from calling `read()` to holding the guard, over every read
+    /// (acquisitions granted at once count as 0)
+    pub avg_read_wait_ns: f64,
+    /// The same for `write()`
+    pub avg_write_wait_ns: f64,
+    pub max_read_wait_ns: u64,
+    pub max_write_wait_ns: u64,
+    /// The longer of the two maxima
+    pub max_wait_ns: u64,
+    pub reads_per_sec: f64,
+    pub writes_per_sec: f64,
+}
+
+/// Runs `readers` lookup tasks and `writers` update tasks against a fresh
+/// `Arc<RwLock<SetManager>>` holding `products` until `duration` has passed,
+/// then reports how many acquisitions each side got and how long they waited.
+/// Waits are measured as in [`benchmark_concurrent`]; every task yields after
+/// each acquisition so readers and writers interleave on any runtime.
+pub async fn benchmark_rwlock_contention(
+    products: &[Product],
+    readers: usize,
+    writers: usize,
+    duration: Duration,
+) -> LockContentionResult {
+    let mut manager = SetManager::new();
+    manager.sync_from_db(products);
+    let sets = Arc::new(RwLock::new(manager));
+    let targets = Arc::new(if products.is_empty() { vec![make_fake_product()] } else { products.to_vec() });
+
+    let start = Instant::now();
+    let (reads, writes) = run_lock_loops(&sets, &targets, readers, writers, LoopUntil::Deadline(start + duration)).await;
+    let total_wall_ns = start.elapsed().as_nanos() as u64;
+
+    let avg = |stats: &LockStats| if stats.ops == 0 { 0.0 } else { stats.wait_ns as f64 / stats.ops as f64 };
+    LockContentionResult {
+        product_count: products.len(),
+        readers,
+        writers,
+        duration_secs: duration.as_secs(),
+        total_reads: reads.ops,
+        total_writes: writes.ops,
+        total_wall_ns,
+        avg_read_wait_ns: avg(&reads),
+        avg_write_wait_ns: avg(&writes),
+        max_read_wait_ns: reads.max_wait_ns,
+        max_write_wait_ns: writes.max_wait_ns,
+        max_wait_ns: reads.max_wait_ns.max(writes.max_wait_ns),
+        reads_per_sec: per_sec(reads.ops, total_wall_ns),
+        writes_per_sec: per_sec(writes.ops, total_wall_ns),
+    }
+}
+
+// ── Batch insert ──────────────────────────────────────────────────────────────
+
+/// Result of [`benchmark_batch_insert`].
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BatchInsertResult {
+    pub product_count: usize,
+    /// [`SetManager::label`] of the managers loaded
+    pub sets: String,
+    /// One write lock, then [`SetManager::batch_insert`]
+    pub batch_ns: u64,
+    /// A write lock and [`SetManager::insert_product`] per product, the way
+    /// single-product handlers do it
+    pub sequential_ns: u64,
+    /// `sequential_ns / batch_ns`
+    pub speedup: f64,
+    /// Both approaches left the sets with the same contents
+    pub results_match: bool,
+}
+
+/// Loads `products` into two empty `Arc<RwLock<SetManager>>`s, once with a
+/// single [`SetManager::batch_insert`] and once product by product, taking the
+/// write lock for every insert. Lock acquisition is part of both timings.
+pub async fn benchmark_batch_insert(products: &[Product]) -> BatchInsertResult {
+    let batch = Arc::new(RwLock::new(SetManager::new()));
+    let start = Instant::now();
+    batch.write().await.batch_insert(products);
+    let batch_dur = start.elapsed();
+
+    let sequential = Arc::new(RwLock::new(SetManager::new()));
+    let start = Instant::now();
+    for p in products {
+        sequential.write().await.insert_product(p);
+    }
+    let sequential_dur = start.elapsed();
+
+    let (batch, sequential) = (batch.read().await, sequential.read().await);
+    let results_match = batch.sizes() == sequential.sizes()
+        && batch.btree_set == sequential.btree_set
+        && batch.index_set.iter().eq(sequential.index_set.iter());
+    let (batch_ns, sequential_ns) = (batch_dur.as_nanos() as u64, sequential_dur.as_nanos() as u64);
+    BatchInsertResult {
+        product_count: batch.hash_set.len(),
+        sets: batch.label(),
+        batch_ns,
+        sequential_ns,
+        speedup: sequential_ns as f64 / batch_ns.max(1) as f64,
+        results_match,
+    }
+}
+
+// ── Serialization ─────────────────────────────────────────────────────────────
+
+/// Wire formats compared by [`benchmark_serialization`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SerializationFormat {
+    Json,
+    Bincode,
+    MessagePack,
+}
+
+impl SerializationFormat {
+    const ALL: [SerializationFormat; 3] = [Self::Json, Self::Bincode, Self::MessagePack];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Json => "JSON",
+            Self::Bincode => "bincode",
+            Self::MessagePack => "MessagePack",
+        }
+    }
+
+    fn encode(self, products: &[Product]) -> anyhow::Result<Vec<u8>> {
+        Ok(match self {
+            Self::Json => serde_json::to_vec(products)?,
+            Self::Bincode => bincode::serde::encode_to_vec(products, bincode::config::standard())?,
+            // Positional (array) form: the compact one, as there are no skipped fields
+            Self::MessagePack => rmp_serde::to_vec(products)?,
+        })
+    }
+
+    fn decode(self, bytes: &[u8]) -> anyhow::Result<Vec<Product>> {
+        Ok(match self {
+            Self::Json => serde_json::from_slice(bytes)?,
+            Self::Bincode => bincode::serde::decode_from_slice(bytes, bincode::config::standard())?.0,
+            Self::MessagePack => rmp_serde::from_slice(bytes)?,
+        })
+    }
+}
+
+/// Time to encode and decode the whole product list once, and its encoded size.
+#[derive(Debug, Clone, Copy)]
+struct FormatTiming {
+    serialize_ns: u64,
+    deserialize_ns: u64,
+    size_bytes: usize,
+}
+
+/// JSON vs. bincode vs. MessagePack over the same product list.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SerializationBenchmarkResult {
+    pub product_count: usize,
+    pub json_serialize_ns: u64,
+    pub json_deserialize_ns: u64,
+    pub json_size_bytes: usize,
+    pub bincode_serialize_ns: u64,
+    pub bincode_deserialize_ns: u64,
+    pub bincode_size_bytes: usize,
+    /// `rmp_serde`, positional encoding
+    pub msgpack_serialize_ns: u64,
+    pub msgpack_deserialize_ns: u64,
+    pub msgpack_size_bytes: usize,
+    pub smallest_format: String,
+    pub fastest_serialize: String,
+    pub fastest_deserialize: String,
+}
+
+fn time_format(format: SerializationFormat, products: &[Product]) -> anyhow::Result<FormatTiming> {
+    let start = Instant::now();
+    let bytes = black_box(format.encode(products)?);
+    let serialize_ns = start.elapsed().as_nanos() as u64;
+
+    let start = Instant::now();
+    let decoded = black_box(format.decode(&bytes)?);
+    let deserialize_ns = start.elapsed().as_nanos() as u64;
+    anyhow::ensure!(decoded.len() == products.len(), "{} round trip lost products", format.name());
+
+    Ok(FormatTiming { serialize_ns, deserialize_ns, size_bytes: bytes.len() })
+}
+
+/// Encodes `products` as one list in each format and decodes it back, timing
+/// both directions once. Fails only if a format can't round-trip a product.
+pub fn benchmark_serialization(products: &[Product]) -> anyhow::Result<SerializationBenchmarkResult> {
+    let timings = SerializationFormat::ALL
+        .map(|format| time_format(format, products).map(|timing| (format, timing)))
+        .into_iter()
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let winner = |key: fn(&FormatTiming) -> u64| {
+        timings.iter().min_by_key(|(_, t)| key(t)).map_or("N/A", |(f, _)| f.name()).to_string()
+    };
+    let [(_, json), (_, bincode), (_, msgpack)] = [timings[0], timings[1], timings[2]];
+
+    Ok(SerializationBenchmarkResult {
+        product_count: products.len(),
+        json_serialize_ns: json.serialize_ns,
+        json_deserialize_ns: json.deserialize_ns,
+        json_size_bytes: json.size_bytes,
+        bincode_serialize_ns: bincode.serialize_ns,
+        bincode_deserialize_ns: bincode.deserialize_ns,
+        bincode_size_bytes: bincode.size_bytes,
+        msgpack_serialize_ns: msgpack.serialize_ns,
+        msgpack_deserialize_ns: msgpack.deserialize_ns,
+        msgpack_size_bytes: msgpack.size_bytes,
+        smallest_format: winner(|t| t.size_bytes as u64),
+        fastest_serialize: winner(|t| t.serialize_ns),
+        fastest_deserialize: winner(|t| t.deserialize_ns),
+    })
+}
+
+// ── Clone overhead ────────────────────────────────────────────────────────────
+
+/// Copies of each product the benchmark makes, one per `HashSet`, `IndexSet`
+/// and `BTreeSet` in [`SetManager::insert_product`].
+const CLONES_PER_INSERT: usize = 3;
+
+/// Result of [`benchmark_clone_overhead`].
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CloneOverheadResult {
+    pub product_count: usize,
+    /// Copies made of each product in every measurement
+    pub clones_per_product: usize,
+    /// `p.clone()` per copy: every `String` field is reallocated
+    pub clone_vec_ns: u64,
+    /// One `Arc::new(p.clone())` per product, then `Arc::clone` per copy
+    pub arc_wrap_ns: u64,
+    /// Inserting the wrapped products into an `AHashSet<Arc<Product>>`
+    pub arc_insert_ns: u64,
+    pub recommendation: String,
+}
+
+/// Times what [`SetManager::insert_product`] pays to copy `products` into
+/// its sets against sharing one `Arc<Product>` per product instead.
+pub fn benchmark_clone_overhead(products: &[Product]) -> CloneOverheadResult {
+    let (cloned, clone_dur) = timed(|| {
+        (0..CLONES_PER_INSERT).map(|_| products.iter().map(Product::clone).collect::<Vec<_>>()).collect::<Vec<_>>()
+    });
+    black_box(&cloned);
+
+    let (shared, arc_dur) = timed(|| {
+        let wrapped: Vec<Arc<Product>> = products.iter().map(|p| Arc::new(p.clone())).collect();
+        (0..CLONES_PER_INSERT).map(|_| wrapped.iter().map(Arc::clone).collect::<Vec<_>>()).collect::<Vec<_>>()
+    });
+
+    let (set, insert_dur) = timed(|| shared[0].iter().cloned().collect::<AHashSet<Arc<Product>>>());
+    black_box(&set);
+
+    let (clone_vec_ns, arc_wrap_ns) = (clone_dur.as_nanos() as u64, arc_dur.as_nanos() as u64);
+    let recommendation = if arc_wrap_ns < clone_vec_ns {
+        format!(
+            "Switching SetManager to Arc<Product> would cut clone cost: sharing took {:.0}% of the time of cloning",
+            arc_wrap_ns as f64 * 100.0 / clone_vec_ns.max(1) as f64
+        )
+    } else {
+        "Keep owned Products: cloning was no slower than wrapping them in Arc at this size".to_string()
+    };
+    CloneOverheadResult {
+        product_count: products.len(),
+        clones_per_product: CLONES_PER_INSERT,
+        clone_vec_ns,
+        arc_wrap_ns,
+        arc_insert_ns: insert_dur.as_nanos() as u64,
+        recommendation,
+    }
+}
+
+// ── Helpers ───────────────────────────────────────────────────────────────────
+
+fn make_fake_product() -> Product {
+    Product {
+        id: Uuid::new_v4(),
+        name: "zzz_nonexistent_product".to_string(),
+        description: None,
+        price_cents: 0,
+        quantity: 0,
+        category: "none".to_string(),
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        version: 1,
+    }
+}
+
+/// Name of the set type with the smallest `key`, or "N/A" for no results.
+fn fastest_by<F>(results: &[SetBenchmarkResult], key: F) -> String
+where
+    F: Fn(&SetBenchmarkResult) -> u64,
+{
+    results
+        .iter()
+        .min_by_key(|r| key(r))
+        .map(|r| r.set_type.as_str())
+        .unwrap_or("N/A")
+        .to_string()
+}
+
+fn summary_row(r: &SetBenchmarkResult) -> SummaryRow {
+    SummaryRow {
+        set_type: r.set_type.clone(),
+        insert_ms: r.insert_all.duration_ms,
+        lookup_hit_us: r.lookup_hit.duration_us,
+        lookup_miss_us: r.lookup_miss.duration_us,
+        iterate_ms: r.iterate_all.duration_ms,
+        remove_ms: r.remove_half.duration_ms,
+        order: r.order_type.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use rand::SeedableRng;
+    use uuid::Uuid;
+
+    fn make(id: Uuid, name: &str) -> Product {
+        Product {
+            id,
+            name: name.to_string(),
+            description: None,
+            price_cents: 500,
+            quantity: 10,
+            category: "Test".to_string(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            version: 1,
+        }
+    }
+
+    // ── SetManager basic ops ───────────────────────────────────────────────────
+
+    #[test]
+    fn new_manager_is_empty() {
+        assert_eq!(SetManager::new().sizes(), SetSizes::uniform(0, None));
+    }
+
+    #[test]
+    fn insert_adds_to_all_sets() {
+        let mut mgr = SetManager::new();
+        mgr.insert_product(&make(Uuid::new_v4(), "Widget"));
+        assert_eq!(mgr.sizes(), SetSizes::uniform(1, None));
+    }
+
+    #[test]
+    fn upsert_skips_products_whose_content_is_unchanged() {
+        let mut mgr = SetManager::new();
+        let lamp = make(Uuid::new_v4(), "Lamp");
+        assert!(mgr.upsert_product(&lamp), "new products are written");
+
+        let touched = Product { updated_at: Utc::now(), version: lamp.version + 1, ..lamp.clone() };
+        assert!(!mgr.upsert_product(&touched));
+        assert_eq!(mgr.hash_set.get(&lamp).unwrap().version, lamp.version, "no-op leaves the stored copy alone");
+
+        let renamed = Product { name: "Desk lamp".to_string(), ..touched };
+        assert!(mgr.upsert_product(&renamed));
+        assert_eq!(mgr.sizes(), SetSizes::uniform(1, None));
+        assert_eq!(mgr.btree_set.first().unwrap().name, "Desk lamp");
+    }
+
+    #[test]
+    fn inserting_same_id_twice_does_not_grow_sets() {
+        let mut mgr = SetManager::new();
+        let id = Uuid::new_v4();
+        mgr.insert_product(&make(id, "First"));
+        mgr.insert_product(&make(id, "Second")); // duplicate UUID
+        assert_eq!(mgr.sizes(), SetSizes::uniform(1, None));
+    }
+
+    #[test]
+    fn remove_product_removes_from_all_sets() {
+        let mut mgr = SetManager::new();
+        let id = Uuid::new_v4();
+        mgr.insert_product(&make(id, "Widget"));
+        mgr.remove_product(id);
+        assert_eq!(mgr.sizes(), SetSizes::uniform(0, None));
+    }
+
+    #[test]
+    fn remove_products_drops_the_whole_batch_and_keeps_order() {
+        let mut mgr = SetManager::with_fx_set(true);
+        let products: Vec<Product> = (0..6).map(|i| make(Uuid::new_v4(), &format!("P{}", i))).collect();
+        mgr.sync_from_db(&products);
+
+        let ids: HashSet<Uuid> = [products[1].id, products[4].id, Uuid::new_v4()].into();
+        mgr.remove_products(&ids);
+
+        assert_eq!(mgr.sizes(), SetSizes::uniform(4, Some(4)));
+        let order: Vec<&str> = mgr.index_set.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(order, vec!["P0", "P2", "P3", "P5"]);
+        assert!(mgr.range_by_price(i64::MIN, i64::MAX).iter().all(|p| !ids.contains(&p.id)));
+    }
+
+    #[test]
+    fn restoring_a_snapshot_brings_back_the_exact_state() {
+        let mut mgr = SetManager::with_fx_set(true);
+        let products: Vec<Product> = (0..8).map(|i| make(Uuid::new_v4(), &format!("P{}", 7 - i))).collect();
+        mgr.sync_from_db(&products);
+        let state = |m: &SetManager| {
+            (
+                serde_json::to_value(m.index_set.iter().collect::<Vec<_>>()).unwrap(),
+                m.btree_set.iter().map(|p| p.id).collect::<Vec<_>>(),
+                m.price_index.iter().copied().collect::<Vec<_>>(),
+                m.sizes(),
+            )
+        };
+        let before = state(&mgr);
+
+        // Through JSON, as the snapshot travels over the API
+        let json = serde_json::to_string(&mgr.snapshot()).unwrap();
+        let snap: SetSnapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(snap.products.len(), 8);
+
+        let mut changed = products[2].clone();
+        changed.name = "Renamed".to_string();
+        changed.price_cents = 9_999;
+        mgr.insert_product(&changed);
+        mgr.remove_products(&[products[0].id, products[5].id].into());
+        mgr.insert_product(&make(Uuid::new_v4(), "Extra"));
+        assert_ne!(state(&mgr), before);
+
+        mgr.restore_from_snapshot(&snap);
+        assert_eq!(state(&mgr), before);
+        assert_eq!(mgr.dash_set.len(), 8);
+        assert!(products.iter().all(|p| mgr.ahash_set.contains(p) && mgr.fx_set.as_ref().unwrap().contains(p)));
+    }
+
+    #[test]
+    fn remove_nonexistent_id_is_noop() {
+        let mut mgr = SetManager::new();
+        mgr.insert_product(&make(Uuid::new_v4(), "Widget"));
+        mgr.remove_product(Uuid::new_v4()); // different ID
+        assert_eq!(mgr.sizes(), SetSizes::uniform(1, None));
+    }
+
+    #[test]
+    fn sync_from_db_replaces_all_contents() {
+        let mut mgr = SetManager::new();
+        let old = make(Uuid::new_v4(), "Old");
+        mgr.insert_product(&old);
+
+        let new_products = vec![
+            make(Uuid::new_v4(), "Beta"),
+            make(Uuid::new_v4(), "Gamma"),
+        ];
+        mgr.sync_from_db(&new_products);
+
+        assert_eq!(mgr.sizes(), SetSizes::uniform(2, None));
+        assert!(!mgr.hash_set.contains(&old), "Old product must be gone after sync");
+    }
+
+    #[test]
+    fn batch_insert_merges_into_every_set() {
+        let mut mgr = SetManager::new().with_name_trie(true);
+        let kept = make(Uuid::new_v4(), "Kept");
+        mgr.insert_product(&kept);
+
+        let renamed = make(kept.id, "Renamed");
+        let batch: Vec<Product> = (0..5).map(|i| make(Uuid::new_v4(), &format!("P{}", i))).chain([renamed.clone()]).collect();
+        mgr.batch_insert(&batch);
+
+        assert_eq!(mgr.sizes(), SetSizes::uniform(6, None));
+        for p in &batch {
+            assert!(mgr.hash_set.contains(p) && mgr.index_set.contains(p) && mgr.btree_set.contains(p), "{}", p.name);
+        }
+        // The stored copy was replaced, not duplicated under its old name
+        assert!(mgr.btree_set.iter().all(|p| p.name != "Kept"));
+        assert_eq!(mgr.autocomplete("Renamed", 10), vec![("Renamed".to_string(), kept.id)]);
+        assert!(mgr.autocomplete("Kept", 10).is_empty());
+    }
+
+    #[tokio::test]
+    async fn batch_insert_benchmark_matches_sequential_inserts() {
+        let products: Vec<Product> = (0..50).map(|i| make(Uuid::new_v4(), &format!("P{:02}", i))).collect();
+        let result = benchmark_batch_insert(&products).await;
+        assert_eq!(result.product_count, 50);
+        assert!(result.results_match);
+        assert!(result.speedup > 0.0);
+    }
+
+    // ── Order guarantees ───────────────────────────────────────────────────────
+
+    #[test]
+    fn index_set_preserves_insertion_order() {
+        let mut mgr = SetManager::new();
+        let names = ["Zebra", "Alpha", "Mango", "Delta"];
+        for name in &names {
+            mgr.insert_product(&make(Uuid::new_v4(), name));
+        }
+        let observed: Vec<&str> = mgr.index_set.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(observed, names, "IndexSet must preserve insertion (FIFO) order");
+    }
+
+    #[test]
+    fn btree_set_iterates_alphabetically() {
+        let mut mgr = SetManager::new();
+        mgr.insert_product(&make(Uuid::new_v4(), "Zebra"));
+        mgr.insert_product(&make(Uuid::new_v4(), "Alpha"));
+        mgr.insert_product(&make(Uuid::new_v4(), "Mango"));
+        let observed: Vec<&str> = mgr.btree_set.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(observed, vec!["Alpha", "Mango", "Zebra"]);
+    }
+
+    #[test]
+    fn hash_set_contains_inserted_product() {
+        let mut mgr = SetManager::new();
+        let p = make(Uuid::new_v4(), "Widget");
+        mgr.insert_product(&p);
+        assert!(mgr.hash_set.contains(&p));
+        assert!(mgr.index_set.contains(&p));
+        assert!(mgr.btree_set.contains(&p));
+        assert!(mgr.ahash_set.contains(&p));
+        assert!(mgr.dash_set.contains(&p));
+    }
+
+    #[test]
+    fn ahash_set_deduplicates_by_id_like_hash_set() {
+        let id = Uuid::new_v4();
+        let first = make(id, "First");
+        let renamed = make(id, "Renamed");
+
+        let mut std_set = HashSet::new();
+        let mut ahash_set = AHashSet::new();
+        for p in [&first, &renamed] {
+            std_set.insert(p.clone());
+            ahash_set.insert(p.clone());
+        }
+
+        assert_eq!(ahash_set.len(), 1, "AHashSet must deduplicate products by ID");
+        assert_eq!(ahash_set.len(), std_set.len());
+        assert!(ahash_set.contains(&renamed));
+    }
+
+    #[test]
+    fn ahash_set_reinsert_after_rename_keeps_single_entry() {
+        let mut mgr = SetManager::new();
+        let id = Uuid::new_v4();
+        mgr.insert_product(&make(id, "Before"));
+        mgr.insert_product(&make(id, "After"));
+        assert_eq!(mgr.ahash_set.len(), 1);
+        mgr.remove_product(id);
+        assert!(mgr.ahash_set.is_empty());
+    }
+
+    #[test]
+    fn fastest_by_picks_minimum_and_handles_empty() {
+        let products: Vec<Product> = (0..5).map(|i| make(Uuid::new_v4(), &format!("P{}", i))).collect();
+        let mut results = SetManager::new().run_benchmark(products, &SetBenchmarkConfig::default()).unwrap().results;
+        for (i, r) in results.iter_mut().enumerate() {
+            r.insert_all.duration_ns = 100 - i as u64;
+        }
+        assert_eq!(fastest_by(&results, |r| r.insert_all.duration_ns), "RwLock<HashSet>");
+        assert_eq!(fastest_by(&[], |r| r.insert_all.duration_ns), "N/A");
+    }
+
+    // ── Benchmark correctness ──────────────────────────────────────────────────
+
+    #[test]
+    fn benchmark_reports_correct_product_count() {
+        let products: Vec<Product> = (0..50)
+            .map(|i| make(Uuid::new_v4(), &format!("Product {:03}", i)))
+            .collect();
+        let mut mgr = SetManager::new();
+        let report = mgr.run_benchmark(products, &SetBenchmarkConfig::default()).unwrap();
+        assert_eq!(report.product_count, 50);
+        assert_eq!(report.results.len(), 9 + cfg!(feature = "skiplist") as usize);
+    }
+
+    #[test]
+    fn benchmark_iteration_sample_at_most_10_items() {
+        let products: Vec<Product> = (0..30)
+            .map(|i| make(Uuid::new_v4(), &format!("P {:02}", i)))
+            .collect();
+        let mut mgr = SetManager::new();
+        let report = mgr.run_benchmark(products, &SetBenchmarkConfig::default()).unwrap();
+        for r in &report.results {
+            assert!(
+                r.iteration_order_sample.len() <= 10,
+                "{} sample had {} items",
+                r.set_type,
+                r.iteration_order_sample.len()
+            );
+        }
+    }
+
+    #[test]
+    fn benchmark_btree_sample_is_alphabetically_sorted() {
+        let products = vec![
+            make(Uuid::new_v4(), "Zebra"),
+            make(Uuid::new_v4(), "Alpha"),
+            make(Uuid::new_v4(), "Mango"),
+        ];
+        let mut mgr = SetManager::new();
+        let report = mgr.run_benchmark(products, &SetBenchmarkConfig::default()).unwrap();
+        let btree = report.results.iter().find(|r| r.set_type == "BTreeSet").unwrap();
+        assert_eq!(
+            btree.iteration_order_sample,
+            vec!["Alpha", "Mango", "Zebra"],
+            "BTreeSet sample must be alphabetically sorted"
+        );
+    }
+
+    #[test]
+    fn benchmark_index_sample_preserves_insertion_order() {
+        let products = vec![
+            make(Uuid::new_v4(), "Zebra"),
+            make(Uuid::new_v4(), "Alpha"),
+            make(Uuid::new_v4(), "Mango"),
+        ];
+        let mut mgr = SetManager::new();
+        let report = mgr.run_benchmark(products, &SetBenchmarkConfig::default()).unwrap();
+        let index = report
+            .results
+            .iter()
+            .find(|r| r.set_type.contains("Index"))
+            .unwrap();
+        assert_eq!(
+            index.iteration_order_sample,
+            vec!["Zebra", "Alpha", "Mango"],
+            "IndexSet sample must preserve insertion order"
+        );
+    }
+
+    /// An `IndexSet` of A..E with the third element (C) removed using `strategy`.
+    fn names_after_removing_third(strategy: RemoveStrategy) -> Vec<String> {
+        let products: Vec<Product> = ["A", "B", "C", "D", "E"].iter().map(|n| make(Uuid::new_v4(), n)).collect();
+        let mut set: IndexSet<Product> = products.iter().cloned().collect();
+        index_set_remove(&mut set, &products[2..3], strategy);
+        set.iter().map(|p| p.name.clone()).collect()
+    }
+
+    #[test]
+    fn swap_remove_moves_the_last_element_into_the_gap() {
+        assert_eq!(names_after_removing_third(RemoveStrategy::Swap), vec!["A", "B", "E", "D"]);
+    }
+
+    #[test]
+    fn shift_remove_preserves_insertion_order() {
+        assert_eq!(names_after_removing_third(RemoveStrategy::Shift), vec!["A", "B", "D", "E"]);
+    }
+
+    #[test]
+    fn rebalance_restores_fifo_order_after_a_swap_remove() {
+        let start = Utc::now();
+        let mut manager = SetManager::new();
+        for (i, name) in ["A", "B", "C", "D", "E"].iter().enumerate() {
+            let mut p = make(Uuid::new_v4(), name);
+            p.created_at = start + chrono::Duration::seconds(i as i64);
+            manager.insert_product(&p);
+        }
+        assert!(manager.index_set_is_fifo());
+
+        let third = manager.index_set[2].clone();
+        manager.index_set.swap_remove(&third);
+        assert!(!manager.index_set_is_fifo(), "E was moved into C's slot");
+
+        manager.rebalance_index_set();
+        assert!(manager.index_set_is_fifo());
+        let names: Vec<&str> = manager.index_set.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["A", "B", "D", "E"]);
+    }
+
+    #[test]
+    fn kendall_tau_is_one_for_identical_orders_and_minus_one_for_reversed() {
+        let ids: Vec<Uuid> = (0..50).map(|_| Uuid::new_v4()).collect();
+        assert_eq!(kendall_tau(&ids, &ids.clone()), 1.0);
+
+        let reversed: Vec<Uuid> = ids.iter().rev().copied().collect();
+        assert_eq!(kendall_tau(&ids, &reversed), -1.0);
+
+        // One swapped neighbour pair out of 6 pairs: (5 - 1) / 6
+        assert!((kendall_tau(&[1, 2, 3, 4], &[2, 1, 3, 4]) - 4.0 / 6.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn kendall_tau_only_compares_shared_items() {
+        assert_eq!(kendall_tau(&[1, 9, 2, 3], &[1, 2, 8, 3]), 1.0);
+        assert_eq!(kendall_tau(&[1], &[1]), 1.0);
+        assert_eq!(kendall_tau::<u8>(&[], &[]), 1.0);
+    }
+
+    #[test]
+    fn benchmark_runs_the_configured_index_remove_strategies() {
+        let products: Vec<Product> = (0..20).map(|i| make(Uuid::new_v4(), &format!("P {:02}", i))).collect();
+        let strategies = |config: SetBenchmarkConfig| -> Vec<(String, RemoveStrategy)> {
+            let report = SetManager::new().run_benchmark(products.clone(), &config).unwrap();
+            report.results.into_iter().filter_map(|r| Some((r.set_type, r.remove_strategy?))).collect()
+        };
+
+        assert_eq!(
+            strategies(SetBenchmarkConfig::default()),
+            vec![
+                ("IndexSet (LinkedHashSet)".to_string(), RemoveStrategy::Swap),
+                ("IndexSet (shift_remove)".to_string(), RemoveStrategy::Shift),
+            ]
+        );
+        let only_shift = SetBenchmarkConfig { index_remove_strategy: Some(RemoveStrategy::Shift), ..Default::default() };
+        assert_eq!(strategies(only_shift), vec![("IndexSet (shift_remove)".to_string(), RemoveStrategy::Shift)]);
+    }
+
+    #[test]
+    fn shift_remove_is_skipped_for_large_runs_unless_requested() {
+        let limit = SetBenchmarkConfig::SHIFT_REMOVE_AUTO_LIMIT;
+        let auto = SetBenchmarkConfig::default();
+        assert!(auto.runs_shift_remove(limit));
+        assert!(!auto.runs_shift_remove(limit + 1));
+        let shift = SetBenchmarkConfig { index_remove_strategy: Some(RemoveStrategy::Shift), ..Default::default() };
+        assert!(shift.runs_shift_remove(limit + 1));
+        let swap = SetBenchmarkConfig { index_remove_strategy: Some(RemoveStrategy::Swap), ..Default::default() };
+        assert!(!swap.runs_shift_remove(0));
+    }
+
+    #[test]
+    fn benchmark_iterate_times_all_elements_not_just_10() {
+        // With only 5 products the iterate timing must still cover all 5 (sample == all names)
+        let products: Vec<Product> = (0..5)
+            .map(|i| make(Uuid::new_v4(), &format!("Item {:02}", i)))
+            .collect();
+        let mut mgr = SetManager::new();
+        let report = mgr.run_benchmark(products, &SetBenchmarkConfig::default()).unwrap();
+        for r in &report.results {
+            assert_eq!(
+                r.iteration_order_sample.len(),
+                5,
+                "{} should expose all 5 items when count < 10",
+                r.set_type
+            );
+        }
+    }
+
+    #[test]
+    fn benchmark_syncs_manager_sets_after_run() {
+        let products: Vec<Product> = (0..10)
+            .map(|i| make(Uuid::new_v4(), &format!("P{}", i)))
+            .collect();
+        let mut mgr = SetManager::new();
+        mgr.run_benchmark(products, &SetBenchmarkConfig::default()).unwrap();
+        // After benchmark the manager sets should be populated
+        assert_eq!(mgr.sizes(), SetSizes::uniform(10, None));
+    }
+
+    // ── Category counts ────────────────────────────────────────────────────────
+
+    fn in_category(name: &str, category: &str) -> Product {
+        Product { category: category.to_string(), ..make(Uuid::new_v4(), name) }
+    }
+
+    #[test]
+    fn category_counts_group_by_category() {
+        let mut mgr = SetManager::new();
+        for (name, cat) in [("A", "Books"), ("B", "Toys"), ("C", "Books"), ("D", "Books")] {
+            mgr.insert_product(&in_category(name, cat));
+        }
+        let counts = mgr.category_counts();
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts["Books"], 3);
+        assert_eq!(counts["Toys"], 1);
+    }
+
+    #[test]
+    fn diff_ids_reports_products_missing_on_either_side() {
+        let mut mgr = SetManager::new();
+        let (kept, stale) = (make_fake_product(), make_fake_product());
+        mgr.sync_from_db(&[kept.clone(), stale.clone()]);
+        assert!(!mgr.diff_ids(&[kept.id, stale.id]).has_drift());
+
+        let fresh = Uuid::new_v4();
+        let diff = mgr.diff_ids(&[kept.id, fresh]);
+        assert_eq!(diff.in_db_not_in_sets, [fresh]);
+        assert_eq!(diff.in_sets_not_in_db, [stale.id]);
+        assert!(!diff.count_mismatch, "same size, different products");
+        assert!(diff.has_drift());
+
+        let diff = mgr.diff_ids(&[kept.id]);
+        assert!(diff.count_mismatch && diff.in_db_not_in_sets.is_empty());
+    }
+
+    #[test]
+    fn partitions_cover_every_product_in_name_order() {
+        let mut mgr = SetManager::new();
+        for (name, category, price, quantity) in [
+            ("Rug", "Home", 1000, 3),
+            ("Drill", "Tools", 5000, 2),
+            ("Lamp", "Home", 500, 6),
+            ("Novel", "Books", 1200, 10),
+            ("Chair", "Home", 1500, 2),
+            ("Saw", "Tools", 2500, 1),
+        ] {
+            mgr.insert_product(&Product { category: category.to_string(), ..valued(name, price, quantity) });
+        }
+
+        let partitions = mgr.partition_by_category();
+        assert_eq!(partitions.len(), 3);
+        assert_eq!(partitions.values().map(|p| p.products.len()).sum::<usize>(), mgr.btree_set.len());
+
+        let home = &partitions["Home"];
+        let names: Vec<&str> = home.products.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["Chair", "Lamp", "Rug"]);
+        assert_eq!((home.total_quantity, home.total_value_cents), (11, 9000));
+        assert_eq!((partitions["Tools"].total_quantity, partitions["Tools"].total_value_cents), (3, 12_500));
+
+        assert_eq!(mgr.largest_category(), Some(("Home", 3)));
+        assert_eq!(mgr.smallest_category(), Some(("Books", 1)));
+    }
+
+    #[test]
+    fn largest_and_smallest_category_break_ties_by_name() {
+        let mut mgr = SetManager::new();
+        assert_eq!(mgr.largest_category(), None);
+        assert!(mgr.partition_by_category().is_empty());
+
+        for (name, cat) in [("A", "Toys"), ("B", "Books"), ("C", "Garden"), ("D", "Garden")] {
+            mgr.insert_product(&in_category(name, cat));
+        }
+        assert_eq!(mgr.largest_category(), Some(("Garden", 2)));
+        assert_eq!(mgr.smallest_category(), Some(("Books", 1)));
+    }
+
+    #[test]
+    fn insert_product_replaces_stored_fields_in_every_set() {
+        let mut mgr = SetManager::new();
+        let p = make(Uuid::new_v4(), "Lamp");
+        mgr.insert_product(&p);
+        mgr.insert_product(&Product { quantity: 99, ..p.clone() });
+
+        assert_eq!(mgr.hash_set.get(&p).unwrap().quantity, 99);
+        assert_eq!(mgr.index_set.get(&p).unwrap().quantity, 99);
+        assert_eq!(mgr.btree_set.get(&p).unwrap().quantity, 99);
+        assert_eq!(mgr.ahash_set.get(&p).unwrap().quantity, 99);
+        assert_eq!(mgr.dash_set.get(&p).unwrap().quantity, 99);
+    }
+
+    #[test]
+    fn category_counts_follow_updates_and_removals() {
+        let mut mgr = SetManager::new();
+        let p = in_category("Lamp", "Home");
+        mgr.insert_product(&p);
+        mgr.insert_product(&Product { category: "Lighting".to_string(), ..p.clone() });
+        assert_eq!(mgr.category_counts(), HashMap::from([("Lighting".to_string(), 1)]));
+
+        mgr.remove_product(p.id);
+        assert!(mgr.category_counts().is_empty());
+    }
+
+    fn stocked(name: &str, quantity: i32) -> Product {
+        Product { quantity, ..make(Uuid::new_v4(), name) }
+    }
+
+    #[test]
+    fn low_stock_includes_the_threshold_and_keeps_name_order() {
+        let mut mgr = SetManager::new();
+        for (name, quantity) in [("Rug", 0), ("Lamp", 10), ("Desk", 11), ("Chair", 3), ("Sofa", 500)] {
+            mgr.insert_product(&stocked(name, quantity));
+        }
+
+        let names = |found: Vec<&Product>| found.iter().map(|p| p.name.clone()).collect::<Vec<_>>();
+        assert_eq!(names(mgr.low_stock_products(10)), ["Chair", "Lamp", "Rug"]);
+        assert_eq!(names(mgr.low_stock_products(0)), ["Rug"]);
+        assert!(mgr.low_stock_products(-1).is_empty());
+        assert_eq!(mgr.low_stock_products(i32::MAX).len(), 5);
+    }
+
+    #[test]
+    fn products_by_category_filters_and_keeps_name_order() {
+        let mut mgr = SetManager::new();
+        for (name, category) in [("Rug", "Home"), ("Drill", "Tools"), ("Lamp", "Home"), ("Chair", "Home")] {
+            mgr.insert_product(&Product { category: category.to_string(), ..make(Uuid::new_v4(), name) });
+        }
+
+        let home: Vec<&str> = mgr.products_by_category("Home").map(|p| p.name.as_str()).collect();
+        assert_eq!(home, ["Chair", "Lamp", "Rug"]);
+        assert_eq!(mgr.products_by_category("Tools").count(), 1);
+        assert_eq!(mgr.products_by_category("home").count(), 0, "exact match, like the DB");
+    }
+
+    #[test]
+    fn low_stock_follows_quantity_updates() {
+        let mut mgr = SetManager::new();
+        let lamp = stocked("Lamp", 50);
+        mgr.insert_product(&lamp);
+        assert!(mgr.low_stock_products(10).is_empty());
+
+        mgr.insert_product(&Product { quantity: 2, ..lamp.clone() });
+        assert_eq!(mgr.low_stock_products(10)[0].quantity, 2);
+        mgr.remove_product(lamp.id);
+        assert!(mgr.low_stock_products(10).is_empty());
+    }
+
+    // ── Inventory report ───────────────────────────────────────────────────────
+
+    fn valued(name: &str, price_cents: i64, quantity: i32) -> Product {
+        Product { price_cents, quantity, ..make(Uuid::new_v4(), name) }
+    }
+
+    #[test]
+    fn inventory_report_orders_by_value_then_name() {
+        let mut mgr = SetManager::new();
+        for (name, price, quantity) in [("Rug", 1000, 3), ("Lamp", 500, 6), ("Desk", 20_000, 1), ("Chair", 1500, 2), ("Sofa", 0, 40)] {
+            mgr.insert_product(&valued(name, price, quantity));
+        }
+
+        let report = mgr.inventory_report();
+        let rows: Vec<(&str, i64)> = report.iter().map(|r| (r.name.as_str(), r.total_value_cents)).collect();
+        assert_eq!(rows, [("Desk", 20_000), ("Chair", 3000), ("Lamp", 3000), ("Rug", 3000), ("Sofa", 0)]);
+        assert!(report.windows(2).all(|w| (Reverse(w[0].total_value_cents), &w[0].name) < (Reverse(w[1].total_value_cents), &w[1].name)));
+        assert_eq!(report.iter().map(|r| r.total_value_cents).sum::<i64>(), 29_000);
+    }
+
+    #[test]
+    fn inventory_report_follows_updates_and_removals() {
+        let mut mgr = SetManager::new();
+        assert!(mgr.inventory_report().is_empty());
+
+        let lamp = valued("Lamp", 100, 1);
+        mgr.insert_product(&lamp);
+        mgr.insert_product(&valued("Rug", 100, 5));
+        assert_eq!(mgr.inventory_report()[0].name, "Rug");
+
+        mgr.insert_product(&Product { quantity: 50, ..lamp.clone() });
+        let report = mgr.inventory_report();
+        assert_eq!((report[0].product_id, report[0].total_value_cents, report.len()), (lamp.id, 5000, 2));
+
+        mgr.remove_product(lamp.id);
+        assert_eq!(mgr.inventory_report().len(), 1);
+    }
+
+    #[test]
+    fn abc_analysis_from_sets_classifies_every_product() {
+        use crate::models::AbcClass;
+
+        let mut mgr = SetManager::new();
+        for p in crate::seed::generate_products(100) {
+            mgr.insert_product(&p);
+        }
+
+        let result = mgr.abc_analysis_from_sets();
+        assert_eq!(result.a_count + result.b_count + result.c_count, 100);
+        assert!(result.items.windows(2).all(|w| w[0].value_cents >= w[1].value_cents));
+        assert_eq!(result.items.iter().map(|i| i.value_cents).sum::<i64>(), result.total_value_cents);
+        assert!((result.a_value_pct + result.b_value_pct + result.c_value_pct - 100.0).abs() < 1e-6);
+        assert_eq!(result.items[0].class, AbcClass::A);
+    }
+
+    #[test]
+    fn price_histogram_counts_every_product_exactly_once() {
+        let mut mgr = SetManager::new();
+        let products = crate::seed::generate_products(300);
+        mgr.sync_from_db(&products);
+
+        let histogram = mgr.price_histogram(&[0, 1_000, 5_000, 10_000, 50_000]);
+        assert_eq!(histogram.iter().map(|b| b.count).sum::<i64>(), 300);
+        assert!(histogram.windows(2).all(|w| w[0].upper_cents == Some(w[1].lower_cents)), "no gaps");
+        for bucket in &histogram {
+            let upper = bucket.upper_cents.unwrap_or(i64::MAX);
+            let inside: Vec<&Product> =
+                products.iter().filter(|p| (bucket.lower_cents..upper).contains(&p.price_cents)).collect();
+            assert_eq!(bucket.count, inside.len() as i64, "bucket from {}", bucket.lower_cents);
+            let value: i64 = inside.iter().map(|p| p.price_cents * i64::from(p.quantity)).sum();
+            assert_eq!(bucket.total_value_cents, value);
+        }
+
+        // A boundary price belongs to the bucket it starts
+        mgr.reset();
+        mgr.insert_product(&priced("Edge", 1_000));
+        let counts: Vec<i64> = mgr.price_histogram(&[0, 1_000]).iter().map(|b| b.count).collect();
+        assert_eq!(counts, [0, 1]);
+    }
+
+    #[test]
+    fn every_serialization_format_round_trips_the_product_list() {
+        let mut products = crate::seed::generate_products(50);
+        products[0].description = Some("Multi-line \"quoted\"\ndescription".to_string());
+        let expected = serde_json::to_value(&products).unwrap();
+
+        for format in SerializationFormat::ALL {
+            let bytes = format.encode(&products).unwrap();
+            let decoded = format.decode(&bytes).unwrap();
+            // Product equality is by id only, so compare every field through JSON
+            assert_eq!(serde_json::to_value(&decoded).unwrap(), expected, "{}", format.name());
+        }
+    }
+
+    #[test]
+    fn serialization_benchmark_reports_every_format() {
+        let products = crate::seed::generate_products(200);
+        let result = benchmark_serialization(&products).unwrap();
+        assert_eq!(result.product_count, 200);
+        assert_eq!(result.json_size_bytes, serde_json::to_vec(&products).unwrap().len());
+        assert!(result.bincode_size_bytes < result.json_size_bytes, "binary formats drop the field names");
+        assert!(result.msgpack_size_bytes < result.json_size_bytes);
+        for winner in [&result.smallest_format, &result.fastest_serialize, &result.fastest_deserialize] {
+            assert!(["JSON", "bincode", "MessagePack"].contains(&winner.as_str()), "{}", winner);
+        }
+
+        let empty = benchmark_serialization(&[]).unwrap();
+        assert_eq!((empty.product_count, empty.json_size_bytes), (0, 2), "`[]`");
+    }
+
+    #[test]
+    fn clone_overhead_benchmark_recommends_one_way_or_the_other() {
+        let result = benchmark_clone_overhead(&crate::seed::generate_products(200));
+        assert_eq!((result.product_count, result.clones_per_product), (200, CLONES_PER_INSERT));
+        assert!(result.clone_vec_ns > 0 && result.arc_wrap_ns > 0 && result.arc_insert_ns > 0);
+        let switch = result.arc_wrap_ns < result.clone_vec_ns;
+        assert_eq!(result.recommendation.starts_with("Switching"), switch, "{}", result.recommendation);
+    }
+
+    #[test]
+    fn incremental_sync_replaces_changed_products_and_keeps_the_rest() {
+        let mut mgr = SetManager::new();
+        let products: Vec<Product> = (0..5).map(|i| make(Uuid::new_v4(), &format!("S{}", i))).collect();
+        mgr.sync_from_db(&products);
+        mgr.last_sync_at = Some(Utc::now());
+
+        let renamed = Product { name: "S1 v2".to_string(), version: 2, ..products[1].clone() };
+        let added = make(Uuid::new_v4(), "S5");
+        mgr.incremental_sync(&[renamed.clone(), added]);
+
+        assert_eq!(mgr.sizes(), SetSizes::uniform(6, None));
+        assert_eq!(mgr.hash_set.get(&renamed).unwrap().name, "S1 v2");
+        assert!(!mgr.btree_set.iter().any(|p| p.name == "S1"), "stale copy evicted");
+        assert!(mgr.last_sync_at.is_some(), "only wholesale replacement clears the sync time");
+
+        mgr.sync_from_db(&products);
+        assert!(mgr.last_sync_at.is_none());
+    }
+
+    // ── HashMap by id ──────────────────────────────────────────────────────────
+
+    #[test]
+    fn id_map_follows_inserts_updates_and_removals() {
+        let mut mgr = SetManager::new();
+        let products: Vec<Product> = (0..10).map(|i| make(Uuid::new_v4(), &format!("M{:02}", i))).collect();
+        mgr.sync_from_db(&products);
+        assert_eq!(mgr.id_map.len(), 10);
+
+        let renamed = Product { name: "Renamed".to_string(), ..products[0].clone() };
+        mgr.insert_product(&renamed);
+        assert_eq!(mgr.id_map[&renamed.id].name, "Renamed");
+
+        mgr.remove_products(&ids_of(products[..4].iter()));
+        assert_eq!(mgr.id_map.len(), 6);
+        assert!(!mgr.id_map.contains_key(&products[0].id));
+
+        mgr.reset();
+        assert!(mgr.id_map.is_empty());
+    }
+
+    #[test]
+    fn id_map_get_beats_a_linear_scan_of_hash_set() {
+        let mut mgr = SetManager::new();
+        let products: Vec<Product> = (0..20_000).map(|i| make(Uuid::new_v4(), &format!("M{:05}", i))).collect();
+        mgr.sync_from_db(&products);
+        let targets: Vec<Uuid> = products.iter().step_by(100).map(|p| p.id).collect();
+
+        let (found, map_time) = timed(|| targets.iter().filter(|id| mgr.id_map.contains_key(black_box(*id))).count());
+        let (scanned, scan_time) =
+            timed(|| targets.iter().filter(|id| mgr.hash_set.iter().any(|p| p.id == *black_box(*id))).count());
+        assert_eq!((found, scanned), (targets.len(), targets.len()));
+        assert!(map_time < scan_time, "get {:?} vs scan {:?}", map_time, scan_time);
+    }
+
+    #[test]
+    fn hashmap_benchmark_is_reported_next_to_the_sets() {
+        let products: Vec<Product> = (0..200).map(|i| make(Uuid::new_v4(), &format!("H{:03}", i))).collect();
+        let result = benchmark_hashmap(&products, &SetBenchmarkConfig::default());
+        assert_eq!(result.set_type, "HashMap<Uuid, Product>");
+        assert_eq!(result.product_count, 200);
+        assert!(result.extra_info.unwrap().contains("HashSet::contains"));
+
+        let report = SetManager::new().run_benchmark(products, &SetBenchmarkConfig { threads: 1, ..Default::default() }).unwrap();
+        assert!(report.results.iter().any(|r| r.set_type == "HashMap<Uuid, Product>"));
+        assert_ne!(report.winner_lookup, "HashMap<Uuid, Product>", "not a set, so never the winner");
+    }
+
+    // ── DashSet (concurrent) ───────────────────────────────────────────────────
+
+    fn test_pool(threads: usize) -> rayon::ThreadPool {
+        rayon::ThreadPoolBuilder::new().num_threads(threads).build().unwrap()
+    }
+
+    #[test]
+    fn dashmap_benchmark_reports_threads_and_throughput() {
+        let products: Vec<Product> = (0..200).map(|i| make(Uuid::new_v4(), &format!("D{:03}", i))).collect();
+        let result = benchmark_dashmap_set(&products, &SetBenchmarkConfig::default(), &test_pool(3));
+        assert_eq!(result.set_type, "DashSet");
+        assert_eq!(result.product_count, 200);
+        assert_eq!(result.threads, Some(3));
+        assert!(result.per_thread_ops_per_sec.unwrap() > 0.0);
+        assert_eq!(result.iteration_order_sample.len(), 10);
+    }
+
+    #[test]
+    fn dashmap_benchmark_handles_more_threads_than_products() {
+        let products = vec![make(Uuid::new_v4(), "Solo")];
+        let result = benchmark_dashmap_set(&products, &SetBenchmarkConfig::default(), &test_pool(8));
+        assert_eq!(result.iteration_order_sample, vec!["Solo"]);
+    }
+
+    #[test]
+    fn rwlock_baseline_runs_the_same_phases_as_dashmap() {
+        let products: Vec<Product> = (0..200).map(|i| make(Uuid::new_v4(), &format!("R{:03}", i))).collect();
+        let pool = test_pool(4);
+        let baseline = benchmark_rwlock_hash_set(&products, &SetBenchmarkConfig::default(), &pool);
+        let dash = benchmark_dashmap_set(&products, &SetBenchmarkConfig::default(), &pool);
+        assert_eq!(baseline.set_type, "RwLock<HashSet>");
+        assert_eq!(baseline.threads, dash.threads);
+        assert_eq!(baseline.product_count, dash.product_count);
+        assert_eq!(baseline.iteration_order_sample.len(), 10);
+        assert!(baseline.per_thread_ops_per_sec.unwrap() > 0.0);
+    }
+
+    #[test]
+    fn only_concurrent_result_carries_thread_count() {
+        let products: Vec<Product> = (0..20).map(|i| make(Uuid::new_v4(), &format!("P{}", i))).collect();
+        let config = SetBenchmarkConfig { threads: 2, ..Default::default() };
+        let report = SetManager::new().run_benchmark(products, &config).unwrap();
+        for r in &report.results {
+            assert_eq!(r.threads.is_some(), matches!(r.set_type.as_str(), "DashSet" | "RwLock<HashSet>"), "{}", r.set_type);
+        }
+    }
+
+    #[test]
+    fn benchmark_config_clamps_and_defaults() {
+        let zero = SetBenchmarkConfig { threads: 0, top_k: 0, warmup_count: 0, lookup_samples: 0, repeat_runs: 0, index_remove_strategy: None }
+            .normalized();
+        assert_eq!((zero.threads, zero.top_k), (1, 1));
+        assert_eq!((zero.warmup_count, zero.lookup_samples, zero.repeat_runs), (0, 1, 1));
+        let huge = SetBenchmarkConfig {
+            threads: 10_000,
+            top_k: usize::MAX,
+            warmup_count: usize::MAX,
+            lookup_samples: usize::MAX,
+            repeat_runs: u32::MAX,
+            index_remove_strategy: None,
+        }
+        .normalized();
+        assert_eq!(huge.threads, SetBenchmarkConfig::MAX_THREADS);
+        assert_eq!(huge.top_k, SetBenchmarkConfig::MAX_TOP_K);
+        assert_eq!(huge.warmup_count, SetBenchmarkConfig::MAX_WARMUP_COUNT);
+        assert_eq!(huge.lookup_samples, SetBenchmarkConfig::MAX_LOOKUP_SAMPLES);
+        assert_eq!(huge.repeat_runs, SetBenchmarkConfig::MAX_REPEAT_RUNS);
+        let parsed: SetBenchmarkConfig = serde_json::from_str("{}").unwrap();
+        assert!(parsed.threads >= 1);
+        assert_eq!(parsed.top_k, 10);
+        assert_eq!((parsed.warmup_count, parsed.lookup_samples, parsed.repeat_runs), (1_000, 1_000, 1));
+        assert_eq!(parsed.index_remove_strategy, None);
+        let aliased: SetBenchmarkConfig = serde_json::from_str(r#"{ "k": 3, "index_remove_strategy": "shift" }"#).unwrap();
+        assert_eq!(aliased.top_k, 3);
+        assert_eq!(aliased.index_remove_strategy, Some(RemoveStrategy::Shift));
+    }
+
+    // ── Repeat runs ────────────────────────────────────────────────────────────
+
+    #[test]
+    fn timing_across_runs_is_the_median_with_min_and_max() {
+        let odd = OpTiming::across_runs(&[30, 10, 20]);
+        assert_eq!((odd.duration_ns, odd.min_ns, odd.max_ns), (20, Some(10), Some(30)));
+        let even = OpTiming::across_runs(&[40, 10, 20, 30]);
+        assert_eq!((even.duration_ns, even.min_ns, even.max_ns), (25, Some(10), Some(40)));
+        assert_eq!(OpTiming::from(Duration::from_nanos(7)).min_ns, None);
+    }
+
+    #[test]
+    fn timed_multi_stats_are_ordered() {
+        let multi = timed_multi(|| std::thread::sleep(Duration::from_millis(1)), 5);
+        assert_eq!(multi.runs.len(), 5);
+        assert!(multi.min >= Duration::from_millis(1));
+        assert!(multi.min <= multi.mean && multi.mean <= multi.max, "{:?}", multi);
+        assert!(multi.min <= multi.p50 && multi.p50 <= multi.p95 && multi.p95 <= multi.p99 && multi.p99 <= multi.max);
+        assert!(multi.std_dev_ns >= 0.0);
+
+        let once = timed_multi(|| 1 + 1, 0);
+        assert_eq!(once.runs.len(), 1, "always at least one run");
+        assert_eq!((once.min, once.p99, once.std_dev_ns), (once.max, once.max, 0.0));
+    }
+
+    #[test]
+    fn repeated_read_only_steps_report_their_spread() {
+        let products: Vec<Product> = (0..50).map(|i| priced(&format!("P{:03}", i), i)).collect();
+        let config = SetBenchmarkConfig { lookup_samples: 20, repeat_runs: 4, ..Default::default() };
+        for r in [benchmark_hash_set(&products, &config), benchmark_index_set(&products, &config), benchmark_btree_set(&products, &config)] {
+            for t in [&r.lookup_hit, &r.lookup_miss, &r.iterate_all] {
+                assert!(t.min_ns.unwrap() <= t.duration_ns && t.duration_ns <= t.max_ns.unwrap(), "{}: {:?}", r.set_type, t);
+            }
+            // Inserts and removals change the set, so they are timed once per pass
+            assert_eq!((r.insert_all.min_ns, r.remove_half.min_ns), (None, None));
+            assert_eq!(r.iteration_order_sample.len(), 10);
+        }
+    }
+
+    #[test]
+    fn repeated_benchmark_timings_fall_between_min_and_max() {
+        let products: Vec<Product> = (0..100).map(|i| priced(&format!("P{:03}", i), i)).collect();
+        let config = SetBenchmarkConfig { threads: 2, lookup_samples: 50, repeat_runs: 3, ..Default::default() };
+        let report = SetManager::new().run_benchmark(products, &config).unwrap();
+
+        assert_eq!(report.results.len(), 9 + cfg!(feature = "skiplist") as usize);
+        for r in &report.results {
+            for t in [&r.insert_all, &r.lookup_hit, &r.lookup_miss, &r.iterate_all, &r.remove_half] {
+                let (min, max) = (t.min_ns.unwrap(), t.max_ns.unwrap());
+                assert!(min <= t.duration_ns && t.duration_ns <= max, "{}: {:?}", r.set_type, t);
+            }
+            assert_eq!(r.product_count, 100);
+        }
+    }
+
+    #[test]
+    fn single_run_benchmark_omits_min_and_max() {
+        let products: Vec<Product> = (0..10).map(|i| make(Uuid::new_v4(), &format!("P{}", i))).collect();
+        let config = SetBenchmarkConfig { threads: 1, ..Default::default() };
+        let report = SetManager::new().run_benchmark(products, &config).unwrap();
+        assert!(report.results.iter().all(|r| r.insert_all.min_ns.is_none() && r.insert_all.max_ns.is_none()));
+        let json = serde_json::to_value(&report.results[0].insert_all).unwrap();
+        assert!(json.get("min_ns").is_none());
+    }
+
+    // ── BinaryHeap top-k ───────────────────────────────────────────────────────
+
+    fn priced(name: &str, price_cents: i64) -> Product {
+        Product { price_cents, ..make(Uuid::new_v4(), name) }
+    }
+
+    #[test]
+    fn stats_follow_inserts_and_removes() {
+        let mut mgr = SetManager::new();
+        let empty = mgr.stats();
+        assert_eq!(
+            empty,
+            SetStats { min_price_cents: 0, max_price_cents: 0, avg_price_cents: 0.0, total_quantity: 0, total_value_cents: 0, product_count: 0 }
+        );
+        assert_eq!(empty.inventory_value_dollars(), 0.0);
+
+        // `make` stocks 10 units each
+        let cheap = priced("Cheap", 100);
+        let dear = priced("Dear", 900);
+        mgr.insert_product(&cheap);
+        mgr.insert_product(&dear);
+        let stats = mgr.stats();
+        assert_eq!((stats.min_price_cents, stats.max_price_cents, stats.avg_price_cents), (100, 900, 500.0));
+        assert_eq!((stats.total_quantity, stats.total_value_cents, stats.product_count), (20, 10_000, 2));
+        assert_eq!(stats.inventory_value_dollars(), 100.0);
+
+        mgr.remove_product(dear.id);
+        let stats = mgr.stats();
+        assert_eq!((stats.max_price_cents, stats.avg_price_cents, stats.total_value_cents), (100, 100.0, 1_000));
+        assert_eq!(stats.discrepancies(&SetStats { total_quantity: 11, ..stats }), vec!["total_quantity"]);
+    }
+
+    #[test]
+    fn stats_saturate_instead_of_overflowing() {
+        let mut mgr = SetManager::new();
+        mgr.insert_product(&priced("Priceless", i64::MAX));
+        mgr.insert_product(&priced("Also Priceless", i64::MAX));
+        let stats = mgr.stats();
+        assert_eq!((stats.total_value_cents, stats.max_price_cents), (i64::MAX, i64::MAX));
+    }
+
+    /// 100 products whose prices and quantities are distinct and shuffled.
+    fn shuffled_manager() -> SetManager {
+        let mut mgr = SetManager::new();
+        for i in 0..100_i64 {
+            // 37 is coprime with 100, so both keys hit every value in 0..100 once
+            let p = Product { quantity: ((i * 37 + 11) % 100) as i32, ..priced(&format!("P{:03}", i), (i * 37) % 100) };
+            mgr.insert_product(&p);
+        }
+        mgr
+    }
+
+    #[test]
+    fn top_n_by_price_returns_the_most_expensive_in_descending_order() {
+        let mgr = shuffled_manager();
+        let top = mgr.top_n_by_price(10);
+        assert_eq!(top.iter().map(|p| p.price_cents).collect::<Vec<_>>(), (90..100).rev().collect::<Vec<i64>>());
+
+        let threshold = top.last().unwrap().price_cents;
+        let picked: HashSet<Uuid> = top.iter().map(|p| p.id).collect();
+        assert!(mgr.hash_set.iter().filter(|p| !picked.contains(&p.id)).all(|p| p.price_cents < threshold));
+    }
+
+    #[test]
+    fn top_n_by_quantity_returns_the_largest_stock_in_descending_order() {
+        let mgr = shuffled_manager();
+        let top = mgr.top_n_by_quantity(10);
+        assert_eq!(top.iter().map(|p| p.quantity).collect::<Vec<_>>(), (90..100).rev().collect::<Vec<i32>>());
+    }
+
+    #[test]
+    fn top_n_handles_zero_and_more_than_available() {
+        let mgr = shuffled_manager();
+        assert!(mgr.top_n_by_price(0).is_empty());
+        assert_eq!(mgr.top_n_by_price(500).len(), 100);
+    }
+
+    #[test]
+    fn top_n_heap_and_sort_agree_even_with_ties() {
+        let products: Vec<Product> = (0..100).map(|i| priced(&format!("P{:03}", i), i % 7)).collect();
+        for n in [1, 5, 15, 100] {
+            let heap: Vec<Uuid> = top_n_by_heap(products.iter(), n, |p| p.price_cents).iter().map(|p| p.id).collect();
+            let sort: Vec<Uuid> = top_n_by_sort(products.iter(), n, |p| p.price_cents).iter().map(|p| p.id).collect();
+            assert_eq!(heap, sort, "n = {}", n);
+        }
+    }
+
+    #[test]
+    fn top_k_returns_the_most_expensive_in_descending_order() {
+        let prices = [500, 20, 9_000, 75, 9_000, 310, 1, 4_200];
+        let heap = || -> BinaryHeap<ProductByPrice> {
+            prices.iter().enumerate().map(|(i, &c)| ProductByPrice(priced(&format!("P{}", i), c))).collect()
+        };
+
+        let top = pop_top_k(&mut heap(), 3);
+        assert_eq!(top.iter().map(|p| p.price_cents).collect::<Vec<_>>(), vec![9_000, 9_000, 4_200]);
+
+        let mut sorted = prices.to_vec();
+        sorted.sort_unstable_by(|a, b| b.cmp(a));
+        let all = pop_top_k(&mut heap(), 100);
+        assert_eq!(all.iter().map(|p| p.price_cents).collect::<Vec<_>>(), sorted);
+    }
+
+    #[test]
+    fn product_by_price_ignores_name_order() {
+        let cheap = ProductByPrice(priced("AAA", 1));
+        let pricey = ProductByPrice(priced("ZZZ", 2));
+        assert!(pricey > cheap);
+        assert!(make(Uuid::new_v4(), "ZZZ") > make(Uuid::new_v4(), "AAA"));
+    }
+
+    #[test]
+    fn binary_heap_benchmark_samples_the_top_k() {
+        let products: Vec<Product> = (0..100).map(|i| priced(&format!("P{:03}", i), i * 10)).collect();
+        let result = benchmark_binary_heap(&products, &SetBenchmarkConfig { top_k: 4, ..Default::default() });
+
+        assert_eq!(result.set_type, "BinaryHeap");
+        assert_eq!(result.iteration_order_sample, vec!["P099", "P098", "P097", "P096"]);
+        let info = result.extra_info.unwrap();
+        assert!(info.starts_with("top-4 by price"), "{}", info);
+        assert!(info.ends_with("top price = 990 cents"), "{}", info);
+    }
+
+    #[test]
+    fn binary_heap_result_is_reported_but_never_wins() {
+        let products: Vec<Product> = (0..50).map(|i| priced(&format!("P{}", i), i)).collect();
+        let config = SetBenchmarkConfig { threads: 1, top_k: 5, ..Default::default() };
+        let report = SetManager::new().run_benchmark(products, &config).unwrap();
+
+        let heap = report.results.iter().find(|r| r.set_type == "BinaryHeap").unwrap();
+        assert_eq!(heap.iteration_order_sample.len(), 5);
+        assert_eq!(report.summary_table.len(), report.results.len());
+        for winner in [&report.winner_insert, &report.winner_lookup, &report.winner_iterate] {
+            assert_ne!(winner, "BinaryHeap");
+        }
+        let mut sets = report.results.iter().filter(|r| !matches!(r.set_type.as_str(), "BinaryHeap" | "HashMap<Uuid, Product>"));
+        assert!(sets.all(|r| r.extra_info.is_none()));
+    }
+
+    #[test]
+    fn dash_set_tracks_insert_and_remove() {
+        let mut mgr = SetManager::new();
+        let id = Uuid::new_v4();
+        mgr.insert_product(&make(id, "Before"));
+        mgr.insert_product(&make(id, "After"));
+        assert_eq!(mgr.dash_set.len(), 1);
+        mgr.remove_product(id);
+        assert!(mgr.dash_set.is_empty());
+    }
+
+    // ── Run comparison ─────────────────────────────────────────────────────────
+
+    /// A result whose five timings are all `ns`.
+    fn timed_result(set_type: &str, ns: u64) -> SetBenchmarkResult {
+        let t = OpTiming::from_ns(ns);
+        SetBenchmarkResult {
+            set_type: set_type.to_string(),
+            description: String::new(),
+            product_count: 100,
+            insert_all: t.clone(),
+            lookup_hit: t.clone(),
+            lookup_miss: t.clone(),
+            iterate_all: t.clone(),
+            remove_half: t,
+            iteration_order_sample: vec![],
+            order_guaranteed: false,
+            order_type: String::new(),
+            threads: None,
+            per_thread_ops_per_sec: None,
+            extra_info: None,
+            remove_strategy: None,
+        }
+    }
+
+    #[test]
+    fn memory_estimate_grows_in_proportion_to_the_product_count() {
+        let mut mgr = SetManager::new();
+        assert_eq!(mgr.memory_estimate_bytes().total_bytes, 0);
+
+        let products: Vec<Product> = (0..4_000).map(|i| make(Uuid::new_v4(), &format!("P{:04}", i))).collect();
+        mgr.sync_from_db(&products[..1_000]);
+        let small = mgr.memory_estimate_bytes();
+        mgr.sync_from_db(&products);
+        let large = mgr.memory_estimate_bytes();
+
+        let product = std::mem::size_of::<Product>();
+        for (name, bytes) in [("hash", small.hash_set_bytes), ("index", small.index_set_bytes), ("btree", small.btree_set_bytes)] {
+            assert!(bytes >= 1_000 * product, "{} set smaller than its elements: {}", name, bytes);
+            assert!(bytes <= 3 * 1_000 * product, "{} set implausibly large: {}", name, bytes);
+        }
+        assert_eq!(
+            large.total_bytes,
+            large.hash_set_bytes + large.index_set_bytes + large.btree_set_bytes
+        );
+        let ratio = large.total_bytes as f64 / small.total_bytes as f64;
+        assert!((3.0..=5.0).contains(&ratio), "4x the products gave {:.2}x the bytes", ratio);
+
+        mgr.reset();
+        assert_eq!(mgr.memory_estimate_bytes().btree_set_bytes, 0);
+    }
+
+    fn report_of(results: Vec<SetBenchmarkResult>) -> BenchmarkReport {
+        BenchmarkReport {
+            run_id: Uuid::new_v4(),
+            run_at: Utc::now().to_rfc3339(),
+            product_count: 100,
+            results,
+            winner_insert: String::new(),
+            winner_lookup: String::new(),
+            winner_iterate: String::new(),
+            summary_table: vec![],
+            price_range: None,
+            prefix_search: None,
+        }
+    }
+
+    #[test]
+    fn msgpack_round_trips_every_field() {
+        let products: Vec<Product> = (0..60).map(|i| make(Uuid::new_v4(), &format!("Item {:02}", i))).collect();
+        let config = SetBenchmarkConfig { threads: 2, repeat_runs: 2, lookup_samples: 20, warmup_count: 10, ..Default::default() };
+        let report = SetManager::new().run_benchmark(products, &config).unwrap();
+        // Optional fields on both sides of `skip_serializing_if`
+        assert!(report.price_range.is_some() && report.results[0].insert_all.min_ns.is_some());
+        assert!(report.results.iter().any(|r| r.threads.is_none()));
+
+        let bytes = report.to_msgpack().unwrap();
+        let decoded = BenchmarkReport::from_msgpack(&bytes).unwrap();
+        assert_eq!(serde_json::to_value(&decoded).unwrap(), serde_json::to_value(&report).unwrap());
+        assert!(bytes.len() < serde_json::to_vec(&report).unwrap().len());
+
+        let sparse = report_of(vec![timed_result("HashSet", 1_000)]);
+        let decoded = BenchmarkReport::from_msgpack(&sparse.to_msgpack().unwrap()).unwrap();
+        assert_eq!(serde_json::to_value(&decoded).unwrap(), serde_json::to_value(&sparse).unwrap());
+        assert!(BenchmarkReport::from_msgpack(b"not msgpack").is_err());
+    }
+
+    #[test]
+    fn compare_reports_signs_improvements_negative_and_regressions_positive() {
+        let a = report_of(vec![timed_result("BTreeSet", 1_000_000), timed_result("HashSet", 2_000_000)]);
+        let b = report_of(vec![timed_result("BTreeSet", 880_000), timed_result("HashSet", 2_500_000)]);
+        let delta = compare_reports(&a, &b);
+
+        assert_eq!((delta.run_a, delta.run_b), (a.run_id, b.run_id));
+        let btree = &delta.sets[0];
+        assert_eq!(btree.set_type, "BTreeSet");
+        assert!((btree.insert_delta_ms - -0.12).abs() < 1e-9);
+        assert!((btree.insert_delta_pct - -12.0).abs() < 1e-9);
+        assert!((btree.lookup_hit_delta_us - -120.0).abs() < 1e-9);
+
+        let hash = &delta.sets[1];
+        assert!((hash.remove_delta_ms - 0.5).abs() < 1e-9);
+        assert!((hash.iterate_delta_pct - 25.0).abs() < 1e-9);
+
+        assert!(delta.verdicts.contains(&"BTreeSet insert improved by 12%".to_string()));
+        assert!(delta.verdicts.contains(&"HashSet lookup miss regressed by 25%".to_string()));
+        assert_eq!(delta.verdicts.len(), 10);
     }
 
-    // ── SetManager basic ops ───────────────────────────────────────────────────
+    #[test]
+    fn compare_reports_skips_noise_and_unmatched_sets() {
+        let a = report_of(vec![timed_result("HashSet", 1_000), timed_result("FxHashSet", 1_000), timed_result("Idle", 0)]);
+        let b = report_of(vec![timed_result("HashSet", 1_040), timed_result("BinaryHeap", 9_000), timed_result("Idle", 50)]);
+        let delta = compare_reports(&a, &b);
+
+        assert_eq!(delta.sets.iter().map(|d| d.set_type.as_str()).collect::<Vec<_>>(), vec!["HashSet", "Idle"]);
+        assert!((delta.sets[0].insert_delta_pct - 4.0).abs() < 1e-9);
+        assert_eq!(delta.sets[1].insert_delta_pct, 0.0, "no baseline, no percentage");
+        assert!(delta.verdicts.is_empty(), "{:?}", delta.verdicts);
+        assert_eq!(delta.unmatched_set_types, vec!["FxHashSet", "BinaryHeap"]);
+    }
+
+    fn severities(alerts: &[RegressionAlert], set_type: &str) -> Vec<Severity> {
+        alerts.iter().filter(|a| a.set_type == set_type).map(|a| a.severity).collect()
+    }
 
     #[test]
-    fn new_manager_is_empty() {
-        assert_eq!(SetManager::new().sizes(), (0, 0, 0));
+    fn regression_check_grades_slowdowns_by_threshold() {
+        let baseline = report_of(vec![timed_result("HashSet", 1_000)]);
+        let mut slower = timed_result("HashSet", 1_000);
+        slower.insert_all = OpTiming::from_ns(1_100); // exactly the warning line
+        slower.lookup_hit = OpTiming::from_ns(1_101);
+        slower.lookup_miss = OpTiming::from_ns(1_250); // exactly the regression line
+        slower.iterate_all = OpTiming::from_ns(1_251);
+        slower.remove_half = OpTiming::from_ns(3_000);
+        let alerts = regression_check(&baseline, &report_of(vec![slower]), REGRESSION_WARN_PCT, REGRESSION_FAIL_PCT);
+
+        use Severity::*;
+        assert_eq!(severities(&alerts, "HashSet"), vec![Ok, Warning, Warning, Regression, Regression]);
+        let remove = &alerts[4];
+        assert_eq!((remove.operation.as_str(), remove.baseline_ns, remove.current_ns), ("remove_half", 1_000, 3_000));
+        assert!((remove.pct_change - 200.0).abs() < 1e-9);
     }
 
     #[test]
-    fn insert_adds_to_all_three_sets() {
-        let mut mgr = SetManager::new();
-        mgr.insert_product(&make(Uuid::new_v4(), "Widget"));
-        assert_eq!(mgr.sizes(), (1, 1, 1));
+    fn regression_check_treats_speedups_and_unmatched_sets_quietly() {
+        let baseline = report_of(vec![timed_result("BTreeSet", 2_000), timed_result("FxHashSet", 1_000), timed_result("Idle", 0)]);
+        let current = report_of(vec![timed_result("BTreeSet", 500), timed_result("BinaryHeap", 9_000), timed_result("Idle", 70)]);
+        let alerts = regression_check(&baseline, &current, REGRESSION_WARN_PCT, REGRESSION_FAIL_PCT);
+
+        assert_eq!(alerts.len(), 10, "only BTreeSet and Idle are in both runs");
+        assert!(alerts.iter().all(|a| a.severity == Severity::Ok), "{:?}", alerts);
+        assert!((alerts[0].pct_change - -75.0).abs() < 1e-9);
+        assert_eq!(alerts[5].pct_change, 0.0, "no baseline time, no percentage");
+
+        // Custom thresholds
+        let strict = regression_check(&current, &baseline, 1.0, 50.0);
+        assert_eq!(severities(&strict, "BTreeSet"), vec![Severity::Regression; 5]);
+        let lenient = regression_check(&baseline, &report_of(vec![timed_result("BTreeSet", 2_400)]), 30.0, 60.0);
+        assert_eq!(severities(&lenient, "BTreeSet"), vec![Severity::Ok; 5]);
     }
 
+    // ── Price range ────────────────────────────────────────────────────────────
+
     #[test]
-    fn inserting_same_id_twice_does_not_grow_sets() {
+    fn range_by_price_returns_exactly_the_products_in_bounds() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        let products: Vec<Product> = (0..500).map(|i| priced(&format!("P{}", i), rng.gen_range(0..1_000))).collect();
         let mut mgr = SetManager::new();
-        let id = Uuid::new_v4();
-        mgr.insert_product(&make(id, "First"));
-        mgr.insert_product(&make(id, "Second")); // duplicate UUID
-        assert_eq!(mgr.sizes(), (1, 1, 1));
+        mgr.sync_from_db(&products);
+
+        for (min, max) in [(0, 999), (250, 260), (100, 100), (990, 5_000), (-10, -1)] {
+            let found = mgr.range_by_price(min, max);
+            assert!(found.iter().all(|p| (min..=max).contains(&p.price_cents)), "[{}, {}]", min, max);
+            assert!(found.windows(2).all(|w| w[0].price_cents <= w[1].price_cents), "cheapest first");
+            let expected = products.iter().filter(|p| (min..=max).contains(&p.price_cents)).count();
+            assert_eq!(found.len(), expected, "[{}, {}]", min, max);
+        }
+        assert!(mgr.range_by_price(10, 5).is_empty());
     }
 
     #[test]
-    fn remove_product_removes_from_all_three_sets() {
+    fn range_by_price_follows_updates_and_removals() {
         let mut mgr = SetManager::new();
-        let id = Uuid::new_v4();
-        mgr.insert_product(&make(id, "Widget"));
-        mgr.remove_product(id);
-        assert_eq!(mgr.sizes(), (0, 0, 0));
+        let lamp = priced("Lamp", 100);
+        mgr.insert_product(&lamp);
+        mgr.insert_product(&priced("Rug", 300));
+
+        mgr.insert_product(&Product { price_cents: 500, ..lamp.clone() });
+        assert!(mgr.range_by_price(0, 200).is_empty(), "stale price left in the index");
+        assert_eq!(mgr.range_by_price(400, 600)[0].id, lamp.id);
+        assert_eq!(mgr.price_index.len(), 2);
+
+        mgr.remove_product(lamp.id);
+        assert!(mgr.range_by_price(400, 600).is_empty());
+        assert_eq!(mgr.price_index.iter().collect::<Vec<_>>(), vec![&(300, mgr.btree_set.first().unwrap().id)]);
+        mgr.reset();
+        assert!(mgr.range_by_price(i64::MIN, i64::MAX).is_empty());
     }
 
     #[test]
-    fn remove_nonexistent_id_is_noop() {
-        let mut mgr = SetManager::new();
-        mgr.insert_product(&make(Uuid::new_v4(), "Widget"));
-        mgr.remove_product(Uuid::new_v4()); // different ID
-        assert_eq!(mgr.sizes(), (1, 1, 1));
+    fn range_index_and_filter_scan_select_the_same_products() {
+        let products: Vec<Product> = (0..200).map(|i| priced(&format!("P{:03}", (i * 7919) % 200), (i * 37) % 50)).collect();
+        let set: BTreeSet<&Product> = products.iter().collect();
+        let index: BTreeMap<(i64, Uuid), &Product> = products.iter().map(|p| ((p.price_cents, p.id), p)).collect();
+
+        for (min, max) in [(0, 49), (10, 20), (25, 25), (60, 70), (30, 10)] {
+            let by_range = ids_of(range_via_index(&index, min, max).into_iter());
+            let by_scan = ids_of(range_via_scan(&set, min, max).into_iter());
+            let expected = ids_of(products.iter().filter(|p| (min..=max).contains(&p.price_cents)));
+            assert_eq!(by_range, expected, "range {}..={}", min, max);
+            assert_eq!(by_scan, expected, "scan {}..={}", min, max);
+        }
     }
 
     #[test]
-    fn sync_from_db_replaces_all_contents() {
-        let mut mgr = SetManager::new();
-        let old = make(Uuid::new_v4(), "Old");
-        mgr.insert_product(&old);
+    fn btree_range_benchmark_uses_the_requested_percentiles() {
+        // Prices 0..100, one product each
+        let products: Vec<Product> = (0..100).map(|i| priced(&format!("P{:03}", 99 - i), i)).collect();
+        let result = benchmark_btree_range(&products, 20.0, 29.0);
+        assert_eq!((result.min_cents, result.max_cents), (20, 29));
+        assert_eq!(result.matches, 10);
+        assert!(result.results_match);
 
-        let new_products = vec![
-            make(Uuid::new_v4(), "Beta"),
-            make(Uuid::new_v4(), "Gamma"),
-        ];
-        mgr.sync_from_db(&new_products);
+        let all = benchmark_btree_range(&products, 0.0, 100.0);
+        assert_eq!((all.min_cents, all.max_cents, all.matches), (0, 99, 100));
+        assert!(all.results_match);
 
-        assert_eq!(mgr.sizes(), (2, 2, 2));
-        assert!(!mgr.hash_set.contains(&old), "Old product must be gone after sync");
+        let empty = benchmark_btree_range(&[], 10.0, 90.0);
+        assert_eq!(empty.matches, 0);
+        assert!(empty.results_match);
     }
 
-    // ── Order guarantees ───────────────────────────────────────────────────────
+    #[test]
+    fn range_delete_strategies_leave_the_same_products() {
+        let products: Vec<Product> = (0..200).map(|i| priced(&format!("P{:03}", 199 - i), i)).collect();
+        let result = benchmark_btree_range_delete(&products, 0.25);
+        assert_eq!((result.set_size, result.removed), (200, 50));
+        assert!(result.results_match);
+        assert_eq!(result.chosen, BatchRemoveStrategy::Retain);
+        assert_eq!(result.sweep.len(), RANGE_DELETE_SWEEP.len());
+
+        let most = benchmark_btree_range_delete(&products, 0.9);
+        assert_eq!((most.removed, most.chosen), (180, BatchRemoveStrategy::Rebuild));
+        assert!(most.results_match);
+    }
 
     #[test]
-    fn index_set_preserves_insertion_order() {
+    fn batch_removal_rebuilds_above_the_threshold() {
+        assert_eq!(BatchRemoveStrategy::for_batch(3, 10), BatchRemoveStrategy::Retain);
+        assert_eq!(BatchRemoveStrategy::for_batch(4, 10), BatchRemoveStrategy::Rebuild);
+        assert_eq!(BatchRemoveStrategy::for_batch(1, 0), BatchRemoveStrategy::Retain);
+
+        let products: Vec<Product> = (0..20).map(|i| priced(&format!("P{}", i), i)).collect();
+        let ids: HashSet<Uuid> = products.iter().step_by(2).map(|p| p.id).collect();
+        let mut retained = SetManager::new();
+        retained.sync_from_db(&products);
+        retained.remove_products_with(&ids, BatchRemoveStrategy::Retain);
+
+        let mut rebuilt = SetManager::new();
+        rebuilt.sync_from_db(&products);
+        let batch: Vec<Uuid> = ids.iter().copied().collect();
+        assert_eq!(rebuilt.remove_product_batch(&batch), BatchRemoveStrategy::Rebuild);
+
+        assert_eq!(rebuilt.btree_set, retained.btree_set);
+        assert_eq!(rebuilt.sizes(), retained.sizes());
+        assert_eq!(rebuilt.btree_set.len(), 10);
+    }
+
+    #[test]
+    fn benchmark_reports_price_range_matches() {
+        let products: Vec<Product> = (0..200).map(|i| priced(&format!("P{}", i), i)).collect();
+        let config = SetBenchmarkConfig { threads: 1, ..Default::default() };
+        let report = SetManager::new().run_benchmark(products.clone(), &config).unwrap();
+
+        let range = report.price_range.unwrap();
+        assert!(range.min_cents <= range.max_cents);
+        let expected = products.iter().filter(|p| (range.min_cents..=range.max_cents).contains(&p.price_cents)).count();
+        assert_eq!(range.matches, expected);
+        assert!(SetManager::new().run_benchmark(vec![], &config).unwrap().price_range.is_none());
+    }
+
+    // ── Name-prefix search ─────────────────────────────────────────────────────
+
+    fn named(names: &[&str]) -> SetManager {
         let mut mgr = SetManager::new();
-        let names = ["Zebra", "Alpha", "Mango", "Delta"];
-        for name in &names {
+        for name in names {
             mgr.insert_product(&make(Uuid::new_v4(), name));
         }
-        let observed: Vec<&str> = mgr.index_set.iter().map(|p| p.name.as_str()).collect();
-        assert_eq!(observed, names, "IndexSet must preserve insertion (FIFO) order");
+        mgr
+    }
+
+    fn names<'a>(found: &[&'a Product]) -> Vec<&'a str> {
+        found.iter().map(|p| p.name.as_str()).collect()
     }
 
     #[test]
-    fn btree_set_iterates_alphabetically() {
-        let mut mgr = SetManager::new();
-        mgr.insert_product(&make(Uuid::new_v4(), "Zebra"));
-        mgr.insert_product(&make(Uuid::new_v4(), "Alpha"));
-        mgr.insert_product(&make(Uuid::new_v4(), "Mango"));
-        let observed: Vec<&str> = mgr.btree_set.iter().map(|p| p.name.as_str()).collect();
-        assert_eq!(observed, vec!["Alpha", "Mango", "Zebra"]);
+    fn empty_prefix_returns_every_product() {
+        let mgr = named(&["Widget", "apple", "Gadget"]);
+        assert_eq!(names(&mgr.find_by_name_prefix("")), ["Widget", "apple", "Gadget"]);
+        assert_eq!(names(&mgr.find_by_name_prefix_btree("")), ["Gadget", "Widget", "apple"]);
     }
 
     #[test]
-    fn hash_set_contains_inserted_product() {
-        let mut mgr = SetManager::new();
-        let p = make(Uuid::new_v4(), "Widget");
-        mgr.insert_product(&p);
-        assert!(mgr.hash_set.contains(&p));
-        assert!(mgr.index_set.contains(&p));
-        assert!(mgr.btree_set.contains(&p));
+    fn prefix_with_no_match_returns_nothing() {
+        let mgr = named(&["Widget", "Gadget"]);
+        assert!(mgr.find_by_name_prefix("Gizmo").is_empty());
+        assert!(mgr.find_by_name_prefix_btree("Gizmo").is_empty());
+        // Longer than every name
+        assert!(mgr.find_by_name_prefix_btree("Widgets and more").is_empty());
     }
 
-    // ── Benchmark correctness ──────────────────────────────────────────────────
+    #[test]
+    fn prefix_search_ignores_ascii_case() {
+        let mgr = named(&["Pro Drill", "probe", "PROJECTOR", "Prune", "Apron", "pRoduct"]);
+        assert_eq!(names(&mgr.find_by_name_prefix("pro")), ["Pro Drill", "probe", "PROJECTOR", "pRoduct"]);
+        assert_eq!(names(&mgr.find_by_name_prefix_btree("PRO")), ["PROJECTOR", "Pro Drill", "pRoduct", "probe"]);
+        assert_eq!(names(&mgr.find_by_name_prefix_btree("pro d")), ["Pro Drill"]);
+    }
 
     #[test]
-    fn benchmark_reports_correct_product_count() {
-        let products: Vec<Product> = (0..50)
-            .map(|i| make(Uuid::new_v4(), &format!("Product {:03}", i)))
+    fn btree_prefix_search_agrees_with_index_scan() {
+        let products: Vec<Product> = (0..300)
+            .map(|i| make(Uuid::new_v4(), &format!("{} Item {:03}", ["Mega", "mini", "MEGA", "Max"][i % 4], i)))
             .collect();
         let mut mgr = SetManager::new();
-        let report = mgr.run_benchmark(products);
-        assert_eq!(report.product_count, 50);
-        assert_eq!(report.results.len(), 3);
+        mgr.sync_from_db(&products);
+
+        for prefix in ["m", "mega", "MeGa item 01", "max item 3", "z"] {
+            let mut scanned = mgr.find_by_name_prefix(prefix);
+            scanned.sort();
+            assert_eq!(mgr.find_by_name_prefix_btree(prefix), scanned, "{}", prefix);
+        }
     }
 
     #[test]
-    fn benchmark_iteration_sample_at_most_10_items() {
-        let products: Vec<Product> = (0..30)
-            .map(|i| make(Uuid::new_v4(), &format!("P {:02}", i)))
-            .collect();
-        let mut mgr = SetManager::new();
-        let report = mgr.run_benchmark(products);
-        for r in &report.results {
-            assert!(
-                r.iteration_order_sample.len() <= 10,
-                "{} sample had {} items",
-                r.set_type,
-                r.iteration_order_sample.len()
-            );
+    fn benchmark_reports_short_and_long_prefix_search() {
+        let products: Vec<Product> = (0..200).map(|i| make(Uuid::new_v4(), &format!("Product {:03}", i))).collect();
+        let config = SetBenchmarkConfig { threads: 1, ..Default::default() };
+        let report = SetManager::new().run_benchmark(products, &config).unwrap();
+
+        let search = report.prefix_search.unwrap();
+        assert_eq!((search.short.prefix.as_str(), search.short.matches), ("p", 200));
+        assert_eq!((search.long.prefix.as_str(), search.long.matches), ("product ", 200));
+        for t in [&search.short, &search.long] {
+            assert!(["IndexSet scan", "BTreeSet range"].contains(&t.faster.as_str()));
+        }
+        assert!(SetManager::new().run_benchmark(vec![], &config).unwrap().prefix_search.is_none());
+    }
+
+    // ── Name autocomplete ──────────────────────────────────────────────────────
+
+    fn with_names(names: &[&str]) -> SetManager {
+        let mut mgr = SetManager::new().with_name_trie(true);
+        for name in names {
+            mgr.insert_product(&make(Uuid::new_v4(), name));
         }
+        mgr
+    }
+
+    fn suggested(found: Vec<(String, Uuid)>) -> Vec<String> {
+        found.into_iter().map(|(name, _)| name).collect()
     }
 
     #[test]
-    fn benchmark_btree_sample_is_alphabetically_sorted() {
-        let products = vec![
-            make(Uuid::new_v4(), "Zebra"),
-            make(Uuid::new_v4(), "Alpha"),
-            make(Uuid::new_v4(), "Mango"),
-        ];
-        let mut mgr = SetManager::new();
-        let report = mgr.run_benchmark(products);
-        let btree = report.results.iter().find(|r| r.set_type == "BTreeSet").unwrap();
-        assert_eq!(
-            btree.iteration_order_sample,
-            vec!["Alpha", "Mango", "Zebra"],
-            "BTreeSet sample must be alphabetically sorted"
-        );
+    fn autocomplete_matches_single_characters_and_whole_words() {
+        let mgr = with_names(&["Pro Drill", "Pan", "Protractor", "Lamp", "Pro", "pro mouse"]);
+
+        assert_eq!(suggested(mgr.autocomplete("P", 10)), ["Pan", "Pro", "Pro Drill", "Protractor"]);
+        assert_eq!(suggested(mgr.autocomplete("Pro", 10)), ["Pro", "Pro Drill", "Protractor"]);
+        assert_eq!(suggested(mgr.autocomplete("Pro Drill", 10)), ["Pro Drill"]);
+        assert_eq!(suggested(mgr.autocomplete("P", 2)), ["Pan", "Pro"]);
+        assert!(mgr.autocomplete("Pro Drills", 10).is_empty());
+        for prefix in ["P", "Pro", "L", "pro", "Q"] {
+            assert_eq!(mgr.autocomplete(prefix, 10), mgr.autocomplete_btree(prefix, 10), "prefix {:?}", prefix);
+        }
     }
 
     #[test]
-    fn benchmark_index_sample_preserves_insertion_order() {
-        let products = vec![
-            make(Uuid::new_v4(), "Zebra"),
-            make(Uuid::new_v4(), "Alpha"),
-            make(Uuid::new_v4(), "Mango"),
-        ];
+    fn autocomplete_is_case_sensitive() {
+        let mgr = with_names(&["Pro Drill", "pro mouse", "PRO KIT"]);
+        assert_eq!(suggested(mgr.autocomplete("pro", 10)), ["pro mouse"]);
+        assert_eq!(suggested(mgr.autocomplete("PRO", 10)), ["PRO KIT"]);
+        assert_eq!(suggested(mgr.autocomplete("Pro", 10)), ["Pro Drill"]);
+    }
+
+    #[test]
+    fn name_trie_keeps_duplicate_names_and_follows_renames_and_removals() {
+        let mut mgr = with_names(&["Lamp", "Lamp"]);
+        let found = mgr.autocomplete("Lamp", 10);
+        assert_eq!(found.len(), 2);
+        assert!(found[0].1 < found[1].1, "ties ordered by id like btree_set");
+
+        let lamp = mgr.hash_set.get(&make(found[0].1, "")).unwrap().clone();
+        mgr.insert_product(&Product { name: "Desk Lamp".to_string(), ..lamp.clone() });
+        assert_eq!(suggested(mgr.autocomplete("Lamp", 10)), ["Lamp"]);
+        assert_eq!(mgr.autocomplete("Desk", 10), [("Desk Lamp".to_string(), lamp.id)]);
+
+        mgr.remove_product(lamp.id);
+        assert!(mgr.autocomplete("Desk", 10).is_empty());
+        mgr.sync_from_db(&[make(Uuid::new_v4(), "Rug")]);
+        assert_eq!(suggested(mgr.autocomplete("", 10)), ["Rug"]);
+        mgr.reset();
+        assert!(mgr.name_trie.as_ref().unwrap().is_empty());
+    }
+
+    #[test]
+    fn autocomplete_falls_back_to_the_btree_without_a_trie() {
         let mut mgr = SetManager::new();
-        let report = mgr.run_benchmark(products);
-        let index = report
-            .results
-            .iter()
-            .find(|r| r.set_type.contains("Index"))
-            .unwrap();
-        assert_eq!(
-            index.iteration_order_sample,
-            vec!["Zebra", "Alpha", "Mango"],
-            "IndexSet sample must preserve insertion order"
-        );
+        mgr.insert_product(&make(Uuid::new_v4(), "Lamp"));
+        assert!(mgr.name_trie.is_none());
+        assert_eq!(suggested(mgr.autocomplete("La", 10)), ["Lamp"]);
+
+        let mgr = mgr.with_name_trie(true);
+        assert_eq!(mgr.name_trie.as_ref().unwrap().len(), 1, "built from the existing contents");
     }
 
+    // ── FxHashSet (opt-in) ─────────────────────────────────────────────────────
+
     #[test]
-    fn benchmark_iterate_times_all_elements_not_just_10() {
-        // With only 5 products the iterate timing must still cover all 5 (sample == all names)
-        let products: Vec<Product> = (0..5)
-            .map(|i| make(Uuid::new_v4(), &format!("Item {:02}", i)))
-            .collect();
+    fn fx_set_is_disabled_by_default() {
+        let mgr = SetManager::new();
+        assert!(mgr.fx_set.is_none());
+        assert_eq!(SetManager::with_fx_set(true).sizes(), SetSizes::uniform(0, Some(0)));
+    }
+
+    #[test]
+    fn label_and_sizes_name_only_the_enabled_sets() {
+        let plain = SetManager::new();
+        let with_fx = SetManager::with_fx_set(true);
+        assert!(plain.label().starts_with("HashSet+LinkedHashSet+BTreeSet+AHashSet+DashSet"));
+        assert!(!plain.label().contains("FxHashSet"));
+        assert!(with_fx.label().contains("+FxHashSet"));
+
+        let json = serde_json::to_value(plain.sizes()).unwrap();
+        assert!(json.get("fx_set").is_none());
+        assert_eq!(serde_json::to_value(with_fx.sizes()).unwrap()["fx_set"], 0);
+
+        let mut mgr = SetManager::with_fx_set(true);
+        mgr.insert_product(&make(Uuid::new_v4(), "A"));
+        assert!(mgr.sizes().consistent());
+        mgr.fx_set.as_mut().unwrap().clear();
+        assert!(!mgr.sizes().consistent());
+    }
+
+    #[test]
+    fn fx_set_deduplicates_like_hash_set() {
+        let mut mgr = SetManager::with_fx_set(true);
+        let id = Uuid::new_v4();
+        mgr.insert_product(&make(id, "Before"));
+        mgr.insert_product(&make(id, "After"));
+        mgr.insert_product(&make(Uuid::new_v4(), "Other"));
+        assert_eq!(mgr.sizes(), SetSizes::uniform(2, Some(2)));
+
+        let probe = make(id, "Renamed");
+        let fx = mgr.fx_set.as_ref().unwrap();
+        assert_eq!(fx.contains(&probe), mgr.hash_set.contains(&probe));
+        assert_eq!(fx.get(&probe).unwrap().name, mgr.hash_set.get(&probe).unwrap().name);
+        assert!(mgr.symmetric_difference().is_empty());
+
+        mgr.remove_product(id);
+        assert_eq!(mgr.sizes(), SetSizes::uniform(1, Some(1)));
+        mgr.reset();
+        assert_eq!(mgr.sizes(), SetSizes::uniform(0, Some(0)));
+    }
+
+    #[test]
+    fn diverged_fx_set_shows_in_consistency_checks() {
+        let mut mgr = SetManager::with_fx_set(true);
+        mgr.sync_from_db(&[make(Uuid::new_v4(), "A"), make(Uuid::new_v4(), "B")]);
+        let stray = make(Uuid::new_v4(), "Stray");
+        mgr.fx_set.as_mut().unwrap().insert(stray.clone());
+
+        assert_eq!(mgr.intersection().len(), 2);
+        assert_eq!(mgr.symmetric_difference(), vec![stray.id]);
+    }
+
+    #[test]
+    fn fx_set_is_benchmarked_only_when_enabled() {
+        let products: Vec<Product> = (0..30).map(|i| make(Uuid::new_v4(), &format!("P{}", i))).collect();
+        let config = SetBenchmarkConfig { threads: 1, ..Default::default() };
+        let has_fx = |mgr: &mut SetManager| {
+            mgr.run_benchmark(products.clone(), &config).unwrap().results.iter().any(|r| r.set_type == "FxHashSet")
+        };
+        assert!(!has_fx(&mut SetManager::new()));
+
+        let mut mgr = SetManager::with_fx_set(true);
+        assert!(has_fx(&mut mgr));
+        assert_eq!(mgr.sizes().fx_set, Some(30));
+    }
+
+    // ── Consistency ────────────────────────────────────────────────────────────
+
+    #[test]
+    fn consistent_sets_have_full_intersection_and_no_difference() {
+        let mgr = manager_with(5);
+        assert_eq!(mgr.intersection().len(), 5);
+        assert!(mgr.symmetric_difference().is_empty());
+    }
+
+    #[test]
+    fn symmetric_difference_catches_product_missing_from_one_set() {
+        let mut mgr = manager_with(4);
+        let victim = mgr.btree_set.iter().next().unwrap().clone();
+        mgr.btree_set.remove(&victim);
+
+        assert_eq!(mgr.symmetric_difference(), vec![victim.id]);
+        assert_eq!(mgr.intersection().len(), 3);
+        assert!(mgr.intersection().iter().all(|p| p.id != victim.id));
+    }
+
+    #[test]
+    fn symmetric_difference_catches_product_only_in_one_set() {
+        let mut mgr = manager_with(2);
+        let stray = make(Uuid::new_v4(), "Stray");
+        mgr.index_set.insert(stray.clone());
+
+        assert_eq!(mgr.symmetric_difference(), vec![stray.id]);
+        assert_eq!(mgr.intersection().len(), 2);
+    }
+
+    #[test]
+    fn symmetric_difference_is_sorted_and_deduplicated() {
+        let mut mgr = manager_with(3);
+        let a = make(Uuid::new_v4(), "A");
+        let b = make(Uuid::new_v4(), "B");
+        mgr.hash_set.insert(a.clone());
+        mgr.ahash_set.insert(a.clone());
+        mgr.index_set.insert(b.clone());
+
+        let mut expected = vec![a.id, b.id];
+        expected.sort_unstable();
+        assert_eq!(mgr.symmetric_difference(), expected);
+    }
+
+    // ── Weighted sampling ──────────────────────────────────────────────────────
+
+    fn zipf_weights(n: usize, exponent: f64) -> Vec<f64> {
+        (1..=n).map(|k| 1.0 / (k as f64).powf(exponent)).collect()
+    }
+
+    fn manager_with(n: usize) -> SetManager {
         let mut mgr = SetManager::new();
-        let report = mgr.run_benchmark(products);
-        for r in &report.results {
-            assert_eq!(
-                r.iteration_order_sample.len(),
-                5,
-                "{} should expose all 5 items when count < 10",
-                r.set_type
-            );
+        for i in 0..n {
+            mgr.insert_product(&make(Uuid::new_v4(), &format!("P{:03}", i)));
         }
+        mgr
     }
 
     #[test]
-    fn benchmark_syncs_manager_sets_after_run() {
-        let products: Vec<Product> = (0..10)
-            .map(|i| make(Uuid::new_v4(), &format!("P{}", i)))
-            .collect();
-        let mut mgr = SetManager::new();
-        mgr.run_benchmark(products);
-        // After benchmark the manager sets should be populated
-        let (h, i, b) = mgr.sizes();
-        assert_eq!(h, 10);
-        assert_eq!(i, 10);
-        assert_eq!(b, 10);
+    fn weighted_sample_rejects_mismatched_lengths() {
+        let mgr = manager_with(5);
+        assert!(mgr.weighted_sample(&[1.0; 4]).is_none());
+        assert!(mgr.weighted_sample(&[1.0; 6]).is_none());
+        assert!(mgr.weighted_sample(&[1.0; 5]).is_some());
+    }
+
+    #[test]
+    fn weighted_sample_rejects_zero_weights() {
+        let mgr = manager_with(3);
+        assert!(mgr.weighted_sample(&[0.0; 3]).is_none());
+        assert!(SetManager::new().weighted_sample(&[]).is_none());
+    }
+
+    #[test]
+    fn weighted_sample_never_picks_zero_weight_product() {
+        let mgr = manager_with(3);
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        for _ in 0..1_000 {
+            let p = mgr.weighted_sample_with(&[0.0, 1.0, 0.0], &mut rng).unwrap();
+            assert_eq!(p.name, "P001");
+        }
+    }
+
+    #[test]
+    fn zipf_weighted_sample_favours_early_inserted_products() {
+        let n = 100;
+        let mgr = manager_with(n);
+        let weights = zipf_weights(n, 1.2);
+        let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+
+        let mut hits = vec![0usize; n];
+        for _ in 0..20_000 {
+            let p = mgr.weighted_sample_with(&weights, &mut rng).unwrap();
+            hits[mgr.index_set.get_index_of(p).unwrap()] += 1;
+        }
+
+        let first_ten: usize = hits[..10].iter().sum();
+        let last_ten: usize = hits[n - 10..].iter().sum();
+        assert!(hits[0] > hits[n - 1] * 10, "head {} vs tail {}", hits[0], hits[n - 1]);
+        assert!(first_ten > last_ten * 10, "first ten {} vs last ten {}", first_ten, last_ten);
     }
 
     #[test]
@@ -679,4 +4806,112 @@ mod tests {
         // Duration should be non-negative (trivially true, just validate the type)
         let _ = dur.as_nanos();
     }
+
+    #[cfg(feature = "skiplist")]
+    #[test]
+    fn skip_set_iterates_in_btree_order() {
+        // Repeated names make the `id` tie-break part of the order
+        let products: Vec<Product> = ["Delta", "alpha", "Charlie", "Bravo", "Delta", "Alpha"]
+            .iter()
+            .map(|name| make(Uuid::new_v4(), name))
+            .collect();
+        let mut mgr = SetManager::new();
+        mgr.sync_from_db(&products);
+
+        let skip: Vec<Uuid> = mgr.skip_set.iter().map(|e| e.value().id).collect();
+        let btree: Vec<Uuid> = mgr.btree_set.iter().map(|p| p.id).collect();
+        assert_eq!(skip, btree);
+    }
+
+    #[cfg(feature = "skiplist")]
+    #[test]
+    fn skiplist_benchmark_matches_btree_sample() {
+        let products: Vec<Product> = (0..50).rev().map(|i| make(Uuid::new_v4(), &format!("P{:02}", i))).collect();
+        let config = SetBenchmarkConfig::default();
+        let skip = benchmark_skiplist(&products, &config);
+        let btree = benchmark_btree_set(&products, &config);
+        assert_eq!(skip.set_type, "SkipSet");
+        assert_eq!(skip.order_type, "Sorted by (name, id)");
+        assert_eq!(skip.iteration_order_sample, btree.iteration_order_sample);
+
+        let report = SetManager::new().run_benchmark(products, &config).unwrap();
+        assert!(report.results.iter().any(|r| r.set_type == "SkipSet"));
+    }
+
+    #[cfg(feature = "skiplist")]
+    #[test]
+    fn skip_set_reinsert_after_rename_keeps_single_entry() {
+        let mut mgr = SetManager::new();
+        let id = Uuid::new_v4();
+        mgr.insert_product(&make(id, "Before"));
+        mgr.insert_product(&make(id, "After"));
+        assert_eq!(mgr.skip_set.len(), 1);
+        assert_eq!(mgr.skip_set.front().unwrap().value().name, "After");
+        assert!(mgr.symmetric_difference().is_empty());
+        mgr.remove_product(id);
+        assert!(mgr.skip_set.is_empty());
+    }
+
+    // ── Concurrent access ─────────────────────────────────────────────────────
+
+    fn catalog(n: usize) -> Vec<Product> {
+        (0..n).map(|i| make(Uuid::new_v4(), &format!("P{:05}", i))).collect()
+    }
+
+    #[tokio::test]
+    async fn concurrent_benchmark_counts_every_operation() {
+        let products = catalog(200);
+        let result = benchmark_concurrent(&products, 3, 2, 50).await;
+        assert_eq!(result.product_count, 200);
+        assert_eq!(result.total_reads, 150);
+        assert_eq!(result.total_writes, 100);
+        assert!(result.read_contention_waits <= result.total_reads);
+        assert!(result.write_contention_waits <= result.total_writes);
+        assert!(result.total_wall_ns > 0);
+        assert!(result.reads_per_sec > 0.0 && result.writes_per_sec > 0.0);
+    }
+
+    #[tokio::test]
+    async fn concurrent_benchmark_handles_an_empty_catalog() {
+        let result = benchmark_concurrent(&[], 1, 1, 10).await;
+        assert_eq!((result.total_reads, result.total_writes), (10, 10));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn contention_benchmark_favours_the_larger_side() {
+        let result = benchmark_rwlock_contention(&catalog(500), 8, 1, Duration::from_millis(200)).await;
+        assert!(result.total_reads > result.total_writes, "{} reads vs {} writes", result.total_reads, result.total_writes);
+        assert!(result.total_writes > 0, "the writer must not starve");
+        assert!(result.total_wall_ns >= 200_000_000);
+        assert_eq!(result.max_wait_ns, result.max_read_wait_ns.max(result.max_write_wait_ns));
+        assert!(result.avg_read_wait_ns <= result.max_read_wait_ns as f64);
+    }
+
+    #[tokio::test]
+    async fn contention_benchmark_interleaves_tasks_on_one_thread() {
+        let result = benchmark_rwlock_contention(&[], 4, 1, Duration::from_millis(50)).await;
+        assert!(result.total_reads > 0 && result.total_writes > 0);
+        assert_eq!(result.duration_secs, 0);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn readers_without_writers_scale_with_cores() {
+        let cores = std::thread::available_parallelism().map_or(1, |n| n.get());
+        let readers = cores.min(4);
+        let result = benchmark_concurrent(&catalog(5_000), readers, 0, 20_000).await;
+
+        assert_eq!(result.total_writes, 0);
+        assert_eq!(result.write_contention_waits, 0);
+        // Readers never exclude each other, so nothing should have to wait
+        assert_eq!(result.read_contention_waits, 0);
+        // On a single core the tasks just take turns; there is no speedup to measure
+        if readers > 1 {
+            assert!(
+                result.read_speedup >= readers as f64 * 0.5,
+                "{} readers only reached {:.2}x single-reader throughput",
+                readers,
+                result.read_speedup
+            );
+        }
+    }
 }
@@ -0,0 +1,203 @@
+use std::{future::Future, path::PathBuf, sync::Arc, time::Duration};
+
+use axum::Router;
+use tokio::{
+    net::TcpListener,
+    signal::unix::{signal, SignalKind},
+    sync::{oneshot, RwLock},
+};
+use tracing::{info, warn};
+
+use crate::metrics::MetricsStore;
+
+/// What happens between the shutdown signal and process exit.
+#[derive(Debug, Clone)]
+pub struct ShutdownOptions {
+    /// How long in-flight requests get to finish once the signal arrives
+    pub timeout: Duration,
+    /// Where to write the metrics store as CSV after draining (skipped when `None`)
+    pub metrics_dump_path: Option<PathBuf>,
+}
+
+/// Resolves on the first SIGTERM or SIGINT.
+///
+/// The handlers are installed before this returns, so a signal that arrives
+/// before the future is first polled is not lost.
+pub fn signal_received() -> std::io::Result<impl Future<Output = &'static str>> {
+    let mut terminate = signal(SignalKind::terminate())?;
+    let mut interrupt = signal(SignalKind::interrupt())?;
+    Ok(async move {
+        tokio::select! {
+            _ = terminate.recv() => "SIGTERM",
+            _ = interrupt.recv() => "SIGINT",
+        }
+    })
+}
+
+/// Serves `app` until `shutdown` resolves, then stops accepting connections,
+/// gives in-flight requests up to `options.timeout` to finish, and finally
+/// flushes the metrics store to disk.
+pub async fn serve<F>(
+    listener: TcpListener,
+    app: Router,
+    metrics: Arc<RwLock<MetricsStore>>,
+    options: ShutdownOptions,
+    shutdown: F,
+) -> anyhow::Result<()>
+where
+    F: Future<Output = &'static str> + Send + 'static,
+{
+    let (signalled_tx, signalled_rx) = oneshot::channel();
+    let server = axum::serve(listener, app).with_graceful_shutdown(async move {
+        let signal = shutdown.await;
+        let _ = signalled_tx.send(signal);
+    });
+    let mut server = tokio::spawn(async move { server.await });
+
+    tokio::select! {
+        // The server only returns on its own if accepting failed
+        result = &mut server => return Ok(result??),
+        Ok(signal) = signalled_rx => {
+            info!(signal, timeout_secs = options.timeout.as_secs(), "Shutdown signal received, draining in-flight requests");
+        }
+    }
+
+    match tokio::time::timeout(options.timeout, &mut server).await {
+        Ok(result) => {
+            result??;
+            info!("All in-flight requests finished");
+        }
+        Err(_) => {
+            server.abort();
+            warn!(timeout_secs = options.timeout.as_secs(), "Drain timed out, dropping remaining connections");
+        }
+    }
+
+    if let Some(path) = &options.metrics_dump_path {
+        let entries = dump_metrics(&metrics, path).await?;
+        info!(path = %path.display(), entries, "Flushed metrics store to CSV");
+    }
+
+    info!("Shutdown complete");
+    Ok(())
+}
+
+/// Writes the metrics store to `path` as CSV; returns how many entries were written.
+async fn dump_metrics(metrics: &RwLock<MetricsStore>, path: &std::path::Path) -> anyhow::Result<usize> {
+    let (csv, entries) = {
+        let metrics = metrics.read().await;
+        (metrics.to_csv()?, metrics.entries.len())
+    };
+    tokio::fs::write(path, csv).await?;
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::routing::get;
+    use std::net::SocketAddr;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    async fn slow() -> &'static str {
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        "done"
+    }
+
+    fn app() -> Router {
+        Router::new()
+            .route("/health", get(|| async { "ok" }))
+            .route("/slow", get(slow))
+            .route("/stuck", get(std::future::pending::<()>))
+    }
+
+    fn dump_path() -> PathBuf {
+        std::env::temp_dir().join(format!("metrics-dump-{}.csv", uuid::Uuid::new_v4()))
+    }
+
+    fn metrics_with_entry() -> Arc<RwLock<MetricsStore>> {
+        let mut store = MetricsStore::new();
+        store.record_raw("lookup", "HashSet", 1_234, 1);
+        Arc::new(RwLock::new(store))
+    }
+
+    async fn get_raw(addr: SocketAddr, path: &str) -> String {
+        let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let request = format!("GET {} HTTP/1.1\r\nHost: test\r\nConnection: close\r\n\r\n", path);
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    /// Starts `serve` on an ephemeral port; the returned sender triggers shutdown.
+    async fn start(
+        options: ShutdownOptions,
+        metrics: Arc<RwLock<MetricsStore>>,
+    ) -> (SocketAddr, oneshot::Sender<()>, tokio::task::JoinHandle<anyhow::Result<()>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (tx, rx) = oneshot::channel();
+        let shutdown = async move {
+            let _ = rx.await;
+            "test"
+        };
+        let handle = tokio::spawn(serve(listener, app(), metrics, options, shutdown));
+        (addr, tx, handle)
+    }
+
+    #[tokio::test]
+    async fn sigterm_drains_dumps_metrics_and_stops_accepting() {
+        let path = dump_path();
+        let options = ShutdownOptions { timeout: Duration::from_secs(5), metrics_dump_path: Some(path.clone()) };
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(serve(listener, app(), metrics_with_entry(), options.clone(), signal_received().unwrap()));
+
+        assert!(get_raw(addr, "/health").await.starts_with("HTTP/1.1 200"));
+
+        let status = std::process::Command::new("kill")
+            .args(["-TERM", &std::process::id().to_string()])
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        tokio::time::timeout(options.timeout, server).await.unwrap().unwrap().unwrap();
+
+        let csv = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(csv.starts_with("timestamp,operation,set_type"));
+        assert!(csv.contains("lookup,HashSet,1234"));
+        assert!(tokio::net::TcpStream::connect(addr).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn in_flight_request_finishes_before_shutdown_completes() {
+        let options = ShutdownOptions { timeout: Duration::from_secs(5), metrics_dump_path: None };
+        let (addr, trigger, server) = start(options, metrics_with_entry()).await;
+
+        let request = tokio::spawn(get_raw(addr, "/slow"));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        trigger.send(()).unwrap();
+
+        let response = request.await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200"));
+        assert!(response.ends_with("done"));
+        server.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn drain_gives_up_after_the_timeout() {
+        let path = dump_path();
+        let options = ShutdownOptions { timeout: Duration::from_millis(100), metrics_dump_path: Some(path.clone()) };
+        let (addr, trigger, server) = start(options, metrics_with_entry()).await;
+
+        let _stuck = tokio::spawn(get_raw(addr, "/stuck"));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        trigger.send(()).unwrap();
+
+        tokio::time::timeout(Duration::from_secs(2), server).await.unwrap().unwrap().unwrap();
+        assert!(path.exists(), "metrics are flushed even when the drain times out");
+        std::fs::remove_file(&path).unwrap();
+    }
+}
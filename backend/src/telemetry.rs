@@ -0,0 +1,116 @@
+use axum::http::{HeaderMap, Request};
+use opentelemetry::{propagation::Extractor, propagation::TextMapPropagator, trace::TracerProvider as _};
+use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+use opentelemetry_sdk::{propagation::TraceContextPropagator, trace::SdkTracerProvider, Resource};
+use tracing::{warn, Span};
+use tracing_opentelemetry::{OpenTelemetryLayer, OpenTelemetrySpanExt};
+use tracing_subscriber::registry::LookupSpan;
+
+const SERVICE_NAME: &str = "inventory-service";
+
+/// Batches spans and ships them over OTLP/gRPC to `endpoint`
+/// (e.g. `http://localhost:4317`). Must be called inside the Tokio runtime.
+pub fn init_tracer_provider(endpoint: &str) -> anyhow::Result<SdkTracerProvider> {
+    let exporter = SpanExporter::builder().with_tonic().with_endpoint(endpoint).build()?;
+    Ok(SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(Resource::builder().with_service_name(SERVICE_NAME).build())
+        .build())
+}
+
+/// The `tracing` layer that turns spans into OpenTelemetry spans for `provider`.
+pub fn layer<S>(provider: &SdkTracerProvider) -> OpenTelemetryLayer<S, opentelemetry_sdk::trace::Tracer>
+where
+    S: tracing::Subscriber + for<'span> LookupSpan<'span>,
+{
+    tracing_opentelemetry::layer().with_tracer(provider.tracer(SERVICE_NAME))
+}
+
+/// Exports whatever is still buffered and stops the exporter. Called once the
+/// server has drained, so spans from the last requests aren't lost.
+pub fn shutdown_tracer(provider: SdkTracerProvider) {
+    if let Err(e) = provider.shutdown() {
+        warn!(error = %e, "Failed to flush pending trace spans");
+    }
+}
+
+/// `make_span_with` for the `TraceLayer`: the usual per-request span, made a
+/// child of the caller's trace when the request carries a W3C `traceparent`.
+pub fn make_request_span<B>(req: &Request<B>) -> Span {
+    let span = tracing::info_span!(
+        "request",
+        method = %req.method(),
+        uri = %req.uri(),
+        version = ?req.version(),
+    );
+    let parent = TraceContextPropagator::new().extract(&HeaderExtractor(req.headers()));
+    // Only fails when the span is disabled, in which case there's nothing to link
+    let _ = span.set_parent(parent);
+    span
+}
+
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|v| v.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|k| k.as_str()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{models::Product, seed, sets::SetManager};
+    use opentelemetry_sdk::trace::{InMemorySpanExporter, SpanData};
+    use tracing_subscriber::layer::SubscriberExt;
+
+    /// Runs `f` with every span exported to memory, and returns those spans.
+    fn capture_spans(f: impl FnOnce()) -> Vec<SpanData> {
+        let exporter = InMemorySpanExporter::default();
+        let provider = SdkTracerProvider::builder().with_simple_exporter(exporter.clone()).build();
+        let subscriber = tracing_subscriber::registry().with(layer(&provider));
+        tracing::subscriber::with_default(subscriber, f);
+        provider.force_flush().unwrap();
+        exporter.get_finished_spans().unwrap()
+    }
+
+    fn attribute(span: &SpanData, key: &str) -> Option<String> {
+        span.attributes.iter().find(|kv| kv.key.as_str() == key).map(|kv| kv.value.to_string())
+    }
+
+    #[test]
+    fn run_benchmark_emits_a_span_with_the_product_count() {
+        let products: Vec<Product> = seed::generate_products(40);
+        let spans = capture_spans(|| {
+            SetManager::new().run_benchmark(products, &Default::default()).unwrap();
+        });
+
+        let span = spans.iter().find(|s| s.name == "run_benchmark").expect("run_benchmark span");
+        assert_eq!(attribute(span, "product_count").as_deref(), Some("40"));
+        assert!(attribute(span, "elapsed_ms").is_some());
+    }
+
+    #[test]
+    fn request_span_continues_the_callers_trace() {
+        let trace_id = "4bf92f3577b34a4ba0e5f1e0b4c0a1d2";
+        let req = Request::builder()
+            .uri("/api/products")
+            .header("traceparent", format!("00-{}-00f067aa0ba902b7-01", trace_id))
+            .body(())
+            .unwrap();
+        let spans = capture_spans(|| {
+            make_request_span(&req).in_scope(|| {});
+            make_request_span(&Request::new(())).in_scope(|| {});
+        });
+
+        assert_eq!(spans.len(), 2);
+        assert!(spans.iter().all(|s| s.name == "request"));
+        assert_eq!(spans[0].span_context.trace_id().to_string(), trace_id);
+        assert_eq!(spans[0].parent_span_id.to_string(), "00f067aa0ba902b7");
+        assert_ne!(spans[1].span_context.trace_id().to_string(), trace_id, "no header, new trace");
+    }
+}
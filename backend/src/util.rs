@@ -0,0 +1,34 @@
+//! Small helpers shared by the models and handlers.
+
+/// Splits a comma-separated query value into its trimmed, non-empty parts:
+/// `"a, b,,c"` → `["a", "b", "c"]`. `None` when the value is missing or has no
+/// parts, so "not given" and "given but empty" filter the same way.
+pub fn parse_comma_list(s: Option<String>) -> Option<Vec<String>> {
+    let parts: Vec<String> = s
+        .iter()
+        .flat_map(|s| s.split(','))
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .map(str::to_string)
+        .collect();
+    (!parts.is_empty()).then_some(parts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_trims_and_drops_empty_parts() {
+        let parsed = parse_comma_list(Some(" Electronics,Clothing ,, Books".to_string()));
+        assert_eq!(parsed.unwrap(), ["Electronics", "Clothing", "Books"]);
+        assert_eq!(parse_comma_list(Some("Books".to_string())).unwrap(), ["Books"]);
+    }
+
+    #[test]
+    fn missing_or_blank_values_are_none() {
+        assert_eq!(parse_comma_list(None), None);
+        assert_eq!(parse_comma_list(Some(String::new())), None);
+        assert_eq!(parse_comma_list(Some(" , ,".to_string())), None);
+    }
+}
@@ -147,14 +147,14 @@ async function loadProducts() {
 
   try {
     const d = await apiFetch(`/api/products?${params}`);
-    productState.total = d.count;
+    productState.total = d.total_count;
     renderProductTable(d.data);
-    el('products-count').textContent = `${fmtNum(d.count)} products`;
+    el('products-count').textContent = `${fmtNum(d.total_count)} products`;
     const page = Math.floor(productState.offset / productState.limit) + 1;
-    const pages = Math.ceil(d.count / productState.limit) || 1;
+    const pages = Math.ceil(d.total_count / productState.limit) || 1;
     el('products-page-info').textContent = `Page ${page} / ${pages}`;
-    el('products-prev').disabled = productState.offset === 0;
-    el('products-next').disabled = productState.offset + productState.limit >= d.count;
+    el('products-prev').disabled = !d.has_prev_page;
+    el('products-next').disabled = !d.has_next_page;
   } catch (e) {
     toast(e.message, 'error');
   }
@@ -383,7 +383,7 @@ async function clearAll() {
   setBusy('clear-all-btn', true);
   el('clear-result').innerHTML = '';
   try {
-    const d = await apiFetch('/api/reset', { method: 'DELETE' });
+    const d = await apiFetch('/api/admin/reset', { method: 'DELETE' });
     el('clear-result').innerHTML = `
       <div class="result-card success">
         <div class="result-row"><span>Products deleted</span><strong>${fmtNum(d.deleted_products)}</strong></div>
@@ -701,7 +701,7 @@ function renderMetrics(d) {
   el('metrics-entry-count').textContent = `${fmtNum(d.entry_count)} entries`;
 
   if (!d.aggregated || d.aggregated.length === 0) {
-    el('metrics-table-body').innerHTML = '<tr><td colspan="8" class="empty">No metrics yet — run a benchmark first</td></tr>';
+    el('metrics-table-body').innerHTML = '<tr><td colspan="9" class="empty">No metrics yet — run a benchmark first</td></tr>';
     el('metrics-ascii').textContent = '';
     return;
   }
@@ -712,6 +712,7 @@ function renderMetrics(d) {
       <td><span class="badge">${esc(r.set_type)}</span></td>
       <td>${r.sample_count}</td>
       <td class="mono">${(r.avg_ns / 1000).toFixed(2)}</td>
+      <td class="mono" title="CV ${r.cv_percent.toFixed(1)}%">${(r.std_dev_ns / 1000).toFixed(2)}</td>
       <td class="mono">${(r.p50_ns / 1000).toFixed(2)}</td>
       <td class="mono">${(r.p95_ns / 1000).toFixed(2)}</td>
       <td class="mono">${(r.p99_ns / 1000).toFixed(2)}</td>